@@ -1,16 +1,19 @@
-use std::cmp::min;
+use std::collections::HashMap;
 use std::fs;
 use std::fs::File;
 use std::io::{Read, Write};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::thread;
+use std::thread::JoinHandle;
 use std::time::Duration;
 
 use crate::file::{display_path, modified_duration};
-use color_eyre::eyre::Result;
+use color_eyre::eyre::{eyre, Result};
 use flate2::read::ZlibDecoder;
 use flate2::write::ZlibEncoder;
 use flate2::Compression;
-use once_cell::sync::OnceCell;
+use once_cell::sync::{Lazy, OnceCell};
 use serde::de::DeserializeOwned;
 use serde::Serialize;
 
@@ -21,6 +24,7 @@ where
 {
     cache_file_path: PathBuf,
     fresh_duration: Option<Duration>,
+    stale_while_revalidate: Option<Duration>,
     fresh_files: Vec<PathBuf>,
     cache: Box<OnceCell<T>>,
     no_cache: bool,
@@ -36,6 +40,7 @@ where
             cache: Box::new(OnceCell::new()),
             fresh_files: Vec::new(),
             fresh_duration: None,
+            stale_while_revalidate: None,
             no_cache: false,
         }
     }
@@ -45,6 +50,13 @@ where
         self
     }
 
+    /// how long after `fresh_duration` the cache may still be served by `get_or_try_init_swr`
+    /// while it is refreshed in the background
+    pub fn with_stale_while_revalidate(mut self, duration: Duration) -> Self {
+        self.stale_while_revalidate = Some(duration);
+        self
+    }
+
     pub fn with_fresh_file(mut self, path: PathBuf) -> Self {
         self.fresh_files.push(path);
         self
@@ -56,7 +68,9 @@ where
     {
         let val = self.cache.get_or_try_init(|| {
             let path = &self.cache_file_path;
-            if !self.no_cache && self.is_fresh() {
+            let freshness = self.freshness();
+            trace!("{}", freshness.describe(path));
+            if freshness == Freshness::Fresh {
                 match self.parse() {
                     Ok(val) => return Ok::<_, color_eyre::Report>(val),
                     Err(err) => {
@@ -73,6 +87,79 @@ where
         Ok(val)
     }
 
+    /// same as `get_or_try_init` unless `cache_only` is set (`rtx install --cache-only`). Then the
+    /// cache file is used however old it is and `fetch` never runs, it is an error if there is none.
+    pub fn get_or_try_init_cache_only<F>(&self, cache_only: bool, fetch: F) -> Result<&T>
+    where
+        F: FnOnce() -> Result<T>,
+    {
+        if !cache_only {
+            return self.get_or_try_init(fetch);
+        }
+        self.cache.get_or_try_init(|| {
+            let path = &self.cache_file_path;
+            if !path.exists() {
+                return Err(eyre!(
+                    "{} is not cached and --cache-only is set",
+                    display_path(path)
+                ));
+            }
+            self.parse()
+        })
+    }
+
+    /// same as `get_or_try_init` except when the cache expired less than
+    /// `stale_while_revalidate` ago. Then the stale value is returned right away and `fetch`
+    /// runs on a background thread to update the cache file for the next call.
+    /// If the refresh fails the stale value is left in place.
+    /// rtx waits for the refresh with `finish_refreshes` before it exits so it isn't lost.
+    pub fn get_or_try_init_swr<F>(&self, fetch: F) -> Result<&T>
+    where
+        F: FnOnce() -> Result<T> + Send + 'static,
+        T: Send + 'static,
+    {
+        if let Some(val) = self.cache.get() {
+            return Ok(val);
+        }
+        let freshness = self.freshness();
+        if let Freshness::Revalidate(_) = freshness {
+            trace!("{}", freshness.describe(&self.cache_file_path));
+            if let Ok(val) = self.parse() {
+                self.spawn_refresh(fetch);
+                return Ok(self.cache.get_or_init(|| val));
+            }
+        }
+        self.get_or_try_init(fetch)
+    }
+
+    fn spawn_refresh<F>(&self, fetch: F)
+    where
+        F: FnOnce() -> Result<T> + Send + 'static,
+        T: Send + 'static,
+    {
+        let path = self.cache_file_path.clone();
+        let mut refreshing = REFRESHING.lock().unwrap();
+        if matches!(refreshing.get(&path), Some(handle) if !handle.is_finished()) {
+            return;
+        }
+        let handle = thread::spawn({
+            let path = path.clone();
+            move || match fetch().and_then(|val| write_cache_file(&path, &val)) {
+                Ok(()) => trace!("refreshed {}", display_path(&path)),
+                Err(err) => debug!("failed to refresh {}: {:#}", display_path(&path), err),
+            }
+        });
+        refreshing.insert(path, handle);
+    }
+
+    #[cfg(test)]
+    fn wait_for_refresh(&self) {
+        let handle = REFRESHING.lock().unwrap().remove(&self.cache_file_path);
+        if let Some(handle) = handle {
+            handle.join().unwrap();
+        }
+    }
+
     fn parse(&self) -> Result<T> {
         let path = &self.cache_file_path;
         trace!("reading {}", display_path(path));
@@ -83,15 +170,7 @@ where
     }
 
     pub fn write(&self, val: T) -> Result<()> {
-        let path = &self.cache_file_path;
-        trace!("writing {}", display_path(path));
-        if let Some(parent) = path.parent() {
-            fs::create_dir_all(parent)?;
-        }
-        let mut zlib = ZlibEncoder::new(File::create(path)?, Compression::fast());
-        zlib.write_all(&rmp_serde::to_vec_named(&val)?[..])?;
-
-        Ok(())
+        write_cache_file(&self.cache_file_path, &val)
     }
 
     #[allow(dead_code)]
@@ -104,35 +183,123 @@ where
         Ok(())
     }
 
-    fn is_fresh(&self) -> bool {
+    fn freshness(&self) -> Freshness {
+        if self.no_cache {
+            return Freshness::Disabled;
+        }
         if !self.cache_file_path.exists() {
-            return false;
+            return Freshness::Missing;
         }
-        if let Some(fresh_duration) = self.freshest_duration() {
-            if let Ok(metadata) = self.cache_file_path.metadata() {
-                if let Ok(modified) = metadata.modified() {
-                    return modified.elapsed().unwrap_or_default() < fresh_duration;
-                }
+        let age = match self.cache_file_path.metadata().and_then(|m| m.modified()) {
+            Ok(modified) => modified.elapsed().unwrap_or_default(),
+            Err(_) => return Freshness::Fresh,
+        };
+        if let Some(fresh_duration) = self.fresh_duration {
+            if age >= fresh_duration {
+                return match self.stale_while_revalidate {
+                    Some(swr) if age < fresh_duration + swr => {
+                        Freshness::Revalidate(fresh_duration)
+                    }
+                    _ => Freshness::Expired(fresh_duration),
+                };
+            }
+        }
+        for path in &self.fresh_files {
+            // a missing fresh_file is treated as just modified
+            if modified_duration(path).unwrap_or_default() <= age {
+                return Freshness::StaleFile(path.clone());
             }
         }
-        true
+        Freshness::Fresh
     }
+}
 
-    fn freshest_duration(&self) -> Option<Duration> {
-        let mut freshest = self.fresh_duration;
-        for path in &self.fresh_files {
-            let duration = modified_duration(path).unwrap_or_default();
-            freshest = Some(match freshest {
-                None => duration,
-                Some(freshest) => min(freshest, duration),
-            })
+/// background refreshes started by `get_or_try_init_swr`, so only one runs per cache file
+static REFRESHING: Lazy<Mutex<HashMap<PathBuf, JoinHandle<()>>>> = Lazy::new(Default::default);
+
+/// waits for the background refreshes, otherwise they'd be stopped when rtx exits and the stale
+/// value would be served again next time
+pub fn finish_refreshes() {
+    let handles = REFRESHING.lock().unwrap().drain().collect::<Vec<_>>();
+    for (path, handle) in handles {
+        if !handle.is_finished() {
+            trace!("waiting for {} to be refreshed", display_path(&path));
+        }
+        let _ = handle.join();
+    }
+}
+
+/// removes the files in `dir` last written more than `max_age` ago. For caches with a file per key,
+/// which would otherwise keep growing with every key that is no longer used.
+pub fn prune(dir: &Path, max_age: Duration) {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+    for path in entries.filter_map(|e| e.ok()).map(|e| e.path()) {
+        if modified_duration(&path).map_or(false, |age| age > max_age) {
+            trace!("pruning {}", display_path(&path));
+            if let Err(err) = fs::remove_file(&path) {
+                debug!("failed to remove {}: {:#}", display_path(&path), err);
+            }
+        }
+    }
+}
+
+/// writes to a temp file first so a concurrent reader never sees a partial file
+fn write_cache_file<T: Serialize>(path: &Path, val: &T) -> Result<()> {
+    trace!("writing {}", display_path(path));
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let tmp_path = path.with_extension(format!("{}.tmp", std::process::id()));
+    let mut zlib = ZlibEncoder::new(File::create(&tmp_path)?, Compression::fast());
+    zlib.write_all(&rmp_serde::to_vec_named(val)?[..])?;
+    zlib.finish()?;
+    fs::rename(&tmp_path, path)?;
+
+    Ok(())
+}
+
+/// whether a cache file can be used, and if not, why
+#[derive(Debug, PartialEq)]
+enum Freshness {
+    Fresh,
+    Disabled,
+    Missing,
+    Expired(Duration),
+    /// expired, but still within the stale-while-revalidate window
+    Revalidate(Duration),
+    StaleFile(PathBuf),
+}
+
+impl Freshness {
+    fn describe(&self, cache_file: &Path) -> String {
+        let cache_file = display_path(cache_file);
+        match self {
+            Freshness::Fresh => format!("cache hit: {cache_file}"),
+            Freshness::Disabled => format!("cache disabled: {cache_file}"),
+            Freshness::Missing => format!("cache miss: {cache_file} does not exist"),
+            Freshness::Expired(d) => format!(
+                "cache stale: {cache_file} is older than {}",
+                humantime::format_duration(*d)
+            ),
+            Freshness::Revalidate(d) => format!(
+                "cache stale: {cache_file} is older than {}, refreshing in background",
+                humantime::format_duration(*d)
+            ),
+            Freshness::StaleFile(f) => format!(
+                "cache stale: {} was modified after {cache_file}",
+                display_path(f)
+            ),
         }
-        freshest
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::dirs;
+
     use super::*;
 
     #[test]
@@ -145,4 +312,129 @@ mod tests {
         let val = cache.get_or_try_init(|| Ok(2)).unwrap();
         assert_eq!(val, &1);
     }
+
+    #[test]
+    fn test_cache_stale_file() {
+        let dir = dirs::CACHE.join("test_cache_stale_file");
+        let fresh_file = dir.join("fresh_file");
+        let cache_file = dir.join("cache.msgpack.z");
+        let _ = fs::remove_dir_all(&dir);
+        let cache = CacheManager::new(cache_file.clone()).with_fresh_file(fresh_file.clone());
+        assert_eq!(cache.freshness(), Freshness::Missing);
+
+        cache.write(1).unwrap();
+        let now = filetime::FileTime::now();
+        fs::write(&fresh_file, "").unwrap();
+        filetime::set_file_mtime(
+            &cache_file,
+            filetime::FileTime::from_unix_time(now.unix_seconds() - 60, 0),
+        )
+        .unwrap();
+        let freshness = cache.freshness();
+        assert_eq!(freshness, Freshness::StaleFile(fresh_file.clone()));
+        assert_eq!(
+            freshness.describe(&cache_file),
+            format!(
+                "cache stale: {} was modified after {}",
+                display_path(&fresh_file),
+                display_path(&cache_file)
+            )
+        );
+        let val = cache.get_or_try_init(|| Ok(2)).unwrap();
+        assert_eq!(val, &2);
+
+        filetime::set_file_mtime(
+            &fresh_file,
+            filetime::FileTime::from_unix_time(now.unix_seconds() - 120, 0),
+        )
+        .unwrap();
+        assert_eq!(cache.freshness(), Freshness::Fresh);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_cache_stale_while_revalidate() {
+        let dir = dirs::CACHE.join("test_cache_swr");
+        let cache_file = dir.join("cache.msgpack.z");
+        let _ = fs::remove_dir_all(&dir);
+        let new_cache = || {
+            CacheManager::<i32>::new(cache_file.clone())
+                .with_fresh_duration(Some(Duration::from_secs(60)))
+                .with_stale_while_revalidate(Duration::from_secs(60))
+        };
+        let set_age = |secs| {
+            let now = filetime::FileTime::now();
+            filetime::set_file_mtime(
+                &cache_file,
+                filetime::FileTime::from_unix_time(now.unix_seconds() - secs, 0),
+            )
+            .unwrap();
+        };
+
+        // expired 30s ago: the stale value is served and refreshed in the background
+        let cache = new_cache();
+        cache.write(1).unwrap();
+        set_age(90);
+        assert_eq!(
+            cache.freshness(),
+            Freshness::Revalidate(Duration::from_secs(60))
+        );
+        assert_eq!(cache.get_or_try_init_swr(|| Ok(2)).unwrap(), &1);
+        cache.wait_for_refresh();
+        assert_eq!(new_cache().freshness(), Freshness::Fresh);
+        assert_eq!(new_cache().parse().unwrap(), 2);
+
+        // a failed refresh keeps the old value
+        set_age(90);
+        let cache = new_cache();
+        assert_eq!(
+            cache.get_or_try_init_swr(|| Err(eyre!("offline"))).unwrap(),
+            &2
+        );
+        cache.wait_for_refresh();
+        assert_eq!(new_cache().parse().unwrap(), 2);
+
+        // past the window it is fetched before returning
+        set_age(150);
+        let cache = new_cache();
+        assert_eq!(
+            cache.freshness(),
+            Freshness::Expired(Duration::from_secs(60))
+        );
+        assert_eq!(cache.get_or_try_init_swr(|| Ok(3)).unwrap(), &3);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_cache_only() {
+        let dir = dirs::CACHE.join("test_cache_only");
+        let cache_file = dir.join("cache.msgpack.z");
+        let _ = fs::remove_dir_all(&dir);
+        let cache = CacheManager::<i32>::new(cache_file.clone());
+        let err = cache
+            .get_or_try_init_cache_only(true, || panic!("fetch should not be called"))
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "{} is not cached and --cache-only is set",
+                display_path(&cache_file)
+            )
+        );
+
+        cache.write(1).unwrap();
+        let new_cache = || {
+            CacheManager::<i32>::new(cache_file.clone()).with_fresh_duration(Some(Duration::ZERO))
+        };
+        let val = new_cache()
+            .get_or_try_init_cache_only(true, || panic!("fetch should not be called"))
+            .cloned();
+        assert_eq!(val.unwrap(), 1);
+        // without it the expired cache is fetched again
+        let val = new_cache()
+            .get_or_try_init_cache_only(false, || Ok(2))
+            .cloned();
+        assert_eq!(val.unwrap(), 2);
+        fs::remove_dir_all(&dir).unwrap();
+    }
 }
@@ -0,0 +1,51 @@
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+/// installs that are running, SIGTERM only waits for them to clean up when there are some
+static INSTALLING: AtomicUsize = AtomicUsize::new(0);
+
+static CANCELLED: AtomicBool = AtomicBool::new(false);
+
+/// On SIGTERM (e.g.: a canceled CI job) installs in progress are stopped and their partially
+/// written install directories are removed instead of exiting right away like with ctrl-c.
+/// The handler only sets a flag, `CmdLineRunner` sees it and passes SIGTERM on to the script.
+#[cfg(unix)]
+pub fn handle_sigterm() {
+    extern "C" fn on_sigterm(_: libc::c_int) {
+        if INSTALLING.load(Ordering::SeqCst) == 0 {
+            // 128 + SIGTERM, what the shell reports for a process killed by it
+            unsafe { libc::_exit(143) }
+        }
+        cancel();
+    }
+    unsafe {
+        let handler: extern "C" fn(libc::c_int) = on_sigterm;
+        libc::signal(libc::SIGTERM, handler as libc::sighandler_t);
+    }
+}
+
+#[cfg(not(unix))]
+pub fn handle_sigterm() {}
+
+pub fn cancel() {
+    CANCELLED.store(true, Ordering::SeqCst);
+}
+
+pub fn is_cancelled() -> bool {
+    CANCELLED.load(Ordering::SeqCst)
+}
+
+/// marks an install as running until it is dropped
+pub struct Installing;
+
+impl Installing {
+    pub fn start() -> Self {
+        INSTALLING.fetch_add(1, Ordering::SeqCst);
+        Self
+    }
+}
+
+impl Drop for Installing {
+    fn drop(&mut self) {
+        INSTALLING.fetch_sub(1, Ordering::SeqCst);
+    }
+}
@@ -3,7 +3,6 @@ use color_eyre::eyre::Result;
 use crate::cli::command::Command;
 use crate::config::Config;
 use crate::dirs;
-
 use crate::env::RTX_EXE;
 use crate::file::touch_dir;
 use crate::output::Output;
@@ -29,6 +28,15 @@ pub struct Activate {
     #[clap(long)]
     status: bool,
 
+    /// Only add the shims dir to PATH instead of updating it on every prompt
+    ///
+    /// Nothing runs when changing directories so there is no prompt overhead,
+    /// but every call to a tool goes through a shim which is slower. Env vars
+    /// set by plugins and `[env]` are only seen by processes started through a shim.
+    /// Run `rtx reshim` if a new bin doesn't have a shim yet.
+    #[clap(long, verbatim_doc_comment, conflicts_with = "status")]
+    shims: bool,
+
     /// noop
     #[clap(long, short, hide = true)]
     quiet: bool,
@@ -42,7 +50,10 @@ impl Command for Activate {
         // touch ROOT to allow hook-env to run
         let _ = touch_dir(&dirs::ROOT);
 
-        let output = shell.activate(&RTX_EXE, self.status);
+        let output = match self.shims {
+            true => shell.activate_shims(&dirs::SHIMS),
+            false => shell.activate(&RTX_EXE, self.status),
+        };
         out.stdout.write(output);
 
         Ok(())
@@ -55,5 +66,6 @@ static AFTER_LONG_HELP: &str = color_print::cstr!(
   $ <bold>eval "$(rtx activate zsh)"</bold>
   $ <bold>rtx activate fish | source</bold>
   $ <bold>execx($(rtx activate xonsh))</bold>
+  $ <bold>eval "$(rtx activate bash --shims)"</bold>  # only use shims
 "#
 );
@@ -1,4 +1,4 @@
-use color_eyre::eyre::Result;
+use color_eyre::eyre::{eyre, Result};
 
 use crate::cli::command::Command;
 use crate::config::config_file::ConfigFile;
@@ -8,6 +8,7 @@ use crate::output::Output;
 /// Add/update an alias for a plugin
 ///
 /// This modifies the contents of ~/.config/rtx/config.toml
+/// The alias and value can't be empty or contain whitespace or "@".
 #[derive(Debug, clap::Args)]
 #[clap(visible_aliases = ["add", "create"], after_long_help = AFTER_LONG_HELP, verbatim_doc_comment)]
 pub struct AliasSet {
@@ -21,6 +22,11 @@ pub struct AliasSet {
 
 impl Command for AliasSet {
     fn run(self, mut config: Config, _out: &mut Output) -> Result<()> {
+        validate_name("alias", &self.alias)?;
+        validate_name("value", &self.value)?;
+        if self.alias == self.value {
+            return Err(eyre!("alias {} cannot point to itself", self.alias));
+        }
         config
             .global_config
             .set_alias(&self.plugin, &self.alias, &self.value);
@@ -28,6 +34,13 @@ impl Command for AliasSet {
     }
 }
 
+fn validate_name(kind: &str, name: &str) -> Result<()> {
+    if name.is_empty() || name.contains(char::is_whitespace) || name.contains('@') {
+        return Err(eyre!("invalid {}: {:?}", kind, name));
+    }
+    Ok(())
+}
+
 static AFTER_LONG_HELP: &str = color_print::cstr!(
     r#"<bold><underline>Examples:</underline></bold>
   $ <bold>rtx alias set node lts/hydrogen 18.0.0</bold>
@@ -36,8 +49,11 @@ static AFTER_LONG_HELP: &str = color_print::cstr!(
 
 #[cfg(test)]
 pub mod tests {
+    use pretty_assertions::assert_str_eq;
+
+    use crate::config::Config;
     use crate::test::reset_config;
-    use crate::{assert_cli, assert_cli_snapshot};
+    use crate::{assert_cli, assert_cli_err, assert_cli_snapshot};
 
     #[test]
     fn test_alias_set() {
@@ -47,4 +63,34 @@ pub mod tests {
         assert_cli_snapshot!("aliases");
         reset_config();
     }
+
+    #[test]
+    fn test_alias_set_resolve() {
+        reset_config();
+        assert_cli!("alias", "set", "tiny", "my/new-alias", "3.1.0");
+
+        let config = Config::load().unwrap();
+        let v = config
+            .resolve_alias(&"tiny".into(), "my/new-alias")
+            .unwrap();
+        assert_str_eq!(v, "3.1.0");
+
+        assert_cli!("alias", "unset", "tiny", "my/new-alias");
+        let config = Config::load().unwrap();
+        let v = config
+            .resolve_alias(&"tiny".into(), "my/new-alias")
+            .unwrap();
+        assert_str_eq!(v, "my/new-alias");
+        reset_config();
+    }
+
+    #[test]
+    fn test_alias_set_invalid() {
+        let err = assert_cli_err!("alias", "set", "tiny", "my alias", "3.1.0");
+        assert_str_eq!(err.to_string(), "invalid alias: \"my alias\"");
+        let err = assert_cli_err!("alias", "set", "tiny", "lts", "tiny@3.1.0");
+        assert_str_eq!(err.to_string(), "invalid value: \"tiny@3.1.0\"");
+        let err = assert_cli_err!("alias", "set", "tiny", "lts", "lts");
+        assert_str_eq!(err.to_string(), "alias lts cannot point to itself");
+    }
 }
@@ -0,0 +1,16 @@
+use clap::{Arg, ArgAction};
+
+/// read before clap parses anything since config is loaded first, see `env::CONFIG_FILE_ARGS`.
+/// It isn't global so it can only go before the subcommand, where it is looked for.
+pub struct ConfigFile;
+
+impl ConfigFile {
+    pub fn arg() -> Arg {
+        Arg::new("config")
+            .long("config")
+            .value_name("FILE")
+            .help("Use this config file instead of looking for config files in the current directory and its parents")
+            .long_help("Use this config file instead of looking for config files in the current directory and its parents\nCan be used more than once, later files take precedence. The global config is still loaded.")
+            .action(ArgAction::Append)
+    }
+}
@@ -0,0 +1,15 @@
+use clap::{Arg, ArgAction};
+
+pub struct Json(pub bool);
+
+impl Json {
+    /// subcommands with a --json flag of their own keep it, clap doesn't propagate a global arg
+    /// to a subcommand that already has one with the same id
+    pub fn arg() -> Arg {
+        Arg::new("json")
+            .long("json")
+            .help("Output json for the commands that support it, e.g.: rtx ls --json")
+            .action(ArgAction::SetTrue)
+            .global(true)
+    }
+}
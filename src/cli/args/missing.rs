@@ -0,0 +1,27 @@
+use clap::builder::{PossibleValuesParser, TypedValueParser};
+use clap::Arg;
+
+use crate::config::MissingRuntimeBehavior;
+
+pub struct Missing(pub MissingRuntimeBehavior);
+
+impl Missing {
+    pub fn arg() -> Arg {
+        Arg::new("missing")
+            .long("missing")
+            .value_name("BEHAVIOR")
+            .help("What to do when a tool version is not installed, for this command only\noverrides missing_runtime_behavior and RTX_MISSING_RUNTIME_BEHAVIOR")
+            .value_parser(
+                PossibleValuesParser::new([
+                    "autoinstall",
+                    "autoinstall_prompt",
+                    "prompt",
+                    "warn",
+                    "ignore",
+                ])
+                    .map(|s| s.parse::<MissingRuntimeBehavior>().unwrap()),
+            )
+            .conflicts_with("install-missing")
+            .global(true)
+    }
+}
@@ -1,6 +1,10 @@
+pub mod config_file;
 pub mod install_missing;
 pub mod jobs;
+pub mod json;
 pub mod log_level;
+pub mod missing;
+pub mod no_lock;
 pub mod raw;
 pub mod tool;
 pub mod verbose;
@@ -0,0 +1,13 @@
+use clap::{Arg, ArgAction};
+
+pub struct NoLock(pub bool);
+
+impl NoLock {
+    pub fn arg() -> Arg {
+        Arg::new("no-lock")
+            .long("no-lock")
+            .help("Don't wait for other rtx processes that are installing or changing plugins")
+            .action(ArgAction::SetTrue)
+            .global(true)
+    }
+}
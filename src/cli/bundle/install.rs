@@ -0,0 +1,165 @@
+use std::path::PathBuf;
+
+use color_eyre::eyre::Result;
+use console::style;
+use itertools::Itertools;
+
+use crate::cli::command::Command;
+use crate::config::config_file::rtx_toml::RtxToml;
+use crate::config::config_file::{self, ConfigFile};
+use crate::config::Config;
+use crate::config::MissingRuntimeBehavior::AutoInstall;
+use crate::output::Output;
+use crate::plugins::ExternalPlugin;
+use crate::tool::Tool;
+use crate::ui::multi_progress_report::MultiProgressReport;
+
+/// Install the plugins and tool versions in a bundle file
+///
+/// A bundle uses the same syntax as .rtx.toml but only [plugins] and [tools]
+/// are used. Plugins are added first (using the url in the bundle if it is
+/// not already installed) then any missing tool versions are installed.
+/// Nothing is written to any config file.
+#[derive(Debug, clap::Args)]
+#[clap(verbatim_doc_comment, after_long_help = AFTER_LONG_HELP)]
+pub struct BundleInstall {
+    /// Path to the bundle file
+    #[clap(default_value = "rtx.bundle.toml", value_hint = clap::ValueHint::FilePath)]
+    file: PathBuf,
+}
+
+impl Command for BundleInstall {
+    fn run(self, mut config: Config, out: &mut Output) -> Result<()> {
+        let is_trusted = config_file::is_trusted(&config.settings, &self.file);
+        let bundle = RtxToml::from_file(&self.file, is_trusted)?;
+        let mpr = MultiProgressReport::new(&config.settings);
+
+        for (name, urls) in bundle.plugins().into_iter().sorted() {
+            if config.get_or_create_tool(&name).is_installed() {
+                rtxprintln!(out, "plugin {} already installed", name);
+                continue;
+            }
+            let mut plugin = ExternalPlugin::new(&name);
+            plugin.repo_urls = urls;
+            let tool = Tool::new(name.clone(), Box::new(plugin));
+            let mut pr = mpr.add();
+            tool.decorate_progress_bar(&mut pr, None);
+            tool.install(&config, &mut pr, false)?;
+            rtxprintln!(out, "plugin {} installed", name);
+        }
+
+        config.settings.missing_runtime_behavior = AutoInstall;
+        let mut ts = bundle.to_toolset().clone();
+        ts.resolve(&mut config);
+        let missing = ts
+            .list_missing_versions(&config)
+            .into_iter()
+            .map(|tv| tv.to_string())
+            .collect_vec();
+        ts.install_missing(&mut config, mpr)?;
+        for tv in ts
+            .list_current_versions(&config)
+            .into_iter()
+            .map(|(_, tv)| tv)
+        {
+            match missing.contains(&tv.to_string()) {
+                true => rtxprintln!(out, "{} installed", style(tv).cyan()),
+                false => rtxprintln!(out, "{} already installed", style(tv).cyan()),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+static AFTER_LONG_HELP: &str = color_print::cstr!(
+    r#"<bold><underline>Examples:</underline></bold>
+  $ <bold>cat rtx.bundle.toml</bold>
+  [plugins]
+  node = "https://github.com/asdf-vm/asdf-nodejs"
+
+  [tools]
+  node = "20"
+  python = ["3.11", "3.10"]
+
+  $ <bold>rtx bundle install</bold>
+  plugin node installed
+  node@20.0.0 installed
+  python@3.11.4 already installed
+  python@3.10.12 installed
+"#
+);
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use indoc::indoc;
+    use pretty_assertions::assert_str_eq;
+
+    use crate::file::remove_all;
+    use crate::test::git_commit;
+    use crate::{assert_cli, cmd, dirs};
+
+    #[test]
+    fn test_bundle_install() {
+        let bundle = dirs::HOME.join("rtx.bundle.toml");
+        fs::write(
+            &bundle,
+            indoc! {r#"
+                [plugins]
+                tiny = "https://github.com/rtx-plugins/rtx-tiny"
+
+                [tools]
+                tiny = "2.0.1"
+            "#},
+        )
+        .unwrap();
+        assert_cli!("uninstall", "tiny@2.0.1");
+        let stdout = assert_cli!("bundle", "install", bundle.to_string_lossy());
+        assert_str_eq!(
+            stdout,
+            "plugin tiny already installed\ntiny@2.0.1 installed\n"
+        );
+        let stdout = assert_cli!("bundle", "install", bundle.to_string_lossy());
+        assert_str_eq!(
+            stdout,
+            "plugin tiny already installed\ntiny@2.0.1 already installed\n"
+        );
+        assert_cli!("uninstall", "tiny@2.0.1");
+        fs::remove_file(bundle).unwrap();
+    }
+
+    #[test]
+    fn test_bundle_install_missing_plugin() {
+        let repo = dirs::CACHE.join("bundle-plugin");
+        let _ = remove_all(&repo);
+        let _ = remove_all(dirs::PLUGINS.join("bundle-tiny"));
+        cmd!("cp", "-r", dirs::PLUGINS.join("tiny"), &repo)
+            .run()
+            .unwrap();
+        git_commit(&repo, "init");
+        let bundle = dirs::HOME.join("rtx.missing.bundle.toml");
+        fs::write(
+            &bundle,
+            format!(
+                "[plugins]\nbundle-tiny = \"file://{}\"\n\n[tools]\nbundle-tiny = \"3.1.0\"\n",
+                repo.display()
+            ),
+        )
+        .unwrap();
+
+        let stdout = assert_cli!("bundle", "install", bundle.to_string_lossy());
+        let installed = dirs::INSTALLS.join("bundle-tiny/3.1.0").exists();
+        remove_all(dirs::PLUGINS.join("bundle-tiny")).unwrap();
+        let _ = remove_all(dirs::INSTALLS.join("bundle-tiny"));
+        let _ = remove_all(dirs::CACHE.join("bundle-tiny"));
+        remove_all(&repo).unwrap();
+        fs::remove_file(bundle).unwrap();
+        assert_str_eq!(
+            stdout,
+            "plugin bundle-tiny installed\nbundle-tiny@3.1.0 installed\n"
+        );
+        assert!(installed);
+    }
+}
@@ -0,0 +1,35 @@
+use clap::Subcommand;
+use color_eyre::eyre::Result;
+
+use crate::cli::command::Command;
+use crate::config::Config;
+use crate::output::Output;
+
+mod install;
+
+/// Install plugins and tools declared in a bundle file
+#[derive(Debug, clap::Args)]
+#[clap(verbatim_doc_comment)]
+pub struct Bundle {
+    #[clap(subcommand)]
+    command: Commands,
+}
+
+#[derive(Debug, Subcommand)]
+enum Commands {
+    Install(install::BundleInstall),
+}
+
+impl Commands {
+    pub fn run(self, config: Config, out: &mut Output) -> Result<()> {
+        match self {
+            Self::Install(cmd) => cmd.run(config, out),
+        }
+    }
+}
+
+impl Command for Bundle {
+    fn run(self, config: Config, out: &mut Output) -> Result<()> {
+        self.command.run(config, out)
+    }
+}
@@ -0,0 +1,45 @@
+use color_eyre::eyre::Result;
+
+use crate::cli::command::Command;
+use crate::config::Config;
+use crate::dirs;
+use crate::output::Output;
+
+/// Prints the rtx cache directory
+///
+/// This is RTX_CACHE_DIR if set, otherwise ~/.cache/rtx
+#[derive(Debug, clap::Args)]
+#[clap(verbatim_doc_comment, after_long_help = AFTER_LONG_HELP)]
+pub struct CacheDir {}
+
+impl Command for CacheDir {
+    fn run(self, _config: Config, out: &mut Output) -> Result<()> {
+        rtxprintln!(out, "{}", dirs::CACHE.display());
+        Ok(())
+    }
+}
+
+static AFTER_LONG_HELP: &str = color_print::cstr!(
+    r#"<bold><underline>Examples:</underline></bold>
+  $ <bold>rtx cache dir</bold>
+  /home/user/.cache/rtx
+"#
+);
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_str_eq;
+
+    use crate::assert_cli;
+    use crate::env;
+
+    #[test]
+    fn test_cache_dir() {
+        let stdout = assert_cli!("cache", "dir");
+        // RTX_CACHE_DIR is set by the test setup
+        assert_str_eq!(
+            stdout.trim(),
+            env::HOME.join("data/cache").display().to_string()
+        );
+    }
+}
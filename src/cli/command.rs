@@ -1,4 +1,4 @@
-use color_eyre::eyre::Result;
+use color_eyre::eyre::{eyre, Report, Result};
 
 use crate::config::Config;
 use crate::output::Output;
@@ -10,3 +10,19 @@ pub trait Command: Sized {
     /// CLI command entry point
     fn run(self, config: Config, output: &mut Output) -> Result<()>;
 }
+
+/// A command with a `--json` flag of its own, the global `--json` turns it on
+///
+/// e.g.: `rtx --json current` is the same as `rtx current --json`
+pub trait JsonOutput: Command {
+    /// the command with its `--json` flag set
+    fn json(self) -> Result<Self>;
+}
+
+pub fn run_json<C: JsonOutput>(cmd: C, config: Config, out: &mut Output) -> Result<()> {
+    cmd.json()?.run(config, out)
+}
+
+pub fn json_not_supported() -> Report {
+    eyre!("json not supported for this command")
+}
@@ -0,0 +1,114 @@
+use std::fs;
+use std::path::PathBuf;
+
+use clap_complete::Shell;
+use color_eyre::eyre::{eyre, Result};
+
+use crate::cli::command::Command;
+use crate::cli::completion::render;
+use crate::config::Config;
+use crate::env;
+use crate::file::{create_dir_all, display_path};
+use crate::output::Output;
+use crate::shell::ShellType;
+
+/// Install shell completions
+///
+/// Writes the completion script for the shell to the location it loads completions from:
+///   bash: ~/.local/share/bash-completion/completions/rtx
+///   fish: ~/.config/fish/completions/rtx.fish
+///   zsh:  ~/.zfunc/_rtx (this directory must be in your fpath)
+#[derive(Debug, clap::Args)]
+#[clap(verbatim_doc_comment, after_long_help = AFTER_LONG_HELP)]
+pub struct CompletionInstall {
+    /// Shell type to install completions for
+    /// defaults to the current shell
+    #[clap(long, short, verbatim_doc_comment)]
+    shell: Option<Shell>,
+
+    /// Print the completions instead of writing them to a file
+    #[clap(long)]
+    print: bool,
+}
+
+impl Command for CompletionInstall {
+    fn run(self, _config: Config, out: &mut Output) -> Result<()> {
+        let shell = match self.shell.or_else(current_shell) {
+            Some(shell) => shell,
+            None => Err(eyre!("unable to detect shell, specify one with --shell"))?,
+        };
+        let script = render(shell);
+        if self.print {
+            rtxprintln!(out, "{}", script);
+            return Ok(());
+        }
+        let path = completion_path(shell)?;
+        create_dir_all(path.parent().unwrap())?;
+        fs::write(&path, script)?;
+        rtxstatusln!(
+            out,
+            "installed {} completions to {}",
+            shell,
+            display_path(&path)
+        );
+        if shell == Shell::Zsh {
+            rtxstatusln!(
+                out,
+                "ensure {} is in your fpath before compinit runs",
+                display_path(path.parent().unwrap())
+            );
+        }
+        Ok(())
+    }
+}
+
+fn current_shell() -> Option<Shell> {
+    match ShellType::load()? {
+        ShellType::Bash => Some(Shell::Bash),
+        ShellType::Fish => Some(Shell::Fish),
+        ShellType::Zsh => Some(Shell::Zsh),
+        _ => None,
+    }
+}
+
+fn completion_path(shell: Shell) -> Result<PathBuf> {
+    let path = match shell {
+        Shell::Bash => env::XDG_DATA_HOME.join("bash-completion/completions/rtx"),
+        Shell::Fish => env::XDG_CONFIG_HOME.join("fish/completions/rtx.fish"),
+        Shell::Zsh => env::HOME.join(".zfunc/_rtx"),
+        _ => Err(eyre!(
+            "installing completions is not supported for {}",
+            shell
+        ))?,
+    };
+    Ok(path)
+}
+
+static AFTER_LONG_HELP: &str = color_print::cstr!(
+    r#"<bold><underline>Examples:</underline></bold>
+  $ <bold>rtx completion install</bold>
+  $ <bold>rtx completion install --shell fish</bold>
+  $ <bold>rtx completion install --shell zsh --print</bold>
+"#
+);
+
+#[cfg(test)]
+mod tests {
+    use crate::file::remove_all;
+    use crate::{assert_cli, dirs};
+
+    #[test]
+    fn test_completion_install() {
+        let _ = remove_all(dirs::HOME.join(".zfunc"));
+        assert_cli!("completion", "install", "--shell", "zsh");
+        let script = std::fs::read_to_string(dirs::HOME.join(".zfunc/_rtx")).unwrap();
+        assert!(script.contains("#compdef rtx"));
+        remove_all(dirs::HOME.join(".zfunc")).unwrap();
+    }
+
+    #[test]
+    fn test_completion_install_print() {
+        let stdout = assert_cli!("shell-completion", "install", "-s", "fish", "--print");
+        assert!(stdout.contains("complete -c rtx"));
+    }
+}
@@ -1,5 +1,6 @@
 use std::io::Cursor;
 
+use clap::Subcommand;
 use clap_complete::generate;
 use color_eyre::eyre::Result;
 
@@ -8,10 +9,15 @@ use crate::cli::Cli;
 use crate::config::Config;
 use crate::output::Output;
 
+mod install;
+
 /// Generate shell completions
 #[derive(Debug, clap::Args)]
-#[clap(alias = "complete", verbatim_doc_comment, after_long_help = AFTER_LONG_HELP)]
+#[clap(aliases = ["complete", "shell-completion"], verbatim_doc_comment, args_conflicts_with_subcommands = true, after_long_help = AFTER_LONG_HELP)]
 pub struct Completion {
+    #[clap(subcommand)]
+    command: Option<Commands>,
+
     /// Shell type to generate completions for
     #[clap()]
     shell: Option<clap_complete::Shell>,
@@ -21,25 +27,48 @@ pub struct Completion {
     shell_type: Option<clap_complete::Shell>,
 }
 
+#[derive(Debug, Subcommand)]
+enum Commands {
+    Install(install::CompletionInstall),
+}
+
+impl Commands {
+    pub fn run(self, config: Config, out: &mut Output) -> Result<()> {
+        match self {
+            Self::Install(cmd) => cmd.run(config, out),
+        }
+    }
+}
+
 impl Command for Completion {
-    fn run(self, _config: Config, out: &mut Output) -> Result<()> {
+    fn run(self, config: Config, out: &mut Output) -> Result<()> {
+        if let Some(cmd) = self.command {
+            return cmd.run(config, out);
+        }
         let shell = match self.shell.or(self.shell_type) {
             Some(shell) => shell,
             None => panic!("no shell provided"),
         };
 
-        let mut c = Cursor::new(Vec::new());
-        generate(shell, &mut Cli::command(), "rtx", &mut c);
-        rtxprintln!(out, "{}", String::from_utf8(c.into_inner()).unwrap());
+        rtxprintln!(out, "{}", render(shell));
 
         Ok(())
     }
 }
 
+pub fn render(shell: clap_complete::Shell) -> String {
+    let mut c = Cursor::new(Vec::new());
+    generate(shell, &mut Cli::command(), "rtx", &mut c);
+    String::from_utf8(c.into_inner()).unwrap()
+}
+
 static AFTER_LONG_HELP: &str = color_print::cstr!(
     r#"<bold><underline>Examples:</underline></bold>
   $ <bold>rtx completion bash > /etc/bash_completion.d/rtx</bold>
   $ <bold>rtx completion zsh  > /usr/local/share/zsh/site-functions/_rtx</bold>
   $ <bold>rtx completion fish > ~/.config/fish/completions/rtx.fish</bold>
+
+  # write completions for the current shell to where it will find them
+  $ <bold>rtx completion install</bold>
 "#
 );
@@ -0,0 +1,157 @@
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+
+use color_eyre::eyre::Result;
+use console::style;
+use serde_derive::Serialize;
+
+use crate::cli::command::{Command, JsonOutput};
+use crate::config::{config_file, global_config_filenames, Config};
+use crate::file::display_path;
+use crate::output::{to_json, Output};
+use crate::plugins::PluginName;
+
+/// List config files rtx reads in the current directory
+///
+/// These are listed in order of precedence, the first file has the highest precedence.
+/// Next to each file are the tools whose versions come from it, tools set in more than
+/// one file are only shown next to the one that wins, e.g.: a .rtx.local.toml overriding
+/// the .rtx.toml beside it.
+/// Files that do not exist are shown as "missing" and config files that have not been
+/// trusted with `rtx trust` are shown as "untrusted".
+#[derive(Debug, clap::Args)]
+#[clap(visible_alias = "list", verbatim_doc_comment, after_long_help = AFTER_LONG_HELP)]
+pub struct ConfigLs {
+    /// Output in json format
+    #[clap(long, short = 'J')]
+    pub json: bool,
+}
+
+#[derive(Serialize)]
+struct JSONConfigFile {
+    path: PathBuf,
+    exists: bool,
+    trusted: bool,
+    tools: Vec<PluginName>,
+}
+
+impl JsonOutput for ConfigLs {
+    fn json(mut self) -> Result<Self> {
+        self.json = true;
+        Ok(self)
+    }
+}
+
+impl Command for ConfigLs {
+    fn run(self, config: Config, out: &mut Output) -> Result<()> {
+        let mut paths = config.config_files.keys().cloned().collect::<Vec<_>>();
+        for path in global_config_filenames() {
+            if !paths.contains(&path) {
+                paths.push(path);
+            }
+        }
+        let mut tools = tool_sources(&config);
+        let files = paths
+            .into_iter()
+            .map(|path| JSONConfigFile {
+                exists: path.exists(),
+                trusted: config_file::is_trusted(&config.settings, &path),
+                tools: tools.remove(&path).unwrap_or_default(),
+                path,
+            })
+            .collect::<Vec<_>>();
+
+        if self.json {
+            rtxprintln!(out, "{}", to_json("config_files", &files)?);
+            return Ok(());
+        }
+        let width = files
+            .iter()
+            .map(|f| display_path(&f.path).len())
+            .max()
+            .unwrap_or_default();
+        for f in files {
+            let path = display_path(&f.path);
+            match (f.exists, f.trusted) {
+                (false, _) => {
+                    rtxprintln!(out, "{:width$} {}", path, style("missing").dim());
+                }
+                (true, false) => {
+                    rtxprintln!(out, "{:width$} {}", path, style("untrusted").yellow());
+                }
+                (true, true) if f.tools.is_empty() => rtxprintln!(out, "{}", path),
+                (true, true) => {
+                    rtxprintln!(out, "{:width$} {}", path, f.tools.join(" "));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// the config file each tool's versions come from, the first file that sets a tool wins
+fn tool_sources(config: &Config) -> HashMap<PathBuf, Vec<PluginName>> {
+    let mut sources: HashMap<PathBuf, Vec<PluginName>> = HashMap::new();
+    let mut seen = HashSet::new();
+    for (path, cf) in &config.config_files {
+        for plugin in cf.to_toolset().versions.keys() {
+            if seen.insert(plugin.clone()) {
+                sources
+                    .entry(path.clone())
+                    .or_default()
+                    .push(plugin.clone());
+            }
+        }
+    }
+    sources
+}
+
+static AFTER_LONG_HELP: &str = color_print::cstr!(
+    r#"<bold><underline>Examples:</underline></bold>
+  $ <bold>rtx config ls</bold>
+  ~/src/myproj/.rtx.local.toml node
+  ~/src/myproj/.rtx.toml       python shellcheck
+  ~/src/myproj/.tool-versions
+  ~/.tool-versions             missing
+  ~/.config/rtx/config.toml
+"#
+);
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use crate::assert_cli;
+
+    #[test]
+    fn test_config_ls() {
+        let stdout = assert_cli!("config", "ls");
+        let paths = stdout
+            .lines()
+            .map(|l| l.split_whitespace().next().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            paths,
+            vec![
+                "~/cwd/.test-tool-versions",
+                "~/.test-tool-versions",
+                "~/config/config.toml"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_config_ls_json() {
+        let stdout = assert_cli!("cfg", "ls", "--json");
+        let files: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+        let files = files["config_files"].as_array().unwrap();
+        assert!(files.iter().all(|f| f["exists"] == true));
+        assert!(files[0]["path"]
+            .as_str()
+            .unwrap()
+            .ends_with("cwd/.test-tool-versions"));
+        // tiny is also in ~/.test-tool-versions but is overridden by the closer file
+        assert_eq!(files[0]["tools"], serde_json::json!(["tiny"]));
+        assert_eq!(files[1]["tools"], serde_json::json!(["dummy"]));
+    }
+}
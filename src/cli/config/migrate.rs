@@ -0,0 +1,146 @@
+use std::path::PathBuf;
+
+use clap::ValueHint;
+use color_eyre::eyre::{eyre, Result};
+
+use crate::cli::command::Command;
+use crate::config::config_file::rtx_toml::RtxToml;
+use crate::config::config_file::tool_versions::ToolVersions;
+use crate::config::config_file::{self, ConfigFile};
+use crate::config::Config;
+use crate::env::{RTX_DEFAULT_CONFIG_FILENAME, RTX_DEFAULT_TOOL_VERSIONS_FILENAME};
+use crate::file::display_path;
+use crate::output::Output;
+use crate::{dirs, file};
+
+/// Convert a .tool-versions file to .rtx.toml
+///
+/// Every version of each tool is written to the [tools] table of the .rtx.toml
+/// in the same directory. If that file already exists the tools are added to it,
+/// replacing the versions of tools that are in both files.
+#[derive(Debug, clap::Args)]
+#[clap(verbatim_doc_comment, after_long_help = AFTER_LONG_HELP)]
+pub struct ConfigMigrate {
+    /// The .tool-versions file to convert
+    /// defaults to the one in the current directory
+    #[clap(value_hint = ValueHint::FilePath, verbatim_doc_comment)]
+    path: Option<PathBuf>,
+
+    /// Delete the .tool-versions file once converted
+    #[clap(long)]
+    remove: bool,
+
+    /// Print the resulting .rtx.toml instead of writing it
+    #[clap(long, short = 'n', conflicts_with = "remove")]
+    dry_run: bool,
+}
+
+impl Command for ConfigMigrate {
+    fn run(self, config: Config, out: &mut Output) -> Result<()> {
+        let path = self
+            .path
+            .clone()
+            .unwrap_or_else(|| dirs::CURRENT.join(&*RTX_DEFAULT_TOOL_VERSIONS_FILENAME));
+        if !path.is_file() {
+            return Err(eyre!("{} does not exist", display_path(&path)));
+        }
+        let tool_versions =
+            ToolVersions::from_file(&path, config_file::is_trusted(&config.settings, &path))?;
+
+        let rtx_toml_path = path.parent().unwrap().join(&*RTX_DEFAULT_CONFIG_FILENAME);
+        let is_trusted = config_file::is_trusted(&config.settings, &rtx_toml_path);
+        let mut rtx_toml = match rtx_toml_path.exists() {
+            true => RtxToml::from_file(&rtx_toml_path, is_trusted)?,
+            false => RtxToml::init(&rtx_toml_path, is_trusted),
+        };
+        for (plugin, tvl) in &tool_versions.to_toolset().versions {
+            rtx_toml.replace_requests(plugin, &tvl.requests);
+        }
+
+        if self.dry_run {
+            rtxprint!(out, "{}", rtx_toml.dump());
+            return Ok(());
+        }
+        rtx_toml.save()?;
+        rtxstatusln!(
+            out,
+            "migrated {} to {}",
+            display_path(&path),
+            display_path(&rtx_toml_path)
+        );
+        if self.remove {
+            file::remove_all(&path)?;
+            rtxstatusln!(out, "removed {}", display_path(&path));
+        }
+        Ok(())
+    }
+}
+
+static AFTER_LONG_HELP: &str = color_print::cstr!(
+    r#"<bold><underline>Examples:</underline></bold>
+  $ <bold>cat .tool-versions</bold>
+  node 20.0.0 18.16.0
+  python 3.11
+
+  $ <bold>rtx config migrate --remove</bold>
+  $ <bold>cat .rtx.toml</bold>
+  [tools]
+  node = ["20.0.0", "18.16.0"]
+  python = "3.11"
+"#
+);
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use insta::assert_snapshot;
+    use pretty_assertions::assert_eq;
+
+    use crate::config::config_file;
+    use crate::config::Config;
+    use crate::file::remove_all;
+    use crate::{assert_cli, dirs};
+
+    #[test]
+    fn test_config_migrate() {
+        let dir = dirs::CACHE.join("config-migrate");
+        let _ = remove_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(".test-tool-versions");
+        fs::write(&path, "tiny 2 3.1.0\ndummy ref:master\n").unwrap();
+
+        let dry_run = assert_cli!("config", "migrate", "--dry-run", path.to_str().unwrap());
+        assert!(!dir.join(".test.rtx.toml").exists());
+        assert_cli!("config", "migrate", "--remove", path.to_str().unwrap());
+        let rtx_toml = fs::read_to_string(dir.join(".test.rtx.toml")).unwrap();
+        assert_eq!(dry_run, rtx_toml);
+        assert_snapshot!(rtx_toml, @r###"
+        [tools]
+        tiny = ["2", "3.1.0"]
+        dummy = "ref:master"
+        "###);
+        assert!(!path.exists());
+
+        // the new file resolves to the same versions as the old one
+        let mut config = Config::load().unwrap();
+        let resolve = |config: &mut Config, path: &std::path::Path| {
+            let cf = config_file::parse(path, true).unwrap();
+            let mut ts = cf.to_toolset().clone();
+            ts.resolve(config);
+            ts.list_current_versions(config)
+                .into_iter()
+                .map(|(_, tv)| tv.to_string())
+                .collect::<Vec<_>>()
+        };
+        let migrated = resolve(&mut config, &dir.join(".test.rtx.toml"));
+        fs::write(&path, "tiny 2 3.1.0\ndummy ref:master\n").unwrap();
+        let original = resolve(&mut config, &path);
+        remove_all(&dir).unwrap();
+        assert_eq!(migrated, original);
+        assert_eq!(
+            migrated,
+            vec!["tiny@2.1.0", "tiny@3.1.0", "dummy@ref:master"]
+        );
+    }
+}
@@ -0,0 +1,52 @@
+use clap::Subcommand;
+use color_eyre::eyre::Result;
+
+use crate::cli::command::{json_not_supported, run_json, Command};
+use crate::config::Config;
+use crate::output::Output;
+
+mod ls;
+mod migrate;
+
+#[derive(Debug, clap::Args)]
+#[clap(about = "Manage config files", visible_alias = "cfg")]
+pub struct Cfg {
+    #[clap(subcommand)]
+    command: Option<Commands>,
+}
+
+#[derive(Debug, Subcommand)]
+enum Commands {
+    Ls(ls::ConfigLs),
+    Migrate(migrate::ConfigMigrate),
+}
+
+impl Commands {
+    pub fn run(self, config: Config, out: &mut Output) -> Result<()> {
+        match self {
+            Self::Ls(cmd) => cmd.run(config, out),
+            Self::Migrate(cmd) => cmd.run(config, out),
+        }
+    }
+}
+
+impl Command for Cfg {
+    fn run(self, config: Config, out: &mut Output) -> Result<()> {
+        let cmd = self
+            .command
+            .unwrap_or(Commands::Ls(ls::ConfigLs { json: false }));
+
+        cmd.run(config, out)
+    }
+}
+
+impl Cfg {
+    /// `rtx --json config` and `rtx --json config ls`
+    pub fn run_json(self, config: Config, out: &mut Output) -> Result<()> {
+        match self.command {
+            None => run_json(ls::ConfigLs { json: true }, config, out),
+            Some(Commands::Ls(cmd)) => run_json(cmd, config, out),
+            Some(_) => Err(json_not_supported()),
+        }
+    }
+}
@@ -1,9 +1,13 @@
-use color_eyre::eyre::Result;
+use color_eyre::eyre::{eyre, Result};
 
-use crate::cli::command::Command;
+use indexmap::IndexMap;
+use serde_derive::Serialize;
+
+use crate::cli::command::{Command, JsonOutput};
 
 use crate::config::Config;
-use crate::output::Output;
+use crate::errors::Error::{Exit, PluginNotInstalled};
+use crate::output::{to_json, Output};
 use crate::tool::Tool;
 use crate::toolset::{Toolset, ToolsetBuilder};
 
@@ -11,6 +15,8 @@ use crate::toolset::{Toolset, ToolsetBuilder};
 ///
 /// This is similar to `rtx ls --current`, but this only shows the runtime
 /// and/or version. It's designed to fit into scripts more easily.
+/// When a plugin is given, this exits non-zero if it has no active version.
+/// With --quiet nothing is printed and it also exits non-zero if that version is not installed.
 #[derive(Debug, clap::Args)]
 #[clap(verbatim_doc_comment, after_long_help = AFTER_LONG_HELP)]
 pub struct Current {
@@ -18,29 +24,83 @@ pub struct Current {
     /// e.g.: ruby, node
     #[clap()]
     plugin: Option<String>,
+
+    /// Only set the exit code: 0 if the plugin's active versions are installed
+    #[clap(long, short, requires = "plugin")]
+    quiet: bool,
+
+    /// Output in json format
+    /// each active version with the version it was requested as and where it is set
+    #[clap(long, short = 'J', conflicts_with = "quiet", verbatim_doc_comment)]
+    json: bool,
 }
 
 impl Command for Current {
     fn run(self, mut config: Config, out: &mut Output) -> Result<()> {
         let ts = ToolsetBuilder::new().build(&mut config)?;
+        if self.json {
+            return self.json_output(&config, &ts, out);
+        }
         match &self.plugin {
             Some(plugin_name) => match config.tools.get(plugin_name) {
+                Some(plugin) if self.quiet => self.quiet(&config, &ts, plugin),
                 Some(plugin) => self.one(&config, ts, out, plugin),
-                None => {
-                    warn!("Plugin {} is not installed", plugin_name);
-                    Ok(())
-                }
+                None if self.quiet => Err(Exit(1))?,
+                None => Err(PluginNotInstalled(plugin_name.clone()))?,
             },
             None => self.all(&config, ts, out),
         }
     }
 }
 
+#[derive(Serialize)]
+struct JSONCurrentVersion {
+    plugin: String,
+    version: String,
+    requested_version: String,
+    installed: bool,
+    source: IndexMap<String, String>,
+}
+
+impl JsonOutput for Current {
+    fn json(mut self) -> Result<Self> {
+        if self.quiet {
+            return Err(eyre!("--json can't be used with --quiet"));
+        }
+        self.json = true;
+        Ok(self)
+    }
+}
+
 impl Current {
+    fn json_output(&self, config: &Config, ts: &Toolset, out: &mut Output) -> Result<()> {
+        if let Some(plugin_name) = &self.plugin {
+            if !config.tools.contains_key(plugin_name) {
+                Err(PluginNotInstalled(plugin_name.clone()))?;
+            }
+        }
+        let rows = ts
+            .list_current_versions(config)
+            .into_iter()
+            .filter(|(p, _)| match &self.plugin {
+                Some(plugin_name) => &p.name == plugin_name,
+                None => true,
+            })
+            .map(|(p, tv)| JSONCurrentVersion {
+                plugin: p.name.clone(),
+                requested_version: tv.request.version(),
+                installed: p.is_version_installed(&tv),
+                source: ts.versions[&tv.plugin_name].source.as_json(),
+                version: tv.version,
+            })
+            .collect::<Vec<_>>();
+        rtxprintln!(out, "{}", to_json("tools", &rows)?);
+        Ok(())
+    }
+
     fn one(&self, config: &Config, ts: Toolset, out: &mut Output, tool: &Tool) -> Result<()> {
         if !tool.is_installed() {
-            warn!("Plugin {} is not installed", tool.name);
-            return Ok(());
+            Err(PluginNotInstalled(tool.name.clone()))?;
         }
         match ts
             .list_versions_by_plugin(config)
@@ -58,13 +118,28 @@ impl Current {
                         .join(" ")
                 );
             }
-            None => {
-                warn!("Plugin {} does not have a version set", tool.name);
-            }
+            None => Err(eyre!("Plugin {} does not have a version set", tool.name))?,
         };
         Ok(())
     }
 
+    fn quiet(&self, config: &Config, ts: &Toolset, tool: &Tool) -> Result<()> {
+        let versions = ts
+            .list_versions_by_plugin(config)
+            .into_iter()
+            .find(|(p, _)| p.name == tool.name)
+            .map(|(_, versions)| versions);
+        match versions {
+            Some(versions) if tool.is_installed() && !versions.is_empty() => {
+                match versions.iter().all(|tv| tool.is_version_installed(tv)) {
+                    true => Ok(()),
+                    false => Err(Exit(1))?,
+                }
+            }
+            _ => Err(Exit(1))?,
+        }
+    }
+
     fn all(&self, config: &Config, ts: Toolset, out: &mut Output) -> Result<()> {
         for (plugin, versions) in ts.list_versions_by_plugin(config) {
             if versions.is_empty() {
@@ -109,6 +184,9 @@ static AFTER_LONG_HELP: &str = color_print::cstr!(
   # can output multiple versions
   $ <bold>rtx current python</bold>
   3.11.0 3.10.0
+
+  # check that node is active and installed in a script
+  $ <bold>if rtx current --quiet node; then echo "using rtx node"; fi</bold>
 "#
 );
 
@@ -116,7 +194,14 @@ static AFTER_LONG_HELP: &str = color_print::cstr!(
 mod tests {
     use std::env;
 
-    use crate::{assert_cli, assert_cli_snapshot};
+    use pretty_assertions::assert_str_eq;
+
+    use crate::config::Config;
+    use crate::errors::Error;
+    use crate::toolset::{ToolSource, ToolVersionOptions, ToolVersionRequest, Toolset};
+    use crate::{assert_cli, assert_cli_err, assert_cli_snapshot};
+
+    use super::Current;
 
     #[test]
     fn test_current() {
@@ -128,6 +213,14 @@ mod tests {
         assert_cli_snapshot!("current", "tiny");
     }
 
+    #[test]
+    fn test_current_one_not_active() {
+        let err = assert_cli_err!("current", "python");
+        assert_str_eq!(err.to_string(), "Plugin python does not have a version set");
+        let err = assert_cli_err!("current", "not-a-plugin");
+        assert_str_eq!(err.to_string(), "[not-a-plugin] plugin not installed");
+    }
+
     #[test]
     fn test_current_missing() {
         assert_cli!("uninstall", "dummy@1.0.1");
@@ -137,4 +230,53 @@ mod tests {
 
         env::remove_var("RTX_DUMMY_VERSION");
     }
+
+    #[test]
+    fn test_current_quiet() {
+        let exit_status = |err: color_eyre::Report| match err.downcast_ref() {
+            Some(Error::Exit(code)) => *code,
+            _ => panic!("expected an exit status, got: {err}"),
+        };
+        // active and installed
+        assert_str_eq!(assert_cli!("current", "--quiet", "tiny"), "");
+        // inactive
+        assert_eq!(exit_status(assert_cli_err!("current", "-q", "python")), 1);
+        assert_eq!(
+            exit_status(assert_cli_err!("current", "-q", "not-a-plugin")),
+            1
+        );
+
+        // active but not installed
+        let mut config = Config::load().unwrap();
+        let mut ts = Toolset::new(ToolSource::Argument);
+        let tvr = ToolVersionRequest::Version("tiny".into(), "0.0.1".into());
+        ts.add_version(tvr, ToolVersionOptions::new());
+        ts.resolve(&mut config);
+        let current = Current {
+            plugin: Some("tiny".into()),
+            quiet: true,
+            json: false,
+        };
+        let tool = config.tools.get("tiny").unwrap();
+        let err = current.quiet(&config, &ts, tool).unwrap_err();
+        assert_eq!(exit_status(err), 1);
+    }
+
+    #[test]
+    fn test_current_json() {
+        let doc: serde_json::Value =
+            serde_json::from_str(&assert_cli!("--json", "current", "tiny")).unwrap();
+        assert_eq!(
+            doc,
+            serde_json::from_str::<serde_json::Value>(&assert_cli!("current", "tiny", "--json"))
+                .unwrap()
+        );
+        let rows = &doc["tools"];
+        assert_eq!(rows.as_array().unwrap().len(), 1);
+        assert_eq!(rows[0]["plugin"], "tiny");
+        assert_eq!(rows[0]["version"], "3.1.0");
+        assert_eq!(rows[0]["requested_version"], "3");
+        assert_eq!(rows[0]["installed"], true);
+        assert_eq!(rows[0]["source"]["type"], ".tool-versions");
+    }
 }
@@ -0,0 +1,112 @@
+use std::path::PathBuf;
+
+use color_eyre::eyre::{eyre, Result};
+use indexmap::IndexMap;
+
+use crate::cli::command::{Command, JsonOutput};
+use crate::config::Config;
+use crate::dirs;
+use crate::output::{to_json, Output};
+
+/// Prints the rtx data directory
+///
+/// This is RTX_DATA_DIR if set, otherwise ~/.local/share/rtx.
+/// Use --all to see every directory rtx uses, e.g.: when filing a bug report.
+#[derive(Debug, clap::Args)]
+#[clap(verbatim_doc_comment, after_long_help = AFTER_LONG_HELP)]
+pub struct DataDir {
+    /// Print all of rtx's directories, not just the data directory
+    #[clap(long, short)]
+    all: bool,
+
+    /// Output in json format
+    #[clap(long, short = 'J', requires = "all")]
+    json: bool,
+}
+
+impl JsonOutput for DataDir {
+    fn json(mut self) -> Result<Self> {
+        if !self.all {
+            return Err(eyre!("--json requires --all"));
+        }
+        self.json = true;
+        Ok(self)
+    }
+}
+
+impl Command for DataDir {
+    fn run(self, _config: Config, out: &mut Output) -> Result<()> {
+        if !self.all {
+            rtxprintln!(out, "{}", dirs::ROOT.display());
+            return Ok(());
+        }
+        let dirs = all_dirs();
+        if self.json {
+            rtxprintln!(out, "{}", to_json("dirs", &dirs)?);
+        } else {
+            for (name, path) in dirs {
+                rtxprintln!(out, "{:10} {}", name, path.display());
+            }
+        }
+        Ok(())
+    }
+}
+
+fn all_dirs() -> IndexMap<&'static str, PathBuf> {
+    IndexMap::from([
+        ("data", dirs::ROOT.clone()),
+        ("cache", dirs::CACHE.clone()),
+        ("config", dirs::CONFIG.clone()),
+        ("plugins", dirs::PLUGINS.clone()),
+        ("downloads", dirs::DOWNLOADS.clone()),
+        ("installs", dirs::INSTALLS.clone()),
+        ("shims", dirs::SHIMS.clone()),
+    ])
+}
+
+static AFTER_LONG_HELP: &str = color_print::cstr!(
+    r#"<bold><underline>Examples:</underline></bold>
+  $ <bold>rtx data dir</bold>
+  /home/user/.local/share/rtx
+
+  $ <bold>rtx data dir --all</bold>
+  data       /home/user/.local/share/rtx
+  cache      /home/user/.cache/rtx
+  config     /home/user/.config/rtx
+  plugins    /home/user/.local/share/rtx/plugins
+  downloads  /home/user/.local/share/rtx/downloads
+  installs   /home/user/.local/share/rtx/installs
+  shims      /home/user/.local/share/rtx/shims
+"#
+);
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_str_eq;
+
+    use crate::cli::tests::grep;
+    use crate::{assert_cli, env};
+
+    #[test]
+    fn test_data_dir() {
+        // RTX_DATA_DIR is set by the test setup
+        let stdout = assert_cli!("data", "dir");
+        assert_str_eq!(stdout.trim(), env::HOME.join("data").display().to_string());
+    }
+
+    #[test]
+    fn test_data_dir_all() {
+        let stdout = assert_cli!("data", "dir", "--all");
+        let installs = env::HOME.join("data/installs");
+        assert_str_eq!(grep(stdout, "installs"), "installs   ~/data/installs");
+
+        let stdout = assert_cli!("data", "dir", "--all", "--json");
+        let doc: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+        let dirs = &doc["dirs"];
+        assert_eq!(dirs["installs"], installs.display().to_string());
+        assert_eq!(
+            dirs["cache"],
+            env::HOME.join("data/cache").display().to_string()
+        );
+    }
+}
@@ -0,0 +1,44 @@
+use clap::Subcommand;
+use color_eyre::eyre::Result;
+
+use crate::cli::command::{run_json, Command};
+use crate::config::Config;
+use crate::output::Output;
+
+mod dir;
+
+/// Show where rtx keeps its data
+#[derive(Debug, clap::Args)]
+#[clap(verbatim_doc_comment)]
+pub struct Data {
+    #[clap(subcommand)]
+    command: Commands,
+}
+
+#[derive(Debug, Subcommand)]
+enum Commands {
+    Dir(dir::DataDir),
+}
+
+impl Commands {
+    pub fn run(self, config: Config, out: &mut Output) -> Result<()> {
+        match self {
+            Self::Dir(cmd) => cmd.run(config, out),
+        }
+    }
+}
+
+impl Command for Data {
+    fn run(self, config: Config, out: &mut Output) -> Result<()> {
+        self.command.run(config, out)
+    }
+}
+
+impl Data {
+    /// `rtx --json data dir --all`
+    pub fn run_json(self, config: Config, out: &mut Output) -> Result<()> {
+        match self.command {
+            Commands::Dir(cmd) => run_json(cmd, config, out),
+        }
+    }
+}
@@ -6,7 +6,7 @@ use color_eyre::eyre::Result;
 
 use crate::cli::command::Command;
 use crate::config::Config;
-use crate::config::MissingRuntimeBehavior::{Prompt, Warn};
+use crate::config::MissingRuntimeBehavior::{AutoInstallPrompt, Prompt, Warn};
 use crate::hash::hash_to_str;
 use crate::output::Output;
 use crate::toolset::ToolsetBuilder;
@@ -20,7 +20,10 @@ pub struct Envrc {}
 
 impl Command for Envrc {
     fn run(self, mut config: Config, out: &mut Output) -> Result<()> {
-        if config.settings.missing_runtime_behavior == Prompt {
+        if matches!(
+            config.settings.missing_runtime_behavior,
+            Prompt | AutoInstallPrompt
+        ) {
             config.settings.missing_runtime_behavior = Warn;
         }
         let ts = ToolsetBuilder::new()
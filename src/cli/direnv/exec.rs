@@ -5,7 +5,7 @@ use serde_derive::Deserialize;
 use crate::cli::command::Command;
 use crate::cmd;
 use crate::config::Config;
-use crate::config::MissingRuntimeBehavior::{Prompt, Warn};
+use crate::config::MissingRuntimeBehavior::{AutoInstallPrompt, Prompt, Warn};
 use crate::output::Output;
 use crate::toolset::ToolsetBuilder;
 
@@ -23,7 +23,10 @@ struct DirenvWatches {
 
 impl Command for DirenvExec {
     fn run(self, mut config: Config, out: &mut Output) -> Result<()> {
-        if config.settings.missing_runtime_behavior == Prompt {
+        if matches!(
+            config.settings.missing_runtime_behavior,
+            Prompt | AutoInstallPrompt
+        ) {
             config.settings.missing_runtime_behavior = Warn;
         }
         let ts = ToolsetBuilder::new()
@@ -1,26 +1,36 @@
 use std::fmt::Write;
 use std::process::exit;
+use std::time::Duration;
 
 use color_eyre::eyre::Result;
 use console::{pad_str, style, Alignment};
 use indenter::indented;
+use rayon::prelude::*;
 
 use crate::build_time::built_info;
 use crate::cli::command::Command;
 use crate::cli::version::VERSION;
-use crate::config::Config;
+use crate::config::{Config, Settings};
 use crate::git::Git;
 use crate::output::Output;
 use crate::plugins::PluginType;
 use crate::shell::ShellType;
 use crate::toolset::ToolsetBuilder;
-use crate::{cli, cmd};
+use crate::{cli, cmd, dirs};
 use crate::{duration, env};
 
 /// Check rtx installation for possible problems.
 #[derive(Debug, clap::Args)]
 #[clap(verbatim_doc_comment, after_long_help = AFTER_LONG_HELP)]
-pub struct Doctor {}
+pub struct Doctor {
+    /// Skip checks that need network access
+    /// e.g.: checking that plugin repositories are reachable
+    #[clap(long, verbatim_doc_comment)]
+    offline: bool,
+}
+
+/// how long each plugin repository gets to respond to `git ls-remote`
+const REACHABILITY_TIMEOUT: Duration = Duration::from_secs(10);
 
 impl Command for Doctor {
     fn run(self, mut config: Config, out: &mut Output) -> Result<()> {
@@ -52,12 +62,22 @@ impl Command for Doctor {
             }
         }
 
-        if let Some(latest) = cli::version::check_for_new_version(duration::HOURLY) {
-            checks.push(format!(
-                "new rtx version {} available, currently on {}",
-                latest,
-                env!("CARGO_PKG_VERSION")
+        if !self.offline {
+            let sources = plugin_sources(&config);
+            checks.extend(unreachable_sources(
+                &config.settings,
+                &sources,
+                REACHABILITY_TIMEOUT,
             ));
+            if let Some(latest) =
+                cli::version::check_for_new_version(&config.settings, duration::HOURLY)
+            {
+                checks.push(format!(
+                    "new rtx version {} available, currently on {}",
+                    latest,
+                    env!("CARGO_PKG_VERSION")
+                ));
+            }
         }
 
         if !config.is_activated() {
@@ -136,6 +156,42 @@ fn render_plugins(config: &Config) -> String {
     s
 }
 
+/// (plugin name, remote url) of every installed plugin that came from a git repository
+fn plugin_sources(config: &Config) -> Vec<(String, String)> {
+    config
+        .tools
+        .values()
+        .filter(|p| p.is_installed() && matches!(p.plugin.get_type(), PluginType::External))
+        .filter_map(|p| {
+            let git = Git::new(p.plugin_path.clone());
+            if !git.is_repo() {
+                return None;
+            }
+            git.get_remote_url().map(|url| (p.name.clone(), url))
+        })
+        .collect()
+}
+
+/// checks every source at the same time so one slow host doesn't add up with the others
+fn unreachable_sources(
+    settings: &Settings,
+    sources: &[(String, String)],
+    timeout: Duration,
+) -> Vec<String> {
+    sources
+        .par_iter()
+        .filter_map(|(name, url)| {
+            let git = Git::new(dirs::PLUGINS.join(name)).with_proxy(settings.proxy_for(url));
+            match git.ls_remote(url, timeout) {
+                Ok(()) => None,
+                Err(err) => Some(format!(
+                    "plugin {name} repository {url} is not reachable: {err:#}"
+                )),
+            }
+        })
+        .collect()
+}
+
 fn rtx_version() -> String {
     let mut s = style("rtx version:\n").bold().to_string();
     s.push_str(&format!("  {}\n", *VERSION));
@@ -182,5 +238,38 @@ static AFTER_LONG_HELP: &str = color_print::cstr!(
     r#"<bold><underline>Examples:</underline></bold>
   $ <bold>rtx doctor</bold>
   [WARN] plugin node is not installed
+
+  $ <bold>rtx doctor --offline</bold>  # skip the network checks
 "#
 );
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use crate::file::remove_all;
+
+    use super::*;
+
+    #[test]
+    fn test_unreachable_sources() {
+        let repo = dirs::CACHE.join("doctor-reachable-repo");
+        let _ = remove_all(&repo);
+        cmd!("git", "init", "-q", &repo).run().unwrap();
+        let missing = dirs::CACHE.join("doctor-missing-repo");
+        let sources = vec![
+            ("reachable".to_string(), repo.to_string_lossy().to_string()),
+            (
+                "unreachable".to_string(),
+                missing.to_string_lossy().to_string(),
+            ),
+        ];
+        let problems = unreachable_sources(&Settings::default(), &sources, Duration::from_secs(10));
+        assert_eq!(problems.len(), 1);
+        assert!(problems[0].starts_with(&format!(
+            "plugin unreachable repository {} is not reachable",
+            missing.display()
+        )));
+        remove_all(&repo).unwrap();
+    }
+}
@@ -1,11 +1,26 @@
-use color_eyre::eyre::Result;
+use std::collections::{BTreeMap, HashMap};
+use std::env::{join_paths, split_paths};
+use std::fmt::Write;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
+use color_eyre::eyre::{eyre, Result};
+use itertools::Itertools;
+
+use crate::cache;
 use crate::cli::args::tool::{ToolArg, ToolArgParser};
-use crate::cli::command::Command;
+use crate::cli::command::{Command, JsonOutput};
 use crate::config::Config;
-use crate::output::Output;
+use crate::env::__RTX_DIFF;
+use crate::env_diff::EnvDiff;
+use crate::file::{display_path, modified_duration};
+use crate::hash::hash_to_str;
+use crate::output::{to_json, Output};
+use crate::plugins::PluginName;
 use crate::shell::{get_shell, ShellType};
 use crate::toolset::{Toolset, ToolsetBuilder};
+use crate::{dirs, env};
 
 /// Exports env vars to activate rtx a single time
 ///
@@ -15,7 +30,7 @@ use crate::toolset::{Toolset, ToolsetBuilder};
 #[clap(visible_alias = "e", verbatim_doc_comment, after_long_help = AFTER_LONG_HELP)]
 pub struct Env {
     /// Shell type to generate environment variables for
-    #[clap(long, short, overrides_with = "json")]
+    #[clap(long, short, overrides_with_all = ["json", "dotenv", "docker"])]
     shell: Option<ShellType>,
 
     /// Tool(s) to use
@@ -23,41 +38,313 @@ pub struct Env {
     tool: Vec<ToolArg>,
 
     /// Output in JSON format
-    #[clap(long, visible_short_alias = 'J', overrides_with = "shell")]
+    /// [env] from config files overrides vars set by the tools' exec-env scripts
+    #[clap(long, visible_short_alias = 'J', verbatim_doc_comment, overrides_with_all = ["shell", "dotenv", "docker"])]
     json: bool,
+
+    /// Output KEY=VALUE lines for a .env file
+    /// values with spaces or special characters are double-quoted, newlines become \n
+    #[clap(long, verbatim_doc_comment, overrides_with_all = ["shell", "json", "docker"])]
+    dotenv: bool,
+
+    /// Output ENV instructions for a Dockerfile
+    /// PATH keeps the image's own PATH by referencing $PATH
+    #[clap(long, verbatim_doc_comment, overrides_with_all = ["shell", "json", "dotenv"])]
+    docker: bool,
+
+    /// Only output the names of the vars rtx would remove, one per line
+    /// these were set for tools that are no longer active or are unset by exec-env scripts
+    #[clap(long, verbatim_doc_comment, conflicts_with_all = ["json", "dotenv", "docker"])]
+    unset: bool,
+
+    /// Only output the bin paths and exec-env vars of this tool
+    /// [env] and env_path from config files are not included
+    #[clap(long, value_name = "TOOL", verbatim_doc_comment)]
+    only: Option<PluginName>,
+
+    /// Add a directory to the start of PATH, before rtx's tools
+    /// can be passed multiple times
+    #[clap(long, value_name = "DIR", verbatim_doc_comment)]
+    prepend_path: Vec<PathBuf>,
+
+    /// Add a directory to the end of PATH
+    /// can be passed multiple times
+    #[clap(long, value_name = "DIR", verbatim_doc_comment)]
+    append_path: Vec<PathBuf>,
+}
+
+impl JsonOutput for Env {
+    fn json(mut self) -> Result<Self> {
+        if self.unset {
+            return Err(eyre!("--json can't be used with --unset"));
+        }
+        self.json = true;
+        self.shell = None;
+        self.dotenv = false;
+        self.docker = false;
+        Ok(self)
+    }
 }
 
 impl Command for Env {
     fn run(self, mut config: Config, out: &mut Output) -> Result<()> {
+        let cache_path = self.cache_path(&config);
+        if let Some(rendered) = read_cache(&cache_path) {
+            rtxprint!(out, "{}", rendered);
+            return Ok(());
+        }
         let ts = ToolsetBuilder::new()
             .with_install_missing()
             .with_args(&self.tool)
             .build(&mut config)?;
-        if self.json {
-            self.output_json(config, out, ts)
+        if config.settings.warn_shadowed_bins {
+            warn_shadowed_bins(&config, &ts);
+        }
+        let rendered = if self.unset {
+            self.render_unset(config, ts)?
+        } else if self.json {
+            self.render_json(config, ts)?
+        } else if self.dotenv {
+            self.render_dotenv(config, ts)?
+        } else if self.docker {
+            self.render_docker(config, ts)?
         } else {
-            self.output_shell(config, out, ts)
+            self.render_shell(config, ts)?
+        };
+        if let Err(err) = write_cache(&cache_path, &rendered) {
+            debug!("failed to write {}: {:#}", display_path(&cache_path), err);
         }
+        rtxprint!(out, "{}", rendered);
+        Ok(())
     }
 }
 
 impl Env {
-    fn output_json(&self, config: Config, out: &mut Output, ts: Toolset) -> Result<()> {
-        let env = ts.env_with_path(&config);
-        rtxprintln!(out, "{}", serde_json::to_string_pretty(&env)?);
-        Ok(())
+    fn render_json(&self, config: Config, ts: Toolset) -> Result<String> {
+        let env = self.build_env(&config, ts);
+        Ok(to_json("env", &env)? + "\n")
+    }
+
+    fn render_dotenv(&self, config: Config, ts: Toolset) -> Result<String> {
+        let mut s = String::new();
+        for (k, v) in self.build_env(&config, ts) {
+            writeln!(s, "{}={}", k, dotenv_quote(&v))?;
+        }
+        Ok(s)
+    }
+
+    fn render_docker(&self, config: Config, ts: Toolset) -> Result<String> {
+        let mut s = String::new();
+        for (k, v) in self.build_env(&config, ts) {
+            if k == "PATH" {
+                writeln!(s, "ENV PATH=\"{}\"", docker_path(&v, &env::PATH))?;
+            } else if v.contains('\n') {
+                warn!("{k} contains a newline which a Dockerfile ENV can't hold, skipping it");
+            } else {
+                writeln!(s, "ENV {}=\"{}\"", k, docker_escape(&v))?;
+            }
+        }
+        Ok(s)
+    }
+
+    fn render_unset(&self, config: Config, mut ts: Toolset) -> Result<String> {
+        if let Some(plugin) = &self.only {
+            ts.versions.retain(|p, _| p == plugin);
+        }
+        let removed = ts.exec_env_unset(&config);
+        let env = ts.env(&config);
+        let mut s = String::new();
+        for k in vars_to_unset(&__RTX_DIFF, &env, removed, &env::PRISTINE_ENV) {
+            writeln!(s, "{}", k)?;
+        }
+        Ok(s)
     }
 
-    fn output_shell(&self, config: Config, out: &mut Output, ts: Toolset) -> Result<()> {
+    fn render_shell(&self, config: Config, ts: Toolset) -> Result<String> {
         let default_shell = get_shell(Some(ShellType::Bash)).unwrap();
         let shell = get_shell(self.shell).unwrap_or(default_shell);
-        for (k, v) in ts.env_with_path(&config) {
+        let mut s = String::new();
+        for (k, v) in self.build_env(&config, ts) {
             let k = k.to_string();
             let v = v.to_string();
-            rtxprint!(out, "{}", shell.set_env(&k, &v));
+            s.push_str(&shell.set_env(&k, &v));
         }
-        Ok(())
+        Ok(s)
+    }
+
+    /// scripts often call `rtx env` over and over from the same place, so the output is kept
+    /// for a few seconds under a key made from what it depends on: these args, the shell, the
+    /// settings, config files and what is installed, the env rtx was run with and the cwd
+    fn cache_path(&self, config: &Config) -> PathBuf {
+        let modified = |p: &Path| p.metadata().and_then(|m| m.modified()).ok();
+        let config_files = config
+            .config_files
+            .keys()
+            .map(|p| (p.clone(), modified(p)))
+            .collect_vec();
+        let installs = fs::read_dir(&*dirs::INSTALLS)
+            .into_iter()
+            .flatten()
+            .filter_map(|e| e.ok())
+            .map(|e| (e.file_name(), modified(&e.path())))
+            .sorted()
+            .collect_vec();
+        let shell = format!("{:?}", self.shell.or_else(ShellType::load));
+        let pristine_env = env::PRISTINE_ENV.iter().sorted().collect_vec();
+        let key = hash_to_str(&(
+            format!("{self:?}"),
+            shell,
+            format!("{:?}", config.settings),
+            config_files,
+            installs,
+            &config.env,
+            &config.path_dirs,
+            pristine_env,
+            &*dirs::CURRENT,
+        ));
+        dirs::STATE.join("env").join(key)
+    }
+
+    fn build_env(&self, config: &Config, mut ts: Toolset) -> BTreeMap<String, String> {
+        let mut env = match &self.only {
+            Some(plugin) => {
+                ts.versions.retain(|p, _| p == plugin);
+                let mut env = ts.exec_env(config);
+                let path = join_paths([ts.list_paths(config), env::PATH.clone()].concat()).unwrap();
+                env.insert("PATH".into(), path.to_string_lossy().into());
+                env
+            }
+            None => ts.env_with_path(config),
+        };
+        if !self.prepend_path.is_empty() || !self.append_path.is_empty() {
+            let path = self.inject_paths(&env["PATH"]);
+            env.insert("PATH".into(), path);
+        }
+        env
+    }
+
+    fn inject_paths(&self, path: &str) -> String {
+        for dir in self.prepend_path.iter().chain(&self.append_path) {
+            if !dir.is_dir() {
+                warn!("{} is not a directory", display_path(dir));
+            }
+        }
+        let paths = [
+            self.prepend_path.clone(),
+            split_paths(path).collect(),
+            self.append_path.clone(),
+        ]
+        .concat();
+        join_paths(paths).unwrap().to_string_lossy().into()
+    }
+}
+
+/// how long the output of `rtx env` is reused for
+const CACHE_TTL: Duration = Duration::from_secs(5);
+
+fn read_cache(path: &Path) -> Option<String> {
+    match modified_duration(path) {
+        Ok(age) if age < CACHE_TTL => fs::read_to_string(path).ok(),
+        _ => None,
+    }
+}
+
+fn write_cache(path: &Path, rendered: &str) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+        // each cwd and change to the config gets its own file, only the last few seconds are used
+        cache::prune(parent, CACHE_TTL);
+    }
+    // written to a temp file first so a concurrent `rtx env` never reads it half written
+    let tmp_path = path.with_extension(format!("{}.tmp", std::process::id()));
+    fs::write(&tmp_path, rendered)?;
+    fs::rename(tmp_path, path)?;
+    Ok(())
+}
+
+fn warn_shadowed_bins(config: &Config, ts: &Toolset) {
+    let shadowed = ts.list_shadowed_bins(config, &env::PATH);
+    if shadowed.is_empty() {
+        return;
     }
+    let bins = shadowed
+        .iter()
+        .map(|(name, hidden)| format!("{} (hides {})", name, display_path(hidden)))
+        .join(", ");
+    warn!(
+        "bins from rtx take precedence over ones already on PATH: {}",
+        bins
+    );
+}
+
+/// vars a previous activation added that nothing sets anymore and the ones exec-env scripts
+/// remove that are currently set, vars rtx changed are restored rather than unset
+fn vars_to_unset(
+    prev: &EnvDiff,
+    env: &BTreeMap<String, String>,
+    removed: Vec<String>,
+    pristine: &HashMap<String, String>,
+) -> Vec<String> {
+    let deactivated = prev
+        .new
+        .keys()
+        .filter(|k| !prev.old.contains_key(*k) && !env.contains_key(*k))
+        .cloned();
+    let removed = removed.into_iter().filter(|k| pristine.contains_key(k));
+    deactivated.chain(removed).sorted().dedup().collect()
+}
+
+/// leaves simple values bare and double-quotes the rest the way dotenv parsers read them back:
+/// `\\`, `"` and `$` are escaped (the last so nothing gets interpolated) and newlines become `\n`
+fn dotenv_quote(v: &str) -> String {
+    let is_bare = |c: char| c.is_ascii_alphanumeric() || "/._-:,@+%".contains(c);
+    if v.chars().all(is_bare) {
+        return v.to_string();
+    }
+    let mut quoted = String::with_capacity(v.len() + 2);
+    quoted.push('"');
+    for c in v.chars() {
+        match c {
+            '\\' => quoted.push_str("\\\\"),
+            '"' => quoted.push_str("\\\""),
+            '$' => quoted.push_str("\\$"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// the dirs rtx adds around the current PATH, which is replaced with the image's `$PATH`
+fn docker_path(path: &str, current: &[PathBuf]) -> String {
+    let (mut before, mut after) = (vec![], vec![]);
+    let mut seen_current = false;
+    for p in split_paths(path) {
+        if current.contains(&p) {
+            seen_current = true;
+        } else if seen_current {
+            after.push(docker_escape(&p.to_string_lossy()));
+        } else {
+            before.push(docker_escape(&p.to_string_lossy()));
+        }
+    }
+    before.push("$PATH".into());
+    before.extend(after);
+    before.join(":")
+}
+
+/// escapes what is special inside double quotes in a Dockerfile, `$` so it isn't substituted
+fn docker_escape(v: &str) -> String {
+    let mut escaped = String::with_capacity(v.len());
+    for c in v.chars() {
+        if matches!(c, '\\' | '"' | '$') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
 }
 
 static AFTER_LONG_HELP: &str = color_print::cstr!(
@@ -66,19 +353,37 @@ static AFTER_LONG_HELP: &str = color_print::cstr!(
   $ <bold>eval "$(rtx env -s zsh)"</bold>
   $ <bold>rtx env -s fish | source</bold>
   $ <bold>execx($(rtx env -s xonsh))</bold>
+  $ <bold>rtx env --only node -J</bold>          # only what the node plugin sets
+  $ <bold>rtx env --prepend-path ./bin</bold>     # ./bin comes before rtx's tools in PATH
+  $ <bold>rtx env --dotenv > .env</bold>          # KEY=VALUE lines for docker compose and dotenv libraries
+  $ <bold>rtx env --docker >> Dockerfile</bold>   # ENV instructions for a Dockerfile
+  $ <bold>rtx env --unset</bold>                  # names of the vars to remove from the shell
 "#
 );
 
 #[cfg(test)]
 mod tests {
-    use std::env;
+    use std::collections::{BTreeMap, HashMap};
+    use std::path::PathBuf;
+    use std::{env, fs};
 
+    use filetime::FileTime;
     use pretty_assertions::assert_str_eq;
 
+    use crate::cli::command::Command;
     use crate::cli::tests::grep;
+    use crate::config::config_file::rtx_toml::RtxToml;
+    use crate::config::config_file::ConfigFile;
+    use crate::config::Config;
     use crate::dirs;
+    use crate::env_diff::EnvDiff;
+    use crate::file::{make_symlink, remove_all};
+    use crate::output::Output;
+    use crate::shell::ShellType;
     use crate::{assert_cli, assert_cli_snapshot};
 
+    use super::{docker_escape, docker_path, dotenv_quote, vars_to_unset, Env};
+
     #[test]
     fn test_env() {
         let stdout = assert_cli!("env", "-s", "bash");
@@ -133,4 +438,218 @@ mod tests {
     fn test_env_json() {
         assert_cli_snapshot!("env", "-J");
     }
+
+    fn parse_env_json(stdout: &str) -> BTreeMap<String, String> {
+        let doc: serde_json::Value = serde_json::from_str(stdout).unwrap();
+        serde_json::from_value(doc["env"].clone()).unwrap()
+    }
+
+    #[test]
+    fn test_env_json_project_env() {
+        let dir = dirs::CACHE.join("env-json-project-env");
+        let _ = remove_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(".test.rtx.toml");
+        fs::write(
+            &path,
+            "[env]\nJDXCODE_TINY = \"project-{{ 1 + 1 }}\"\nPROJECT_ROOT = \"{{ config_root }}\"\n",
+        )
+        .unwrap();
+        let cf = RtxToml::from_file(&path, true).unwrap();
+        let mut config = Config::load().unwrap();
+        config.env.extend(cf.env());
+
+        let cmd = Env {
+            shell: None,
+            tool: vec![],
+            json: true,
+            dotenv: false,
+            docker: false,
+            unset: false,
+            only: None,
+            prepend_path: vec![],
+            append_path: vec![],
+        };
+        let mut out = Output::tracked();
+        cmd.run(config, &mut out).unwrap();
+        remove_all(&dir).unwrap();
+        let env = parse_env_json(&out.stdout.content);
+        assert_eq!(env["JDXCODE_TINY"], "project-2");
+        assert_eq!(env["PROJECT_ROOT"], dir.to_string_lossy());
+        assert!(env.contains_key("PATH"));
+    }
+
+    #[test]
+    fn test_env_prepend_append_path() {
+        let first = dirs::HOME.join("cwd");
+        let last = dirs::HOME.join("fixtures");
+        let stdout = assert_cli!(
+            "env",
+            "-J",
+            "--prepend-path",
+            first.to_string_lossy().as_ref(),
+            "--append-path",
+            last.to_string_lossy().as_ref()
+        );
+        let env = parse_env_json(&stdout);
+        let paths: Vec<PathBuf> = env::split_paths(&env["PATH"]).collect();
+        assert_eq!(paths.first(), Some(&first));
+        assert_eq!(paths.last(), Some(&last));
+        assert!(paths[1]
+            .to_string_lossy()
+            .starts_with(dirs::INSTALLS.to_string_lossy().as_ref()));
+    }
+
+    #[test]
+    fn test_env_only() {
+        let stdout = assert_cli!("env", "--only", "tiny", "-s", "bash");
+        assert_str_eq!(grep(stdout.clone(), "JDXCODE"), "export JDXCODE_TINY=3.1.0");
+        assert!(!stdout.contains("installs/dummy"));
+
+        let stdout = assert_cli!("env", "--only", "dummy", "-s", "bash");
+        assert!(!stdout.contains("JDXCODE_TINY"));
+        assert!(stdout.contains("installs/dummy"));
+    }
+
+    #[test]
+    fn test_env_dotenv() {
+        let stdout = assert_cli!("env", "--dotenv");
+        assert_str_eq!(grep(stdout.clone(), "JDXCODE"), "JDXCODE_TINY=3.1.0");
+        assert!(stdout.lines().all(|l| !l.starts_with("export ")));
+    }
+
+    #[test]
+    fn test_env_cache() {
+        let cmd = || Env {
+            shell: Some(ShellType::Bash),
+            tool: vec![],
+            json: false,
+            dotenv: false,
+            docker: false,
+            unset: false,
+            only: None,
+            prepend_path: vec![dirs::HOME.join("cwd")],
+            append_path: vec![],
+        };
+        let config = Config::load().unwrap();
+        let path = cmd().cache_path(&config);
+        let _ = fs::remove_file(&path);
+        let expired = path.with_file_name("expired");
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&expired, "").unwrap();
+        let mtime = FileTime::from_unix_time(FileTime::now().unix_seconds() - 60, 0);
+        filetime::set_file_mtime(&expired, mtime).unwrap();
+        let mut out = Output::tracked();
+        cmd().run(config, &mut out).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), out.stdout.content);
+        assert!(path.starts_with(&*dirs::STATE));
+        // writing the cache removes the expired files
+        assert!(!expired.exists());
+
+        // a second run within the TTL prints what is in the cache file
+        fs::write(&path, "export CACHED=1\n").unwrap();
+        let config = Config::load().unwrap();
+        let mut out = Output::tracked();
+        cmd().run(config, &mut out).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_str_eq!(out.stdout.content, "export CACHED=1\n");
+    }
+
+    #[test]
+    fn test_vars_to_unset() {
+        let to_map = |vars: &[(&str, &str)]| {
+            vars.iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect::<HashMap<_, _>>()
+        };
+        // the previous activation added the tiny and dummy vars and changed EDITOR
+        let pristine = to_map(&[("EDITOR", "vi"), ("NO_COLOR", "1")]);
+        let prev = EnvDiff::new(
+            &pristine,
+            to_map(&[
+                ("JDXCODE_TINY", "3.1.0"),
+                ("JDXCODE_DUMMY", "1.0.0"),
+                ("EDITOR", "nano"),
+            ]),
+        );
+        // dummy is no longer active
+        let env = BTreeMap::from([("JDXCODE_TINY".to_string(), "3.1.0".to_string())]);
+        let removed = vec!["NO_COLOR".to_string(), "NOT_SET".to_string()];
+        assert_eq!(
+            vars_to_unset(&prev, &env, removed, &pristine),
+            vec!["JDXCODE_DUMMY", "NO_COLOR"]
+        );
+    }
+
+    #[test]
+    fn test_env_unset() {
+        let plugin = dirs::PLUGINS.join("env-unset");
+        let install = dirs::INSTALLS.join("env-unset/1.0.0");
+        let _ = remove_all(&plugin);
+        make_symlink(&dirs::HOME.join("fixtures/plugins/exec-env-unset"), &plugin).unwrap();
+        fs::create_dir_all(&install).unwrap();
+        let with_tool = assert_cli!("env", "--unset", "env-unset@1.0.0");
+        let without_tool = assert_cli!("env", "--unset");
+        remove_all(&plugin).unwrap();
+        remove_all(install.parent().unwrap()).unwrap();
+        // the fixture's exec-env unsets NO_COLOR which is set for all the tests
+        assert!(with_tool.lines().any(|l| l == "NO_COLOR"));
+        assert!(!without_tool.lines().any(|l| l == "NO_COLOR"));
+    }
+
+    #[test]
+    fn test_dotenv_quote() {
+        let lines = [
+            ("PATH", "/a/bin:/b/bin"),
+            ("EMPTY", ""),
+            ("SPACES", "hello world"),
+            ("QUOTES", r#"say "hi""#),
+            ("VARS", "$HOME/${USER}"),
+            ("BACKSLASH", r"C:\tmp"),
+            ("MULTILINE", "line 1\nline 2"),
+        ]
+        .iter()
+        .map(|(k, v)| format!("{k}={}", dotenv_quote(v)))
+        .collect::<Vec<_>>();
+        assert_str_eq!(
+            lines.join("\n"),
+            r#"PATH=/a/bin:/b/bin
+EMPTY=
+SPACES="hello world"
+QUOTES="say \"hi\""
+VARS="\$HOME/\${USER}"
+BACKSLASH="C:\\tmp"
+MULTILINE="line 1\nline 2""#
+        );
+    }
+
+    #[test]
+    fn test_env_docker() {
+        let stdout = assert_cli!("env", "--docker");
+        let installs = dirs::INSTALLS.display();
+        assert_str_eq!(
+            stdout,
+            format!(
+                "ENV JDXCODE_TINY=\"3.1.0\"\n\
+                 ENV PATH=\"{installs}/tiny/3.1.0/bin:{installs}/dummy/ref-master/bin:$PATH\"\n"
+            )
+        );
+    }
+
+    #[test]
+    fn test_docker_escape() {
+        let current = vec![PathBuf::from("/usr/bin"), PathBuf::from("/bin")];
+        assert_str_eq!(
+            docker_path("/rtx/bin:/usr/bin:/bin:/after/bin", &current),
+            "/rtx/bin:$PATH:/after/bin"
+        );
+        assert_str_eq!(
+            docker_path("/my $dir/bin:/bin", &current),
+            r"/my \$dir/bin:$PATH"
+        );
+        assert_str_eq!(
+            docker_escape(r#"say "hi" to $USER in C:\tmp"#),
+            r#"say \"hi\" to \$USER in C:\\tmp"#
+        );
+    }
 }
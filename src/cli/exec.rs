@@ -4,6 +4,7 @@ use std::path::PathBuf;
 
 use clap::ValueHint;
 use color_eyre::eyre::{eyre, Result};
+use console::style;
 use duct::IntoExecutablePath;
 
 use crate::cli::args::tool::{ToolArg, ToolArgParser};
@@ -14,7 +15,7 @@ use crate::config::Config;
 use crate::config::MissingRuntimeBehavior::Ignore;
 use crate::env;
 use crate::output::Output;
-use crate::toolset::ToolsetBuilder;
+use crate::toolset::{Toolset, ToolsetBuilder};
 
 /// Execute a command with tool(s) set
 ///
@@ -25,6 +26,10 @@ use crate::toolset::ToolsetBuilder;
 /// includes "node 20" but you run `rtx exec python@3.11`; it will still load node@20.
 ///
 /// The "--" separates runtimes from the commands to pass along to the subprocess.
+///
+/// Tools given as arguments that aren't installed are handled with `missing_runtime_behavior`.
+/// They're installed before running the command with "autoinstall" (or if accepted with "prompt" or
+/// "autoinstall_prompt"), otherwise this fails without running the command.
 #[derive(Debug, clap::Args)]
 #[clap(visible_alias = "x", verbatim_doc_comment, after_long_help = AFTER_LONG_HELP)]
 pub struct Exec {
@@ -44,34 +49,70 @@ pub struct Exec {
     /// Change to this directory before executing the command
     #[clap(visible_short_alias = 'C', value_hint = ValueHint::DirPath, long)]
     pub cd: Option<PathBuf>,
+
+    /// Don't install missing tools, fail instead
+    #[clap(long)]
+    pub no_install: bool,
 }
 
 impl Command for Exec {
     fn run(self, mut config: Config, _out: &mut Output) -> Result<()> {
-        let ts = ToolsetBuilder::new()
-            .with_args(&self.tool)
-            .with_install_missing()
-            .build(&mut config)?;
+        let mut tsb = ToolsetBuilder::new().with_args(&self.tool);
+        if !self.no_install {
+            tsb = tsb.with_install_missing();
+        }
+        let ts = tsb.build(&mut config)?;
+        self.ensure_installed(&config, &ts)?;
         let (program, args) = parse_command(&env::SHELL, &self.command, &self.c);
         let mut env = ts.env_with_path(&config);
         if config.settings.missing_runtime_behavior != Ignore {
             // prevent rtx from auto-installing inside a shim
             env.insert("RTX_MISSING_RUNTIME_BEHAVIOR".into(), "warn".into());
         }
+        // a var one tool's exec-env unsets may still be set by another tool or [env]
+        let unset = ts
+            .exec_env_unset(&config)
+            .into_iter()
+            .filter(|k| !env.contains_key(k))
+            .collect();
 
-        self.exec(program, args, env)
+        self.exec(program, args, env, unset)
     }
 }
 
 impl Exec {
+    /// the tools given as arguments must be installed, otherwise the command would run with
+    /// whatever other version happens to be on PATH
+    fn ensure_installed(&self, config: &Config, ts: &Toolset) -> Result<()> {
+        let missing = ts
+            .list_missing_versions(config)
+            .into_iter()
+            .filter(|tv| self.tool.iter().any(|t| t.plugin == tv.plugin_name))
+            .map(|tv| tv.to_string())
+            .collect::<Vec<_>>();
+        if missing.is_empty() {
+            return Ok(());
+        }
+        Err(eyre!(
+            "not installed: {}\ninstall with: {}",
+            missing.join(", "),
+            style(format!("rtx install {}", missing.join(" ")))
+                .yellow()
+                .for_stderr()
+        ))
+    }
+
     #[cfg(not(test))]
-    fn exec<T, U, E>(&self, program: T, args: U, env: BTreeMap<E, E>) -> Result<()>
+    fn exec<T, U, E>(&self, program: T, args: U, env: BTreeMap<E, E>, unset: Vec<E>) -> Result<()>
     where
         T: IntoExecutablePath,
         U: IntoIterator,
         U::Item: Into<OsString>,
         E: AsRef<OsStr>,
     {
+        for k in unset.iter() {
+            env::remove_var(k);
+        }
         for (k, v) in env.iter() {
             env::set_var(k, v);
         }
@@ -85,7 +126,7 @@ impl Exec {
     }
 
     #[cfg(test)]
-    fn exec<T, U, E>(&self, program: T, args: U, env: BTreeMap<E, E>) -> Result<()>
+    fn exec<T, U, E>(&self, program: T, args: U, env: BTreeMap<E, E>, unset: Vec<E>) -> Result<()>
     where
         T: IntoExecutablePath,
         U: IntoIterator,
@@ -96,6 +137,9 @@ impl Exec {
         if let Some(cd) = &self.cd {
             cmd = cmd.dir(cd);
         }
+        for k in unset.iter() {
+            cmd = cmd.env_remove(k);
+        }
         for (k, v) in env.iter() {
             cmd = cmd.env(k, v);
         }
@@ -130,6 +174,9 @@ static AFTER_LONG_HELP: &str = color_print::cstr!(
   # Specify command as a string:
   $ <bold>rtx exec node@20 python@3.11 --command "node -v && python -V"</bold>
 
+  # Fail instead of installing node-20.x if it's missing:
+  $ <bold>rtx x --no-install node@20 -- node ./app.js</bold>
+
   # Run a command in a different directory:
   $ <bold>rtx x -C /path/to/project node@20 -- node ./app.js</bold>
 "#
@@ -137,8 +184,14 @@ static AFTER_LONG_HELP: &str = color_print::cstr!(
 
 #[cfg(test)]
 mod tests {
-    use crate::assert_cli;
+    use console::strip_ansi_codes;
+    use pretty_assertions::assert_str_eq;
+
     use crate::cli::tests::cli_run;
+    use crate::file::{create_dir_all, make_symlink, remove_all};
+    use crate::{assert_cli, assert_cli_err, dirs};
+
+    use super::*;
 
     #[test]
     fn test_exec_ok() {
@@ -160,4 +213,66 @@ mod tests {
     fn test_exec_cd() {
         assert_cli!("exec", "-C", "/tmp", "--", "pwd");
     }
+
+    #[test]
+    fn test_exec_autoinstall() {
+        assert_cli!("exec", "dummy@2.0.0", "--", "dummy");
+        assert!(dirs::INSTALLS.join("dummy/2.0.0").exists());
+        assert_cli!("uninstall", "dummy@2.0.0");
+    }
+
+    #[test]
+    fn test_exec_missing() {
+        let err = assert_cli_err!("exec", "--no-install", "tiny@3.0.0", "--", "echo");
+        assert_str_eq!(
+            strip_ansi_codes(&err.to_string()),
+            "not installed: tiny@3.0.0\ninstall with: rtx install tiny@3.0.0"
+        );
+
+        let mut config = Config::load().unwrap();
+        config.settings.missing_runtime_behavior = Ignore;
+        let exec = Exec {
+            tool: vec![ToolArg::parse("tiny@3.0.0")],
+            command: Some(vec!["echo".into()]),
+            c: None,
+            cd: None,
+            no_install: false,
+        };
+        let err = exec.run(config, &mut Output::tracked()).unwrap_err();
+        assert!(strip_ansi_codes(&err.to_string()).starts_with("not installed: tiny@3.0.0"));
+        assert!(!dirs::INSTALLS.join("tiny/3.0.0").exists());
+    }
+
+    #[test]
+    fn test_exec_env_unset() {
+        // the fixture's exec-env unsets NO_COLOR which is set for all the tests
+        let plugin = dirs::PLUGINS.join("exec-env-unset");
+        let install = dirs::INSTALLS.join("exec-env-unset/1.0.0");
+        let _ = remove_all(&plugin);
+        make_symlink(&dirs::HOME.join("fixtures/plugins/exec-env-unset"), &plugin).unwrap();
+        create_dir_all(&install).unwrap();
+
+        let check = |tool: &str| {
+            cli_run(
+                &[
+                    "rtx",
+                    "exec",
+                    tool,
+                    "--",
+                    "sh",
+                    "-c",
+                    "test -z \"${NO_COLOR+x}\"",
+                ]
+                .map(String::from)
+                .to_vec(),
+            )
+        };
+        let with_tool = check("exec-env-unset@1.0.0");
+        let without_tool = check("tiny@3.1.0");
+        remove_all(&plugin).unwrap();
+        remove_all(install.parent().unwrap()).unwrap();
+
+        assert!(with_tool.is_ok());
+        assert!(without_tool.is_err());
+    }
 }
@@ -64,7 +64,7 @@ impl Command for Global {
     }
 }
 
-fn global_file() -> PathBuf {
+pub fn global_file() -> PathBuf {
     env::RTX_CONFIG_FILE.clone().unwrap_or_else(|| {
         if *env::RTX_USE_TOML {
             dirs::CONFIG.join("config.toml")
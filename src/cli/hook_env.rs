@@ -3,14 +3,14 @@ use std::env::join_paths;
 use std::ops::Deref;
 use std::path::PathBuf;
 
-use color_eyre::eyre::Result;
+use color_eyre::eyre::{Report, Result};
 use console::truncate_str;
 use itertools::Itertools;
 use terminal_size::{terminal_size, Width};
 
 use crate::cli::command::Command;
 use crate::config::Config;
-use crate::config::MissingRuntimeBehavior::{Prompt, Warn};
+use crate::config::MissingRuntimeBehavior::{AutoInstallPrompt, Prompt, Warn};
 use crate::direnv::DirenvDiff;
 use crate::env::__RTX_DIFF;
 use crate::env_diff::{EnvDiff, EnvDiffOperation};
@@ -34,11 +34,15 @@ pub struct HookEnv {
 
 impl Command for HookEnv {
     fn run(self, mut config: Config, out: &mut Output) -> Result<()> {
-        if config.settings.missing_runtime_behavior == Prompt {
+        if matches!(
+            config.settings.missing_runtime_behavior,
+            Prompt | AutoInstallPrompt
+        ) {
             config.settings.missing_runtime_behavior = Warn;
         }
         let ts = ToolsetBuilder::new()
             .with_install_missing()
+            .with_resolution_cache()
             .build(&mut config)?;
         let shell = get_shell(self.shell).expect("no shell provided, use `--shell=zsh`");
         out.stdout.write(hook_env::clear_old_env(&*shell));
@@ -59,11 +63,21 @@ impl Command for HookEnv {
         if self.status {
             self.display_status(&config, &ts, out);
         }
+        hook_env::clear_shown_errors();
 
         Ok(())
     }
 }
 
+/// hook-env runs on every prompt so a broken config would show the same error every time.
+/// This records the last error shown in the current directory and returns true if it's the same.
+pub fn is_repeated_error(err: &Report) -> bool {
+    if env::ARGS.len() < 2 || env::ARGS[1] != "hook-env" {
+        return false;
+    }
+    hook_env::already_shown(&format!("{:#}", err))
+}
+
 impl HookEnv {
     fn display_status(&self, config: &Config, ts: &Toolset, out: &mut Output) {
         let installed_versions = ts
@@ -23,7 +23,7 @@ pub struct Implode {
 
 impl Command for Implode {
     fn run(self, _config: Config, out: &mut Output) -> Result<()> {
-        let mut files = vec![&*dirs::ROOT, &*dirs::CACHE, &*env::RTX_EXE];
+        let mut files = vec![&*dirs::ROOT, &*dirs::CACHE, &*dirs::STATE, &*env::RTX_EXE];
         if self.config {
             files.push(&*dirs::CONFIG);
         }
@@ -1,4 +1,6 @@
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use color_eyre::eyre::{eyre, Result};
 use console::style;
@@ -7,18 +9,22 @@ use rayon::prelude::*;
 use rayon::ThreadPoolBuilder;
 
 use crate::cli::args::tool::{ToolArg, ToolArgParser};
-use crate::cli::command::Command;
-use crate::config::Config;
+use crate::cli::command::{Command, JsonOutput};
+use crate::cli::global::global_file;
 use crate::config::MissingRuntimeBehavior::AutoInstall;
+use crate::config::{config_file, Config, MissingRuntimeBehavior};
 
-use crate::output::Output;
-
+use crate::file::{basename, display_path};
+use crate::output::{to_json, Output};
+use crate::plugins::PluginName;
 use crate::runtime_symlinks::rebuild_symlinks;
 use crate::shims::reshim;
 use crate::tool::Tool;
 use crate::toolset::{
-    ToolVersion, ToolVersionOptions, ToolVersionRequest, Toolset, ToolsetBuilder,
+    install_plugins, write_version_locks, ToolVersion, ToolVersionOptions, ToolVersionRequest,
+    Toolset, ToolsetBuilder,
 };
+use crate::ui::install_summary::{InstallStatus, InstallSummary};
 use crate::ui::multi_progress_report::MultiProgressReport;
 use crate::ui::progress_report::ProgressReport;
 
@@ -42,27 +48,175 @@ pub struct Install {
     #[clap(long, short, requires = "tool")]
     force: bool,
 
+    /// Only install versions that are missing, never reinstall anything
+    /// versions that are already installed are listed as skipped
+    #[clap(long, conflicts_with = "force", verbatim_doc_comment)]
+    only_missing: bool,
+
+    /// Skip the tools that come before this one and install from it onwards
+    /// tools are in the order they are passed or, with no arguments, configured
+    #[clap(
+        long,
+        value_name = "PLUGIN",
+        conflicts_with = "legacy_file",
+        verbatim_doc_comment
+    )]
+    continue_from: Option<PluginName>,
+
+    /// Only run the plugins' download step, nothing is installed
+    /// what was downloaded is left in ~/.local/share/rtx/downloads, e.g.: to prepare an offline machine
+    /// a later `rtx install` of the same versions uses it instead of downloading again
+    #[clap(long, conflicts_with_all = ["force", "only_missing"], verbatim_doc_comment)]
+    download_only: bool,
+
     /// Show installation output
     #[clap(long, short, action = clap::ArgAction::Count)]
     verbose: u8,
+
+    /// Only use cached remote versions, fail instead of fetching them
+    /// can also be set with RTX_CACHE_ONLY=1
+    #[clap(long, verbatim_doc_comment)]
+    cache_only: bool,
+
+    /// Ask plugins to compile from source instead of downloading a prebuilt binary
+    /// sets RTX_BUILD_FROM_SOURCE=1 for the install script
+    #[clap(long, verbatim_doc_comment)]
+    build_from_source: bool,
+
+    /// Install one tool at a time in the order they are configured
+    /// so the output is the same on every run, implies --jobs=1
+    /// can also be set with RTX_KEEP_ORDER=1
+    #[clap(long, verbatim_doc_comment)]
+    keep_order: bool,
+
+    /// Show a table of what was installed, skipped or failed once done
+    /// a failed install doesn't stop the others from being installed so they are all in it
+    #[clap(long, verbatim_doc_comment)]
+    summary: bool,
+
+    /// Output the summary in json format
+    #[clap(long, short = 'J', requires = "summary")]
+    json: bool,
+
+    /// Install the version in a legacy version file like .nvmrc
+    /// The plugin that uses that filename parses it, other config is ignored
+    #[clap(
+        long,
+        value_name = "PATH",
+        conflicts_with = "tool",
+        verbatim_doc_comment
+    )]
+    legacy_file: Option<PathBuf>,
+
+    /// the global --missing flag, the only thing that stops `rtx install` from installing
+    #[clap(from_global)]
+    missing: Option<MissingRuntimeBehavior>,
+}
+
+impl JsonOutput for Install {
+    fn json(mut self) -> Result<Self> {
+        if !self.summary {
+            return Err(eyre!("--json requires --summary"));
+        }
+        self.json = true;
+        Ok(self)
+    }
 }
 
 impl Command for Install {
-    fn run(self, mut config: Config, _out: &mut Output) -> Result<()> {
-        config.settings.missing_runtime_behavior = AutoInstall;
+    fn run(self, mut config: Config, out: &mut Output) -> Result<()> {
+        config.settings.missing_runtime_behavior = self.missing.clone().unwrap_or(AutoInstall);
+        if self.cache_only {
+            config.settings.cache_only = true;
+        }
+        if self.build_from_source {
+            config.settings.build_from_source = true;
+        }
+        if self.keep_order {
+            config.settings.keep_order = true;
+            config.settings.jobs = 1;
+        }
 
-        match &self.tool {
-            Some(runtime) => self.install_runtimes(config, runtime)?,
-            None => self.install_missing_runtimes(config)?,
+        let summary = Arc::new(InstallSummary::default());
+        let mpr = MultiProgressReport::new(&config.settings)
+            .with_summary(summary.clone())
+            .with_keep_going(self.summary);
+        let result = match (&self.tool, &self.legacy_file) {
+            (Some(runtime), _) => self.install_runtimes(config, runtime, mpr),
+            (None, Some(legacy_file)) => match self.parse_legacy_file(&config, legacy_file) {
+                Ok(tool) => {
+                    rtxstatusln!(
+                        out,
+                        "{} specifies {}",
+                        display_path(legacy_file),
+                        style(&tool).cyan().for_stderr()
+                    );
+                    self.install_runtimes(config, &[tool], mpr)
+                }
+                Err(err) => Err(err),
+            },
+            (None, None) => self.install_missing_runtimes(config, mpr),
+        };
+        if self.only_missing {
+            self.output_skipped(out, &summary);
+        }
+        if self.summary {
+            self.output_summary(out, &summary)?;
         }
 
-        Ok(())
+        result
     }
 }
 
 impl Install {
-    fn install_runtimes(&self, mut config: Config, runtimes: &[ToolArg]) -> Result<()> {
-        let mpr = MultiProgressReport::new(config.settings.verbose);
+    fn output_skipped(&self, out: &mut Output, summary: &InstallSummary) {
+        for entry in summary.entries() {
+            if entry.status == InstallStatus::Skipped {
+                let tv = format!("{}@{}", entry.plugin, entry.version);
+                rtxstatusln!(
+                    out,
+                    "{} already installed, skipped",
+                    style(tv).cyan().for_stderr()
+                );
+            }
+        }
+    }
+
+    fn output_summary(&self, out: &mut Output, summary: &InstallSummary) -> Result<()> {
+        if self.json {
+            rtxprintln!(out, "{}", to_json("installs", &summary.entries())?);
+        } else if !summary.entries().is_empty() {
+            rtxprintln!(out, "{}", summary.render());
+        }
+        Ok(())
+    }
+
+    /// the version in `path` parsed by the plugin that has it as a legacy filename,
+    /// this works even if legacy_version_file is disabled
+    fn parse_legacy_file(&self, config: &Config, path: &Path) -> Result<ToolArg> {
+        if !path.is_file() {
+            return Err(eyre!("{} does not exist", display_path(path)));
+        }
+        let filename = basename(path).unwrap_or_default();
+        for tool in config.tools.values() {
+            if tool.legacy_filenames(&config.settings)?.contains(&filename) {
+                let version = tool.parse_legacy_file(path, &config.settings)?;
+                return Ok(ToolArg::parse(&tool.name).with_version(&version));
+            }
+        }
+        Err(eyre!(
+            "no plugin uses {} as a legacy version file",
+            filename
+        ))
+    }
+
+    fn install_runtimes(
+        &self,
+        mut config: Config,
+        runtimes: &[ToolArg],
+        mpr: MultiProgressReport,
+    ) -> Result<()> {
+        let runtimes = self.skip_to_continue_from(runtimes.to_vec(), |r| &r.plugin)?;
         let ts = ToolsetBuilder::new()
             .with_latest_versions()
             .build(&mut config)?;
@@ -70,13 +224,19 @@ impl Install {
             .num_threads(config.settings.jobs)
             .build()?
             .install(|| -> Result<()> {
-                let tool_versions =
-                    self.get_requested_tool_versions(&mut config, &ts, runtimes, &mpr)?;
+                let mut tool_versions =
+                    self.get_requested_tool_versions(&mut config, &ts, &runtimes, &mpr)?;
+                self.register_system_versions(&config, &tool_versions)?;
+                tool_versions
+                    .retain(|(_, tv)| !matches!(tv.request, ToolVersionRequest::System(_)));
                 if tool_versions.is_empty() {
                     warn!("no runtimes to install");
                     warn!("specify a version with `rtx install <PLUGIN>@<VERSION>`");
                     return Ok(());
                 }
+                if self.download_only {
+                    return self.download_versions(&config, &mpr, tool_versions);
+                }
                 self.uninstall_existing_versions(&config, &mpr, &tool_versions)?;
                 self.install_requested_versions(&config, &mpr, tool_versions)?;
                 reshim(&mut config, &ts).map_err(|err| eyre!("failed to reshim: {}", err))?;
@@ -121,35 +281,131 @@ impl Install {
                 }
             }
         }
+        let missing_plugins = requests
+            .iter()
+            .map(|(plugin_name, _, _)| plugin_name)
+            .unique()
+            .map(|plugin_name| config.get_or_create_tool(plugin_name))
+            .filter(|plugin| !plugin.is_installed())
+            .collect_vec();
+        install_plugins(config, &missing_plugins, mpr)?;
         let mut tool_versions = vec![];
         for (plugin_name, tvr, opts) in requests {
             let plugin = config.get_or_create_tool(&plugin_name);
-            if !plugin.is_installed() {
-                let mut pr = mpr.add();
-                if let Err(err) = plugin.install(config, &mut pr, false) {
-                    pr.error();
-                    return Err(err)?;
-                }
-            }
             let tv = tvr.resolve(config, &plugin, opts, ts.latest_versions)?;
             tool_versions.push((plugin, tv));
         }
         Ok(tool_versions)
     }
 
-    fn install_missing_runtimes(&self, mut config: Config) -> Result<()> {
+    /// there is nothing to install for "system" but it is set in the global config
+    /// so `rtx current`, `rtx which` and the shims use the tool from PATH
+    fn register_system_versions(
+        &self,
+        config: &Config,
+        tool_versions: &[(Arc<Tool>, ToolVersion)],
+    ) -> Result<()> {
+        let plugins = tool_versions
+            .iter()
+            .filter(|(_, tv)| matches!(tv.request, ToolVersionRequest::System(_)))
+            .map(|(t, _)| t.name.clone())
+            .collect_vec();
+        if plugins.is_empty() {
+            return Ok(());
+        }
+        let path = global_file();
+        let is_trusted = config_file::is_trusted(&config.settings, &path);
+        let mut cf = match path.exists() {
+            true => config_file::parse(&path, is_trusted)?,
+            false => config_file::init(&path, is_trusted),
+        };
+        for plugin in plugins {
+            cf.replace_versions(&plugin, &["system".into()]);
+            info!(
+                "{} will use the system version, set in {}",
+                style(&plugin).cyan().for_stderr(),
+                display_path(&path)
+            );
+        }
+        cf.save()
+    }
+
+    fn download_versions(
+        &self,
+        config: &Config,
+        mpr: &MultiProgressReport,
+        tool_versions: Vec<(Arc<Tool>, ToolVersion)>,
+    ) -> Result<()> {
+        tool_versions
+            .into_par_iter()
+            .map(|(tool, tv)| {
+                let mut pr = mpr.add();
+                tool.download_version(config, &tv, &mut pr)
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(())
+    }
+
+    fn install_missing_runtimes(&self, mut config: Config, mpr: MultiProgressReport) -> Result<()> {
         let mut ts = ToolsetBuilder::new()
             .with_latest_versions()
             .build(&mut config)?;
-        if ts.list_missing_versions(&config).is_empty() {
+        let versions = ts.versions.into_iter().collect_vec();
+        ts.versions = self
+            .skip_to_continue_from(versions, |(p, _)| p)?
+            .into_iter()
+            .collect();
+        if self.download_only {
+            let tool_versions = ts
+                .list_missing_versions(&config)
+                .into_iter()
+                .map(|tv| (config.get_or_create_tool(&tv.plugin_name), tv.clone()))
+                .collect_vec();
+            return ThreadPoolBuilder::new()
+                .num_threads(config.settings.jobs)
+                .build()?
+                .install(|| self.download_versions(&config, &mpr, tool_versions));
+        }
+        let nothing_missing = ts.list_missing_versions(&config).is_empty();
+        if nothing_missing {
             warn!("no runtimes to install");
         }
-        let mpr = MultiProgressReport::new(config.settings.verbose);
+        if let Some(summary) = mpr.summary() {
+            for (_, tv) in ts.list_current_installed_versions(&config) {
+                summary.add(&tv, InstallStatus::Skipped, Duration::ZERO);
+            }
+        }
         ts.install_missing(&mut config, mpr)?;
+        if nothing_missing && self.only_missing {
+            // install_missing only reshims after installing something
+            reshim(&mut config, &ts).map_err(|err| eyre!("failed to reshim: {}", err))?;
+        }
+        if config.settings.lock_resolved_versions {
+            write_version_locks(&ts)?;
+        }
 
         Ok(())
     }
 
+    /// the tools from the one given with --continue-from onwards
+    fn skip_to_continue_from<T>(
+        &self,
+        tools: Vec<T>,
+        plugin: impl Fn(&T) -> &PluginName,
+    ) -> Result<Vec<T>> {
+        let continue_from = match &self.continue_from {
+            Some(continue_from) => continue_from,
+            None => return Ok(tools),
+        };
+        match tools.iter().position(|t| plugin(t) == continue_from) {
+            Some(idx) => Ok(tools.into_iter().skip(idx).collect()),
+            None => Err(eyre!(
+                "cannot continue from {}, it is not one of the tools to install",
+                style(continue_from).cyan().for_stderr()
+            )),
+        }
+    }
+
     fn uninstall_existing_versions(
         &self,
         config: &Config,
@@ -167,7 +423,12 @@ impl Install {
                 .collect::<Result<Vec<_>>>()?;
         } else {
             for (_, tv) in already_installed_tool_versions {
-                warn!("{} already installed", style(tv).cyan().for_stderr());
+                if !self.only_missing {
+                    warn!("{} already installed", style(&tv).cyan().for_stderr());
+                }
+                if let Some(summary) = mpr.summary() {
+                    summary.add(&tv, InstallStatus::Skipped, Duration::ZERO);
+                }
             }
         }
         Ok(())
@@ -185,16 +446,29 @@ impl Install {
             .into_iter()
             .map(|(t, tvs)| (t, tvs.map(|(_, tv)| tv).collect()))
             .collect();
-        grouped_tool_versions
-            .into_par_iter()
-            .map(|(tool, versions)| {
-                for tv in versions {
-                    self.install_version(config, &tool, &tv, mpr.add())?;
+        let install = |(tool, versions): (Arc<Tool>, Vec<ToolVersion>)| -> Result<()> {
+            for tv in versions {
+                let start = Instant::now();
+                let result = self.install_version(config, &tool, &tv, mpr.add());
+                if let Some(summary) = mpr.summary() {
+                    summary.add_result(&tv, &result, start.elapsed());
                 }
-                Ok(())
-            })
-            .collect::<Result<Vec<_>>>()?;
-        Ok(())
+                result?;
+            }
+            Ok(())
+        };
+        if !mpr.keep_going() {
+            return match config.settings.keep_order {
+                true => grouped_tool_versions.into_iter().try_for_each(install),
+                false => grouped_tool_versions.into_par_iter().try_for_each(install),
+            };
+        }
+        // with --summary a failure doesn't stop the other tools so they all end up in it
+        let results = match config.settings.keep_order {
+            true => grouped_tool_versions.into_iter().map(install).collect_vec(),
+            false => grouped_tool_versions.into_par_iter().map(install).collect(),
+        };
+        results.into_iter().collect()
     }
     fn uninstall_version(
         &self,
@@ -239,6 +513,7 @@ static AFTER_LONG_HELP: &str = color_print::cstr!(
   $ <bold>rtx install node@20</bold>      # install fuzzy node version
   $ <bold>rtx install node</bold>         # install version specified in .tool-versions or .rtx.toml
   $ <bold>rtx install</bold>                # installs everything specified in .tool-versions or .rtx.toml
+  $ <bold>rtx install node@system</bold>  # use the node already on PATH, set in the global config
 "#
 );
 
@@ -246,7 +521,15 @@ static AFTER_LONG_HELP: &str = color_print::cstr!(
 mod tests {
     use pretty_assertions::assert_str_eq;
 
-    use crate::{assert_cli, assert_cli_snapshot, dirs};
+    use crate::cli::tests::{cli_run, grep};
+    use crate::cli::Cli;
+    use crate::config::config_file::tool_versions::ToolVersions;
+    use crate::config::{Config, MissingRuntimeBehavior};
+    use crate::file::{make_executable, remove_all};
+    use crate::git::Git;
+    use crate::output::Output;
+    use crate::test::reset_config;
+    use crate::{assert_cli, assert_cli_err, assert_cli_snapshot, dirs, shims};
 
     #[test]
     fn test_install_force() {
@@ -276,9 +559,221 @@ mod tests {
         assert_cli!("global", "--unset", "dummy");
     }
 
+    #[test]
+    fn test_install_system() {
+        assert_cli!("install", "dummy@system");
+        assert_str_eq!(assert_cli!("current", "dummy").trim(), "system");
+        let output = assert_cli!("which", "sh");
+        assert_str_eq!(
+            output.trim(),
+            shims::which_system("sh").unwrap().to_string_lossy()
+        );
+        reset_config();
+    }
+
+    #[test]
+    fn test_install_summary_json() {
+        let output = assert_cli!("install", "-f", "--summary", "--json", "tiny@3.1.0");
+        let doc: serde_json::Value = serde_json::from_str(&output).unwrap();
+        let summary = &doc["installs"];
+        assert_eq!(summary[0]["plugin"], "tiny");
+        assert_eq!(summary[0]["requested"], "3.1.0");
+        assert_eq!(summary[0]["version"], "3.1.0");
+        assert_eq!(summary[0]["status"], "installed");
+    }
+
+    #[test]
+    fn test_install_legacy_file() {
+        let path = dirs::HOME.join("fixtures/legacy/.tiny-version");
+        let args = ["rtx", "install", "--summary", "--json", "--legacy-file"]
+            .into_iter()
+            .map(String::from)
+            .chain([path.to_string_lossy().to_string()])
+            .collect();
+        let out = cli_run(&args).unwrap();
+        assert_str_eq!(
+            console::strip_ansi_codes(&out.stderr.content).trim(),
+            "rtx ~/fixtures/legacy/.tiny-version specifies tiny@3"
+        );
+        let doc: serde_json::Value = serde_json::from_str(&out.stdout.content).unwrap();
+        let summary = &doc["installs"];
+        assert_eq!(summary[0]["plugin"], "tiny");
+        assert_eq!(summary[0]["requested"], "3");
+        assert_eq!(summary[0]["version"], "3.1.0");
+
+        let err = assert_cli_err!("install", "--legacy-file", "../.test-tool-versions");
+        assert_str_eq!(
+            err.to_string(),
+            "no plugin uses .test-tool-versions as a legacy version file"
+        );
+    }
+
+    #[test]
+    fn test_install_only_missing() {
+        let marker = dirs::INSTALLS.join("tiny/2.1.0/.only-missing");
+        std::fs::write(&marker, "").unwrap();
+        let args = ["rtx", "install", "--only-missing", "tiny@2.1.0"].map(String::from);
+        let out = cli_run(&args.to_vec()).unwrap();
+        assert_str_eq!(
+            console::strip_ansi_codes(&out.stderr.content).trim(),
+            "rtx tiny@2.1.0 already installed, skipped"
+        );
+        // a reinstall would have wiped the install directory
+        assert!(marker.exists());
+        std::fs::remove_file(&marker).unwrap();
+    }
+
+    #[test]
+    fn test_install_missing_behavior_warn() {
+        let plugin = dirs::PLUGINS.join("install-warn");
+        let _ = remove_all(&plugin);
+        std::fs::create_dir_all(plugin.join("bin")).unwrap();
+        for (script, body) in [
+            ("list-all", "echo 1.0.0"),
+            ("install", "mkdir -p $RTX_INSTALL_PATH"),
+        ] {
+            let path = plugin.join("bin").join(script);
+            std::fs::write(&path, format!("#!/usr/bin/env bash\n{body}\n")).unwrap();
+            make_executable(&path).unwrap();
+        }
+        let path = dirs::CURRENT.join(".tool-versions");
+        let tv = ToolVersions::parse_str("install-warn 1.0.0\n", path.clone(), false).unwrap();
+        let mut config = Config::load().unwrap();
+        config.config_files.clear();
+        config.config_files.insert(path, Box::new(tv));
+        // e.g.: run from `rtx exec` or a shim, which set RTX_MISSING_RUNTIME_BEHAVIOR=warn
+        config.settings.missing_runtime_behavior = MissingRuntimeBehavior::Warn;
+
+        let args = vec!["rtx".to_string(), "install".to_string()];
+        let result = Cli::new().run(config, &args, &mut Output::tracked());
+        let installed = dirs::INSTALLS.join("install-warn/1.0.0").exists();
+        remove_all(&plugin).unwrap();
+        let _ = remove_all(dirs::INSTALLS.join("install-warn"));
+        let _ = remove_all(dirs::CACHE.join("install-warn"));
+        result.unwrap();
+        assert!(installed);
+    }
+
+    #[test]
+    fn test_install_only_missing_from_config() {
+        assert_cli!("install", "tiny");
+        let args = ["rtx", "install", "--only-missing"].map(String::from);
+        let out = cli_run(&args.to_vec()).unwrap();
+        let stderr = console::strip_ansi_codes(&out.stderr.content).to_string();
+        assert!(stderr.contains("rtx tiny@3.1.0 already installed, skipped"));
+    }
+
+    #[test]
+    fn test_install_continue_from() {
+        let stdout = assert_cli!(
+            "install",
+            "tiny@2.1.0",
+            "dummy@1.0.0",
+            "--continue-from",
+            "dummy",
+            "--summary",
+            "--json"
+        );
+        let summary: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+        let plugins = summary["installs"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|e| e["plugin"].as_str().unwrap())
+            .collect::<Vec<_>>();
+        // tiny@2.1.0 is installed but would still show up as skipped if it wasn't left out
+        assert_eq!(plugins, vec!["dummy"]);
+
+        let err = assert_cli_err!("install", "tiny@2.1.0", "--continue-from", "dummy");
+        assert_str_eq!(
+            console::strip_ansi_codes(&err.to_string()),
+            "cannot continue from dummy, it is not one of the tools to install"
+        );
+    }
+
+    #[test]
+    fn test_install_receipt() {
+        assert_cli!("install", "-f", "tiny@1.0.1");
+        let path = dirs::INSTALLS.join("tiny/1.0.1/.rtx-receipt.json");
+        let contents = std::fs::read_to_string(&path).unwrap();
+        let receipt: serde_json::Value = serde_json::from_str(&contents).unwrap();
+        assert_eq!(receipt["plugin"], "tiny");
+        assert_eq!(receipt["requested"], "1.0.1");
+        assert_eq!(receipt["version"], "1.0.1");
+        let git = Git::new(dirs::PLUGINS.join("tiny"));
+        let sha = git.is_repo().then(|| git.current_sha().unwrap());
+        assert_eq!(receipt["plugin_sha"], serde_json::json!(sha));
+        assert!(receipt["installed_at"].as_str().unwrap().ends_with('Z'));
+        assert_eq!(
+            receipt["env"]["RTX_DATA_DIR"],
+            dirs::ROOT.to_string_lossy().as_ref()
+        );
+
+        let stdout = assert_cli!("ls", "--receipts", "tiny");
+        assert!(grep(stdout, "tiny@1.0.1").contains(receipt["installed_at"].as_str().unwrap()));
+
+        // a corrupt receipt doesn't fail the whole listing
+        std::fs::write(&path, "{").unwrap();
+        let stdout = assert_cli!("ls", "--receipts", "tiny");
+        std::fs::write(&path, contents).unwrap();
+        assert!(grep(stdout, "tiny@1.0.1").ends_with("invalid receipt"));
+    }
+
     #[test]
     fn test_install_nothing() {
         // this doesn't do anything since dummy isn't specified
         assert_cli_snapshot!("install", "dummy");
     }
+
+    #[test]
+    fn test_install_download_only() {
+        let plugin = dirs::PLUGINS.join("download-only");
+        let _ = remove_all(&plugin);
+        std::fs::create_dir_all(plugin.join("bin")).unwrap();
+        for (script, body) in [
+            ("list-all", "echo 1.0.0"),
+            ("download", "echo downloaded > $RTX_DOWNLOAD_PATH/tarball"),
+            (
+                "install",
+                "mkdir -p $RTX_INSTALL_PATH && touch $RTX_INSTALL_PATH/installed",
+            ),
+        ] {
+            let path = plugin.join("bin").join(script);
+            std::fs::write(&path, format!("#!/usr/bin/env bash\n{body}\n")).unwrap();
+            make_executable(&path).unwrap();
+        }
+
+        let result = cli_run(
+            &["rtx", "install", "--download-only", "download-only@1.0.0"]
+                .map(String::from)
+                .into(),
+        );
+        let downloaded = dirs::DOWNLOADS.join("download-only/1.0.0/tarball").exists();
+        let installed = dirs::INSTALLS.join("download-only").exists();
+
+        // installing afterwards uses the download, it would fail if it downloaded again
+        let download = plugin.join("bin/download");
+        std::fs::write(&download, "#!/usr/bin/env bash\nexit 1\n").unwrap();
+        std::fs::write(
+            plugin.join("bin/install"),
+            "#!/usr/bin/env bash\nmkdir -p $RTX_INSTALL_PATH && cp $RTX_DOWNLOAD_PATH/tarball $RTX_INSTALL_PATH/installed\n",
+        )
+        .unwrap();
+        let install_result = cli_run(
+            &["rtx", "install", "download-only@1.0.0"]
+                .map(String::from)
+                .into(),
+        );
+        let installed_from_download =
+            std::fs::read_to_string(dirs::INSTALLS.join("download-only/1.0.0/installed"));
+        let _ = remove_all(dirs::DOWNLOADS.join("download-only"));
+        let _ = remove_all(dirs::INSTALLS.join("download-only"));
+        let _ = remove_all(dirs::CACHE.join("download-only"));
+        remove_all(&plugin).unwrap();
+        result.unwrap();
+        assert!(downloaded);
+        assert!(!installed);
+        install_result.unwrap();
+        assert_eq!(installed_from_download.unwrap(), "downloaded\n");
+    }
 }
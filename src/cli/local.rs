@@ -7,6 +7,7 @@ use crate::cli::command::Command;
 use crate::config::config_file::ConfigFile;
 use crate::config::{config_file, Config};
 use crate::env::{RTX_DEFAULT_CONFIG_FILENAME, RTX_DEFAULT_TOOL_VERSIONS_FILENAME};
+use crate::file::display_path;
 use crate::output::Output;
 use crate::plugins::PluginName;
 use crate::ui::multi_progress_report::MultiProgressReport;
@@ -114,6 +115,9 @@ pub fn local(
 
     if let Some(plugins) = &remove {
         for plugin in plugins {
+            if !cf.to_toolset().versions.contains_key(plugin) {
+                return Err(eyre!("{} is not set in {}", plugin, display_path(path)));
+            }
             cf.remove_plugin(plugin);
         }
     }
@@ -143,7 +147,7 @@ fn install_missing_runtimes(config: &mut Config, cf: &dyn ConfigFile) -> Result<
     let mut ts = cf.to_toolset().clone();
     ts.resolve(config);
     if !ts.list_missing_versions(config).is_empty() {
-        let mpr = MultiProgressReport::new(config.settings.verbose);
+        let mpr = MultiProgressReport::new(&config.settings);
         ts.install_missing(config, mpr)?;
     }
 
@@ -3,7 +3,7 @@ use std::fmt::{Display, Formatter};
 use std::path::PathBuf;
 use std::sync::Arc;
 
-use color_eyre::eyre::Result;
+use color_eyre::eyre::{eyre, Result};
 use console::style;
 use console::Alignment::Left;
 use indexmap::IndexMap;
@@ -12,13 +12,13 @@ use owo_colors::OwoColorize;
 use serde_derive::Serialize;
 use versions::Versioning;
 
-use crate::cli::command::Command;
+use crate::cli::command::{Command, JsonOutput};
 use crate::config::Config;
 use crate::errors::Error::PluginNotInstalled;
-use crate::output::Output;
+use crate::output::{to_json, Output};
 use crate::plugins::PluginName;
 use crate::tool::Tool;
-use crate::toolset::{ToolSource, ToolVersion, ToolsetBuilder};
+use crate::toolset::{InstallReceipt, ToolSource, ToolVersion, ToolsetBuilder};
 
 /// List installed and/or currently selected tool versions
 #[derive(Debug, clap::Args)]
@@ -31,6 +31,14 @@ pub struct Ls {
     #[clap(hide = true)]
     plugin_arg: Option<PluginName>,
 
+    /// Only show installed versions of [PLUGIN] starting with this prefix
+    /// e.g.: `rtx ls node 18` shows 18.0.0 and 18.1.0 but not 180.0.0
+    #[clap(long, verbatim_doc_comment)]
+    prefix: Option<String>,
+
+    #[clap(hide = true)]
+    prefix_arg: Option<String>,
+
     /// Only show tool versions currently specified in a .tool-versions/.rtx.toml
     #[clap(long, short)]
     current: bool,
@@ -47,21 +55,38 @@ pub struct Ls {
     /// Output in json format
     #[clap(long, visible_short_alias = 'J', overrides_with = "parseable")]
     json: bool,
+
+    /// Show each plugin's versions with the config files that request them
+    #[clap(long, conflicts_with_all = ["parseable", "prefix"])]
+    tree: bool,
+
+    /// Show when each installed version was installed and by which revision of its plugin
+    /// from the receipt written at install time
+    #[clap(long, conflicts_with_all = ["parseable", "prefix", "tree"], verbatim_doc_comment)]
+    receipts: bool,
 }
 
 impl Command for Ls {
     fn run(mut self, mut config: Config, out: &mut Output) -> Result<()> {
         self.plugin = self.plugin.clone().or(self.plugin_arg.clone());
+        self.prefix = self.prefix.clone().or(self.prefix_arg.clone());
         self.verify_plugin(&config)?;
 
         let mut runtimes = get_runtime_list(&mut config, &self.plugin)?;
+        if let Some(prefix) = &self.prefix {
+            return self.display_prefix(&config, runtimes, prefix, out);
+        }
         if self.current {
             runtimes.retain(|(_, _, source)| source.is_some());
         }
         if self.installed {
             runtimes.retain(|(p, tv, _)| p.is_version_installed(tv));
         }
-        if self.json {
+        if self.receipts {
+            self.display_receipts(runtimes, out)
+        } else if self.tree {
+            self.display_tree(&config, runtimes, out)
+        } else if self.json {
             self.display_json(runtimes, out)
         } else if self.parseable {
             self.display_parseable(runtimes, out)
@@ -71,6 +96,14 @@ impl Command for Ls {
     }
 }
 
+impl JsonOutput for Ls {
+    fn json(mut self) -> Result<Self> {
+        self.json = true;
+        self.parseable = false;
+        Ok(self)
+    }
+}
+
 type JSONOutput = IndexMap<PluginName, Vec<JSONToolVersion>>;
 
 #[derive(Serialize)]
@@ -81,6 +114,21 @@ struct JSONToolVersion {
     source: Option<IndexMap<String, String>>,
 }
 
+/// plugin → version → config files (or other sources) requesting it
+type Tree = IndexMap<PluginName, IndexMap<String, TreeVersion>>;
+
+struct TreeVersion {
+    installed: bool,
+    sources: Vec<ToolSource>,
+}
+
+#[derive(Serialize)]
+struct JSONTreeVersion {
+    version: String,
+    installed: bool,
+    sources: Vec<IndexMap<String, String>>,
+}
+
 impl Ls {
     fn verify_plugin(&self, config: &Config) -> Result<()> {
         match &self.plugin {
@@ -95,6 +143,39 @@ impl Ls {
         Ok(())
     }
 
+    fn display_prefix(
+        &self,
+        config: &Config,
+        mut runtimes: Vec<RuntimeRow>,
+        prefix: &str,
+        out: &mut Output,
+    ) -> Result<()> {
+        let plugin_name = match &self.plugin {
+            Some(plugin_name) => plugin_name,
+            None => return Err(eyre!("--prefix requires a plugin, e.g.: rtx ls node 18")),
+        };
+        let tool = config
+            .tools
+            .get(plugin_name)
+            .ok_or_else(|| PluginNotInstalled(plugin_name.clone()))?;
+        let matching = tool.list_installed_versions_with_prefix(prefix)?;
+        runtimes.retain(|(p, tv, _)| p.is_version_installed(tv) && matching.contains(&tv.version));
+        if runtimes.is_empty() {
+            return Err(eyre!(
+                "no installed versions of {} match {}",
+                plugin_name,
+                prefix
+            ));
+        }
+        if self.json {
+            return self.display_json(runtimes, out);
+        }
+        for (_, tv, _) in runtimes {
+            rtxprintln!(out, "{}", tv.version);
+        }
+        Ok(())
+    }
+
     fn display_json(&self, runtimes: Vec<RuntimeRow>, out: &mut Output) -> Result<()> {
         let mut plugins = JSONOutput::new();
         for (plugin_name, runtimes) in &runtimes
@@ -111,12 +192,77 @@ impl Ls {
                 .collect();
             if self.plugin.is_some() {
                 // only display 1 plugin
-                out.stdout.writeln(serde_json::to_string_pretty(&runtimes)?);
+                out.stdout.writeln(to_json("versions", &runtimes)?);
                 return Ok(());
             }
             plugins.insert(plugin_name.clone(), runtimes);
         }
-        out.stdout.writeln(serde_json::to_string_pretty(&plugins)?);
+        out.stdout.writeln(to_json("tools", &plugins)?);
+        Ok(())
+    }
+
+    fn display_tree(
+        &self,
+        config: &Config,
+        runtimes: Vec<RuntimeRow>,
+        out: &mut Output,
+    ) -> Result<()> {
+        let tree = build_tree(config, runtimes);
+        if self.json {
+            let tree = tree
+                .into_iter()
+                .map(|(plugin, versions)| {
+                    let versions = versions
+                        .into_iter()
+                        .map(|(version, tv)| JSONTreeVersion {
+                            version,
+                            installed: tv.installed,
+                            sources: tv.sources.iter().map(|s| s.as_json()).collect(),
+                        })
+                        .collect_vec();
+                    (plugin, versions)
+                })
+                .collect::<IndexMap<_, _>>();
+            out.stdout.writeln(to_json("tools", &tree)?);
+            return Ok(());
+        }
+        for line in render_tree(&tree, console::user_attended()) {
+            rtxprintln!(out, "{}", line);
+        }
+        Ok(())
+    }
+
+    fn display_receipts(&self, runtimes: Vec<RuntimeRow>, out: &mut Output) -> Result<()> {
+        let receipts = runtimes
+            .into_iter()
+            .filter(|(p, tv, _)| p.is_version_installed(tv))
+            .map(|(_, tv, _)| {
+                let receipt = InstallReceipt::read(&tv);
+                if let Err(err) = &receipt {
+                    warn!("invalid receipt for {}: {:#}", tv, err);
+                }
+                (tv.to_string(), receipt)
+            })
+            .collect_vec();
+        if self.json {
+            let receipts = receipts
+                .into_iter()
+                .map(|(tv, r)| (tv, r.ok().flatten()))
+                .collect::<IndexMap<_, _>>();
+            out.stdout.writeln(to_json("receipts", &receipts)?);
+            return Ok(());
+        }
+        for (tv, receipt) in receipts {
+            match receipt {
+                Ok(Some(r)) => {
+                    let sha = r.plugin_sha.as_deref().unwrap_or_default();
+                    let sha = &sha[..sha.len().min(7)];
+                    rtxprintln!(out, "{:30} {} {}", tv, r.installed_at, sha);
+                }
+                Ok(None) => rtxprintln!(out, "{:30} {}", tv, style("no receipt").dim()),
+                Err(_) => rtxprintln!(out, "{:30} {}", tv, style("invalid receipt").red()),
+            }
+        }
         Ok(())
     }
 
@@ -192,6 +338,72 @@ impl Ls {
 
 type RuntimeRow = (Arc<Tool>, ToolVersion, Option<ToolSource>);
 
+fn build_tree(config: &Config, runtimes: Vec<RuntimeRow>) -> Tree {
+    let requested = requested_versions(config);
+    let mut tree = Tree::new();
+    for (tool, tv, source) in runtimes {
+        let mut sources = requested
+            .iter()
+            .filter(|(plugin, version, _)| plugin == &tool.name && version == &tv.version)
+            .map(|(_, _, source)| source.clone())
+            .collect_vec();
+        // versions set outside of a config file only show up in the toolset
+        if let Some(source @ (ToolSource::Argument | ToolSource::Environment(..))) = source {
+            sources.push(source);
+        }
+        let installed = tool.is_version_installed(&tv);
+        tree.entry(tool.name.clone())
+            .or_default()
+            .insert(tv.version, TreeVersion { installed, sources });
+    }
+    tree
+}
+
+/// every version requested by every config file, not just the ones that are active
+fn requested_versions(config: &Config) -> Vec<(PluginName, String, ToolSource)> {
+    config
+        .config_files
+        .values()
+        .flat_map(|cf| cf.to_toolset().versions.values().cloned().collect_vec())
+        .flat_map(|mut tvl| {
+            tvl.resolve(config, false);
+            let source = tvl.source;
+            tvl.versions
+                .into_iter()
+                .map(|tv| (tv.plugin_name, tv.version, source.clone()))
+                .collect_vec()
+        })
+        .collect()
+}
+
+/// box-drawing characters are swapped for plain indentation when they would end up in a file or pipe
+fn render_tree(tree: &Tree, box_drawing: bool) -> Vec<String> {
+    let (branch, last, pipe, blank) = match box_drawing {
+        true => ("├── ", "└── ", "│   ", "    "),
+        false => ("  ", "  ", "  ", "  "),
+    };
+    let connector = |i: usize, len: usize| if i + 1 == len { last } else { branch };
+    let mut lines = vec![];
+    for (plugin, versions) in tree {
+        lines.push(style(plugin).cyan().to_string());
+        for (i, (version, tv)) in versions.iter().enumerate() {
+            let version = match tv.installed {
+                true => version.to_string(),
+                false => format!("{} {}", version, style("(missing)").red()),
+            };
+            lines.push(format!("{}{}", connector(i, versions.len()), version));
+            let indent = if i + 1 == versions.len() { blank } else { pipe };
+            for (j, source) in tv.sources.iter().enumerate() {
+                lines.push(format!(
+                    "{indent}{}{source}",
+                    connector(j, tv.sources.len())
+                ));
+            }
+        }
+    }
+    lines
+}
+
 fn get_runtime_list(
     config: &mut Config,
     plugin_flag: &Option<PluginName>,
@@ -287,23 +499,44 @@ static AFTER_LONG_HELP: &str = color_print::cstr!(
   ⏵  node     20.0.0 (set by ~/src/myapp/.tool-versions)
   ⏵  python     3.11.0 (set by ~/.tool-versions)
 
+  $ <bold>rtx ls node 20</bold>
+  20.0.0
+  20.1.0
+
+  $ <bold>rtx ls --tree</bold>
+  node
+  └── 20.0.0
+      └── ~/src/myapp/.tool-versions
+  python
+  ├── 3.10.0
+  └── 3.11.0
+      ├── ~/.tool-versions
+      └── ~/src/myapp/.rtx.toml
+
+  $ <bold>rtx ls --receipts</bold>
+  node@20.0.0                    2023-06-12T09:41:02Z 8b0d7e1
+  python@3.10.0                  no receipt
+
   $ <bold>rtx ls --parseable</bold>
   node 20.0.0
   python 3.11.0
 
   $ <bold>rtx ls --json</bold>
   {
-    "node": [
-      {
-        "version": "20.0.0",
-        "install_path": "/Users/jdx/.rtx/installs/node/20.0.0",
-        "source": {
-          "type": ".rtx.toml",
-          "path": "/Users/jdx/.rtx.toml"
+    "schema_version": 1,
+    "tools": {
+      "node": [
+        {
+          "version": "20.0.0",
+          "install_path": "/Users/jdx/.rtx/installs/node/20.0.0",
+          "source": {
+            "type": ".rtx.toml",
+            "path": "/Users/jdx/.rtx.toml"
+          }
         }
-      }
-    ],
-    "python": [...]
+      ],
+      "python": [...]
+    }
   }
 "#
 );
@@ -315,6 +548,8 @@ mod tests {
     use crate::file::remove_all;
     use crate::{assert_cli, assert_cli_err, assert_cli_snapshot, dirs};
 
+    use super::*;
+
     #[test]
     fn test_ls() {
         let _ = remove_all(dirs::INSTALLS.as_path());
@@ -339,12 +574,86 @@ mod tests {
         assert_cli_snapshot!("ls", "-c");
     }
 
+    #[test]
+    fn test_ls_tree() {
+        assert_cli_snapshot!("ls", "--current", "--tree");
+    }
+
+    #[test]
+    fn test_render_tree() {
+        let tv = |installed, sources: &[&str]| TreeVersion {
+            installed,
+            sources: sources
+                .iter()
+                .map(|p| ToolSource::ToolVersions(PathBuf::from(p)))
+                .collect(),
+        };
+        let mut tree = Tree::new();
+        tree.insert(
+            "node".into(),
+            IndexMap::from([
+                ("18.0.0".into(), tv(true, &[])),
+                (
+                    "20.0.0".into(),
+                    tv(false, &["/app/.tool-versions", "/.tool-versions"]),
+                ),
+            ]),
+        );
+        tree.insert(
+            "python".into(),
+            IndexMap::from([("3.11.0".into(), tv(true, &["/app/.tool-versions"]))]),
+        );
+        assert_str_eq!(
+            render_tree(&tree, true).join("\n"),
+            [
+                "node",
+                "├── 18.0.0",
+                "└── 20.0.0 (missing)",
+                "    ├── /app/.tool-versions",
+                "    └── /.tool-versions",
+                "python",
+                "└── 3.11.0",
+                "    └── /app/.tool-versions",
+            ]
+            .join("\n")
+        );
+        assert_str_eq!(
+            render_tree(&tree, false).join("\n"),
+            [
+                "node",
+                "  18.0.0",
+                "  20.0.0 (missing)",
+                "    /app/.tool-versions",
+                "    /.tool-versions",
+                "python",
+                "  3.11.0",
+                "    /app/.tool-versions",
+            ]
+            .join("\n")
+        );
+    }
+
     #[test]
     fn test_ls_json() {
         let _ = remove_all(dirs::INSTALLS.as_path());
         assert_cli!("install");
         assert_cli_snapshot!("ls", "--json");
         assert_cli_snapshot!("ls", "--json", "tiny");
+
+        let doc: serde_json::Value =
+            serde_json::from_str(&assert_cli!("ls", "--json", "--tree")).unwrap();
+        assert!(doc["tools"]["tiny"].is_array());
+    }
+
+    #[test]
+    fn test_ls_global_json() {
+        let stdout = assert_cli!("--json", "ls", "--current", "tiny");
+        assert_str_eq!(stdout, assert_cli!("ls", "--current", "tiny", "--json"));
+        let doc: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+        let versions = doc["versions"].as_array().unwrap();
+        assert_eq!(versions.len(), 1);
+        assert_eq!(versions[0]["version"], "3.1.0");
+        assert_eq!(versions[0]["requested_version"], "3");
     }
 
     #[test]
@@ -355,6 +664,26 @@ mod tests {
         assert_cli_snapshot!("ls", "--parseable", "tiny");
     }
 
+    #[test]
+    fn test_ls_prefix() {
+        assert_cli!("install", "tiny@1.0.1");
+        assert_str_eq!(assert_cli!("ls", "tiny", "1.0.1"), "1.0.1\n");
+        assert_str_eq!(assert_cli!("ls", "--prefix", "1.0", "tiny"), "1.0.1\n");
+        let json = assert_cli!("ls", "--json", "tiny", "1.0");
+        assert!(json.contains(r#""version": "1.0.1""#));
+
+        let err = assert_cli_err!("ls", "tiny", "1.0.10");
+        assert_str_eq!(
+            err.to_string(),
+            "no installed versions of tiny match 1.0.10"
+        );
+        let err = assert_cli_err!("ls", "--prefix", "1");
+        assert_str_eq!(
+            err.to_string(),
+            "--prefix requires a plugin, e.g.: rtx ls node 18"
+        );
+    }
+
     #[test]
     fn test_ls_missing_plugin() {
         let err = assert_cli_err!("ls", "missing-plugin");
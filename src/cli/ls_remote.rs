@@ -1,11 +1,12 @@
 use color_eyre::eyre::Result;
+use serde_derive::Serialize;
 use std::sync::Arc;
 
 use crate::cli::args::tool::{ToolArg, ToolArgParser};
-use crate::cli::command::Command;
+use crate::cli::command::{Command, JsonOutput};
 use crate::config::Config;
 use crate::errors::Error::PluginNotInstalled;
-use crate::output::Output;
+use crate::output::{to_json, Output};
 use crate::tool::Tool;
 use crate::toolset::ToolVersionRequest;
 use crate::ui::multi_progress_report::MultiProgressReport;
@@ -13,6 +14,7 @@ use crate::ui::prompt;
 
 /// List runtime versions available for install
 ///
+/// Prereleases (e.g.: 2.0.0-rc1) are hidden unless --all is passed.
 /// note that the results are cached for 24 hours
 /// run `rtx cache clean` to clear the cache and get fresh results
 #[derive(Debug, clap::Args)]
@@ -26,6 +28,25 @@ pub struct LsRemote {
     /// same as the first argument after the "@"
     #[clap(verbatim_doc_comment)]
     prefix: Option<String>,
+
+    /// Show all versions including prereleases
+    #[clap(long)]
+    all: bool,
+
+    /// Put a "*" in front of versions that are already installed
+    #[clap(long, short = 'i')]
+    installed_marker: bool,
+
+    /// Output in json format, each version has an "installed" field
+    #[clap(long, short = 'J')]
+    json: bool,
+}
+
+impl JsonOutput for LsRemote {
+    fn json(mut self) -> Result<Self> {
+        self.json = true;
+        Ok(self)
+    }
 }
 
 impl Command for LsRemote {
@@ -37,7 +58,10 @@ impl Command for LsRemote {
             _ => self.prefix.as_ref(),
         };
 
-        let versions = plugin.list_remote_versions(&config.settings)?;
+        let versions = match self.all {
+            true => plugin.list_remote_versions(&config.settings)?,
+            false => plugin.list_stable_remote_versions(&config.settings)?,
+        };
         let versions = match prefix {
             Some(prefix) => versions
                 .into_iter()
@@ -46,6 +70,10 @@ impl Command for LsRemote {
             None => versions,
         };
 
+        if self.json || self.installed_marker {
+            let installed = plugin.list_installed_versions()?;
+            return self.output_with_installed(out, versions, &installed);
+        }
         for version in versions {
             rtxprintln!(out, "{}", version);
         }
@@ -54,7 +82,35 @@ impl Command for LsRemote {
     }
 }
 
+#[derive(Serialize)]
+struct JSONVersion {
+    version: String,
+    installed: bool,
+}
+
 impl LsRemote {
+    fn output_with_installed(
+        &self,
+        out: &mut Output,
+        versions: Vec<String>,
+        installed: &[String],
+    ) -> Result<()> {
+        let versions = versions.into_iter().map(|version| JSONVersion {
+            installed: installed.contains(&version),
+            version,
+        });
+        if self.json {
+            let versions = versions.collect::<Vec<_>>();
+            rtxprintln!(out, "{}", to_json("versions", &versions)?);
+            return Ok(());
+        }
+        for v in versions {
+            let marker = if v.installed { "*" } else { " " };
+            rtxprintln!(out, "{} {}", marker, v.version);
+        }
+        Ok(())
+    }
+
     fn get_plugin(&self, config: &mut Config) -> Result<Arc<Tool>> {
         let plugin_name = self.plugin.plugin.clone();
         let tool = config.get_or_create_tool(&plugin_name);
@@ -70,7 +126,7 @@ impl LsRemote {
             "Plugin {} is not installed, would you like to install it?",
             tool.name
         ))? {
-            let mpr = MultiProgressReport::new(config.settings.verbose);
+            let mpr = MultiProgressReport::new(&config.settings);
             let mut pr = mpr.add();
             tool.install(config, &mut pr, false)?;
             return Ok(());
@@ -93,12 +149,27 @@ static AFTER_LONG_HELP: &str = color_print::cstr!(
   $ <bold>rtx ls-remote node 20</bold>
   20.0.0
   20.1.0
+
+  $ <bold>rtx ls-remote node --all</bold>
+  20.0.0
+  20.1.0
+  21.0.0-rc.1
+
+  $ <bold>rtx ls-remote node@20 --installed-marker</bold>
+    20.0.0
+  * 20.1.0
 "#
 );
 
 #[cfg(test)]
 mod tests {
-    use crate::assert_cli_snapshot;
+    use std::fs;
+
+    use pretty_assertions::assert_str_eq;
+
+    use crate::cli::tests::grep;
+    use crate::file::{make_executable, remove_all};
+    use crate::{assert_cli, assert_cli_snapshot, dirs};
 
     #[test]
     fn test_list_remote() {
@@ -110,4 +181,42 @@ mod tests {
         assert_cli_snapshot!("list-remote", "dummy", "1");
         assert_cli_snapshot!("list-remote", "dummy@2");
     }
+
+    #[test]
+    fn test_ls_remote_all() {
+        let plugin = dirs::PLUGINS.join("ls-remote-all");
+        let _ = remove_all(&plugin);
+        fs::create_dir_all(plugin.join("bin")).unwrap();
+        let list_all = plugin.join("bin/list-all");
+        fs::write(
+            &list_all,
+            "#!/usr/bin/env bash\necho 1.0.0 2.0.0-rc1 2.0.0\n",
+        )
+        .unwrap();
+        make_executable(&list_all).unwrap();
+
+        let stable = assert_cli!("ls-remote", "ls-remote-all");
+        let all = assert_cli!("ls-remote", "ls-remote-all", "--all");
+        remove_all(&plugin).unwrap();
+        let _ = remove_all(dirs::CACHE.join("ls-remote-all"));
+        assert_str_eq!(stable, "1.0.0\n2.0.0\n");
+        assert_str_eq!(all, "1.0.0\n2.0.0-rc1\n2.0.0\n");
+    }
+
+    #[test]
+    fn test_ls_remote_installed_marker() {
+        assert_cli!("install", "tiny@2.1.0");
+        let stdout = assert_cli!("ls-remote", "tiny@2", "--installed-marker");
+        assert_str_eq!(grep(stdout.clone(), "2.1.0"), "* 2.1.0");
+        assert!(stdout
+            .lines()
+            .all(|l| l.starts_with("* ") || l.starts_with("  ")));
+
+        let stdout = assert_cli!("ls-remote", "tiny@2", "--json");
+        let versions: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+        let versions = versions["versions"].as_array().unwrap();
+        assert!(versions.iter().all(|v| v["installed"].is_boolean()));
+        let v = versions.iter().find(|v| v["version"] == "2.1.0").unwrap();
+        assert_eq!(v["installed"], true);
+    }
 }
@@ -1,11 +1,14 @@
 use clap::{FromArgMatches, Subcommand};
+use std::time::Duration;
+
 use color_eyre::Result;
 use indoc::indoc;
 use log::LevelFilter;
 
-use crate::cli::command::Command;
-use crate::config::Config;
+use crate::cli::command::{json_not_supported, run_json, Command};
 use crate::config::MissingRuntimeBehavior::AutoInstall;
+use crate::config::{Config, MissingRuntimeBehavior};
+use crate::lock_file::global_lock;
 use crate::output::Output;
 
 mod activate;
@@ -13,10 +16,13 @@ mod alias;
 pub mod args;
 mod asdf;
 mod bin_paths;
+mod bundle;
 mod cache;
 pub mod command;
 mod completion;
+mod config;
 mod current;
+mod data;
 mod deactivate;
 mod direnv;
 mod doctor;
@@ -24,13 +30,14 @@ mod env;
 pub mod exec;
 mod external;
 mod global;
-mod hook_env;
+pub mod hook_env;
 mod implode;
 mod install;
 mod latest;
 mod local;
 mod ls;
 mod ls_remote;
+mod outdated;
 mod plugins;
 mod prune;
 #[cfg(debug_assertions)]
@@ -39,10 +46,12 @@ mod reshim;
 mod self_update;
 mod settings;
 mod shell;
+mod shim;
 mod trust;
 mod uninstall;
 mod r#use;
 pub mod version;
+mod version_exists;
 mod r#where;
 mod r#which;
 
@@ -57,9 +66,12 @@ pub enum Commands {
     Alias(alias::Alias),
     Asdf(asdf::Asdf),
     BinPaths(bin_paths::BinPaths),
+    Bundle(bundle::Bundle),
     Cache(cache::Cache),
     Completion(completion::Completion),
+    Config(config::Cfg),
     Current(current::Current),
+    Data(data::Data),
     Deactivate(deactivate::Deactivate),
     Direnv(direnv::Direnv),
     Doctor(doctor::Doctor),
@@ -73,16 +85,19 @@ pub enum Commands {
     Local(local::Local),
     Ls(ls::Ls),
     LsRemote(ls_remote::LsRemote),
+    Outdated(outdated::Outdated),
     Plugins(plugins::Plugins),
     Prune(prune::Prune),
     Reshim(reshim::Reshim),
     SelfUpdate(self_update::SelfUpdate),
     Settings(settings::Settings),
     Shell(shell::Shell),
+    Shim(shim::Shim),
     Trust(trust::Trust),
     Uninstall(uninstall::Uninstall),
     Use(r#use::Use),
     Version(version::Version),
+    VersionExists(version_exists::VersionExists),
     Where(r#where::Where),
     Which(which::Which),
 
@@ -92,14 +107,20 @@ pub enum Commands {
 
 impl Commands {
     pub fn run(self, config: Config, out: &mut Output) -> Result<()> {
+        if out.json {
+            return self.run_json(config, out);
+        }
         match self {
             Self::Activate(cmd) => cmd.run(config, out),
             Self::Alias(cmd) => cmd.run(config, out),
             Self::Asdf(cmd) => cmd.run(config, out),
             Self::BinPaths(cmd) => cmd.run(config, out),
+            Self::Bundle(cmd) => cmd.run(config, out),
             Self::Cache(cmd) => cmd.run(config, out),
             Self::Completion(cmd) => cmd.run(config, out),
+            Self::Config(cmd) => cmd.run(config, out),
             Self::Current(cmd) => cmd.run(config, out),
+            Self::Data(cmd) => cmd.run(config, out),
             Self::Deactivate(cmd) => cmd.run(config, out),
             Self::Direnv(cmd) => cmd.run(config, out),
             Self::Doctor(cmd) => cmd.run(config, out),
@@ -113,16 +134,19 @@ impl Commands {
             Self::Local(cmd) => cmd.run(config, out),
             Self::Ls(cmd) => cmd.run(config, out),
             Self::LsRemote(cmd) => cmd.run(config, out),
+            Self::Outdated(cmd) => cmd.run(config, out),
             Self::Plugins(cmd) => cmd.run(config, out),
             Self::Prune(cmd) => cmd.run(config, out),
             Self::Reshim(cmd) => cmd.run(config, out),
             Self::SelfUpdate(cmd) => cmd.run(config, out),
             Self::Settings(cmd) => cmd.run(config, out),
             Self::Shell(cmd) => cmd.run(config, out),
+            Self::Shim(cmd) => cmd.run(config, out),
             Self::Trust(cmd) => cmd.run(config, out),
             Self::Uninstall(cmd) => cmd.run(config, out),
             Self::Use(cmd) => cmd.run(config, out),
             Self::Version(cmd) => cmd.run(config, out),
+            Self::VersionExists(cmd) => cmd.run(config, out),
             Self::Where(cmd) => cmd.run(config, out),
             Self::Which(cmd) => cmd.run(config, out),
 
@@ -130,6 +154,22 @@ impl Commands {
             Self::RenderHelp(cmd) => cmd.run(config, out),
         }
     }
+
+    /// `rtx --json <command>`, only the commands with a --json flag of their own support it
+    fn run_json(self, config: Config, out: &mut Output) -> Result<()> {
+        match self {
+            Self::Config(cmd) => cmd.run_json(config, out),
+            Self::Current(cmd) => run_json(cmd, config, out),
+            Self::Data(cmd) => cmd.run_json(config, out),
+            Self::Env(cmd) => run_json(cmd, config, out),
+            Self::Install(cmd) => run_json(cmd, config, out),
+            Self::Ls(cmd) => run_json(cmd, config, out),
+            Self::LsRemote(cmd) => run_json(cmd, config, out),
+            Self::Plugins(cmd) => cmd.run_json(config, out),
+            Self::Settings(cmd) => cmd.run_json(config, out),
+            _ => Err(json_not_supported()),
+        }
+    }
 }
 
 impl Cli {
@@ -158,10 +198,14 @@ impl Cli {
                 .arg_required_else_help(true)
                 .subcommand_required(true)
                 .after_long_help(AFTER_LONG_HELP)
+                .arg(args::config_file::ConfigFile::arg())
                 .arg(args::log_level::Debug::arg())
                 .arg(args::install_missing::InstallMissing::arg())
                 .arg(args::jobs::Jobs::arg())
+                .arg(args::json::Json::arg())
                 .arg(args::log_level::LogLevel::arg())
+                .arg(args::missing::Missing::arg())
+                .arg(args::no_lock::NoLock::arg())
                 .arg(args::raw::Raw::arg())
                 .arg(args::log_level::Trace::arg())
                 .arg(args::verbose::Verbose::arg()),
@@ -187,18 +231,26 @@ impl Cli {
         if let Some(jobs) = matches.get_one::<usize>("jobs") {
             config.settings.jobs = *jobs;
         }
+        if let Some(true) = matches.get_one::<bool>("json") {
+            out.json = true;
+        }
         if let Some(raw) = matches.get_one::<bool>("raw") {
             config.settings.raw = *raw;
         }
         if let Some(true) = matches.get_one::<bool>("install-missing") {
             config.settings.missing_runtime_behavior = AutoInstall;
         }
+        if let Some(missing) = matches.get_one::<MissingRuntimeBehavior>("missing") {
+            config.settings.missing_runtime_behavior = missing.clone();
+        }
         if *matches.get_one::<u8>("verbose").unwrap() > 0 {
             config.settings.verbose = true;
+            config.settings.plain_progress = false;
         }
         if config.settings.raw {
             config.settings.jobs = 1;
             config.settings.verbose = true;
+            config.settings.plain_progress = false;
         }
         if let Some((command, sub_m)) = matches.subcommand() {
             external::execute(&config, command, sub_m, self.external_commands)?;
@@ -207,7 +259,39 @@ impl Cli {
         if !is_fast_command(&cmd) {
             config.check_for_new_version();
         }
-        cmd.run(config, out)
+        let no_lock = matches.get_one::<bool>("no-lock") == Some(&true);
+        let _lock = match is_mutating_command(&cmd) && !no_lock {
+            true => global_lock(GLOBAL_LOCK_TIMEOUT)?,
+            false => None,
+        };
+        let result = cmd.run(config, out);
+        crate::cache::finish_refreshes();
+        result
+    }
+}
+
+/// how long a mutating command waits for another one to finish before giving up
+const GLOBAL_LOCK_TIMEOUT: Duration = Duration::from_secs(60 * 30);
+
+/// commands that change installs, plugins, shims or config files and take the global lock
+fn is_mutating_command(cmd: &Commands) -> bool {
+    match cmd {
+        Commands::Plugins(plugins) => plugins.is_mutating(),
+        // the lock is released when the command replaces rtx, it is opened with O_CLOEXEC
+        Commands::Exec(exec) => !exec.no_install,
+        cmd => matches!(
+            cmd,
+            Commands::Global(..)
+                | Commands::Implode(..)
+                | Commands::Install(..)
+                | Commands::Local(..)
+                | Commands::Prune(..)
+                | Commands::Reshim(..)
+                | Commands::SelfUpdate(..)
+                | Commands::Shim(..)
+                | Commands::Uninstall(..)
+                | Commands::Use(..)
+        ),
     }
 }
 
@@ -314,4 +398,99 @@ pub mod tests {
             .unwrap()
             .replace(dirs::HOME.to_string_lossy().as_ref(), "~")
     }
+
+    #[test]
+    fn test_mutating_command_waits_for_lock() {
+        let (tx, rx) = std::sync::mpsc::channel();
+        let holder = std::thread::spawn(move || {
+            let lock = global_lock(GLOBAL_LOCK_TIMEOUT).unwrap();
+            tx.send(()).unwrap();
+            std::thread::sleep(Duration::from_millis(300));
+            drop(lock);
+        });
+        rx.recv().unwrap();
+
+        let start = std::time::Instant::now();
+        crate::assert_cli!("reshim");
+        assert!(start.elapsed() >= Duration::from_millis(300));
+        holder.join().unwrap();
+
+        let _lock = global_lock(GLOBAL_LOCK_TIMEOUT).unwrap();
+        crate::assert_cli!("reshim", "--no-lock");
+    }
+
+    #[test]
+    fn test_is_mutating_command() {
+        let is_mutating = |args: &[&str]| {
+            let matches = Cli::command().get_matches_from(args);
+            is_mutating_command(&Commands::from_arg_matches(&matches).unwrap())
+        };
+        assert!(is_mutating(&["rtx", "use", "tiny@3"]));
+        assert!(is_mutating(&["rtx", "local", "tiny@3"]));
+        assert!(is_mutating(&["rtx", "global", "tiny@3"]));
+        assert!(is_mutating(&["rtx", "x", "tiny@3", "--", "true"]));
+        assert!(!is_mutating(&[
+            "rtx",
+            "x",
+            "--no-install",
+            "tiny@3",
+            "--",
+            "true"
+        ]));
+        assert!(!is_mutating(&["rtx", "ls"]));
+    }
+
+    #[test]
+    fn test_missing_overrides_config() {
+        let run = |args: &[&str]| {
+            let mut config = Config::load().unwrap();
+            // as if set with missing_runtime_behavior in a config file
+            config.settings.missing_runtime_behavior = AutoInstall;
+            let args = args.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+            let mut out = Output::tracked();
+            Cli::new_with_external_commands(&config)
+                .run(config, &args, &mut out)
+                .map(|_| grep(out.stdout.content, "missing_runtime_behavior"))
+        };
+        assert_eq!(
+            run(&["rtx", "settings"]).unwrap(),
+            "missing_runtime_behavior = autoinstall"
+        );
+        assert_eq!(
+            run(&["rtx", "settings", "--missing", "ignore"]).unwrap(),
+            "missing_runtime_behavior = ignore"
+        );
+        assert_eq!(
+            run(&["rtx", "--missing=warn", "settings"]).unwrap(),
+            "missing_runtime_behavior = warn"
+        );
+    }
+
+    #[test]
+    fn test_json_not_supported() {
+        for args in [["version"], ["doctor"]] {
+            let err = assert_cli_err!("--json", args[0]);
+            assert_eq!(err.to_string(), "json not supported for this command");
+        }
+        let err = assert_cli_err!("version", "--json");
+        assert_eq!(err.to_string(), "json not supported for this command");
+        let err = assert_cli_err!("plugins", "uninstall", "tiny", "--json");
+        assert_eq!(err.to_string(), "json not supported for this command");
+        let err = assert_cli_err!("--json", "install", "tiny");
+        assert_eq!(err.to_string(), "--json requires --summary");
+    }
+
+    #[test]
+    fn test_json_schema_version() {
+        for args in [["ls"], ["current"], ["settings"], ["env"]] {
+            let stdout = assert_cli!(args[0], "--json");
+            let doc: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+            assert_eq!(
+                doc["schema_version"],
+                crate::output::JSON_SCHEMA_VERSION,
+                "rtx {} --json",
+                args[0]
+            );
+        }
+    }
 }
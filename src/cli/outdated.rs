@@ -0,0 +1,122 @@
+use color_eyre::eyre::Result;
+use console::{measure_text_width, pad_str, Alignment};
+
+use crate::cli::args::tool::{ToolArg, ToolArgParser};
+use crate::cli::command::Command;
+use crate::config::Config;
+use crate::output::Output;
+use crate::toolset::ToolsetBuilder;
+
+/// Shows the installed tool versions that have a newer version available
+///
+/// A request for an exact version like `node 20.1.0` is never outdated. `latest`, prefixes like
+/// `node 20`, ranges and aliases are compared to the newest version they match. Tools installed
+/// from a git ref are outdated when the ref has new commits on origin.
+#[derive(Debug, clap::Args)]
+#[clap(verbatim_doc_comment, after_long_help = AFTER_LONG_HELP)]
+pub struct Outdated {
+    /// Tool(s) to check, e.g.: node@20 python
+    /// a version overrides the one from the config files, all active tools are checked if none are given
+    #[clap(value_name = "TOOL@VERSION", value_parser = ToolArgParser, verbatim_doc_comment)]
+    tool: Vec<ToolArg>,
+}
+
+impl Command for Outdated {
+    fn run(self, mut config: Config, out: &mut Output) -> Result<()> {
+        let ts = ToolsetBuilder::new()
+            .with_args(&self.tool)
+            .build(&mut config)?;
+        let mut rows = vec![];
+        for (tool, tv) in ts.list_current_versions(&config) {
+            if !self.tool.is_empty() && !self.tool.iter().any(|t| t.plugin == tool.name) {
+                continue;
+            }
+            // a prefix like `node@20` resolves to the newest remote version, compare to what is
+            // actually installed instead
+            let tv = match tv.installed_match(&tool) {
+                Ok(Some(tv)) => tv,
+                Ok(None) => continue,
+                Err(err) => {
+                    warn!(
+                        "failed to list installed versions of {}: {:#}",
+                        tool.name, err
+                    );
+                    continue;
+                }
+            };
+            match tv.newer_version(&tool, &config.settings) {
+                Ok(Some(latest)) => {
+                    rows.push([tool.name.clone(), tv.request.version(), tv.version, latest])
+                }
+                Ok(None) => {}
+                Err(err) => warn!("failed to check if {} is outdated: {:#}", tv, err),
+            }
+        }
+        if rows.is_empty() {
+            rtxstatusln!(out, "all tools are up to date");
+            return Ok(());
+        }
+        let header = ["Tool", "Requested", "Current", "Latest"].map(String::from);
+        let widths = (0..3)
+            .map(|i| {
+                rows.iter()
+                    .chain([&header])
+                    .map(|r| measure_text_width(&r[i]))
+                    .max()
+                    .unwrap_or_default()
+            })
+            .collect::<Vec<_>>();
+        for row in [&header].into_iter().chain(&rows) {
+            let cols = widths
+                .iter()
+                .enumerate()
+                .map(|(i, w)| pad_str(&row[i], *w, Alignment::Left, None))
+                .collect::<Vec<_>>();
+            rtxprintln!(out, "{}  {}", cols.join("  "), row[3]);
+        }
+        Ok(())
+    }
+}
+
+static AFTER_LONG_HELP: &str = color_print::cstr!(
+    r#"<bold><underline>Examples:</underline></bold>
+  $ <bold>rtx outdated</bold>
+  Tool  Requested  Current  Latest
+  node  20         20.0.0   20.1.0
+  $ <bold>rtx outdated node@18</bold>   # check node 18 even if another version is active
+"#
+);
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::file::{make_executable, remove_all};
+    use crate::{assert_cli, dirs};
+
+    #[test]
+    fn test_outdated() {
+        let plugin = dirs::PLUGINS.join("outdated-cli");
+        let _ = remove_all(&plugin);
+        fs::create_dir_all(plugin.join("bin")).unwrap();
+        for (script, body) in [("list-all", "echo 1.0.0 1.1.0 2.0.0"), ("install", "")] {
+            let path = plugin.join("bin").join(script);
+            fs::write(&path, format!("#!/usr/bin/env bash\n{body}\n")).unwrap();
+            make_executable(&path).unwrap();
+        }
+        fs::create_dir_all(dirs::INSTALLS.join("outdated-cli/1.0.0")).unwrap();
+        fs::create_dir_all(dirs::INSTALLS.join("outdated-cli/2.0.0")).unwrap();
+
+        let outdated = assert_cli!("outdated", "outdated-cli@1");
+        let exact = assert_cli!("outdated", "outdated-cli@2.0.0");
+        remove_all(&plugin).unwrap();
+        remove_all(dirs::INSTALLS.join("outdated-cli")).unwrap();
+        let _ = remove_all(dirs::CACHE.join("outdated-cli"));
+        assert_eq!(
+            outdated,
+            "Tool          Requested  Current  Latest\n\
+             outdated-cli  1          1.0.0    1.1.0\n"
+        );
+        assert_eq!(exact, "");
+    }
+}
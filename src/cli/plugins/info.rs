@@ -0,0 +1,134 @@
+use color_eyre::eyre::Result;
+use console::style;
+use itertools::Itertools;
+
+use crate::cli::command::{Command, JsonOutput};
+use crate::config::Config;
+use crate::errors::Error::PluginNotInstalled;
+use crate::output::{to_json, Output};
+use crate::plugins::{PluginMetadata, PluginType};
+
+/// Show details about a plugin
+///
+/// This includes where the plugin was installed from, the git ref it is on,
+/// how many versions of it are installed and the aliases and legacy filenames it provides.
+#[derive(Debug, clap::Args)]
+#[clap(verbatim_doc_comment, after_long_help = AFTER_LONG_HELP)]
+pub struct PluginsInfo {
+    /// Plugin to show details for
+    plugin: String,
+
+    /// Output in json format
+    #[clap(long, short = 'J')]
+    json: bool,
+}
+
+impl JsonOutput for PluginsInfo {
+    fn json(mut self) -> Result<Self> {
+        self.json = true;
+        Ok(self)
+    }
+}
+
+impl Command for PluginsInfo {
+    fn run(self, config: Config, out: &mut Output) -> Result<()> {
+        let tool = match config.tools.get(&self.plugin) {
+            Some(tool) if tool.is_installed() => tool,
+            _ => return Err(PluginNotInstalled(self.plugin))?,
+        };
+        let metadata = tool.plugin.metadata(&config.settings)?;
+        if self.json {
+            rtxprintln!(out, "{}", to_json("plugin", &metadata)?);
+            return Ok(());
+        }
+        for (key, value) in display_rows(&metadata) {
+            rtxprintln!(out, "{:19} {}", format!("{key}:"), value);
+        }
+        Ok(())
+    }
+}
+
+fn display_rows(metadata: &PluginMetadata) -> Vec<(&'static str, String)> {
+    let mut rows = vec![
+        ("name", style(&metadata.name).cyan().to_string()),
+        (
+            "type",
+            match metadata.plugin_type {
+                PluginType::Core => "core".to_string(),
+                PluginType::External => "external".to_string(),
+            },
+        ),
+    ];
+    if let Some(url) = &metadata.remote_url {
+        rows.push(("remote url", url.clone()));
+    }
+    match (&metadata.branch, &metadata.sha) {
+        (Some(branch), Some(sha)) => rows.push(("ref", format!("{branch}#{sha}"))),
+        (None, Some(sha)) => rows.push(("ref", sha.clone())),
+        _ => {}
+    }
+    rows.push((
+        "installed versions",
+        metadata.installed_versions.to_string(),
+    ));
+    if !metadata.aliases.is_empty() {
+        let aliases = metadata
+            .aliases
+            .iter()
+            .map(|(from, to)| format!("{from} -> {to}"))
+            .join(", ");
+        rows.push(("aliases", aliases));
+    }
+    if !metadata.legacy_filenames.is_empty() {
+        rows.push(("legacy filenames", metadata.legacy_filenames.join(", ")));
+    }
+    if let Some(version) = &metadata.min_rtx_version {
+        rows.push(("min rtx version", version.clone()));
+    }
+    rows
+}
+
+static AFTER_LONG_HELP: &str = color_print::cstr!(
+    r#"<bold><underline>Examples:</underline></bold>
+  $ <bold>rtx plugins info node</bold>
+  name:               node
+  type:               external
+  remote url:         https://github.com/asdf-vm/asdf-nodejs.git
+  ref:                master#4d5e6f7
+  installed versions: 3
+  aliases:            lts -> 20, lts-hydrogen -> 18
+  legacy filenames:   .nvmrc, .node-version
+
+  $ <bold>rtx plugins info node --json</bold>
+"#
+);
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_str_eq;
+
+    use crate::cli::tests::grep;
+    use crate::{assert_cli, assert_cli_err};
+
+    #[test]
+    fn test_plugin_info() {
+        let stdout = assert_cli!("plugins", "info", "tiny");
+        assert_str_eq!(grep(stdout.clone(), "name:"), "name:               tiny");
+        assert_str_eq!(
+            grep(stdout, "legacy filenames:"),
+            "legacy filenames:   .tiny-version"
+        );
+
+        let stdout = assert_cli!("plugins", "info", "tiny", "--json");
+        let doc: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+        let metadata = &doc["plugin"];
+        assert_eq!(metadata["type"], "external");
+        assert_eq!(metadata["aliases"]["lts"], "3.1.0");
+    }
+
+    #[test]
+    fn test_plugin_info_not_installed() {
+        let err = assert_cli_err!("plugins", "info", "not-a-plugin");
+        assert_str_eq!(err.to_string(), "[not-a-plugin] plugin not installed");
+    }
+}
@@ -1,4 +1,4 @@
-use color_eyre::eyre::{eyre, Result};
+use color_eyre::eyre::{eyre, Result, WrapErr};
 use rayon::prelude::*;
 use rayon::ThreadPoolBuilder;
 use url::Url;
@@ -7,8 +7,12 @@ use crate::cli::command::Command;
 use crate::config::Config;
 use crate::output::Output;
 use crate::plugins::{ExternalPlugin, Plugin, PluginName};
+use crate::runtime_symlinks::rebuild_symlinks;
+use crate::shims::reshim;
 use crate::tool::Tool;
-use crate::toolset::ToolsetBuilder;
+use crate::toolset::{
+    plugin_install_result, ToolVersionOptions, ToolVersionRequest, ToolsetBuilder,
+};
 use crate::ui::multi_progress_report::MultiProgressReport;
 
 /// Install a plugin
@@ -28,6 +32,7 @@ pub struct PluginsInstall {
 
     /// The git url of the plugin
     /// e.g.: https://github.com/asdf-vm/asdf-node.git
+    /// or a shorthand for it: asdf-vm/asdf-node, github:asdf-vm/asdf-node, gitlab:owner/repo
     #[clap(help = "The git url of the plugin", value_hint = clap::ValueHint::Url, verbatim_doc_comment)]
     git_url: Option<String>,
 
@@ -41,6 +46,10 @@ pub struct PluginsInstall {
     #[clap(short, long, conflicts_with_all = ["name", "force"], verbatim_doc_comment)]
     all: bool,
 
+    /// Also install the latest stable version of the plugin once it is added
+    #[clap(long, conflicts_with = "all")]
+    install: bool,
+
     /// Show installation output
     #[clap(long, short, action = clap::ArgAction::Count, verbatim_doc_comment)]
     verbose: u8,
@@ -51,20 +60,25 @@ pub struct PluginsInstall {
 
 impl Command for PluginsInstall {
     fn run(self, mut config: Config, _out: &mut Output) -> Result<()> {
-        let mpr = MultiProgressReport::new(config.settings.verbose);
+        let mpr = MultiProgressReport::new(&config.settings);
         if self.all {
-            return self.install_all_missing_plugins(&mut config, mpr);
+            return self.install_all_missing_plugins(&mut config, &mpr);
         }
         let (name, git_url) = get_name_and_url(&self.name.clone().unwrap(), &self.git_url)?;
-        if git_url.is_some() {
+        let plugins = if git_url.is_some() {
             self.install_one(&config, &name, git_url, &mpr)?;
+            vec![name]
         } else {
             let mut plugins: Vec<PluginName> = vec![name];
             if let Some(second) = self.git_url.clone() {
                 plugins.push(second);
             };
             plugins.extend(self.rest.clone());
-            self.install_many(&mut config, &plugins, mpr)?;
+            self.install_many(&mut config, &plugins, &mpr)?;
+            plugins
+        };
+        if self.install {
+            self.install_latest_versions(&mut config, &plugins, &mpr)?;
         }
 
         Ok(())
@@ -75,7 +89,7 @@ impl PluginsInstall {
     fn install_all_missing_plugins(
         &self,
         config: &mut Config,
-        mpr: MultiProgressReport,
+        mpr: &MultiProgressReport,
     ) -> Result<()> {
         let ts = ToolsetBuilder::new().build(config)?;
         let missing_plugins = ts.list_missing_plugins(config);
@@ -90,17 +104,21 @@ impl PluginsInstall {
         &self,
         config: &mut Config,
         plugins: &[PluginName],
-        mpr: MultiProgressReport,
+        mpr: &MultiProgressReport,
     ) -> Result<()> {
         ThreadPoolBuilder::new()
             .num_threads(config.settings.jobs)
             .build()?
-            .install(|| -> Result<()> {
-                plugins
+            .install(|| {
+                let failures = plugins
                     .into_par_iter()
-                    .map(|plugin| self.install_one(config, plugin, None, &mpr))
-                    .collect::<Result<Vec<_>>>()?;
-                Ok(())
+                    .filter_map(|plugin| {
+                        self.install_one(config, plugin, None, mpr)
+                            .err()
+                            .map(|err| (plugin.clone(), err))
+                    })
+                    .collect();
+                plugin_install_result(failures)
             })
     }
 
@@ -112,15 +130,43 @@ impl PluginsInstall {
         mpr: &MultiProgressReport,
     ) -> Result<()> {
         let mut plugin = ExternalPlugin::new(name);
-        plugin.repo_url = git_url;
+        plugin.repo_urls = git_url.into_iter().collect();
         if !self.force && plugin.is_installed() {
             mpr.warn(format!("plugin {} already installed", name));
         } else {
             let mut pr = mpr.add();
             let tool = Tool::new(plugin.name.clone(), Box::new(plugin));
             tool.decorate_progress_bar(&mut pr, None);
-            tool.install(config, &mut pr, self.force)?;
+            if let Err(err) = tool.install(config, &mut pr, self.force) {
+                pr.error();
+                return Err(err);
+            }
+        }
+        Ok(())
+    }
+
+    fn install_latest_versions(
+        &self,
+        config: &mut Config,
+        plugins: &[PluginName],
+        mpr: &MultiProgressReport,
+    ) -> Result<()> {
+        for name in plugins {
+            let tool = config.get_or_create_tool(name);
+            let tvr = ToolVersionRequest::Version(name.clone(), "latest".into());
+            let tv = tvr.resolve(config, &tool, ToolVersionOptions::new(), true)?;
+            if tool.is_version_installed(&tv) {
+                continue;
+            }
+            let mut pr = mpr.add();
+            if let Err(err) = tool.install_version(config, &tv, &mut pr, false) {
+                pr.error();
+                return Err(err).wrap_err_with(|| format!("failed to install {tv}"));
+            }
         }
+        let ts = ToolsetBuilder::new().build(config)?;
+        reshim(config, &ts).wrap_err("failed to reshim")?;
+        rebuild_symlinks(config)?;
         Ok(())
     }
 }
@@ -129,15 +175,47 @@ fn get_name_and_url(name: &str, git_url: &Option<String>) -> Result<(String, Opt
     Ok(match git_url {
         Some(url) => match url.contains("://") {
             true => (name.to_string(), Some(url.clone())),
-            false => (name.to_string(), None),
+            false => (name.to_string(), expand_repo_shorthand(url)),
         },
         None => match name.contains("://") {
             true => (get_name_from_url(name)?, Some(name.to_string())),
-            false => (name.to_string(), None),
+            false => match expand_repo_shorthand(name) {
+                Some(url) => (get_name_from_url(&url)?, Some(url)),
+                None => (name.to_string(), None),
+            },
         },
     })
 }
 
+/// expands `owner/repo` and `github:owner/repo` to the https url of the repository,
+/// `gitlab:` and `codeberg:` work the same way for those hosts. A trailing `#ref` is kept.
+fn expand_repo_shorthand(s: &str) -> Option<String> {
+    let (repo, git_ref) = match s.split_once('#') {
+        Some((repo, git_ref)) => (repo, Some(git_ref)),
+        None => (s, None),
+    };
+    let (host, path) = match repo.split_once(':') {
+        Some(("github", path)) => ("github.com", path),
+        Some(("gitlab", path)) => ("gitlab.com", path),
+        Some(("codeberg", path)) => ("codeberg.org", path),
+        Some(_) => return None,
+        None => ("github.com", repo),
+    };
+    // gitlab repos can be in subgroups so anything after a prefix may have more than 2 parts
+    let valid = match repo.contains(':') {
+        true => regex!(r"^[\w.-]+(/[\w.-]+)+$").is_match(path),
+        false => regex!(r"^[\w.-]+/[\w.-]+$").is_match(path),
+    };
+    if !valid {
+        return None;
+    }
+    let path = path.strip_suffix(".git").unwrap_or(path);
+    Some(match git_ref {
+        Some(git_ref) => format!("https://{host}/{path}.git#{git_ref}"),
+        None => format!("https://{host}/{path}.git"),
+    })
+}
+
 fn get_name_from_url(url: &str) -> Result<String> {
     if let Ok(url) = Url::parse(url) {
         if let Some(segments) = url.path_segments() {
@@ -163,6 +241,13 @@ static AFTER_LONG_HELP: &str = color_print::cstr!(
   # (node is inferred from the url)
   $ <bold>rtx plugins install https://github.com/rtx-plugins/rtx-nodejs.git</bold>
 
+  # install the node plugin from a GitHub repo, gitlab: and codeberg: work too
+  $ <bold>rtx plugins install node asdf-vm/asdf-nodejs</bold>
+  $ <bold>rtx plugins install node github:asdf-vm/asdf-nodejs</bold>
+
+  # install the node plugin and the latest version of node
+  $ <bold>rtx plugins install node --install</bold>
+
   # install the node plugin using a specific ref
   $ <bold>rtx plugins install node https://github.com/rtx-plugins/rtx-nodejs.git#v1.0.0</bold>
 "#
@@ -172,7 +257,14 @@ static AFTER_LONG_HELP: &str = color_print::cstr!(
 mod tests {
     use insta::assert_display_snapshot;
 
+    use pretty_assertions::assert_eq;
+
     use crate::cli::tests::cli_run;
+    use crate::file::remove_all;
+    use crate::test::git_commit;
+    use crate::{assert_cli, cmd, dirs};
+
+    use super::*;
 
     #[test]
     fn test_plugin_install_invalid_url() {
@@ -180,4 +272,79 @@ mod tests {
         let err = cli_run(&args).unwrap_err();
         assert_display_snapshot!(err);
     }
+
+    #[test]
+    fn test_expand_repo_shorthand() {
+        let expand = |s: &str| expand_repo_shorthand(s);
+        assert_eq!(
+            expand("asdf-vm/asdf-nodejs").unwrap(),
+            "https://github.com/asdf-vm/asdf-nodejs.git"
+        );
+        assert_eq!(
+            expand("github:asdf-vm/asdf-nodejs.git").unwrap(),
+            "https://github.com/asdf-vm/asdf-nodejs.git"
+        );
+        assert_eq!(
+            expand("gitlab:group/subgroup/asdf-foo").unwrap(),
+            "https://gitlab.com/group/subgroup/asdf-foo.git"
+        );
+        assert_eq!(
+            expand("codeberg:owner/rtx-foo#v1.0.0").unwrap(),
+            "https://codeberg.org/owner/rtx-foo.git#v1.0.0"
+        );
+        assert_eq!(expand("node"), None);
+        assert_eq!(expand("tiny:"), None);
+        assert_eq!(expand("a/b/c"), None);
+        assert_eq!(expand("bitbucket:owner/repo"), None);
+        assert_eq!(expand("git@github.com:owner/repo.git"), None);
+    }
+
+    #[test]
+    fn test_get_name_and_url() {
+        let get =
+            |name: &str, url: Option<&str>| get_name_and_url(name, &url.map(String::from)).unwrap();
+        let nodejs = Some("https://github.com/asdf-vm/asdf-nodejs.git".to_string());
+        assert_eq!(
+            get("node", Some("asdf-vm/asdf-nodejs")),
+            ("node".into(), nodejs.clone())
+        );
+        assert_eq!(
+            get("github:asdf-vm/asdf-nodejs", None),
+            ("nodejs".into(), nodejs)
+        );
+        // a second plugin name is not a shorthand
+        assert_eq!(get("node", Some("python")), ("node".into(), None));
+    }
+
+    #[test]
+    fn test_plugin_install_unreachable_url() {
+        let url = format!(
+            "file://{}",
+            dirs::CACHE.join("does-not-exist.git").display()
+        );
+        let args = ["rtx", "plugin", "add", "unreachable", &url]
+            .map(String::from)
+            .into();
+        let err = cli_run(&args).unwrap_err();
+        assert!(format!("{err:#}").contains("does not exist or is not reachable"));
+        assert!(!dirs::PLUGINS.join("unreachable").exists());
+    }
+
+    #[test]
+    fn test_plugin_install_with_version() {
+        let repo = dirs::CACHE.join("install-on-add");
+        let _ = remove_all(&repo);
+        cmd!("cp", "-r", dirs::PLUGINS.join("tiny"), &repo)
+            .run()
+            .unwrap();
+        git_commit(&repo, "init");
+
+        let url = format!("file://{}", repo.display());
+        assert_cli!("plugin", "add", "install-on-add", &url, "--install");
+        let installed = dirs::INSTALLS.join("install-on-add/3.1.0/VERSION").exists();
+        assert_cli!("plugin", "rm", "install-on-add");
+        remove_all(dirs::INSTALLS.join("install-on-add")).unwrap();
+        remove_all(&repo).unwrap();
+        assert!(installed);
+    }
 }
@@ -1,10 +1,22 @@
-use color_eyre::eyre::Result;
+use std::cmp::Reverse;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
 
-use crate::cli::command::Command;
+use chrono::{DateTime, Local};
+use color_eyre::eyre::{eyre, Result};
+use indicatif::HumanBytes;
+use itertools::Itertools;
+use serde_derive::Serialize;
+use serde_json::json;
+
+use crate::cli::command::{Command, JsonOutput};
 use crate::cli::plugins::ls_remote::PluginsLsRemote;
 use crate::config::Config;
-use crate::output::Output;
+use crate::git::Git;
+use crate::output::{to_json, Output};
 use crate::plugins::PluginType;
+use crate::tool::Tool;
+use crate::{dirs, file};
 
 /// List installed plugins
 ///
@@ -26,6 +38,33 @@ pub struct PluginsLs {
     /// e.g.: https://github.com/asdf-vm/asdf-node.git
     #[clap(short, long, verbatim_doc_comment)]
     pub urls: bool,
+
+    /// Only show plugins missing scripts they need
+    /// e.g.: bin/list-all or bin/install
+    #[clap(long, verbatim_doc_comment, group = "json_output")]
+    pub missing_scripts: bool,
+
+    /// Show the disk space used by each plugin's installs, downloads and cache
+    /// Largest first
+    #[clap(long, verbatim_doc_comment, group = "json_output")]
+    pub size: bool,
+
+    /// Only show plugins updated within this long, e.g.: 7d or 12h
+    /// uses the date of the checked out commit, or when the plugin was
+    /// last installed/updated if it isn't a git repository
+    #[clap(
+        long,
+        value_name = "DURATION",
+        value_parser = humantime::parse_duration,
+        verbatim_doc_comment,
+        group = "json_output"
+    )]
+    pub updated_since: Option<Duration>,
+
+    /// Output in json format
+    /// includes whether each plugin is a symlink, e.g.: from `rtx plugins link`
+    #[clap(long, conflicts_with = "all", verbatim_doc_comment)]
+    pub json: bool,
 }
 
 impl Command for PluginsLs {
@@ -38,19 +77,36 @@ impl Command for PluginsLs {
             .run(config, out);
         }
 
-        let mut plugins = config
-            .tools
-            .values()
-            .filter(|p| !config.is_plugin_hidden(p.plugin.name()))
-            .collect::<Vec<_>>();
+        let plugins = self.plugins(&config);
 
-        if self.core {
-            plugins.retain(|p| matches!(p.plugin.get_type(), PluginType::Core));
-        } else {
-            plugins.retain(|p| matches!(p.plugin.get_type(), PluginType::External));
+        if self.size {
+            return self.output_sizes(out, plugins);
         }
-
-        if self.urls {
+        if let Some(since) = self.updated_since {
+            return self.output_updated_since(out, plugins, since);
+        }
+        if self.missing_scripts {
+            let plugins = plugins
+                .into_iter()
+                .map(|p| (p.name.clone(), p.plugin.missing_scripts()))
+                .collect::<Vec<_>>();
+            if self.json {
+                let plugins = plugins
+                    .into_iter()
+                    .map(|(name, missing)| json!({"name": name, "missing_scripts": missing}))
+                    .collect::<Vec<_>>();
+                rtxprintln!(out, "{}", to_json("plugins", &plugins)?);
+                return Ok(());
+            }
+            for (name, missing) in plugins {
+                if !missing.is_empty() {
+                    rtxprintln!(out, "{:29} missing {}", name, missing.join(", "));
+                }
+            }
+        } else if self.json {
+            let plugins = json_plugins(plugins);
+            rtxprintln!(out, "{}", to_json("plugins", &plugins)?);
+        } else if self.urls {
             for plugin in plugins {
                 if let Some(url) = plugin.get_remote_url() {
                     rtxprintln!(out, "{:29} {}", plugin.name, url);
@@ -60,22 +116,181 @@ impl Command for PluginsLs {
             }
         } else {
             for plugin in plugins {
-                rtxprintln!(out, "{}", plugin.name);
+                let mut details = vec![];
+                if plugin.display_name() != plugin.name {
+                    details.push(plugin.display_name());
+                }
+                // symlinked plugins are skipped by `rtx plugins update`
+                if plugin.plugin_path.is_symlink() {
+                    details.push("(symlink)");
+                }
+                match details.is_empty() {
+                    true => rtxprintln!(out, "{}", plugin.name),
+                    false => rtxprintln!(out, "{:29} {}", plugin.name, details.join(" ")),
+                }
             }
         }
         Ok(())
     }
 }
 
+#[derive(Serialize)]
+struct JSONPlugin {
+    name: String,
+    #[serde(rename = "type")]
+    plugin_type: PluginType,
+    url: Option<String>,
+    is_symlink: bool,
+}
+
+impl JsonOutput for PluginsLs {
+    fn json(mut self) -> Result<Self> {
+        if self.all {
+            return Err(eyre!("--json can't be used with --all"));
+        }
+        self.json = true;
+        Ok(self)
+    }
+}
+
+fn json_plugins(plugins: Vec<&Arc<Tool>>) -> Vec<JSONPlugin> {
+    plugins
+        .into_iter()
+        .map(|p| JSONPlugin {
+            name: p.name.clone(),
+            plugin_type: p.plugin.get_type(),
+            url: p.get_remote_url(),
+            is_symlink: p.plugin_path.is_symlink(),
+        })
+        .collect()
+}
+
+impl PluginsLs {
+    /// the installed plugins that are shown, external ones unless --core is used
+    fn plugins<'a>(&self, config: &'a Config) -> Vec<&'a Arc<Tool>> {
+        let mut plugins = config
+            .tools
+            .values()
+            .filter(|p| !config.is_plugin_hidden(p.plugin.name()))
+            .collect::<Vec<_>>();
+
+        if self.core {
+            plugins.retain(|p| matches!(p.plugin.get_type(), PluginType::Core));
+        } else {
+            plugins.retain(|p| matches!(p.plugin.get_type(), PluginType::External));
+        }
+        plugins
+    }
+
+    fn output_sizes(&self, out: &mut Output, plugins: Vec<&Arc<Tool>>) -> Result<()> {
+        let sizes = plugins
+            .into_iter()
+            .map(|p| {
+                let installs = file::dir_size(&p.installs_path)?;
+                let downloads = file::dir_size(&dirs::DOWNLOADS.join(&p.name))?;
+                let cache = file::dir_size(&dirs::CACHE.join(&p.name))?;
+                Ok((p.name.clone(), installs, downloads, cache))
+            })
+            .collect::<Result<Vec<_>>>()?
+            .into_iter()
+            .sorted_by_key(|(name, installs, downloads, cache)| {
+                (Reverse(installs + downloads + cache), name.clone())
+            })
+            .collect_vec();
+        if self.json {
+            let sizes = sizes
+                .into_iter()
+                .map(|(name, installs, downloads, cache)| {
+                    json!({
+                        "name": name,
+                        "installs": installs,
+                        "downloads": downloads,
+                        "cache": cache,
+                        "total": installs + downloads + cache,
+                    })
+                })
+                .collect_vec();
+            rtxprintln!(out, "{}", to_json("plugins", &sizes)?);
+            return Ok(());
+        }
+        for (name, installs, downloads, cache) in sizes {
+            let total = HumanBytes(installs + downloads + cache);
+            rtxprintln!(out, "{:29} {}", name, total);
+        }
+        Ok(())
+    }
+
+    fn output_updated_since(
+        &self,
+        out: &mut Output,
+        plugins: Vec<&Arc<Tool>>,
+        since: Duration,
+    ) -> Result<()> {
+        let plugins = plugins
+            .into_iter()
+            .filter_map(|p| last_updated(p).map(|updated| (p.name.clone(), updated)))
+            .filter(|(_, updated)| updated.elapsed().unwrap_or_default() <= since)
+            .map(|(name, updated)| (name, DateTime::<Local>::from(updated)))
+            .collect_vec();
+        if self.json {
+            let plugins = plugins
+                .into_iter()
+                .map(|(name, updated)| json!({"name": name, "updated_at": updated.to_rfc3339()}))
+                .collect_vec();
+            rtxprintln!(out, "{}", to_json("plugins", &plugins)?);
+            return Ok(());
+        }
+        for (name, updated) in plugins {
+            rtxprintln!(out, "{:29} {}", name, updated.format("%Y-%m-%d %H:%M"));
+        }
+        Ok(())
+    }
+}
+
+/// the date of the commit checked out in the plugin, falling back to the mtime of the
+/// plugin directory which is touched on install and update
+fn last_updated(tool: &Tool) -> Option<SystemTime> {
+    let git = Git::new(tool.plugin_path.clone());
+    let updated = match git.is_repo() {
+        true => git.head_commit_time(),
+        false => tool
+            .plugin_path
+            .metadata()
+            .and_then(|m| m.modified())
+            .map_err(|e| e.into()),
+    };
+    match updated {
+        Ok(updated) => Some(updated),
+        Err(err) => {
+            debug!("failed to get update time of {}: {:#}", tool.name, err);
+            None
+        }
+    }
+}
+
 static AFTER_LONG_HELP: &str = color_print::cstr!(
     r#"<bold><underline>Examples:</underline></bold>
   $ <bold>rtx plugins ls</bold>
   node
   ruby
 
+  $ <bold>rtx plugins ls</bold>     # plugins can set a display-name in rtx.plugin.toml
+  node                          Node.js
+  ruby
+
   $ <bold>rtx plugins ls --urls</bold>
   node                        https://github.com/asdf-vm/asdf-node.git
   ruby                          https://github.com/asdf-vm/asdf-ruby.git
+
+  $ <bold>rtx plugins ls --missing-scripts</bold>
+  ruby                          missing bin/install
+
+  $ <bold>rtx plugins ls --size</bold>
+  node                          1.21 GiB
+  ruby                          305.50 MiB
+
+  $ <bold>rtx plugins ls --updated-since 7d</bold>
+  node                          2023-06-12 09:41
 "#
 );
 
@@ -83,8 +298,12 @@ static AFTER_LONG_HELP: &str = color_print::cstr!(
 mod tests {
     use pretty_assertions::assert_str_eq;
 
+    use std::fs;
+
     use crate::cli::tests::grep;
-    use crate::{assert_cli, assert_cli_snapshot};
+    use crate::file::{make_symlink, remove_all};
+    use crate::test::git_cmd;
+    use crate::{assert_cli, assert_cli_snapshot, dirs};
 
     #[test]
     fn test_plugin_list() {
@@ -105,4 +324,169 @@ mod tests {
             "zephyr                        https://github.com/nsaunders/asdf-zephyr.git"
         );
     }
+
+    #[test]
+    fn test_plugin_list_display_name() {
+        let plugin_path = dirs::PLUGINS.join("display-name");
+        let _ = remove_all(&plugin_path);
+        fs::create_dir_all(&plugin_path).unwrap();
+        fs::write(
+            plugin_path.join("rtx.plugin.toml"),
+            "display-name = \"Display Name\"\n",
+        )
+        .unwrap();
+
+        let stdout = assert_cli!("plugin", "list");
+        assert_str_eq!(
+            grep(stdout.clone(), "display-name"),
+            "display-name                  Display Name"
+        );
+        assert_str_eq!(grep(stdout, "dummy"), "dummy");
+
+        remove_all(&plugin_path).unwrap();
+    }
+
+    #[test]
+    fn test_plugin_list_symlink() {
+        let link = dirs::PLUGINS.join("symlinked");
+        let _ = fs::remove_file(&link);
+        make_symlink(&dirs::PLUGINS.join("dummy"), &link).unwrap();
+
+        let stdout = assert_cli!("plugin", "list");
+        let json = assert_cli!("plugin", "list", "--json");
+        fs::remove_file(&link).unwrap();
+        assert_str_eq!(
+            grep(stdout, "symlinked"),
+            "symlinked                     (symlink)"
+        );
+        let plugins: serde_json::Value = serde_json::from_str(&json).unwrap();
+        let is_symlink = |name: &str| {
+            plugins["plugins"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .find(|p| p["name"] == name)
+                .map(|p| p["is_symlink"].clone())
+                .unwrap()
+        };
+        assert_eq!(is_symlink("symlinked"), true);
+        assert_eq!(is_symlink("dummy"), false);
+    }
+
+    #[test]
+    fn test_plugin_list_missing_scripts() {
+        let stdout = assert_cli!("plugin", "list", "--missing-scripts");
+        assert_str_eq!(stdout, "");
+
+        let stdout = assert_cli!("plugin", "list", "--missing-scripts", "--json");
+        let plugins: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+        let dummy = plugins["plugins"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|p| p["name"] == "dummy")
+            .unwrap();
+        assert_eq!(dummy["missing_scripts"], serde_json::json!([]));
+    }
+
+    #[test]
+    fn test_plugin_list_size() {
+        let name = "size-test";
+        let plugin_path = dirs::PLUGINS.join(name);
+        let installs = dirs::INSTALLS.join(name);
+        let cleanup = || {
+            for dir in [&plugin_path, &installs] {
+                let _ = remove_all(dir);
+            }
+            let _ = remove_all(dirs::DOWNLOADS.join(name));
+            let _ = remove_all(dirs::CACHE.join(name));
+        };
+        cleanup();
+        fs::create_dir_all(&plugin_path).unwrap();
+        fs::create_dir_all(installs.join("1.0.0")).unwrap();
+        fs::create_dir_all(dirs::DOWNLOADS.join(name)).unwrap();
+        fs::create_dir_all(dirs::CACHE.join(name)).unwrap();
+        fs::write(installs.join("1.0.0/bin"), vec![0; 1000]).unwrap();
+        fs::write(dirs::DOWNLOADS.join(name).join("src.tar.gz"), vec![0; 200]).unwrap();
+        fs::write(dirs::CACHE.join(name).join("remote_versions"), vec![0; 30]).unwrap();
+        // symlinks aren't followed so this doesn't count the plugin dir
+        make_symlink(&plugin_path, &installs.join("latest")).unwrap();
+
+        let stdout = assert_cli!("plugin", "list", "--size", "--json");
+        let sizes: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+        let size = sizes["plugins"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|p| p["name"] == name)
+            .cloned();
+        let stdout = assert_cli!("plugin", "list", "--size");
+        cleanup();
+
+        assert_eq!(
+            size.unwrap(),
+            serde_json::json!({
+                "name": name,
+                "installs": 1000,
+                "downloads": 200,
+                "cache": 30,
+                "total": 1230,
+            })
+        );
+        assert_str_eq!(grep(stdout, name), "size-test                     1.20 KiB");
+    }
+
+    #[test]
+    fn test_plugin_list_updated_since() {
+        let commit = |name: &str, date: &str| {
+            let path = dirs::PLUGINS.join(name);
+            let _ = remove_all(&path);
+            fs::create_dir_all(&path).unwrap();
+            fs::write(path.join("README.md"), name).unwrap();
+            git_cmd(&path, &["init", "-q"]).run().unwrap();
+            git_cmd(&path, &["add", "-A"]).run().unwrap();
+            git_cmd(&path, &["commit", "-qm", name])
+                .env("GIT_COMMITTER_DATE", date)
+                .run()
+                .unwrap();
+            path
+        };
+        let recent = commit("updated-recent", &chrono::Local::now().to_rfc3339());
+        let old = commit("updated-old", "2020-01-01T00:00:00Z");
+
+        let stdout = assert_cli!("plugin", "list", "--updated-since", "7d", "--json");
+        let stdout_90d = assert_cli!("plugin", "list", "--updated-since", "90d");
+        remove_all(&recent).unwrap();
+        remove_all(&old).unwrap();
+
+        let plugins: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+        let names = plugins["plugins"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|p| p["name"].as_str().unwrap())
+            .collect::<Vec<_>>();
+        assert!(names.contains(&"updated-recent"));
+        assert!(!names.contains(&"updated-old"));
+        assert!(stdout_90d.contains("updated-recent"));
+        assert!(!stdout_90d.contains("updated-old"));
+    }
+
+    #[test]
+    fn test_plugin_list_global_json() {
+        let stdout = assert_cli!("--json", "plugins");
+        assert_eq!(stdout, assert_cli!("--json", "plugins", "ls"));
+        assert_eq!(stdout, assert_cli!("plugins", "--json"));
+        assert_eq!(stdout, assert_cli!("plugins", "ls", "--json"));
+        let plugins: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+        let tiny = plugins["plugins"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|p| p["name"] == "tiny")
+            .unwrap();
+        assert_eq!(tiny["type"], "external");
+        assert!(tiny.get("url").is_some());
+        assert_eq!(tiny["is_symlink"], false);
+    }
 }
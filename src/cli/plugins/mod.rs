@@ -1,10 +1,11 @@
 use clap::Subcommand;
 use color_eyre::eyre::Result;
 
-use crate::cli::command::Command;
+use crate::cli::command::{json_not_supported, run_json, Command};
 use crate::config::Config;
 use crate::output::Output;
 
+mod info;
 mod install;
 mod link;
 mod ls;
@@ -38,6 +39,7 @@ pub struct Plugins {
 
 #[derive(Debug, Subcommand)]
 enum Commands {
+    Info(info::PluginsInfo),
     Install(install::PluginsInstall),
     Link(link::PluginsLink),
     Ls(ls::PluginsLs),
@@ -49,6 +51,7 @@ enum Commands {
 impl Commands {
     pub fn run(self, config: Config, out: &mut Output) -> Result<()> {
         match self {
+            Self::Info(cmd) => cmd.run(config, out),
             Self::Install(cmd) => cmd.run(config, out),
             Self::Link(cmd) => cmd.run(config, out),
             Self::Ls(cmd) => cmd.run(config, out),
@@ -59,13 +62,49 @@ impl Commands {
     }
 }
 
-impl Command for Plugins {
-    fn run(self, config: Config, out: &mut Output) -> Result<()> {
-        let cmd = self.command.unwrap_or(Commands::Ls(ls::PluginsLs {
+impl Plugins {
+    /// `rtx --json plugins`, `rtx --json plugins ls` and `rtx --json plugins info`
+    pub fn run_json(self, config: Config, out: &mut Output) -> Result<()> {
+        let ls = self.ls();
+        match self.command {
+            None => run_json(ls, config, out),
+            Some(Commands::Info(cmd)) => run_json(cmd, config, out),
+            Some(Commands::Ls(cmd)) => run_json(cmd, config, out),
+            Some(_) => Err(json_not_supported()),
+        }
+    }
+
+    /// what `rtx plugins` runs without a subcommand
+    fn ls(&self) -> ls::PluginsLs {
+        ls::PluginsLs {
             all: self.all,
             core: self.core,
             urls: self.urls,
-        }));
+            missing_scripts: false,
+            size: false,
+            updated_since: None,
+            json: false,
+        }
+    }
+
+    /// whether the subcommand changes installed plugins
+    pub fn is_mutating(&self) -> bool {
+        matches!(
+            self.command,
+            Some(
+                Commands::Install(..)
+                    | Commands::Link(..)
+                    | Commands::Uninstall(..)
+                    | Commands::Update(..)
+            )
+        )
+    }
+}
+
+impl Command for Plugins {
+    fn run(self, config: Config, out: &mut Output) -> Result<()> {
+        let ls = self.ls();
+        let cmd = self.command.unwrap_or(Commands::Ls(ls));
 
         cmd.run(config, out)
     }
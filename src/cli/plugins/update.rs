@@ -1,10 +1,18 @@
+use std::sync::Arc;
+
 use color_eyre::eyre::{eyre, Result};
 use console::style;
+use dialoguer::theme::ColorfulTheme;
+use dialoguer::MultiSelect;
+use rayon::prelude::*;
+use rayon::ThreadPoolBuilder;
 
 use crate::cli::command::Command;
 use crate::config::Config;
+use crate::git::Git;
 use crate::output::Output;
 use crate::plugins::PluginName;
+use crate::tool::Tool;
 
 /// Updates a plugin to the latest version
 ///
@@ -19,13 +27,27 @@ pub struct Update {
     /// Update all plugins
     #[clap(long, short = 'a', conflicts_with = "plugin", hide = true)]
     all: bool,
+
+    /// Show the commits pulled in by the update
+    #[clap(long, short = 'l')]
+    log: bool,
+
+    /// Update even if the plugin has local changes, they will be lost
+    /// otherwise plugins with uncommitted changes are skipped
+    #[clap(long, short, verbatim_doc_comment)]
+    force: bool,
+
+    /// Choose the plugins to update from a list showing how far behind each one is
+    /// the plugins with new commits are selected to start with
+    #[clap(long, short, conflicts_with_all = ["plugin", "all"], verbatim_doc_comment)]
+    interactive: bool,
 }
 
 impl Command for Update {
     fn run(self, config: Config, out: &mut Output) -> Result<()> {
-        let plugins: Vec<_> = match self.plugin {
+        let plugins: Vec<_> = match &self.plugin {
             Some(plugins) => plugins
-                .into_iter()
+                .iter()
                 .map(|p| {
                     let (p, ref_) = match p.split_once('@') {
                         Some((p, ref_)) => (p, Some(ref_.to_string())),
@@ -37,6 +59,11 @@ impl Command for Update {
                     Ok((plugin.clone(), ref_))
                 })
                 .collect::<Result<_>>()?,
+            None if self.interactive => self
+                .prompt_for_plugins(&config)?
+                .into_iter()
+                .map(|p| (p, None))
+                .collect(),
             None => config
                 .external_plugins()
                 .into_iter()
@@ -44,25 +71,172 @@ impl Command for Update {
                 .collect::<Vec<_>>(),
         };
 
+        if self.interactive {
+            return self.update_parallel(&config, out, plugins);
+        }
         for (plugin, ref_) in plugins {
             rtxprintln!(out, "updating plugin {}", plugin.name);
-            plugin.update(ref_)?;
+            let revs = plugin.update(&config, ref_, self.force)?;
+            for line in self.log_lines(&plugin, revs)? {
+                rtxprintln!(out, "{}", line);
+            }
         }
         Ok(())
     }
 }
 
+type PluginStatus = Option<(usize, usize)>;
+
+impl Update {
+    fn prompt_for_plugins(&self, config: &Config) -> Result<Vec<Arc<Tool>>> {
+        if !console::user_attended_stderr() {
+            return Err(eyre!("--interactive needs a terminal"));
+        }
+        let plugins = config
+            .external_plugins()
+            .into_par_iter()
+            .map(|(_, p)| {
+                let git = Git::new(p.plugin_path.clone()).with_proxy(
+                    config
+                        .settings
+                        .proxy_for(&p.get_remote_url().unwrap_or_default()),
+                );
+                let status = git.behind_ahead(None).map_err(|err| {
+                    debug!("checking if {} is outdated: {:#}", p.name, err);
+                });
+                (p, status.ok())
+            })
+            .collect::<Vec<_>>();
+        let width = plugins.iter().map(|(p, _)| p.name.len()).max().unwrap_or(0);
+        let items = plugins
+            .iter()
+            .map(|(p, status)| format!("{:width$}  {}", p.name, display_status(status)))
+            .collect::<Vec<_>>();
+        let defaults = plugins
+            .iter()
+            .map(|(_, status)| is_behind(status))
+            .collect::<Vec<_>>();
+        let selected = MultiSelect::with_theme(&ColorfulTheme::default())
+            .with_prompt("Select plugins to update")
+            .items(&items)
+            .defaults(&defaults)
+            .interact()?;
+        Ok(select_plugins(&plugins, &selected))
+    }
+
+    /// prints each plugin's output once they are all done so it isn't interleaved
+    fn update_parallel(
+        &self,
+        config: &Config,
+        out: &mut Output,
+        plugins: Vec<(Arc<Tool>, Option<String>)>,
+    ) -> Result<()> {
+        let results = ThreadPoolBuilder::new()
+            .num_threads(config.settings.jobs)
+            .build()?
+            .install(|| {
+                plugins
+                    .into_par_iter()
+                    .map(|(plugin, ref_)| {
+                        let revs = plugin.update(config, ref_, self.force)?;
+                        Ok((plugin.name.clone(), self.log_lines(&plugin, revs)?))
+                    })
+                    .collect::<Vec<Result<_>>>()
+            });
+        for result in results {
+            let (name, lines) = result?;
+            rtxprintln!(out, "updated plugin {}", name);
+            for line in lines {
+                rtxprintln!(out, "{}", line);
+            }
+        }
+        Ok(())
+    }
+
+    fn log_lines(&self, plugin: &Tool, revs: Option<(String, String)>) -> Result<Vec<String>> {
+        match (self.log, revs) {
+            (true, Some((pre, post))) => {
+                let git = Git::new(plugin.plugin_path.clone());
+                Ok(git
+                    .log_oneline(&pre, &post)?
+                    .into_iter()
+                    .map(|(sha, subject)| {
+                        format!("  {} {}", style(sha).yellow().for_stdout(), subject)
+                    })
+                    .collect())
+            }
+            _ => Ok(vec![]),
+        }
+    }
+}
+
+fn is_behind(status: &PluginStatus) -> bool {
+    matches!(status, Some((behind, _)) if *behind > 0)
+}
+
+fn display_status(status: &PluginStatus) -> String {
+    match status {
+        None => style("unknown").dim().for_stderr().to_string(),
+        Some((0, 0)) => "up to date".into(),
+        Some((behind, 0)) => format!("{behind} behind"),
+        Some((0, ahead)) => format!("{ahead} ahead"),
+        Some((behind, ahead)) => format!("{behind} behind, {ahead} ahead"),
+    }
+}
+
+/// the plugins at the indexes chosen in the prompt
+fn select_plugins<T: Clone>(plugins: &[(T, PluginStatus)], selected: &[usize]) -> Vec<T> {
+    selected.iter().map(|&i| plugins[i].0.clone()).collect()
+}
+
 static AFTER_LONG_HELP: &str = color_print::cstr!(
     r#"<bold><underline>Examples:</underline></bold>
   $ <bold>rtx plugins update</bold>              # update all plugins
   $ <bold>rtx plugins update node</bold>       # update only node
   $ <bold>rtx plugins update node@beta</bold>  # specify a ref
+  $ <bold>rtx plugins update --log node</bold> # show the new commits
+  $ <bold>rtx plugins update -i</bold>         # pick which plugins to update
 "#
 );
 
 #[cfg(test)]
 mod tests {
-    use crate::assert_cli;
+    use std::fs;
+
+    use crate::file::{make_executable, remove_all};
+    use crate::git::Git;
+    use crate::test::git_commit;
+    use crate::{assert_cli, assert_cli_err, dirs};
+
+    use super::{display_status, is_behind, select_plugins};
+
+    #[test]
+    fn test_plugin_update_selection() {
+        let plugins = [
+            ("node", Some((3, 0))),
+            ("python", Some((0, 0))),
+            ("ruby", None),
+            ("tiny", Some((1, 2))),
+        ];
+        let items = plugins
+            .iter()
+            .map(|(_, s)| console::strip_ansi_codes(&display_status(s)).to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            items,
+            vec!["3 behind", "up to date", "unknown", "1 behind, 2 ahead"]
+        );
+        // the outdated plugins are selected to start with
+        let defaults = plugins
+            .iter()
+            .enumerate()
+            .filter(|(_, (_, s))| is_behind(s))
+            .map(|(i, _)| i)
+            .collect::<Vec<_>>();
+        assert_eq!(select_plugins(&plugins, &defaults), vec!["node", "tiny"]);
+        assert_eq!(select_plugins(&plugins, &[2]), vec!["ruby"]);
+        assert!(select_plugins(&plugins, &[]).is_empty());
+    }
 
     #[test]
     fn test_plugin_update() {
@@ -75,4 +249,94 @@ mod tests {
         // assert_cli!("p", "update"); tested in e2e
         assert_cli!("plugins", "update", "tiny");
     }
+
+    #[test]
+    fn test_plugin_update_rollback() {
+        let origin = dirs::CACHE.join("rollback-origin");
+        let plugin_path = dirs::PLUGINS.join("rollback");
+        let _ = remove_all(&origin);
+        let _ = remove_all(&plugin_path);
+        fs::create_dir_all(origin.join("bin")).unwrap();
+        let hook = origin.join("bin/post-plugin-update");
+        fs::write(&hook, "#!/usr/bin/env bash\nexit 1\n").unwrap();
+        make_executable(&hook).unwrap();
+        git_commit(&origin, "first");
+        let git = Git::new(plugin_path.clone());
+        git.clone(origin.to_string_lossy().as_ref()).unwrap();
+        let first = git.current_sha().unwrap();
+        fs::write(origin.join("README.md"), "second").unwrap();
+        git_commit(&origin, "second");
+
+        let err = assert_cli_err!("plugins", "update", "rollback");
+        assert_eq!(
+            err.to_string(),
+            format!("post-plugin-update failed, rolled back rollback to {first}")
+        );
+        assert_eq!(git.current_sha().unwrap(), first);
+
+        remove_all(&origin).unwrap();
+        remove_all(&plugin_path).unwrap();
+    }
+
+    #[test]
+    fn test_plugin_update_log() {
+        let origin = dirs::CACHE.join("update-log-origin");
+        let plugin_path = dirs::PLUGINS.join("update-log");
+        let _ = remove_all(&origin);
+        let _ = remove_all(&plugin_path);
+        let commit = |msg: &str| {
+            fs::write(origin.join("README.md"), msg).unwrap();
+            git_commit(&origin, msg);
+        };
+        fs::create_dir_all(&origin).unwrap();
+        commit("first");
+        let git = Git::new(plugin_path.clone());
+        git.clone(origin.to_string_lossy().as_ref()).unwrap();
+        commit("second");
+        let sha = Git::new(origin.clone()).current_sha_short().unwrap();
+
+        let stdout = assert_cli!("plugins", "update", "--log", "update-log");
+        assert_eq!(
+            stdout,
+            format!("updating plugin update-log\n  {sha} second\n")
+        );
+
+        remove_all(&origin).unwrap();
+        remove_all(&plugin_path).unwrap();
+    }
+
+    #[test]
+    fn test_plugin_update_dirty() {
+        let origin = dirs::CACHE.join("update-dirty-origin");
+        let plugin_path = dirs::PLUGINS.join("update-dirty");
+        let _ = remove_all(&origin);
+        let _ = remove_all(&plugin_path);
+        let commit = |msg: &str| {
+            fs::write(origin.join("README.md"), msg).unwrap();
+            git_commit(&origin, msg);
+        };
+        fs::create_dir_all(&origin).unwrap();
+        commit("first");
+        let git = Git::new(plugin_path.clone());
+        git.clone(origin.to_string_lossy().as_ref()).unwrap();
+        let first = git.current_sha().unwrap();
+        commit("second");
+        assert!(!git.is_dirty().unwrap());
+        fs::write(plugin_path.join("README.md"), "local change").unwrap();
+        assert!(git.is_dirty().unwrap());
+
+        assert_cli!("plugins", "update", "update-dirty");
+        assert_eq!(git.current_sha().unwrap(), first);
+        assert_eq!(
+            fs::read_to_string(plugin_path.join("README.md")).unwrap(),
+            "local change"
+        );
+
+        assert_cli!("plugins", "update", "--force", "update-dirty");
+        assert_ne!(git.current_sha().unwrap(), first);
+        assert!(!git.is_dirty().unwrap());
+
+        remove_all(&origin).unwrap();
+        remove_all(&plugin_path).unwrap();
+    }
 }
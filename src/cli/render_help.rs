@@ -47,6 +47,13 @@ fn render_commands() -> String {
         match command.has_subcommands() {
             true => {
                 let name = command.get_name().to_string();
+                // commands like `rtx completion` do something on their own as well as having subcommands
+                if command.get_positionals().next().is_some() {
+                    let mut c = command.clone().disable_help_subcommand(true);
+                    if let Some(output) = render_command(None, &mut c) {
+                        doc.push_str(&output);
+                    }
+                }
                 for subcommand in command.get_subcommands_mut() {
                     if let Some(output) = render_command(Some(&name), subcommand) {
                         doc.push_str(&output);
@@ -71,6 +78,9 @@ fn render_command(parent: Option<&str>, c: &mut clap::Command) -> Option<String>
     }
     let strip_usage = |s: StyledStr| {
         s.to_string()
+            .lines()
+            .next()
+            .unwrap_or_default()
             .strip_prefix("Usage: ")
             .unwrap_or_default()
             .to_string()
@@ -28,12 +28,23 @@ pub struct Reshim {
     pub plugin: Option<String>,
     #[clap(hide = true)]
     pub version: Option<String>,
+
+    /// Only remove shims for bins that are no longer installed
+    /// e.g.: after `rtx uninstall`, other shims are left as they are
+    #[clap(long, verbatim_doc_comment)]
+    pub prune: bool,
 }
 
 impl Command for Reshim {
-    fn run(self, mut config: Config, _out: &mut Output) -> Result<()> {
+    fn run(self, mut config: Config, out: &mut Output) -> Result<()> {
         let ts = ToolsetBuilder::new().build(&mut config)?;
 
+        if self.prune {
+            for name in shims::prune_shims(&config, &ts)? {
+                rtxstatusln!(out, "removed shim {}", name);
+            }
+            return Ok(());
+        }
         shims::reshim(&mut config, &ts)
     }
 }
@@ -43,5 +54,39 @@ static AFTER_LONG_HELP: &str = color_print::cstr!(
   $ <bold>rtx reshim</bold>
   $ <bold>~/.local/share/rtx/shims/node -v</bold>
   v20.0.0
+  $ <bold>rtx reshim --prune</bold>
+  rtx removed shim npx
 "#
 );
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::file::{make_executable, remove_all};
+    use crate::{assert_cli, dirs};
+
+    #[test]
+    fn test_reshim_prune() {
+        let plugin_path = dirs::PLUGINS.join("prune-test");
+        let bin = dirs::INSTALLS.join("prune-test/1.0.0/bin/prune-test-bin");
+        let shim = dirs::SHIMS.join("prune-test-bin");
+        fs::create_dir_all(&plugin_path).unwrap();
+        fs::create_dir_all(bin.parent().unwrap()).unwrap();
+        fs::write(&bin, "#!/bin/sh\n").unwrap();
+        make_executable(&bin).unwrap();
+
+        assert_cli!("reshim");
+        assert!(shim.exists());
+        assert_cli!("uninstall", "prune-test@1.0.0");
+        // uninstalling leaves the shim behind
+        assert!(shim.exists());
+        assert_cli!("reshim", "--prune");
+        let exists = shim.exists();
+        remove_all(&plugin_path).unwrap();
+        let _ = remove_all(dirs::INSTALLS.join("prune-test"));
+
+        assert!(!exists);
+        assert!(dirs::SHIMS.join("rtx-tiny").exists());
+    }
+}
@@ -1,8 +1,8 @@
 use color_eyre::eyre::Result;
 
-use crate::cli::command::Command;
+use crate::cli::command::{Command, JsonOutput};
 use crate::config::Config;
-use crate::output::Output;
+use crate::output::{to_json, Output};
 
 /// Show current settings
 ///
@@ -12,11 +12,27 @@ use crate::output::Output;
 /// but managed separately with `rtx aliases`
 #[derive(Debug, clap::Args)]
 #[clap(visible_alias = "list", after_long_help = AFTER_LONG_HELP, verbatim_doc_comment)]
-pub struct SettingsLs {}
+pub struct SettingsLs {
+    /// Output in json format
+    #[clap(long, short = 'J')]
+    pub json: bool,
+}
+
+impl JsonOutput for SettingsLs {
+    fn json(mut self) -> Result<Self> {
+        self.json = true;
+        Ok(self)
+    }
+}
 
 impl Command for SettingsLs {
     fn run(self, config: Config, out: &mut Output) -> Result<()> {
-        for (key, value) in config.settings.to_index_map() {
+        let settings = config.settings.to_index_map();
+        if self.json {
+            rtxprintln!(out, "{}", to_json("settings", &settings)?);
+            return Ok(());
+        }
+        for (key, value) in settings {
             rtxprintln!(out, "{} = {}", key, value);
         }
         Ok(())
@@ -27,6 +43,15 @@ static AFTER_LONG_HELP: &str = color_print::cstr!(
     r#"<bold><underline>Examples:</underline></bold>
   $ <bold>rtx settings</bold>
   legacy_version_file = false
+
+  $ <bold>rtx settings --json</bold>
+  {
+    "schema_version": 1,
+    "settings": {
+      "legacy_version_file": "false",
+      ...
+    }
+  }
 "#
 );
 
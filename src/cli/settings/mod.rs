@@ -1,7 +1,7 @@
 use clap::Subcommand;
 use color_eyre::eyre::Result;
 
-use crate::cli::command::Command;
+use crate::cli::command::{json_not_supported, run_json, Command};
 use crate::config::Config;
 use crate::output::Output;
 
@@ -36,9 +36,22 @@ impl Commands {
     }
 }
 
+impl Settings {
+    /// `rtx --json settings` and `rtx --json settings ls`
+    pub fn run_json(self, config: Config, out: &mut Output) -> Result<()> {
+        match self.command {
+            None => run_json(ls::SettingsLs { json: false }, config, out),
+            Some(Commands::Ls(cmd)) => run_json(cmd, config, out),
+            Some(_) => Err(json_not_supported()),
+        }
+    }
+}
+
 impl Command for Settings {
     fn run(self, config: Config, out: &mut Output) -> Result<()> {
-        let cmd = self.command.unwrap_or(Commands::Ls(ls::SettingsLs {}));
+        let cmd = self
+            .command
+            .unwrap_or(Commands::Ls(ls::SettingsLs { json: false }));
 
         cmd.run(config, out)
     }
@@ -19,27 +19,109 @@ pub struct SettingsSet {
 
 impl Command for SettingsSet {
     fn run(self, mut config: Config, _out: &mut Output) -> Result<()> {
-        let value: toml_edit::Value = match self.key.as_str() {
-            "experimental" => parse_bool(&self.value)?,
-            "missing_runtime_behavior" => self.value.into(),
-            "always_keep_download" => parse_bool(&self.value)?,
-            "always_keep_install" => parse_bool(&self.value)?,
-            "legacy_version_file" => parse_bool(&self.value)?,
-            "plugin_autoupdate_last_check_duration" => parse_i64(&self.value)?,
-            "verbose" => parse_bool(&self.value)?,
-            "asdf_compat" => parse_bool(&self.value)?,
-            "jobs" => parse_i64(&self.value)?,
-            "shorthands_file" => self.value.into(),
-            "disable_default_shorthands" => parse_bool(&self.value)?,
-            "raw" => parse_bool(&self.value)?,
-            _ => return Err(eyre!("Unknown setting: {}", self.key)),
+        let setting_type = match SETTINGS.iter().find(|(k, _)| *k == self.key) {
+            Some((_, setting_type)) => setting_type,
+            None => {
+                let keys = SETTINGS.iter().map(|(k, _)| *k).collect::<Vec<_>>();
+                return Err(eyre!(
+                    "Unknown setting: {}\nvalid settings: {}",
+                    self.key,
+                    keys.join(", ")
+                ));
+            }
         };
+        let value = setting_type.parse(&self.value)?;
 
         config.global_config.update_setting(&self.key, value);
         config.global_config.save()
     }
 }
 
+enum SettingType {
+    Bool,
+    Int,
+    /// an integer in the given unit or something like "1 week"
+    Duration(&'static str),
+    Path,
+    String,
+    OneOf(&'static [&'static str]),
+}
+
+/// the settings that can be written to ~/.config/rtx/config.toml with `rtx settings set`
+static SETTINGS: &[(&str, SettingType)] = &[
+    ("experimental", SettingType::Bool),
+    (
+        "missing_runtime_behavior",
+        SettingType::OneOf(&[
+            "autoinstall",
+            "autoinstall_prompt",
+            "prompt",
+            "warn",
+            "ignore",
+        ]),
+    ),
+    ("always_keep_download", SettingType::Bool),
+    ("always_keep_install", SettingType::Bool),
+    ("build_from_source", SettingType::Bool),
+    ("legacy_version_file", SettingType::Bool),
+    (
+        "plugin_autoupdate_last_check_duration",
+        SettingType::Duration("minutes"),
+    ),
+    ("plugin_clone_depth", SettingType::Int),
+    ("plugin_clone_single_branch", SettingType::Bool),
+    ("plugin_update_rollback", SettingType::Bool),
+    ("plugin_verify_url", SettingType::Bool),
+    ("verbose", SettingType::Bool),
+    ("progress_log_file", SettingType::Path),
+    ("asdf_compat", SettingType::Bool),
+    ("use_tool_versions", SettingType::Bool),
+    ("jobs", SettingType::Int),
+    ("keep_order", SettingType::Bool),
+    ("superproject_root", SettingType::Bool),
+    ("warn_shadowed_bins", SettingType::Bool),
+    ("shorthands_file", SettingType::Path),
+    ("disable_default_shorthands", SettingType::Bool),
+    ("plugin_default_org", SettingType::String),
+    ("pre_install_hook", SettingType::String),
+    ("post_install_hook", SettingType::String),
+    ("proxy", SettingType::String),
+    ("no_proxy", SettingType::String),
+    (
+        "log_level",
+        SettingType::OneOf(&["off", "error", "warn", "info", "debug", "trace"]),
+    ),
+    ("raw", SettingType::Bool),
+    ("script_timeout", SettingType::Duration("seconds")),
+    ("lock_resolved_versions", SettingType::Bool),
+];
+
+impl SettingType {
+    fn parse(&self, value: &str) -> Result<toml_edit::Value> {
+        match self {
+            SettingType::Bool => parse_bool(value),
+            SettingType::Int => parse_i64(value),
+            SettingType::Duration(unit) => match parse_i64(value) {
+                Ok(n) => Ok(n),
+                Err(_) => match humantime::parse_duration(value) {
+                    Ok(_) => Ok(value.into()),
+                    Err(_) => Err(eyre!(
+                        "{} must be a number of {} or a duration",
+                        value,
+                        unit
+                    )),
+                },
+            },
+            SettingType::Path if value.is_empty() => Err(eyre!("path must not be empty")),
+            SettingType::Path | SettingType::String => Ok(value.into()),
+            SettingType::OneOf(options) => match options.contains(&value) {
+                true => Ok(value.into()),
+                false => Err(eyre!("{} must be one of: {}", value, options.join(", "))),
+            },
+        }
+    }
+}
+
 fn parse_bool(value: &str) -> Result<toml_edit::Value> {
     match value {
         "true" => Ok(true.into()),
@@ -58,15 +140,20 @@ fn parse_i64(value: &str) -> Result<toml_edit::Value> {
 static AFTER_LONG_HELP: &str = color_print::cstr!(
     r#"<bold><underline>Examples:</underline></bold>
   $ <bold>rtx settings set legacy_version_file true</bold>
+  $ <bold>rtx settings set plugin_autoupdate_last_check_duration "1 week"</bold>
 "#
 );
 
 #[cfg(test)]
 pub mod tests {
+    use std::time::Duration;
+
     use insta::assert_snapshot;
+    use pretty_assertions::assert_str_eq;
 
-    use crate::assert_cli;
+    use crate::config::Config;
     use crate::test::reset_config;
+    use crate::{assert_cli, assert_cli_err};
 
     #[test]
     fn test_settings_set() {
@@ -85,4 +172,42 @@ pub mod tests {
         assert_snapshot!(stdout);
         reset_config();
     }
+
+    #[test]
+    fn test_settings_set_read_back() {
+        reset_config();
+        assert_cli!("settings", "set", "plugin_clone_single_branch", "true");
+        assert_cli!("settings", "set", "jobs", "7");
+        assert_cli!("settings", "set", "script_timeout", "90s");
+
+        let settings = Config::load().unwrap().global_config.settings();
+        assert_eq!(settings.plugin_clone_single_branch, Some(true));
+        assert_eq!(settings.jobs, Some(7));
+        assert_eq!(settings.script_timeout, Some(Duration::from_secs(90)));
+
+        assert_cli!("settings", "set", "script_timeout", "1500ms");
+        let stdout = assert_cli!("settings");
+        reset_config();
+        assert!(stdout.contains("script_timeout = 1s 500ms\n"));
+    }
+
+    #[test]
+    fn test_settings_set_invalid() {
+        let err = assert_cli_err!("settings", "set", "jobs", "many");
+        assert_str_eq!(err.to_string(), "many must be a number");
+        let err = assert_cli_err!("settings", "set", "script_timeout", "soon");
+        assert_str_eq!(
+            err.to_string(),
+            "soon must be a number of seconds or a duration"
+        );
+        let err = assert_cli_err!("settings", "set", "missing_runtime_behavior", "maybe");
+        assert_str_eq!(
+            err.to_string(),
+            "maybe must be one of: autoinstall, autoinstall_prompt, prompt, warn, ignore"
+        );
+        let err = assert_cli_err!("settings", "set", "not_a_setting", "1");
+        assert!(err
+            .to_string()
+            .starts_with("Unknown setting: not_a_setting\nvalid settings: experimental, "));
+    }
 }
@@ -47,15 +47,25 @@ mod tests {
         missing_runtime_behavior = autoinstall
         always_keep_download = true
         always_keep_install = true
+        build_from_source = false
         legacy_version_file = true
         plugin_autoupdate_last_check_duration = 20
+        plugin_clone_depth = 1
+        plugin_clone_single_branch = false
+        plugin_update_rollback = true
+        plugin_verify_url = true
         trusted_config_paths = []
         verbose = true
         asdf_compat = false
+        use_tool_versions = false
         jobs = 2
+        keep_order = false
+        superproject_root = false
+        warn_shadowed_bins = false
         disable_default_shorthands = false
         log_level = INFO
         raw = false
+        lock_resolved_versions = false
         "###);
 
         reset_config();
@@ -0,0 +1,128 @@
+use std::path::PathBuf;
+
+use color_eyre::eyre::Result;
+use console::style;
+
+use crate::cli::command::Command;
+use crate::config::Config;
+use crate::errors::Error::PluginNotInstalled;
+use crate::file::display_path;
+use crate::output::Output;
+use crate::plugins::PluginName;
+use crate::shims;
+
+/// Adds a shim for a bin that isn't in the tool's bin paths
+///
+/// The shim is named after the bin and runs it from whichever version of the tool is
+/// active, just like the shims `rtx reshim` creates. It is kept when the shims are rebuilt.
+#[derive(Debug, clap::Args)]
+#[clap(verbatim_doc_comment, after_long_help = AFTER_LONG_HELP)]
+pub struct ShimAdd {
+    /// The tool the bin belongs to
+    pub tool: PluginName,
+    /// Path to the bin inside the tool's install directory
+    /// e.g.: libexec/node-gyp
+    #[clap(verbatim_doc_comment)]
+    pub bin: PathBuf,
+}
+
+impl Command for ShimAdd {
+    fn run(self, config: Config, out: &mut Output) -> Result<()> {
+        let tool = match config.tools.get(&self.tool) {
+            Some(tool) if tool.is_installed() => tool,
+            _ => return Err(PluginNotInstalled(self.tool))?,
+        };
+        let shim = shims::add_manual_shim(&tool.name, &self.bin)?;
+        let installed = tool.list_installed_versions()?;
+        if !installed.is_empty()
+            && !installed
+                .iter()
+                .any(|v| tool.installs_path.join(v).join(&self.bin).exists())
+        {
+            warn!(
+                "{} was not found in any installed version of {}",
+                self.bin.display(),
+                style(&tool.name).cyan().for_stderr()
+            );
+        }
+        rtxstatusln!(out, "created shim {}", display_path(&shim));
+        Ok(())
+    }
+}
+
+static AFTER_LONG_HELP: &str = color_print::cstr!(
+    r#"<bold><underline>Examples:</underline></bold>
+  $ <bold>rtx shim add node lib/node_modules/npm/bin/npx-cli.js</bold>
+  rtx created shim ~/.local/share/rtx/shims/npx-cli.js
+  $ <bold>npx-cli.js --version</bold>
+  9.6.7
+"#
+);
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use crate::{assert_cli, assert_cli_err, dirs, shims};
+
+    #[test]
+    fn test_shim_add_rm() {
+        let shim = dirs::SHIMS.join("tiny-manual");
+        assert_cli!("shim", "add", "tiny", "libexec/tiny-manual");
+        assert!(shim.is_symlink());
+
+        // kept when the shims are rebuilt
+        assert_cli!("reshim");
+        assert!(shim.is_symlink());
+
+        assert_cli!("shim", "rm", "tiny-manual");
+        assert!(!shim.is_symlink());
+        let err = assert_cli_err!("shim", "rm", "tiny-manual");
+        assert_eq!(
+            err.to_string(),
+            "tiny-manual is not a shim added with `rtx shim add`"
+        );
+    }
+
+    #[test]
+    fn test_shim_add_outside_install_dir() {
+        let err = assert_cli_err!("shim", "add", "tiny", "../dummy/bin/dummy");
+        assert_eq!(
+            err.to_string(),
+            "../dummy/bin/dummy must be a path inside the tool's install directory, e.g.: bin/foo"
+        );
+        // clap refuses an empty arg but the check doesn't rely on that
+        let err = shims::add_manual_shim(&"tiny".into(), Path::new("")).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            " must be a path inside the tool's install directory, e.g.: bin/foo"
+        );
+    }
+
+    #[test]
+    fn test_shim_add_existing() {
+        assert_cli!("shim", "add", "tiny", "libexec/tiny-existing");
+        // adding the same one again is fine
+        assert_cli!("shim", "add", "tiny", "libexec/tiny-existing");
+        let err = assert_cli_err!("shim", "add", "dummy", "bin/tiny-existing");
+        assert_cli!("shim", "rm", "tiny-existing");
+        assert_eq!(
+            err.to_string(),
+            "tiny-existing is already a shim for tiny in libexec/tiny-existing, remove it first with `rtx shim rm tiny-existing`"
+        );
+    }
+
+    #[test]
+    fn test_shim_rm_outside_shims() {
+        let outside = dirs::ROOT.join("shim-rm-outside");
+        std::fs::write(&outside, "").unwrap();
+        let err = assert_cli_err!("shim", "rm", "../shim-rm-outside");
+        let exists = outside.exists();
+        std::fs::remove_file(&outside).unwrap();
+        assert!(exists);
+        assert_eq!(
+            err.to_string(),
+            "../shim-rm-outside is not a shim added with `rtx shim add`"
+        );
+    }
+}
@@ -0,0 +1,38 @@
+use clap::Subcommand;
+use color_eyre::eyre::Result;
+
+use crate::cli::command::Command;
+use crate::config::Config;
+use crate::output::Output;
+
+mod add;
+mod rm;
+
+/// Manage shims that `rtx reshim` doesn't create
+#[derive(Debug, clap::Args)]
+#[clap(verbatim_doc_comment)]
+pub struct Shim {
+    #[clap(subcommand)]
+    command: Commands,
+}
+
+#[derive(Debug, Subcommand)]
+enum Commands {
+    Add(add::ShimAdd),
+    Rm(rm::ShimRm),
+}
+
+impl Commands {
+    pub fn run(self, config: Config, out: &mut Output) -> Result<()> {
+        match self {
+            Self::Add(cmd) => cmd.run(config, out),
+            Self::Rm(cmd) => cmd.run(config, out),
+        }
+    }
+}
+
+impl Command for Shim {
+    fn run(self, config: Config, out: &mut Output) -> Result<()> {
+        self.command.run(config, out)
+    }
+}
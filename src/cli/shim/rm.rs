@@ -0,0 +1,31 @@
+use color_eyre::eyre::Result;
+
+use crate::cli::command::Command;
+use crate::config::Config;
+use crate::output::Output;
+use crate::shims;
+
+/// Removes a shim added with `rtx shim add`
+///
+/// Shims created by `rtx reshim` can't be removed this way since they
+/// would come back the next time the shims are rebuilt.
+#[derive(Debug, clap::Args)]
+#[clap(verbatim_doc_comment, visible_alias = "remove", after_long_help = AFTER_LONG_HELP)]
+pub struct ShimRm {
+    /// Name of the shim
+    pub name: String,
+}
+
+impl Command for ShimRm {
+    fn run(self, _config: Config, out: &mut Output) -> Result<()> {
+        shims::remove_manual_shim(&self.name)?;
+        rtxstatusln!(out, "removed shim {}", self.name);
+        Ok(())
+    }
+}
+
+static AFTER_LONG_HELP: &str = color_print::cstr!(
+    r#"<bold><underline>Examples:</underline></bold>
+  $ <bold>rtx shim rm npx-cli.js</bold>
+"#
+);
@@ -1,20 +1,42 @@
+use std::sync::Arc;
+
 use color_eyre::eyre::{eyre, Result};
 use console::style;
+use indicatif::HumanBytes;
+use itertools::Itertools;
 
 use crate::cli::args::tool::{ToolArg, ToolArgParser};
 use crate::cli::command::Command;
 use crate::config::Config;
+use crate::file;
 use crate::output::Output;
-use crate::toolset::ToolsetBuilder;
+use crate::tool::Tool;
+use crate::toolset::{ToolVersion, ToolVersionRequest, ToolsetBuilder};
 use crate::ui::multi_progress_report::MultiProgressReport;
+use crate::ui::prompt;
 
 /// Removes runtime versions
+///
+/// A partial version like `node@18` removes the installed version matching it.
+/// If more than one installed version matches, pass `--all` to remove all of them.
+///
+/// Before anything is removed this shows how much disk space will be freed, counting the
+/// install, download and cache directories, and asks to continue. Pass `--yes` to skip that,
+/// it is also skipped when not run interactively.
 #[derive(Debug, clap::Args)]
 #[clap(verbatim_doc_comment, alias = "remove", alias = "rm", after_long_help = AFTER_LONG_HELP)]
 pub struct Uninstall {
     /// Tool(s) to remove
     #[clap(required = true, value_parser = ToolArgParser)]
     tool: Vec<ToolArg>,
+
+    /// Remove every installed version matching a partial version
+    #[clap(long, short)]
+    all: bool,
+
+    /// Don't ask before removing the versions
+    #[clap(long, short)]
+    yes: bool,
 }
 
 impl Command for Uninstall {
@@ -24,8 +46,18 @@ impl Command for Uninstall {
             .iter()
             .map(|a| {
                 let tool = config.get_or_create_tool(&a.plugin);
-                let tv = match &a.tvr {
-                    Some(tvr) => tvr.resolve(&config, &tool, Default::default(), false)?,
+                let tvs = match &a.tvr {
+                    Some(ToolVersionRequest::Version(_, v)) => {
+                        let v = config.resolve_alias(&tool.name, v)?;
+                        match self.installed_matching(&tool, &v)? {
+                            Some(tvs) => tvs,
+                            None => {
+                                let tvr = a.tvr.as_ref().unwrap();
+                                vec![tvr.resolve(&config, &tool, Default::default(), false)?]
+                            }
+                        }
+                    }
+                    Some(tvr) => vec![tvr.resolve(&config, &tool, Default::default(), false)?],
                     None => {
                         let ts = ToolsetBuilder::new().build(&mut config)?;
                         let tv = ts
@@ -33,20 +65,31 @@ impl Command for Uninstall {
                             .get(&a.plugin)
                             .and_then(|v| v.versions.first())
                             .expect("no version found");
-                        tv.clone()
+                        vec![tv.clone()]
                     }
                 };
-                Ok((tool, tv))
+                Ok(tvs
+                    .into_iter()
+                    .map(|tv| (tool.clone(), tv))
+                    .collect::<Vec<_>>())
             })
-            .collect::<Result<Vec<_>>>()?;
+            .collect::<Result<Vec<_>>>()?
+            .into_iter()
+            .flatten()
+            .filter(|(plugin, tv)| {
+                let installed = plugin.is_version_installed(tv);
+                if !installed {
+                    warn!("{} is not installed", style(&tv).cyan().for_stderr());
+                }
+                installed
+            })
+            .collect::<Vec<_>>();
+        if tool_versions.is_empty() || !self.confirm(&tool_versions)? {
+            return Ok(());
+        }
 
-        let mpr = MultiProgressReport::new(config.settings.verbose);
+        let mpr = MultiProgressReport::new(&config.settings);
         for (plugin, tv) in tool_versions {
-            if !plugin.is_version_installed(&tv) {
-                warn!("{} is not installed", style(&tv).cyan().for_stderr());
-                continue;
-            }
-
             let mut pr = mpr.add();
             plugin.decorate_progress_bar(&mut pr, Some(&tv));
             if let Err(err) = plugin.uninstall_version(&config, &tv, &pr, false) {
@@ -59,9 +102,126 @@ impl Command for Uninstall {
     }
 }
 
+impl Uninstall {
+    /// shows the space that will be freed and asks to continue, without asking with `--yes` or
+    /// when there is nobody to answer
+    fn confirm(&self, tool_versions: &[(Arc<Tool>, ToolVersion)]) -> Result<bool> {
+        if self.yes || !console::user_attended_stderr() {
+            return Ok(true);
+        }
+        let mut size = 0;
+        for (_, tv) in tool_versions {
+            size += reclaimable_size(tv)?;
+        }
+        let versions = tool_versions
+            .iter()
+            .map(|(_, tv)| style(tv).cyan().for_stderr().to_string())
+            .join(", ");
+        Ok(prompt::confirm(&format!(
+            "Uninstall {versions} and free {}?",
+            HumanBytes(size)
+        ))?)
+    }
+
+    /// finds the installed versions a (possibly partial) version refers to
+    /// returns None if nothing installed matches so the request is resolved as usual
+    fn installed_matching(&self, tool: &Tool, v: &str) -> Result<Option<Vec<ToolVersion>>> {
+        let installed = tool.list_installed_versions()?;
+        let matches = match installed.iter().any(|iv| iv == v) {
+            true => vec![v.to_string()],
+            false => tool.list_installed_versions_with_prefix(v)?,
+        };
+        if matches.len() > 1 && !self.all {
+            return Err(eyre!(
+                "multiple versions of {} match {}: {}\nuse {} to uninstall all of them",
+                tool.name,
+                v,
+                matches.join(", "),
+                style("--all").yellow().for_stderr()
+            ));
+        }
+        Ok(match matches.is_empty() {
+            true => None,
+            false => Some(
+                matches
+                    .into_iter()
+                    .map(|iv| {
+                        let tvr = ToolVersionRequest::Version(tool.name.clone(), iv.clone());
+                        ToolVersion::new(tool, tvr, Default::default(), iv)
+                    })
+                    .collect(),
+            ),
+        })
+    }
+}
+
+/// bytes taken up by a version's install, download and cache directories
+fn reclaimable_size(tv: &ToolVersion) -> Result<u64> {
+    Ok(file::dir_size(&tv.install_path())?
+        + file::dir_size(&tv.download_path())?
+        + file::dir_size(&tv.cache_path())?)
+}
+
 static AFTER_LONG_HELP: &str = color_print::cstr!(
     r#"<bold><underline>Examples:</underline></bold>
-  $ <bold>rtx uninstall node@18.0.0</bold> # will uninstall specific version
-  $ <bold>rtx uninstall node</bold>        # will uninstall current node version
+  $ <bold>rtx uninstall node@18.0.0</bold>   # will uninstall specific version
+  $ <bold>rtx uninstall node@18</bold>       # will uninstall the installed node 18.x version
+  $ <bold>rtx uninstall --all node@18</bold> # will uninstall every installed node 18.x version
+  $ <bold>rtx uninstall node</bold>          # will uninstall current node version
+  $ <bold>rtx uninstall -y node@18.0.0</bold> # will not ask before uninstalling
 "#
 );
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use console::strip_ansi_codes;
+    use pretty_assertions::{assert_eq, assert_str_eq};
+
+    use crate::config::Config;
+    use crate::file::remove_all;
+    use crate::toolset::{ToolVersion, ToolVersionRequest};
+    use crate::{assert_cli, assert_cli_err, dirs, file};
+
+    use super::reclaimable_size;
+
+    #[test]
+    fn test_uninstall_prefix() {
+        assert_cli!("install", "tiny@1.1.0");
+        let err = assert_cli_err!("uninstall", "tiny@1");
+        assert_str_eq!(
+            strip_ansi_codes(&err.to_string()),
+            "multiple versions of tiny match 1: 1.0.1, 1.1.0\nuse --all to uninstall all of them"
+        );
+        assert!(dirs::INSTALLS.join("tiny/1.1.0").exists());
+
+        assert_cli!("uninstall", "tiny@1.1");
+        assert!(!dirs::INSTALLS.join("tiny/1.1.0").exists());
+        assert!(dirs::INSTALLS.join("tiny/1.0.1").exists());
+    }
+
+    #[test]
+    fn test_uninstall_reclaimable_size() {
+        let mut config = Config::load().unwrap();
+        let tool = config.get_or_create_tool(&"tiny".to_string());
+        let tvr = ToolVersionRequest::Version(tool.name.clone(), "0.9.8".into());
+        let tv = ToolVersion::new(&tool, tvr, Default::default(), "0.9.8".into());
+        for (dir, size) in [
+            (tv.install_path().join("bin"), 100),
+            (tv.download_path(), 20),
+            (tv.cache_path(), 3),
+        ] {
+            file::create_dir_all(&dir).unwrap();
+            fs::write(dir.join("file"), vec![0; size]).unwrap();
+        }
+        let size = reclaimable_size(&tv).unwrap();
+
+        assert_cli!("uninstall", "--yes", "tiny@0.9.8");
+        let install_removed = !tv.install_path().exists();
+        remove_all(tv.cache_path()).unwrap();
+        assert_eq!(size, 123);
+        assert!(install_removed);
+        assert!(!tv.download_path().exists());
+    }
+}
@@ -1,20 +1,25 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-use color_eyre::eyre::Result;
+use color_eyre::eyre::{eyre, Result};
+use color_eyre::Section;
+use itertools::Itertools;
 
 use crate::cli::args::tool::{ToolArg, ToolArgParser};
 use crate::cli::command::Command;
-use crate::cli::local::local;
+use crate::cli::local::{get_parent_path, local};
 use crate::config::{Config, MissingRuntimeBehavior};
-use crate::env::RTX_DEFAULT_CONFIG_FILENAME;
+use crate::env::{RTX_DEFAULT_CONFIG_FILENAME, RTX_DEFAULT_TOOL_VERSIONS_FILENAME};
 use crate::output::Output;
 use crate::plugins::PluginName;
+use crate::ui::prompt;
 use crate::{dirs, env};
 
 /// Change the active version of a tool locally or globally.
 ///
 /// This will install the tool if it is not already installed.
 /// By default, this will use an `.rtx.toml` file in the current directory.
+/// If there is only a `.tool-versions` file in the current directory, that is updated instead.
+/// Set `use_tool_versions` to create `.tool-versions` files by default.
 /// Use the --global flag to use the global config file instead.
 /// This replaces asdf's `local` and `global` commands, however those are still available in rtx.
 #[derive(Debug, clap::Args)]
@@ -38,7 +43,8 @@ pub struct Use {
     fuzzy: bool,
 
     /// Remove the tool(s) from config file
-    #[clap(long, value_name = "TOOL", aliases = ["rm", "unset"])]
+    /// Without --global or --path, this uses the closest .rtx.toml/.tool-versions
+    #[clap(long, verbatim_doc_comment, value_name = "TOOL", aliases = ["rm", "unset"])]
     remove: Option<Vec<PluginName>>,
 
     /// Use the global config file (~/.config/rtx/config.toml) instead of the local one
@@ -48,6 +54,10 @@ pub struct Use {
     /// Specify a path to a config file
     #[clap(short, long, overrides_with = "global", value_hint = clap::ValueHint::FilePath)]
     path: Option<PathBuf>,
+
+    /// Use the tool(s) even if there is no installed plugin or shorthand with that name
+    #[clap(long, short)]
+    force: bool,
 }
 
 impl Command for Use {
@@ -60,11 +70,18 @@ impl Command for Use {
                 Some(_) => r,
                 None => ToolArg::parse(&format!("{}@latest", r.plugin)),
             })
-            .collect();
+            .collect::<Vec<_>>();
+        if !self.force {
+            for r in &runtimes {
+                check_plugin_name(&config, &r.plugin)?;
+            }
+        }
         let path = match (self.global, self.path) {
             (true, _) => global_file(),
             (false, Some(p)) => p,
-            (false, None) => dirs::CURRENT.join(&*RTX_DEFAULT_CONFIG_FILENAME),
+            // only removing tools so use whichever config file is closest
+            (false, None) if runtimes.is_empty() && self.remove.is_some() => get_parent_path()?,
+            (false, None) => default_config_path(&dirs::CURRENT, config.settings.use_tool_versions),
         };
         local(
             config,
@@ -79,6 +96,51 @@ impl Command for Use {
     }
 }
 
+/// a typo'd name would otherwise be written to the config file and fail to resolve every time
+fn check_plugin_name(config: &Config, name: &PluginName) -> Result<()> {
+    if config.tools.contains_key(name) || config.has_repo_url(name) {
+        return Ok(());
+    }
+    let known = config
+        .tools
+        .keys()
+        .chain(config.get_shorthands().keys())
+        .collect_vec();
+    let msg = match closest_name(name, &known) {
+        Some(suggestion) => format!("unknown plugin {name}, did you mean {suggestion}?"),
+        None => format!("unknown plugin {name}"),
+    };
+    if prompt::confirm(&format!("{msg} Use {name} anyway?"))? {
+        return Ok(());
+    }
+    Err(eyre!(msg).suggestion("use --force to use it anyway"))
+}
+
+/// the known name with the fewest edits from `name`, if it is close enough to be a typo
+fn closest_name<'a>(name: &str, known: &[&'a PluginName]) -> Option<&'a PluginName> {
+    let max_distance = (name.len() / 3).max(1);
+    known
+        .iter()
+        .map(|k| (strsim::damerau_levenshtein(name, k), *k))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .min()
+        .map(|(_, k)| k)
+}
+
+/// updates whichever config file already exists in `dir`, otherwise creates a new one
+/// in the format from the `use_tool_versions` setting
+fn default_config_path(dir: &Path, use_tool_versions: bool) -> PathBuf {
+    let rtx_toml = dir.join(&*RTX_DEFAULT_CONFIG_FILENAME);
+    let tool_versions = dir.join(&*RTX_DEFAULT_TOOL_VERSIONS_FILENAME);
+    match (rtx_toml.exists(), tool_versions.exists()) {
+        // .rtx.toml overrides .tool-versions in the same directory so it is the one to update
+        (true, _) => rtx_toml,
+        (false, true) => tool_versions,
+        (false, false) if use_tool_versions => tool_versions,
+        (false, false) => rtx_toml,
+    }
+}
+
 fn global_file() -> PathBuf {
     env::RTX_CONFIG_FILE
         .clone()
@@ -94,20 +156,29 @@ static AFTER_LONG_HELP: &str = color_print::cstr!(
   # set the current version of node to 20.x in ~/.config/rtx/config.toml
   # will write the precise version (e.g.: 20.0.0)
   $ <bold>rtx use -g --pin node@20</bold>
+
+  # remove node from the closest .rtx.toml/.tool-versions
+  $ <bold>rtx use --rm node</bold>
 "#
 );
 
 #[cfg(test)]
 mod tests {
+    use indoc::indoc;
     use insta::assert_snapshot;
+    use pretty_assertions::assert_eq;
     use std::fs;
 
-    use crate::{assert_cli, dirs};
+    use crate::config::Config;
+    use crate::{assert_cli, assert_cli_err, dirs};
+
+    use super::{check_plugin_name, closest_name, default_config_path};
 
     #[test]
     fn test_use_local() {
         let cf_path = dirs::CURRENT.join(".test.rtx.toml");
-        let _ = fs::remove_file(&cf_path);
+        // cwd also has a .test-tool-versions, .test.rtx.toml is preferred if both exist
+        fs::write(&cf_path, "").unwrap();
 
         assert_cli!("use", "tiny@2");
         assert_snapshot!(fs::read_to_string(&cf_path).unwrap());
@@ -130,6 +201,95 @@ mod tests {
         let _ = fs::remove_file(&cf_path);
     }
 
+    #[test]
+    fn test_use_remove() {
+        let dir = dirs::CURRENT.join("use-remove");
+        let _ = fs::create_dir_all(&dir);
+        let tv_path = dir.join(".test-tool-versions");
+        fs::write(
+            &tv_path,
+            indoc! {r#"
+            # installed tools
+            tiny  2
+            dummy ref:master # keep this comment
+            "#},
+        )
+        .unwrap();
+        let tv_path_str = tv_path.to_string_lossy().to_string();
+        assert_cli!("use", "--rm", "tiny", "--path", &tv_path_str);
+        assert_eq!(
+            fs::read_to_string(&tv_path).unwrap(),
+            indoc! {r#"
+            # installed tools
+            dummy ref:master # keep this comment
+            "#}
+        );
+        let err = assert_cli_err!("use", "--rm", "tiny", "--path", &tv_path_str);
+        assert!(err.to_string().starts_with("tiny is not set in"));
+
+        let cf_path = dir.join(".test.rtx.toml");
+        fs::write(
+            &cf_path,
+            indoc! {r#"
+            # installed tools
+            [tools]
+            dummy = "ref:master" # keep this comment
+            tiny = "2"
+
+            [settings]
+            verbose = true
+            "#},
+        )
+        .unwrap();
+        assert_cli!(
+            "use",
+            "--rm",
+            "tiny",
+            "--path",
+            &cf_path.to_string_lossy().to_string()
+        );
+        assert_eq!(
+            fs::read_to_string(&cf_path).unwrap(),
+            indoc! {r#"
+            # installed tools
+            [tools]
+            dummy = "ref:master" # keep this comment
+
+            [settings]
+            verbose = true
+            "#}
+        );
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_default_config_path() {
+        let dir = dirs::CACHE.join("test-use-default-config-path");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let rtx_toml = dir.join(".test.rtx.toml");
+        let tool_versions = dir.join(".test-tool-versions");
+
+        // creating a new file
+        assert_eq!(default_config_path(&dir, false), rtx_toml);
+        assert_eq!(default_config_path(&dir, true), tool_versions);
+
+        // updating an existing .tool-versions
+        fs::write(&tool_versions, "tiny 2\n").unwrap();
+        assert_eq!(default_config_path(&dir, false), tool_versions);
+        assert_eq!(default_config_path(&dir, true), tool_versions);
+
+        // updating an existing .rtx.toml, preferred when both exist
+        fs::write(&rtx_toml, "").unwrap();
+        assert_eq!(default_config_path(&dir, false), rtx_toml);
+        assert_eq!(default_config_path(&dir, true), rtx_toml);
+        fs::remove_file(&tool_versions).unwrap();
+        assert_eq!(default_config_path(&dir, true), rtx_toml);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
     #[test]
     fn test_use_global() {
         let cf_path = dirs::CONFIG.join("config.toml");
@@ -141,4 +301,27 @@ mod tests {
 
         fs::write(&cf_path, orig).unwrap();
     }
+
+    #[test]
+    fn test_use_typo() {
+        let err = assert_cli_err!("use", "tinyy@2");
+        assert_eq!(err.to_string(), "unknown plugin tinyy, did you mean tiny?");
+        let err = assert_cli_err!("use", "xyzzyq@2");
+        assert_eq!(err.to_string(), "unknown plugin xyzzyq");
+
+        // the plugin_default_org url is only a guess, it doesn't make a typo a known plugin
+        let mut config = Config::load().unwrap();
+        config.settings.plugin_default_org = Some("my-org".into());
+        let err = check_plugin_name(&config, &"tinyy".to_string()).unwrap_err();
+        assert_eq!(err.to_string(), "unknown plugin tinyy, did you mean tiny?");
+    }
+
+    #[test]
+    fn test_closest_name() {
+        let known = ["node", "nodejs", "deno", "python"].map(String::from);
+        let known = known.iter().collect::<Vec<_>>();
+        assert_eq!(closest_name("nod", &known), Some(&known[0]).copied());
+        assert_eq!(closest_name("pyhton", &known), Some(&known[3]).copied());
+        assert_eq!(closest_name("ruby", &known), None);
+    }
 }
@@ -9,7 +9,7 @@ use versions::Versioning;
 
 use crate::build_time::{built_info, BUILD_TIME};
 use crate::cli::command::Command;
-use crate::config::Config;
+use crate::config::{Config, Settings};
 use crate::env::CI;
 use crate::file::modified_duration;
 use crate::output::Output;
@@ -47,8 +47,8 @@ pub static VERSION: Lazy<String> = Lazy::new(|| {
 pub static RAW_VERSION: Lazy<String> = Lazy::new(|| env!("CARGO_PKG_VERSION").to_string());
 
 impl Command for Version {
-    fn run(self, _config: Config, out: &mut Output) -> Result<()> {
-        show_version(out);
+    fn run(self, config: Config, out: &mut Output) -> Result<()> {
+        show_version(out, &config.settings);
         Ok(())
     }
 }
@@ -57,22 +57,25 @@ pub fn print_version_if_requested(args: &[String], out: &mut Output) {
     if args.len() == 2 && (args[0] == "rtx" || args[0].ends_with("/rtx")) {
         let cmd = &args[1].to_lowercase();
         if cmd == "version" || cmd == "-v" || cmd == "--version" {
-            show_version(out);
+            // the version is shown even if the config can't be loaded, the proxy settings from it
+            // are only needed to check for a newer one
+            let settings = Config::load().map(|c| c.settings).unwrap_or_default();
+            show_version(out, &settings);
             std::process::exit(0);
         }
     }
 }
 
-fn show_version(out: &mut Output) {
+fn show_version(out: &mut Output, settings: &Settings) {
     rtxprintln!(out, "{}", *VERSION);
-    show_latest();
+    show_latest(settings);
 }
 
-fn show_latest() {
+fn show_latest(settings: &Settings) {
     if *CI {
         return;
     }
-    if let Some(latest) = check_for_new_version(duration::DAILY) {
+    if let Some(latest) = check_for_new_version(settings, duration::DAILY) {
         warn!("rtx version {} available", latest);
         if cfg!(feature = "self_update") {
             let cmd = style("rtx self-update").bright().yellow().for_stderr();
@@ -81,8 +84,10 @@ fn show_latest() {
     }
 }
 
-pub fn check_for_new_version(cache_duration: Duration) -> Option<String> {
-    if let Some(latest) = get_latest_version(cache_duration).and_then(|v| Versioning::new(&v)) {
+pub fn check_for_new_version(settings: &Settings, cache_duration: Duration) -> Option<String> {
+    if let Some(latest) =
+        get_latest_version(settings, cache_duration).and_then(|v| Versioning::new(&v))
+    {
         let current = Versioning::new(env!("CARGO_PKG_VERSION")).unwrap();
         if current < latest {
             return Some(latest.to_string());
@@ -91,7 +96,7 @@ pub fn check_for_new_version(cache_duration: Duration) -> Option<String> {
     None
 }
 
-fn get_latest_version(duration: Duration) -> Option<String> {
+fn get_latest_version(settings: &Settings, duration: Duration) -> Option<String> {
     let version_file_path = dirs::CACHE.join("latest-version");
     if let Ok(metadata) = modified_duration(&version_file_path) {
         if metadata < duration {
@@ -101,22 +106,22 @@ fn get_latest_version(duration: Duration) -> Option<String> {
         }
     }
     let _ = fs::create_dir_all(&*dirs::CACHE);
-    let version = get_latest_version_call();
+    let version = get_latest_version_call(settings);
     let _ = fs::write(version_file_path, version.clone().unwrap_or_default());
     version
 }
 
 #[cfg(test)]
-fn get_latest_version_call() -> Option<String> {
+fn get_latest_version_call(_settings: &Settings) -> Option<String> {
     Some("0.0.0".to_string())
 }
 
 #[cfg(not(test))]
-fn get_latest_version_call() -> Option<String> {
+fn get_latest_version_call(settings: &Settings) -> Option<String> {
     let timeout = Duration::from_secs(3);
     const URL: &str = "http://rtx.pub/VERSION";
     debug!("checking rtx version from {}", URL);
-    let client = crate::http::Client::new().ok()?;
+    let client = crate::http::Client::new(settings).ok()?;
     match client.get(URL).timeout(timeout).send() {
         Ok(res) => {
             if res.status().is_success() {
@@ -0,0 +1,90 @@
+use color_eyre::eyre::{eyre, Result};
+use console::style;
+
+use crate::cli::args::tool::{ToolArg, ToolArgParser};
+use crate::cli::command::Command;
+use crate::config::Config;
+use crate::errors::Error::PluginNotInstalled;
+use crate::output::Output;
+use crate::toolset::ToolVersionRequest;
+
+/// Checks if a version of a tool is available to install
+///
+/// Exits with 0 if the version exists and 1 if it does not.
+/// This is much faster than attempting an install to find out.
+#[derive(Debug, clap::Args)]
+#[clap(verbatim_doc_comment, after_long_help = AFTER_LONG_HELP)]
+pub struct VersionExists {
+    /// Tool to check
+    /// e.g.: node@20.0.0
+    #[clap(value_parser = ToolArgParser, verbatim_doc_comment)]
+    tool: ToolArg,
+
+    /// The version to check
+    /// same as the argument after the "@"
+    #[clap(verbatim_doc_comment)]
+    version: Option<String>,
+}
+
+impl Command for VersionExists {
+    fn run(self, config: Config, _out: &mut Output) -> Result<()> {
+        let version = match (self.tool.tvr, self.version) {
+            (None, Some(version)) => version,
+            (Some(ToolVersionRequest::Version(_, version)), None) => version,
+            _ => Err(eyre!(
+                "specify a single version, e.g.: {}",
+                style(format!("rtx version-exists {}@1.0.0", self.tool.plugin))
+                    .yellow()
+                    .for_stderr()
+            ))?,
+        };
+        let tool = match config.tools.get(&self.tool.plugin) {
+            Some(tool) => tool,
+            None => Err(PluginNotInstalled(self.tool.plugin.clone()))?,
+        };
+        let version = config.resolve_alias(&tool.name, &version)?;
+
+        match tool.version_exists(&config.settings, &version)? {
+            true => Ok(()),
+            false => Err(eyre!(
+                "{} does not exist",
+                style(format!("{}@{}", tool.name, version))
+                    .cyan()
+                    .for_stderr()
+            )),
+        }
+    }
+}
+
+static AFTER_LONG_HELP: &str = color_print::cstr!(
+    r#"<bold><underline>Examples:</underline></bold>
+  $ <bold>rtx version-exists node 20.0.0 && echo yes</bold>
+  yes
+
+  $ <bold>rtx version-exists node@99.0.0</bold>
+  rtx node@99.0.0 does not exist
+"#
+);
+
+#[cfg(test)]
+mod tests {
+    use console::strip_ansi_codes;
+    use pretty_assertions::assert_str_eq;
+
+    use crate::{assert_cli, assert_cli_err};
+
+    #[test]
+    fn test_version_exists() {
+        assert_cli!("version-exists", "dummy", "1.1.0");
+        assert_cli!("version-exists", "dummy@2.0.0");
+    }
+
+    #[test]
+    fn test_version_exists_missing() {
+        let err = assert_cli_err!("version-exists", "dummy@1.1");
+        assert_str_eq!(
+            strip_ansi_codes(&err.to_string()),
+            "dummy@1.1 does not exist"
+        );
+    }
+}
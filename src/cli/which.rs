@@ -19,26 +19,35 @@ pub struct Which {
     /// Show the version instead of the path
     #[clap(long, conflicts_with = "plugin")]
     pub version: bool,
+
+    /// Show every tool that provides the bin, not just the one that is used
+    /// they are listed in order of precedence
+    #[clap(long, short, verbatim_doc_comment)]
+    pub all: bool,
 }
 
 impl Command for Which {
     fn run(self, mut config: Config, out: &mut Output) -> Result<()> {
         let ts = ToolsetBuilder::new().build(&mut config)?;
 
-        match ts.which(&config, &self.bin_name) {
-            Some((p, tv)) => {
-                if self.version {
-                    rtxprintln!(out, "{}", tv.version);
-                } else if self.plugin {
-                    rtxprintln!(out, "{}", p.name);
-                } else {
-                    let path = p.which(&config, &tv, &self.bin_name)?;
-                    rtxprintln!(out, "{}", path.unwrap().display());
-                }
-                Ok(())
+        let found = match self.all {
+            true => ts.which_all(&config, &self.bin_name),
+            false => ts.which(&config, &self.bin_name).into_iter().collect(),
+        };
+        if found.is_empty() {
+            return Err(eyre!("{} not found", self.bin_name));
+        }
+        for (p, tv) in found {
+            if self.version {
+                rtxprintln!(out, "{}", tv.version);
+            } else if self.plugin {
+                rtxprintln!(out, "{}", p.name);
+            } else {
+                let path = p.which(&config, &tv, &self.bin_name)?;
+                rtxprintln!(out, "{}", path.unwrap().display());
             }
-            None => Err(eyre!("{} not found", self.bin_name)),
         }
+        Ok(())
     }
 }
 
@@ -50,12 +59,15 @@ static AFTER_LONG_HELP: &str = color_print::cstr!(
   node
   $ <bold>rtx which node --version</bold>
   20.0.0
+  $ <bold>rtx which node --all</bold>
+  /home/username/.local/share/rtx/installs/node/20.0.0/bin/node
+  /home/username/.local/share/rtx/installs/node/18.16.0/bin/node
 "#
 );
 
 #[cfg(test)]
 mod tests {
-    use crate::{assert_cli, assert_cli_snapshot};
+    use crate::{assert_cli, assert_cli_err, assert_cli_snapshot};
 
     #[test]
     fn test_which() {
@@ -73,6 +85,14 @@ mod tests {
         assert_cli!("uninstall", "dummy@1.0.0");
     }
 
+    #[test]
+    fn test_which_all() {
+        let stdout = assert_cli!("which", "--all", "rtx-tiny");
+        assert!(stdout.trim().ends_with("installs/tiny/3.1.0/bin/rtx-tiny"));
+        let err = assert_cli_err!("which", "--all", "not-a-bin");
+        assert_eq!(err.to_string(), "not-a-bin not found");
+    }
+
     #[test]
     fn test_which_version() {
         assert_cli!("global", "dummy@1.0.0");
@@ -1,14 +1,20 @@
 use color_eyre::Result;
+use std::collections::HashSet;
 use std::ffi::{OsStr, OsString};
 use std::io::{BufRead, BufReader, Write};
+use std::os::unix::process::CommandExt;
 use std::process::{Command, ExitStatus, Stdio};
-use std::sync::mpsc::channel;
+use std::sync::mpsc::{channel, RecvTimeoutError};
+use std::sync::Mutex;
 use std::thread;
+use std::time::{Duration, Instant};
 
+use crate::cancel;
 use crate::config::Settings;
-use crate::errors::Error::ScriptFailed;
+use crate::errors::Error::{ScriptFailed, ScriptTimedOut};
 use crate::ui::progress_report::ProgressReport;
 use duct::{Expression, IntoExecutablePath};
+use once_cell::sync::Lazy;
 
 /// Create a command with any number of of positional arguments, which may be
 /// different types (anything that implements
@@ -87,6 +93,7 @@ pub struct CmdLineRunner<'a> {
     settings: &'a Settings,
     pr: Option<&'a ProgressReport>,
     stdin: Option<String>,
+    timeout: Option<Duration>,
 }
 impl<'a> CmdLineRunner<'a> {
     pub fn new<P: AsRef<OsStr>>(settings: &'a Settings, program: P) -> Self {
@@ -100,6 +107,7 @@ impl<'a> CmdLineRunner<'a> {
             settings,
             pr: None,
             stdin: None,
+            timeout: None,
         }
     }
 
@@ -136,6 +144,13 @@ impl<'a> CmdLineRunner<'a> {
         self
     }
 
+    /// kills the process and everything it spawned if it runs longer than this
+    /// (not enforced with RTX_RAW=1 since the process needs to stay in the terminal's process group)
+    pub fn with_timeout(&mut self, timeout: Duration) -> &mut Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
     pub fn stdin_string(&mut self, input: impl Into<String>) -> &mut Self {
         self.cmd.stdin(Stdio::piped());
         self.stdin = Some(input.into());
@@ -147,7 +162,12 @@ impl<'a> CmdLineRunner<'a> {
         if self.settings.raw {
             return self.execute_raw();
         }
+        // run in a new process group so children can be killed along with it on a timeout,
+        // SIGTERM or ctrl-c
+        self.cmd.process_group(0);
         let mut cp = self.cmd.spawn()?;
+        let pid = cp.id();
+        let _running = Running::start(pid);
         let stdout = BufReader::new(cp.stdout.take().unwrap());
         let stderr = BufReader::new(cp.stderr.take().unwrap());
         let (tx, rx) = channel();
@@ -185,7 +205,24 @@ impl<'a> CmdLineRunner<'a> {
         let mut combined_output = vec![];
         let mut wait_for_count = 3;
         let mut status = None;
-        for line in rx {
+        let deadline = self.timeout.map(|t| Instant::now() + t);
+        let mut timed_out = false;
+        let mut canceled = false;
+        loop {
+            if !canceled && cancel::is_cancelled() {
+                // rtx got SIGTERM, pass it on so the script can stop and clean up
+                canceled = true;
+                signal_process_group(pid, libc::SIGTERM);
+            }
+            if !timed_out && deadline.map_or(false, |d| Instant::now() >= d) {
+                timed_out = true;
+                kill_process_group(pid);
+            }
+            let line = match rx.recv_timeout(POLL_INTERVAL) {
+                Ok(line) => line,
+                Err(RecvTimeoutError::Timeout) => continue,
+                Err(RecvTimeoutError::Disconnected) => break,
+            };
             match line {
                 ChildProcessOutput::Stdout(line) => {
                     self.on_stdout(&line);
@@ -208,6 +245,12 @@ impl<'a> CmdLineRunner<'a> {
         }
         let status = status.unwrap();
 
+        if timed_out {
+            if let Some(pr) = self.pr {
+                pr.error();
+            }
+            Err(ScriptTimedOut(self.get_program(), self.timeout.unwrap()))?;
+        }
         if !status.success() {
             self.on_error(combined_output.join("\n"), status)?;
         }
@@ -225,8 +268,10 @@ impl<'a> CmdLineRunner<'a> {
 
     fn on_stdout(&self, line: &str) {
         if !line.trim().is_empty() {
-            if let Some(pr) = self.pr {
-                pr.set_message(line)
+            match self.pr {
+                Some(pr) if pr.is_plain() => pr.log(line),
+                Some(pr) => pr.set_message(line),
+                None => (),
             }
         }
     }
@@ -234,6 +279,7 @@ impl<'a> CmdLineRunner<'a> {
     fn on_stderr(&self, line: &str) {
         if !line.trim().is_empty() {
             match self.pr {
+                Some(pr) if pr.is_plain() => pr.log(line),
                 Some(pr) => pr.println(line),
                 None => eprintln!("{}", line),
             }
@@ -244,7 +290,8 @@ impl<'a> CmdLineRunner<'a> {
         match self.pr {
             Some(pr) => {
                 pr.error();
-                if !self.settings.verbose && !output.trim().is_empty() {
+                // plain progress only writes the script's output to the log file
+                if (!self.settings.verbose || pr.is_plain()) && !output.trim().is_empty() {
                     pr.println(output);
                 }
             }
@@ -268,6 +315,48 @@ impl<'a> CmdLineRunner<'a> {
     }
 }
 
+/// how often a running command checks for its timeout or rtx being canceled
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// process groups of the commands running right now
+static RUNNING: Lazy<Mutex<HashSet<u32>>> = Lazy::new(Default::default);
+
+/// tracks a command in `RUNNING` until it is dropped
+struct Running(u32);
+
+impl Running {
+    fn start(pid: u32) -> Self {
+        RUNNING.lock().unwrap().insert(pid);
+        Self(pid)
+    }
+}
+
+impl Drop for Running {
+    fn drop(&mut self) {
+        RUNNING.lock().unwrap().remove(&self.0);
+    }
+}
+
+/// commands run in their own process group so they don't get the ctrl-c from the terminal,
+/// this passes it on to them before rtx exits
+pub fn interrupt_running() {
+    for pid in RUNNING.lock().unwrap().iter() {
+        signal_process_group(*pid, libc::SIGINT);
+    }
+}
+
+/// the process was started in its own process group so this also kills anything it spawned
+pub fn kill_process_group(pid: u32) {
+    signal_process_group(pid, libc::SIGKILL);
+}
+
+fn signal_process_group(pid: u32, signal: libc::c_int) {
+    debug!("sending signal {} to process group {}", signal, pid);
+    unsafe {
+        libc::kill(-(pid as i32), signal);
+    }
+}
+
 enum ChildProcessOutput {
     Stdout(String),
     Stderr(String),
@@ -277,11 +366,48 @@ enum ChildProcessOutput {
 
 #[cfg(test)]
 mod tests {
-    use crate::cmd;
+    use std::time::{Duration, Instant};
+
+    use crate::cancel;
+    use crate::cmd::CmdLineRunner;
+    use crate::config::Settings;
+    use crate::errors::Error;
+    use crate::test::run_isolated;
 
     #[test]
     fn test_cmd() {
         let output = cmd!("echo", "foo", "bar").read().unwrap();
         assert_eq!("foo bar", output);
     }
+
+    #[test]
+    fn test_cmd_line_runner_timeout() {
+        let settings = Settings::default();
+        let mut cmd = CmdLineRunner::new(&settings, "sh");
+        cmd.arg("-c")
+            .arg("sleep 10 & sleep 10; wait")
+            .with_timeout(Duration::from_millis(100));
+        let start = Instant::now();
+        let err = cmd.execute().unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<Error>(),
+            Some(Error::ScriptTimedOut(_, _))
+        ));
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_cmd_line_runner_canceled() {
+        if run_isolated("cmd::tests::test_cmd_line_runner_canceled").is_some() {
+            return;
+        }
+        let settings = Settings::default();
+        let mut cmd = CmdLineRunner::new(&settings, "sh");
+        cmd.arg("-c").arg("sleep 10 & sleep 10; wait");
+        let start = Instant::now();
+        cancel::cancel();
+        let result = cmd.execute();
+        assert!(result.is_err());
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
 }
@@ -38,7 +38,7 @@ impl ConfigFile for LegacyVersionFile {
         self.path.as_path()
     }
 
-    fn plugins(&self) -> HashMap<PluginName, String> {
+    fn plugins(&self) -> HashMap<PluginName, Vec<String>> {
         Default::default()
     }
 
@@ -33,7 +33,8 @@ pub enum ConfigFileType {
 pub trait ConfigFile: Debug + Display + Send + Sync {
     fn get_type(&self) -> ConfigFileType;
     fn get_path(&self) -> &Path;
-    fn plugins(&self) -> HashMap<PluginName, String>;
+    /// repository urls of each plugin, later ones are mirrors tried if the first can't be cloned
+    fn plugins(&self) -> HashMap<PluginName, Vec<String>>;
     fn env(&self) -> HashMap<String, String>;
     fn env_remove(&self) -> Vec<String> {
         vec![]
@@ -56,7 +57,7 @@ impl dyn ConfigFile {
         runtimes: &[ToolArg],
         pin: bool,
     ) -> Result<()> {
-        let mpr = MultiProgressReport::new(config.settings.verbose);
+        let mpr = MultiProgressReport::new(&config.settings);
         let mut ts = self.to_toolset().to_owned();
         ts.resolve(config);
         ts.latest_versions = true;
@@ -8,7 +8,7 @@ use color_eyre::eyre::eyre;
 use color_eyre::{Result, Section};
 use log::LevelFilter;
 use tera::Context;
-use toml_edit::{table, value, Array, Document, Item, Value};
+use toml_edit::{table, value, Array, Document, InlineTable, Item, Value};
 
 use crate::config::config_file::{ConfigFile, ConfigFileType};
 use crate::config::settings::SettingsBuilder;
@@ -35,7 +35,7 @@ pub struct RtxToml {
     settings: SettingsBuilder,
     alias: AliasMap,
     doc: Document,
-    plugins: HashMap<String, String>,
+    plugins: HashMap<String, Vec<String>>,
     is_trusted: bool,
 }
 
@@ -84,7 +84,7 @@ impl RtxToml {
                 "alias" => self.alias = self.parse_alias(k, v)?,
                 "tools" => self.toolset = self.parse_toolset(k, v)?,
                 "settings" => self.settings = self.parse_settings(k, v)?,
-                "plugins" => self.plugins = self.parse_hashmap(k, v)?,
+                "plugins" => self.plugins = self.parse_plugins(k, v)?,
                 _ => Err(eyre!("unknown key: {}", k))?,
             }
         }
@@ -207,21 +207,31 @@ impl RtxToml {
         }
     }
 
-    fn parse_hashmap(&mut self, key: &str, v: &Item) -> Result<HashMap<String, String>> {
+    /// `name = "url"` or `name = ["url", "mirror-url"]`
+    fn parse_plugins(&mut self, key: &str, v: &Item) -> Result<HashMap<String, Vec<String>>> {
         match v.as_table_like() {
             Some(table) => {
-                let mut env = HashMap::new();
+                let mut plugins = HashMap::new();
                 for (k, v) in table.iter() {
-                    match v.as_str() {
-                        Some(s) => {
-                            let k = self.parse_template(key, k)?;
-                            let s = self.parse_template(key, s)?;
-                            env.insert(k, s);
-                        }
-                        _ => parse_error!(key, v, "string")?,
-                    }
+                    let key = format!("{}.{}", key, k);
+                    let urls = match v.as_value() {
+                        Some(Value::String(s)) => vec![s.value().to_string()],
+                        Some(Value::Array(arr)) => arr
+                            .iter()
+                            .map(|v| match v.as_str() {
+                                Some(s) => Ok(s.to_string()),
+                                None => parse_error!(key, v, "string"),
+                            })
+                            .collect::<Result<Vec<_>>>()?,
+                        _ => parse_error!(key, v, "string or array of strings")?,
+                    };
+                    let urls = urls
+                        .iter()
+                        .map(|url| self.parse_template(&key, url))
+                        .collect::<Result<Vec<_>>>()?;
+                    plugins.insert(k.to_string(), urls);
                 }
-                Ok(env)
+                Ok(plugins)
             }
             _ => parse_error!(key, v, "table"),
         }
@@ -396,24 +406,76 @@ impl RtxToml {
                         "always_keep_install" => {
                             settings.always_keep_install = Some(self.parse_bool(&k, v)?)
                         }
+                        "build_from_source" => {
+                            settings.build_from_source = Some(self.parse_bool(&k, v)?)
+                        }
                         "plugin_autoupdate_last_check_duration" => {
                             settings.plugin_autoupdate_last_check_duration =
                                 Some(self.parse_duration_minutes(&k, v)?)
                         }
+                        "plugin_clone_depth" => {
+                            settings.plugin_clone_depth = Some(self.parse_usize(&k, v)?)
+                        }
+                        "plugin_clone_single_branch" => {
+                            settings.plugin_clone_single_branch = Some(self.parse_bool(&k, v)?)
+                        }
+                        "plugin_update_rollback" => {
+                            settings.plugin_update_rollback = Some(self.parse_bool(&k, v)?)
+                        }
+                        "plugin_verify_url" => {
+                            settings.plugin_verify_url = Some(self.parse_bool(&k, v)?)
+                        }
                         "trusted_config_paths" => {
                             settings.trusted_config_paths = self.parse_paths(&k, v)?;
                         }
                         "verbose" => settings.verbose = Some(self.parse_bool(&k, v)?),
+                        "progress_log_file" => {
+                            settings.progress_log_file = Some(self.parse_path(&k, v)?)
+                        }
                         "asdf_compat" => settings.asdf_compat = Some(self.parse_bool(&k, v)?),
+                        "use_tool_versions" => {
+                            settings.use_tool_versions = Some(self.parse_bool(&k, v)?)
+                        }
                         "jobs" => settings.jobs = Some(self.parse_usize(&k, v)?),
+                        "keep_order" => settings.keep_order = Some(self.parse_bool(&k, v)?),
+                        "superproject_root" => {
+                            settings.superproject_root = Some(self.parse_bool(&k, v)?)
+                        }
+                        "warn_shadowed_bins" => {
+                            settings.warn_shadowed_bins = Some(self.parse_bool(&k, v)?)
+                        }
                         "shorthands_file" => {
                             settings.shorthands_file = Some(self.parse_path(&k, v)?)
                         }
                         "disable_default_shorthands" => {
                             settings.disable_default_shorthands = Some(self.parse_bool(&k, v)?)
                         }
+                        "plugin_default_org" => {
+                            settings.plugin_default_org = Some(self.parse_string(&k, v)?)
+                        }
+                        // these are shell commands, so a project file needs to be trusted
+                        // before it can run them on install
+                        "pre_install_hook" => {
+                            self.trust_check()?;
+                            settings.pre_install_hook = Some(self.parse_string(&k, v)?)
+                        }
+                        "post_install_hook" => {
+                            self.trust_check()?;
+                            settings.post_install_hook = Some(self.parse_string(&k, v)?)
+                        }
+                        "proxy" => settings.proxy = Some(self.parse_string(&k, v)?),
+                        "no_proxy" => {
+                            settings.no_proxy =
+                                Some(env::split_no_proxy(&self.parse_string(&k, v)?))
+                        }
                         "log_level" => settings.log_level = Some(self.parse_log_level(&k, v)?),
                         "raw" => settings.raw = Some(self.parse_bool(&k, v)?),
+                        "script_timeout" => {
+                            settings.script_timeout = Some(self.parse_duration_seconds(&k, v)?)
+                        }
+                        "lock_resolved_versions" => {
+                            settings.lock_resolved_versions = Some(self.parse_bool(&k, v)?)
+                        }
                         _ => Err(eyre!("Unknown config setting: {}", k))?,
                     };
                 }
@@ -424,6 +486,51 @@ impl RtxToml {
         Ok(settings)
     }
 
+    /// like `replace_versions` but keeps the options, a version with options is written
+    /// as an inline table: `{ version = "3.11", virtualenv = ".venv" }`
+    pub(crate) fn replace_requests(
+        &mut self,
+        plugin_name: &PluginName,
+        requests: &[(ToolVersionRequest, ToolVersionOptions)],
+    ) {
+        match self.toolset.versions.get_mut(plugin_name) {
+            Some(tvl) => tvl.requests = requests.to_vec(),
+            None => {
+                let source = ToolSource::RtxToml(self.path.clone());
+                let mut tvl = ToolVersionList::new(plugin_name.clone(), source);
+                tvl.requests = requests.to_vec();
+                self.toolset.versions.insert(plugin_name.clone(), tvl);
+            }
+        }
+        let tools = self
+            .doc
+            .entry("tools")
+            .or_insert_with(table)
+            .as_table_mut()
+            .unwrap();
+
+        let to_value = |(tvr, opts): &(ToolVersionRequest, ToolVersionOptions)| {
+            if opts.is_empty() {
+                return Value::from(tvr.version());
+            }
+            let mut t = InlineTable::new();
+            t.insert("version", tvr.version().into());
+            for (k, v) in opts {
+                t.insert(k, v.into());
+            }
+            Value::InlineTable(t)
+        };
+        if requests.len() == 1 {
+            tools.insert(plugin_name, value(to_value(&requests[0])));
+        } else {
+            let mut arr = Array::new();
+            for request in requests {
+                arr.push(to_value(request));
+            }
+            tools.insert(plugin_name, Item::Value(Value::Array(arr)));
+        }
+    }
+
     pub(crate) fn set_alias(&mut self, plugin: &str, from: &str, to: &str) {
         self.alias
             .entry(plugin.into())
@@ -465,6 +572,14 @@ impl RtxToml {
         }
     }
 
+    fn parse_duration_seconds(&mut self, k: &str, v: &Item) -> Result<Duration> {
+        match v.as_value() {
+            Some(Value::String(s)) => Ok(humantime::parse_duration(s.value())?),
+            Some(Value::Integer(i)) => Ok(Duration::from_secs(*i.value() as u64)),
+            _ => parse_error!(k, v, "duration")?,
+        }
+    }
+
     fn parse_bool(&mut self, k: &str, v: &Item) -> Result<bool> {
         match v.as_value().map(|v| v.as_bool()) {
             Some(Some(v)) => Ok(v),
@@ -530,8 +645,9 @@ impl RtxToml {
             "ignore" => Ok(MissingRuntimeBehavior::Ignore),
             "prompt" => Ok(MissingRuntimeBehavior::Prompt),
             "autoinstall" => Ok(MissingRuntimeBehavior::AutoInstall),
+            "autoinstall_prompt" => Ok(MissingRuntimeBehavior::AutoInstallPrompt),
             _ => Err(eyre!(
-                "expected {k} to be one of: 'warn', 'ignore', 'prompt', 'autoinstall'. Got: {v}"
+                "expected {k} to be one of: 'warn', 'ignore', 'prompt', 'autoinstall', 'autoinstall_prompt'. Got: {v}"
             )),
         }
     }
@@ -603,7 +719,7 @@ impl RtxToml {
     fn trust_check(&mut self) -> Result<()> {
         let default_cmd = String::new();
         let cmd = env::ARGS.get(1).unwrap_or(&default_cmd).as_str();
-        if self.is_trusted || cmd == "trust" || cmd == "completion" || cfg!(test) {
+        if self.is_trusted || cmd == "trust" || cmd == "completion" {
             return Ok(());
         }
         if cmd != "hook-env" {
@@ -636,7 +752,7 @@ impl ConfigFile for RtxToml {
         self.path.as_path()
     }
 
-    fn plugins(&self) -> HashMap<PluginName, String> {
+    fn plugins(&self) -> HashMap<PluginName, Vec<String>> {
         self.plugins.clone()
     }
 
@@ -665,33 +781,16 @@ impl ConfigFile for RtxToml {
     }
 
     fn replace_versions(&mut self, plugin_name: &PluginName, versions: &[String]) {
-        if let Some(plugin) = self.toolset.versions.get_mut(plugin_name) {
-            plugin.requests = versions
-                .iter()
-                .map(|s| {
-                    (
-                        ToolVersionRequest::new(plugin_name.clone(), s),
-                        Default::default(),
-                    )
-                })
-                .collect();
-        }
-        let tools = self
-            .doc
-            .entry("tools")
-            .or_insert_with(table)
-            .as_table_mut()
-            .unwrap();
-
-        if versions.len() == 1 {
-            tools.insert(plugin_name, value(versions[0].clone()));
-        } else {
-            let mut arr = Array::new();
-            for v in versions {
-                arr.push(v);
-            }
-            tools.insert(plugin_name, Item::Value(Value::Array(arr)));
-        }
+        let requests = versions
+            .iter()
+            .map(|s| {
+                (
+                    ToolVersionRequest::new(plugin_name.clone(), s),
+                    Default::default(),
+                )
+            })
+            .collect::<Vec<_>>();
+        self.replace_requests(plugin_name, &requests);
     }
 
     fn save(&self) -> Result<()> {
@@ -767,6 +866,56 @@ mod tests {
         assert_display_snapshot!(cf);
     }
 
+    #[test]
+    fn test_install_hook_untrusted() {
+        let toml = formatdoc! {r#"
+        [settings]
+        post_install_hook = "touch pwned"
+        "#};
+        let mut cf = RtxToml::init(PathBuf::from("/tmp/.rtx.toml").as_path(), false);
+        let err = cf.parse(&toml).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<crate::errors::Error>(),
+            Some(UntrustedConfig())
+        ));
+
+        let mut cf = RtxToml::init(PathBuf::from("/tmp/.rtx.toml").as_path(), true);
+        cf.parse(&toml).unwrap();
+        assert_eq!(
+            cf.settings().post_install_hook,
+            Some("touch pwned".to_string())
+        );
+    }
+
+    #[test]
+    fn test_plugin_mirrors() {
+        let mut cf = RtxToml::init(PathBuf::from("/tmp/.rtx.toml").as_path(), true);
+        cf.parse(&formatdoc! {r#"
+        [plugins]
+        node = "https://github.com/jdxcode/rtx-node"
+        python = ["https://github.com/jdxcode/rtx-python", "https://git.example.com/rtx-python"]
+        "#})
+            .unwrap();
+
+        assert_eq!(
+            cf.plugins()["python"],
+            vec![
+                "https://github.com/jdxcode/rtx-python",
+                "https://git.example.com/rtx-python"
+            ]
+        );
+        assert_eq!(
+            cf.plugins()["node"],
+            vec!["https://github.com/jdxcode/rtx-node"]
+        );
+
+        let mut cf = RtxToml::init(PathBuf::from("/tmp/.rtx.toml").as_path(), true);
+        let err = cf.parse("[plugins]\nnode = 1").unwrap_err();
+        assert!(err
+            .to_string()
+            .contains(r#""plugins.node" to be a string or array of strings"#));
+    }
+
     #[test]
     fn test_path_dirs() {
         let p = dirs::HOME.join("fixtures/.rtx.toml");
@@ -841,6 +990,29 @@ mod tests {
         assert_display_snapshot!(cf);
     }
 
+    #[test]
+    fn test_replace_requests() {
+        let mut cf = RtxToml::init(PathBuf::from("/tmp/.rtx.toml").as_path(), true);
+        let python = PluginName::from("python");
+        let opts = ToolVersionOptions::from([("virtualenv".into(), ".venv".into())]);
+        cf.replace_requests(
+            &python,
+            &[
+                (ToolVersionRequest::new(python.clone(), "3.11"), opts),
+                (
+                    ToolVersionRequest::new(python.clone(), "3.10"),
+                    Default::default(),
+                ),
+            ],
+        );
+
+        assert_eq!(cf.toolset.versions[&python].requests.len(), 2);
+        assert_snapshot!(cf.dump(), @r###"
+        [tools]
+        python = [{ version = "3.11", virtualenv = ".venv" }, "3.10"]
+        "###);
+    }
+
     #[test]
     fn test_remove_plugin() {
         let mut cf = RtxToml::init(PathBuf::from("/tmp/.rtx.toml").as_path(), true);
@@ -154,7 +154,7 @@ impl ConfigFile for ToolVersions {
         self.path.as_path()
     }
 
-    fn plugins(&self) -> HashMap<PluginName, String> {
+    fn plugins(&self) -> HashMap<PluginName, Vec<String>> {
         Default::default()
     }
 
@@ -167,7 +167,8 @@ impl ConfigFile for ToolVersions {
     }
 
     fn remove_plugin(&mut self, plugin: &PluginName) {
-        self.plugins.remove(plugin);
+        self.plugins.shift_remove(plugin);
+        self.toolset.versions.shift_remove(plugin);
     }
 
     fn replace_versions(&mut self, plugin_name: &PluginName, versions: &[String]) {
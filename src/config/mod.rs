@@ -1,6 +1,6 @@
 use std::collections::{BTreeMap, HashMap};
 use std::fmt::{Display, Formatter};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::thread;
 
@@ -19,7 +19,7 @@ use crate::config::tracking::Tracker;
 use crate::env::CI;
 use crate::plugins::core::{CORE_PLUGINS, EXPERIMENTAL_CORE_PLUGINS};
 use crate::plugins::{ExternalPlugin, Plugin, PluginName, PluginType};
-use crate::shorthands::{get_shorthands, Shorthands};
+use crate::shorthands::{get_org_repo_url, get_shorthands, Shorthands};
 use crate::tool::Tool;
 use crate::{cli, dirs, duration, env, file, hook_env};
 
@@ -45,15 +45,25 @@ pub struct Config {
     pub should_exit_early: bool,
     pub project_root: Option<PathBuf>,
     shorthands: OnceCell<HashMap<String, String>>,
-    repo_urls: HashMap<PluginName, String>,
+    repo_urls: HashMap<PluginName, Vec<String>>,
 }
 
 impl Config {
     pub fn load() -> Result<Self> {
+        Self::load_with_config_files(&env::CONFIG_FILE_ARGS)
+    }
+
+    /// with `explicit_files` (from `--config`) set, only those and the global config are read
+    fn load_with_config_files(explicit_files: &[PathBuf]) -> Result<Self> {
+        for f in explicit_files {
+            if !f.is_file() {
+                return Err(eyre!("config file not found: {}", file::display_path(f)));
+            }
+        }
         let global_config = load_rtxrc()?;
         let mut settings_b = global_config.settings();
         let settings = settings_b.build();
-        let config_filenames = load_config_filenames(&settings, &BTreeMap::new());
+        let config_filenames = load_config_filenames(&settings, &BTreeMap::new(), explicit_files);
         let tools = load_tools(&settings)?;
         let config_files = load_all_config_files(
             &settings_b.build(),
@@ -62,14 +72,14 @@ impl Config {
             &BTreeMap::new(),
             ConfigMap::new(),
         )?;
-        for cf in config_files.values() {
+        for cf in settings_merge_order(&config_files) {
             settings_b.merge(cf.settings());
         }
         let settings = settings_b.build();
         trace!("Settings: {:#?}", settings);
 
         let legacy_files = load_legacy_files(&settings, &tools);
-        let config_filenames = load_config_filenames(&settings, &legacy_files);
+        let config_filenames = load_config_filenames(&settings, &legacy_files, explicit_files);
         let config_track = track_config_files(&config_filenames);
 
         let config_files = load_all_config_files(
@@ -88,8 +98,8 @@ impl Config {
 
         let mut repo_urls = HashMap::new();
         for cf in config_files.values() {
-            for (plugin_name, repo_url) in cf.plugins() {
-                repo_urls.insert(plugin_name, repo_url);
+            for (plugin_name, urls) in cf.plugins() {
+                repo_urls.insert(plugin_name, urls);
             }
         }
         config_track.join().unwrap();
@@ -100,7 +110,7 @@ impl Config {
             aliases: load_aliases(&config_files),
             all_aliases: OnceCell::new(),
             shorthands: OnceCell::new(),
-            project_root: get_project_root(&config_files),
+            project_root: get_project_root(&settings, &config_files, &dirs::CURRENT),
             config_files,
             settings,
             legacy_files,
@@ -120,12 +130,22 @@ impl Config {
             .get_or_init(|| get_shorthands(&self.settings))
     }
 
-    pub fn get_repo_url(&self, plugin_name: &PluginName) -> Option<String> {
-        match self.repo_urls.get(plugin_name) {
-            Some(url) => Some(url),
-            None => self.get_shorthands().get(plugin_name),
+    /// the urls a plugin can be cloned from in the order to try them, empty if it has none.
+    /// With plugin_default_org the org's fork comes first and the shorthand is the fallback.
+    pub fn get_repo_urls(&self, plugin_name: &PluginName) -> Vec<String> {
+        if let Some(urls) = self.repo_urls.get(plugin_name) {
+            return urls.clone();
         }
-        .cloned()
+        get_org_repo_url(&self.settings, plugin_name)
+            .into_iter()
+            .chain(self.get_shorthands().get(plugin_name).cloned())
+            .collect()
+    }
+
+    /// whether rtx knows where `plugin_name` comes from. The plugin_default_org url doesn't count,
+    /// it is a guess that isn't checked until the plugin is installed.
+    pub fn has_repo_url(&self, plugin_name: &PluginName) -> bool {
+        self.repo_urls.contains_key(plugin_name) || self.get_shorthands().contains_key(plugin_name)
     }
 
     pub fn get_all_aliases(&self) -> &AliasMap {
@@ -231,7 +251,8 @@ impl Config {
         if *CI || !console::user_attended_stderr() || *env::RTX_HIDE_UPDATE_WARNING {
             return;
         }
-        if let Some(latest) = cli::version::check_for_new_version(duration::WEEKLY) {
+        if let Some(latest) = cli::version::check_for_new_version(&self.settings, duration::WEEKLY)
+        {
             warn!(
                 "newer rtx version {} available, currently on {}",
                 latest,
@@ -250,20 +271,27 @@ impl Config {
     }
 }
 
-fn get_project_root(config_files: &ConfigMap) -> Option<PathBuf> {
-    for (p, cf) in config_files.into_iter() {
-        if p == &get_global_rtx_toml() {
-            // ~/.config/rtx/config.toml is not a project config file
-            continue;
-        }
-        match cf.get_type() {
-            ConfigFileType::RtxToml | ConfigFileType::ToolVersions => {
-                return Some(p.parent()?.to_path_buf());
+fn get_project_root(settings: &Settings, config_files: &ConfigMap, cwd: &Path) -> Option<PathBuf> {
+    let roots = config_files
+        .into_iter()
+        // ~/.config/rtx/config.toml is not a project config file
+        .filter(|(p, _)| *p != &get_global_rtx_toml())
+        .filter(|(_, cf)| {
+            matches!(
+                cf.get_type(),
+                ConfigFileType::RtxToml | ConfigFileType::ToolVersions
+            )
+        })
+        .filter_map(|(p, _)| p.parent())
+        .collect_vec();
+    if settings.superproject_root {
+        if let Some(submodule_root) = file::find_submodule_root(cwd) {
+            if let Some(root) = roots.iter().find(|r| !r.starts_with(&submodule_root)) {
+                return Some(root.to_path_buf());
             }
-            _ => {}
         }
     }
-    None
+    roots.first().map(|r| r.to_path_buf())
 }
 
 fn load_rtxrc() -> Result<RtxToml> {
@@ -339,34 +367,89 @@ fn load_legacy_files(settings: &Settings, tools: &ToolMap) -> BTreeMap<String, P
 fn load_config_filenames(
     settings: &Settings,
     legacy_filenames: &BTreeMap<String, PluginName>,
+    explicit_files: &[PathBuf],
 ) -> Vec<PathBuf> {
+    if !explicit_files.is_empty() {
+        let global_files = global_config_filenames()
+            .into_iter()
+            .filter(|p| p.is_file());
+        return explicit_files
+            .iter()
+            .cloned()
+            .chain(global_files)
+            .unique()
+            .collect();
+    }
+    let filenames = config_filenames(settings, legacy_filenames);
+    let mut config_files = file::FindUp::new(&dirs::CURRENT, &filenames).collect::<Vec<_>>();
+    config_files.extend(
+        global_config_filenames()
+            .into_iter()
+            .filter(|p| p.is_file()),
+    );
+
+    config_files.into_iter().unique().collect()
+}
+
+/// the filenames searched for in each directory, the last one has the highest precedence
+fn config_filenames(
+    settings: &Settings,
+    legacy_filenames: &BTreeMap<String, PluginName>,
+) -> Vec<String> {
     let mut filenames = legacy_filenames.keys().cloned().collect_vec();
     filenames.push(env::RTX_DEFAULT_TOOL_VERSIONS_FILENAME.clone());
     filenames.push(env::RTX_DEFAULT_CONFIG_FILENAME.clone());
+    filenames.push(local_config_filename(&env::RTX_DEFAULT_CONFIG_FILENAME));
     if settings.experimental && *env::RTX_DEFAULT_CONFIG_FILENAME == ".rtx.toml" {
-        filenames.push(".rtx.local.toml".to_string());
         if let Some(env) = &*env::RTX_ENV {
             filenames.push(format!(".rtx.{}.toml", env));
             filenames.push(format!(".rtx.{}.local.toml", env));
         }
     }
+    filenames
+}
 
-    let mut config_files = file::FindUp::new(&dirs::CURRENT, &filenames).collect::<Vec<_>>();
+/// the order settings from config files are merged in, the last one wins. That is the order the
+/// files were found in, except a .rtx.local.toml comes right after the .rtx.toml beside it.
+fn settings_merge_order(config_files: &ConfigMap) -> Vec<&dyn ConfigFile> {
+    let filename = env::RTX_DEFAULT_CONFIG_FILENAME.as_str();
+    let local_filename = local_config_filename(filename);
+    let is_named = |p: &Path, name: &str| p.file_name().map_or(false, |f| f == name);
+    let mut order = vec![];
+    for (path, cf) in config_files {
+        if is_named(path, &local_filename)
+            && config_files.contains_key(&path.with_file_name(filename))
+        {
+            continue;
+        }
+        order.push(cf.as_ref());
+        if is_named(path, filename) {
+            if let Some(local) = config_files.get(&path.with_file_name(&local_filename)) {
+                order.push(local.as_ref());
+            }
+        }
+    }
+    order
+}
 
+/// ".rtx.toml" -> ".rtx.local.toml", for overrides that aren't meant to be committed
+fn local_config_filename(filename: &str) -> String {
+    match filename.strip_suffix(".toml") {
+        Some(stem) => format!("{stem}.local.toml"),
+        None => format!("{filename}.local"),
+    }
+}
+
+/// config files outside of the project directories, these are read if they exist
+pub fn global_config_filenames() -> Vec<PathBuf> {
+    let mut filenames = vec![];
     if env::RTX_CONFIG_FILE.is_none() && !*env::RTX_USE_TOML {
         // only add ~/.tool-versions if RTX_CONFIG_FILE is not set
         // because that's how the user overrides the default
-        let home_config = dirs::HOME.join(env::RTX_DEFAULT_TOOL_VERSIONS_FILENAME.as_str());
-        if home_config.is_file() {
-            config_files.push(home_config);
-        }
+        filenames.push(dirs::HOME.join(env::RTX_DEFAULT_TOOL_VERSIONS_FILENAME.as_str()));
     };
-    let global_config = get_global_rtx_toml();
-    if global_config.is_file() {
-        config_files.push(global_config);
-    }
-
-    config_files.into_iter().unique().collect()
+    filenames.push(get_global_rtx_toml());
+    filenames
 }
 
 fn get_global_rtx_toml() -> PathBuf {
@@ -496,8 +579,14 @@ impl Display for Config {
 
 #[cfg(test)]
 mod tests {
+    use std::fs;
+
     use insta::assert_display_snapshot;
 
+    use crate::config::config_file::tool_versions::ToolVersions;
+    use crate::file::remove_all;
+    use crate::toolset::{ToolSource, ToolsetBuilder};
+
     use super::*;
 
     #[test]
@@ -505,4 +594,136 @@ mod tests {
         let config = Config::load().unwrap();
         assert_display_snapshot!(config);
     }
+
+    #[test]
+    fn test_get_repo_urls_default_org() {
+        let mut config = Config::load().unwrap();
+        config.settings.plugin_default_org = Some("my-org".into());
+        let node = "node".to_string();
+        // the org's fork is tried first, installing falls back to the shorthand without it
+        assert_eq!(
+            config.get_repo_urls(&node),
+            vec![
+                "https://github.com/my-org/asdf-node.git".to_string(),
+                config.get_shorthands()["node"].clone(),
+            ]
+        );
+        assert!(config.has_repo_url(&node));
+        let unknown = "not-a-shorthand".to_string();
+        assert_eq!(config.get_repo_urls(&unknown).len(), 1);
+        assert!(!config.has_repo_url(&unknown));
+    }
+
+    #[test]
+    fn test_load_with_config_files() {
+        let explicit = dirs::HOME.join("fixtures/explicit.rtx.toml");
+        let mut config = Config::load_with_config_files(&[explicit.clone()]).unwrap();
+        // test/cwd/.test-tool-versions is not read, only the explicit file and global config
+        assert_eq!(
+            config.config_files.keys().collect_vec(),
+            vec![
+                &explicit,
+                &dirs::HOME.join(".test-tool-versions"),
+                &dirs::CONFIG.join("config.toml"),
+            ]
+        );
+        let ts = ToolsetBuilder::new().build(&mut config).unwrap();
+        let (tvr, _) = &ts.versions["tiny"].requests[0];
+        assert_eq!(tvr.to_string(), "tiny@1");
+
+        let err = Config::load_with_config_files(&[dirs::HOME.join("missing.toml")]).unwrap_err();
+        assert_eq!(err.to_string(), "config file not found: ~/missing.toml");
+    }
+
+    #[test]
+    fn test_local_config_file() {
+        let dir = dirs::CACHE.join("test-local-config-file");
+        let _ = remove_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        assert_eq!(local_config_filename(".rtx.toml"), ".rtx.local.toml");
+        let rtx_toml = dir.join(&*env::RTX_DEFAULT_CONFIG_FILENAME);
+        let local = dir.join(local_config_filename(&env::RTX_DEFAULT_CONFIG_FILENAME));
+        fs::write(
+            &rtx_toml,
+            "[settings]\nplugin_clone_depth = 2\nbuild_from_source = true\n[tools]\ntiny = \"2\"\ndummy = \"1\"\n",
+        )
+        .unwrap();
+        fs::write(
+            &local,
+            "[settings]\nplugin_clone_depth = 3\n[tools]\ntiny = \"1\"\n",
+        )
+        .unwrap();
+
+        // closer to the current directory, its settings are still overridden by the files above
+        let nested = dir.join("nested").join(&*env::RTX_DEFAULT_CONFIG_FILENAME);
+        fs::create_dir_all(nested.parent().unwrap()).unwrap();
+        fs::write(
+            &nested,
+            "[settings]\nplugin_clone_depth = 4\nbuild_from_source = false\n",
+        )
+        .unwrap();
+
+        let filenames = config_filenames(&Settings::default(), &BTreeMap::new());
+        let found = file::FindUp::new(nested.parent().unwrap(), &filenames)
+            .take(3)
+            .collect_vec();
+        assert_eq!(found, vec![nested, local.clone(), rtx_toml.clone()]);
+
+        let mut config = Config::load_with_config_files(&found).unwrap();
+        let ts = ToolsetBuilder::new().build(&mut config).unwrap();
+        let trusted = Settings {
+            trusted_config_paths: vec![rtx_toml.clone()],
+            ..Settings::default()
+        };
+        let local_trusted = config_file::is_trusted(&trusted, &local);
+        remove_all(&dir).unwrap();
+
+        assert_eq!(config.settings.plugin_clone_depth, 3);
+        assert!(config.settings.build_from_source);
+        assert_eq!(ts.versions["tiny"].source, ToolSource::RtxToml(local));
+        assert_eq!(ts.versions["dummy"].source, ToolSource::RtxToml(rtx_toml));
+        // trusting .rtx.toml does not trust the file that overrides it
+        assert!(!local_trusted);
+    }
+
+    #[test]
+    fn test_get_project_root_submodule() {
+        let superproject = dirs::CACHE.join("test-superproject");
+        let submodule = superproject.join("vendor/lib");
+        let nested = submodule.join("nested");
+        let _ = remove_all(&superproject);
+        fs::create_dir_all(superproject.join(".git")).unwrap();
+        fs::create_dir_all(nested.join("src")).unwrap();
+        fs::write(submodule.join(".git"), "gitdir: ../../.git/modules/lib\n").unwrap();
+        fs::write(
+            nested.join(".git"),
+            "gitdir: ../../../.git/modules/nested\n",
+        )
+        .unwrap();
+        let mut config_files = ConfigMap::new();
+        for dir in [&nested, &superproject] {
+            let path = dir.join(".tool-versions");
+            let cf: Box<dyn ConfigFile> = Box::new(ToolVersions::init(&path, true));
+            config_files.insert(path, cf);
+        }
+        let cwd = nested.join("src");
+
+        let mut settings = Settings::default();
+        assert_eq!(
+            get_project_root(&settings, &config_files, &cwd),
+            Some(nested.clone())
+        );
+        settings.superproject_root = true;
+        assert_eq!(
+            get_project_root(&settings, &config_files, &cwd),
+            Some(superproject.clone())
+        );
+        // vendor/ belongs to the superproject, the outermost submodule is returned for nested ones
+        assert_eq!(
+            file::find_submodule_root(&superproject.join("vendor")),
+            None
+        );
+        assert_eq!(file::find_submodule_root(&cwd), Some(submodule));
+        remove_all(&superproject).unwrap();
+    }
 }
@@ -1,7 +1,9 @@
 use std::fmt::{Debug, Display, Formatter};
 use std::path::PathBuf;
+use std::str::FromStr;
 use std::time::Duration;
 
+use color_eyre::eyre::{eyre, Error, Result};
 use indexmap::IndexMap;
 use log::LevelFilter;
 
@@ -14,16 +16,39 @@ pub struct Settings {
     pub missing_runtime_behavior: MissingRuntimeBehavior,
     pub always_keep_download: bool,
     pub always_keep_install: bool,
+    pub build_from_source: bool,
     pub legacy_version_file: bool,
     pub plugin_autoupdate_last_check_duration: Duration,
+    pub plugin_clone_depth: usize,
+    pub plugin_clone_single_branch: bool,
+    pub plugin_update_rollback: bool,
+    pub plugin_verify_url: bool,
     pub trusted_config_paths: Vec<PathBuf>,
     pub verbose: bool,
+    pub progress_log_file: Option<PathBuf>,
     pub asdf_compat: bool,
+    pub use_tool_versions: bool,
     pub jobs: usize,
+    pub keep_order: bool,
+    pub superproject_root: bool,
+    pub warn_shadowed_bins: bool,
     pub shorthands_file: Option<PathBuf>,
     pub disable_default_shorthands: bool,
+    pub plugin_default_org: Option<String>,
+    pub pre_install_hook: Option<String>,
+    pub post_install_hook: Option<String>,
+    pub proxy: Option<String>,
+    pub no_proxy: Vec<String>,
     pub log_level: LevelFilter,
     pub raw: bool,
+    pub script_timeout: Option<Duration>,
+    pub lock_resolved_versions: bool,
+    /// only remote data that is already cached is used, set with `rtx install --cache-only` or
+    /// RTX_CACHE_ONLY. It isn't a config file setting.
+    pub cache_only: bool,
+    /// progress is shown as one plain line per phase instead of the output of every script,
+    /// when stderr is not a tty and verbose output wasn't asked for. It isn't a config file setting.
+    pub plain_progress: bool,
 }
 
 impl Default for Settings {
@@ -33,16 +58,35 @@ impl Default for Settings {
             missing_runtime_behavior: MissingRuntimeBehavior::Warn,
             always_keep_download: *RTX_ALWAYS_KEEP_DOWNLOAD,
             always_keep_install: *RTX_ALWAYS_KEEP_INSTALL,
+            build_from_source: *RTX_BUILD_FROM_SOURCE,
             legacy_version_file: true,
             plugin_autoupdate_last_check_duration: Duration::from_secs(60 * 60 * 24 * 7),
+            plugin_clone_depth: 1,
+            plugin_clone_single_branch: false,
+            plugin_update_rollback: true,
+            plugin_verify_url: !*RTX_CACHE_ONLY,
             trusted_config_paths: RTX_TRUSTED_CONFIG_PATHS.clone(),
             verbose: *RTX_VERBOSE || !console::user_attended_stderr(),
+            progress_log_file: RTX_PROGRESS_LOG_FILE.clone(),
             asdf_compat: *RTX_ASDF_COMPAT,
+            use_tool_versions: *RTX_USE_TOOL_VERSIONS,
             jobs: *RTX_JOBS,
+            keep_order: *RTX_KEEP_ORDER,
+            superproject_root: *RTX_SUPERPROJECT_ROOT,
+            warn_shadowed_bins: *RTX_WARN_SHADOWED_BINS,
             shorthands_file: RTX_SHORTHANDS_FILE.clone(),
             disable_default_shorthands: *RTX_DISABLE_DEFAULT_SHORTHANDS,
+            plugin_default_org: RTX_PLUGIN_DEFAULT_ORG.clone(),
+            pre_install_hook: RTX_PRE_INSTALL_HOOK.clone(),
+            post_install_hook: RTX_POST_INSTALL_HOOK.clone(),
+            proxy: RTX_PROXY.clone(),
+            no_proxy: RTX_NO_PROXY.clone(),
             log_level: *RTX_LOG_LEVEL,
             raw: *RTX_RAW,
+            script_timeout: *RTX_SCRIPT_TIMEOUT,
+            lock_resolved_versions: *RTX_LOCK_RESOLVED_VERSIONS,
+            cache_only: *RTX_CACHE_ONLY,
+            plain_progress: !*RTX_VERBOSE && !console::user_attended_stderr(),
         }
     }
 }
@@ -63,6 +107,10 @@ impl Settings {
             "always_keep_install".to_string(),
             self.always_keep_install.to_string(),
         );
+        map.insert(
+            "build_from_source".to_string(),
+            self.build_from_source.to_string(),
+        );
         map.insert(
             "legacy_version_file".to_string(),
             self.legacy_version_file.to_string(),
@@ -71,13 +119,48 @@ impl Settings {
             "plugin_autoupdate_last_check_duration".to_string(),
             (self.plugin_autoupdate_last_check_duration.as_secs() / 60).to_string(),
         );
+        map.insert(
+            "plugin_clone_depth".to_string(),
+            self.plugin_clone_depth.to_string(),
+        );
+        map.insert(
+            "plugin_clone_single_branch".to_string(),
+            self.plugin_clone_single_branch.to_string(),
+        );
+        map.insert(
+            "plugin_update_rollback".to_string(),
+            self.plugin_update_rollback.to_string(),
+        );
+        map.insert(
+            "plugin_verify_url".to_string(),
+            self.plugin_verify_url.to_string(),
+        );
         map.insert(
             "trusted_config_paths".to_string(),
             format!("{:?}", self.trusted_config_paths),
         );
         map.insert("verbose".into(), self.verbose.to_string());
+        if let Some(progress_log_file) = &self.progress_log_file {
+            map.insert(
+                "progress_log_file".into(),
+                progress_log_file.to_string_lossy().to_string(),
+            );
+        }
         map.insert("asdf_compat".into(), self.asdf_compat.to_string());
+        map.insert(
+            "use_tool_versions".into(),
+            self.use_tool_versions.to_string(),
+        );
         map.insert("jobs".into(), self.jobs.to_string());
+        map.insert("keep_order".into(), self.keep_order.to_string());
+        map.insert(
+            "superproject_root".into(),
+            self.superproject_root.to_string(),
+        );
+        map.insert(
+            "warn_shadowed_bins".into(),
+            self.warn_shadowed_bins.to_string(),
+        );
         if let Some(shorthands_file) = &self.shorthands_file {
             map.insert(
                 "shorthands_file".into(),
@@ -88,8 +171,33 @@ impl Settings {
             "disable_default_shorthands".into(),
             self.disable_default_shorthands.to_string(),
         );
+        if let Some(org) = &self.plugin_default_org {
+            map.insert("plugin_default_org".into(), org.to_string());
+        }
+        if let Some(hook) = &self.pre_install_hook {
+            map.insert("pre_install_hook".into(), hook.to_string());
+        }
+        if let Some(hook) = &self.post_install_hook {
+            map.insert("post_install_hook".into(), hook.to_string());
+        }
+        if let Some(proxy) = &self.proxy {
+            map.insert("proxy".into(), proxy.to_string());
+        }
+        if !self.no_proxy.is_empty() {
+            map.insert("no_proxy".into(), self.no_proxy.join(","));
+        }
         map.insert("log_level".into(), self.log_level.to_string());
         map.insert("raw".into(), self.raw.to_string());
+        if let Some(timeout) = self.script_timeout {
+            map.insert(
+                "script_timeout".into(),
+                humantime::format_duration(timeout).to_string(),
+            );
+        }
+        map.insert(
+            "lock_resolved_versions".into(),
+            self.lock_resolved_versions.to_string(),
+        );
         map
     }
 }
@@ -100,16 +208,33 @@ pub struct SettingsBuilder {
     pub missing_runtime_behavior: Option<MissingRuntimeBehavior>,
     pub always_keep_download: Option<bool>,
     pub always_keep_install: Option<bool>,
+    pub build_from_source: Option<bool>,
     pub legacy_version_file: Option<bool>,
     pub plugin_autoupdate_last_check_duration: Option<Duration>,
+    pub plugin_clone_depth: Option<usize>,
+    pub plugin_clone_single_branch: Option<bool>,
+    pub plugin_update_rollback: Option<bool>,
+    pub plugin_verify_url: Option<bool>,
     pub trusted_config_paths: Vec<PathBuf>,
     pub verbose: Option<bool>,
+    pub progress_log_file: Option<PathBuf>,
     pub asdf_compat: Option<bool>,
+    pub use_tool_versions: Option<bool>,
     pub jobs: Option<usize>,
+    pub keep_order: Option<bool>,
+    pub superproject_root: Option<bool>,
+    pub warn_shadowed_bins: Option<bool>,
     pub shorthands_file: Option<PathBuf>,
     pub disable_default_shorthands: Option<bool>,
+    pub plugin_default_org: Option<String>,
+    pub pre_install_hook: Option<String>,
+    pub post_install_hook: Option<String>,
+    pub proxy: Option<String>,
+    pub no_proxy: Option<Vec<String>>,
     pub log_level: Option<LevelFilter>,
     pub raw: Option<bool>,
+    pub script_timeout: Option<Duration>,
+    pub lock_resolved_versions: Option<bool>,
 }
 
 impl SettingsBuilder {
@@ -132,6 +257,9 @@ impl SettingsBuilder {
         if other.always_keep_install.is_some() {
             self.always_keep_install = other.always_keep_install;
         }
+        if other.build_from_source.is_some() {
+            self.build_from_source = other.build_from_source;
+        }
         if other.legacy_version_file.is_some() {
             self.legacy_version_file = other.legacy_version_file;
         }
@@ -139,28 +267,76 @@ impl SettingsBuilder {
             self.plugin_autoupdate_last_check_duration =
                 other.plugin_autoupdate_last_check_duration;
         }
+        if other.plugin_clone_depth.is_some() {
+            self.plugin_clone_depth = other.plugin_clone_depth;
+        }
+        if other.plugin_clone_single_branch.is_some() {
+            self.plugin_clone_single_branch = other.plugin_clone_single_branch;
+        }
+        if other.plugin_update_rollback.is_some() {
+            self.plugin_update_rollback = other.plugin_update_rollback;
+        }
+        if other.plugin_verify_url.is_some() {
+            self.plugin_verify_url = other.plugin_verify_url;
+        }
         self.trusted_config_paths.extend(other.trusted_config_paths);
         if other.verbose.is_some() {
             self.verbose = other.verbose;
         }
+        if other.progress_log_file.is_some() {
+            self.progress_log_file = other.progress_log_file;
+        }
         if other.asdf_compat.is_some() {
             self.asdf_compat = other.asdf_compat;
         }
+        if other.use_tool_versions.is_some() {
+            self.use_tool_versions = other.use_tool_versions;
+        }
         if other.jobs.is_some() {
             self.jobs = other.jobs;
         }
+        if other.keep_order.is_some() {
+            self.keep_order = other.keep_order;
+        }
+        if other.superproject_root.is_some() {
+            self.superproject_root = other.superproject_root;
+        }
+        if other.warn_shadowed_bins.is_some() {
+            self.warn_shadowed_bins = other.warn_shadowed_bins;
+        }
         if other.shorthands_file.is_some() {
             self.shorthands_file = other.shorthands_file;
         }
         if other.disable_default_shorthands.is_some() {
             self.disable_default_shorthands = other.disable_default_shorthands;
         }
+        if other.plugin_default_org.is_some() {
+            self.plugin_default_org = other.plugin_default_org;
+        }
+        if other.pre_install_hook.is_some() {
+            self.pre_install_hook = other.pre_install_hook;
+        }
+        if other.post_install_hook.is_some() {
+            self.post_install_hook = other.post_install_hook;
+        }
+        if other.proxy.is_some() {
+            self.proxy = other.proxy;
+        }
+        if other.no_proxy.is_some() {
+            self.no_proxy = other.no_proxy;
+        }
         if other.log_level.is_some() {
             self.log_level = other.log_level;
         }
         if other.raw.is_some() {
             self.raw = other.raw;
         }
+        if other.script_timeout.is_some() {
+            self.script_timeout = other.script_timeout;
+        }
+        if other.lock_resolved_versions.is_some() {
+            self.lock_resolved_versions = other.lock_resolved_versions;
+        }
         self
     }
 
@@ -168,15 +344,11 @@ impl SettingsBuilder {
         let mut settings = Settings::default();
         settings.experimental = self.experimental.unwrap_or(settings.experimental);
         settings.missing_runtime_behavior = match env::RTX_MISSING_RUNTIME_BEHAVIOR
-            .to_owned()
-            .unwrap_or_default()
-            .as_ref()
+            .as_deref()
+            .and_then(|v| v.parse().ok())
         {
-            "autoinstall" => MissingRuntimeBehavior::AutoInstall,
-            "warn" => MissingRuntimeBehavior::Warn,
-            "ignore" => MissingRuntimeBehavior::Ignore,
-            "prompt" => MissingRuntimeBehavior::Prompt,
-            _ => self
+            Some(behavior) => behavior,
+            None => self
                 .missing_runtime_behavior
                 .clone()
                 .unwrap_or(settings.missing_runtime_behavior),
@@ -187,27 +359,73 @@ impl SettingsBuilder {
         settings.always_keep_install = self
             .always_keep_install
             .unwrap_or(settings.always_keep_install);
+        settings.build_from_source = self.build_from_source.unwrap_or(settings.build_from_source);
         settings.legacy_version_file = self
             .legacy_version_file
             .unwrap_or(settings.legacy_version_file);
         settings.plugin_autoupdate_last_check_duration = self
             .plugin_autoupdate_last_check_duration
             .unwrap_or(settings.plugin_autoupdate_last_check_duration);
+        settings.plugin_clone_depth = self
+            .plugin_clone_depth
+            .unwrap_or(settings.plugin_clone_depth);
+        settings.plugin_clone_single_branch = self
+            .plugin_clone_single_branch
+            .unwrap_or(settings.plugin_clone_single_branch);
+        settings.plugin_update_rollback = self
+            .plugin_update_rollback
+            .unwrap_or(settings.plugin_update_rollback);
+        settings.plugin_verify_url = self.plugin_verify_url.unwrap_or(settings.plugin_verify_url);
         settings
             .trusted_config_paths
             .extend(self.trusted_config_paths.clone());
         settings.verbose = self.verbose.unwrap_or(settings.verbose);
+        settings.progress_log_file = self
+            .progress_log_file
+            .clone()
+            .or(settings.progress_log_file);
         settings.asdf_compat = self.asdf_compat.unwrap_or(settings.asdf_compat);
+        settings.use_tool_versions = self.use_tool_versions.unwrap_or(settings.use_tool_versions);
         settings.jobs = self.jobs.unwrap_or(settings.jobs);
+        settings.keep_order = self.keep_order.unwrap_or(settings.keep_order);
+        settings.superproject_root = self.superproject_root.unwrap_or(settings.superproject_root);
+        settings.warn_shadowed_bins = self
+            .warn_shadowed_bins
+            .unwrap_or(settings.warn_shadowed_bins);
         settings.shorthands_file = self.shorthands_file.clone().or(settings.shorthands_file);
         settings.disable_default_shorthands = self
             .disable_default_shorthands
             .unwrap_or(settings.disable_default_shorthands);
+        settings.plugin_default_org = self
+            .plugin_default_org
+            .clone()
+            .or(settings.plugin_default_org);
+        settings.pre_install_hook = self.pre_install_hook.clone().or(settings.pre_install_hook);
+        settings.post_install_hook = self
+            .post_install_hook
+            .clone()
+            .or(settings.post_install_hook);
+        settings.proxy = self.proxy.clone().or(settings.proxy);
+        settings.no_proxy = self.no_proxy.clone().unwrap_or(settings.no_proxy);
         settings.log_level = self.log_level.unwrap_or(settings.log_level);
         settings.raw = self.raw.unwrap_or(settings.raw);
+        settings.script_timeout = self.script_timeout.or(settings.script_timeout);
+        if settings.script_timeout == Some(Duration::ZERO) {
+            settings.script_timeout = None;
+        }
+        settings.lock_resolved_versions = self
+            .lock_resolved_versions
+            .unwrap_or(settings.lock_resolved_versions);
 
+        if self.verbose == Some(true) {
+            settings.plain_progress = false;
+        }
         if settings.raw {
             settings.verbose = true;
+            settings.plain_progress = false;
+            settings.jobs = 1;
+        }
+        if settings.keep_order {
             settings.jobs = 1;
         }
 
@@ -215,6 +433,30 @@ impl SettingsBuilder {
     }
 }
 
+impl Settings {
+    /// the proxy to use for `url` unless its host is excluded by `no_proxy`
+    pub fn proxy_for(&self, url: &str) -> Option<&str> {
+        let proxy = self.proxy.as_deref()?;
+        let host = url_host(url);
+        let excluded = self.no_proxy.iter().any(|np| {
+            let np = np.trim_start_matches('.');
+            np == "*" || host == np || host.ends_with(&format!(".{np}"))
+        });
+        match excluded {
+            true => None,
+            false => Some(proxy),
+        }
+    }
+}
+
+/// host of a url, also handles scp-like git urls such as "git@github.com:jdxcode/rtx"
+fn url_host(url: &str) -> &str {
+    let url = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let url = url.split(['/', '?']).next().unwrap_or_default();
+    let url = url.rsplit_once('@').map_or(url, |(_, host)| host);
+    url.split(':').next().unwrap_or_default()
+}
+
 impl Display for Settings {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         self.to_index_map().fmt(f)
@@ -224,6 +466,8 @@ impl Display for Settings {
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub enum MissingRuntimeBehavior {
     AutoInstall,
+    /// asks once for each tool whether to install it
+    AutoInstallPrompt,
     Prompt,
     Warn,
     Ignore,
@@ -233,6 +477,7 @@ impl Display for MissingRuntimeBehavior {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
             MissingRuntimeBehavior::AutoInstall => write!(f, "autoinstall"),
+            MissingRuntimeBehavior::AutoInstallPrompt => write!(f, "autoinstall_prompt"),
             MissingRuntimeBehavior::Prompt => write!(f, "prompt"),
             MissingRuntimeBehavior::Warn => write!(f, "warn"),
             MissingRuntimeBehavior::Ignore => write!(f, "ignore"),
@@ -240,10 +485,29 @@ impl Display for MissingRuntimeBehavior {
     }
 }
 
+impl FromStr for MissingRuntimeBehavior {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "autoinstall" => Ok(MissingRuntimeBehavior::AutoInstall),
+            "autoinstall_prompt" => Ok(MissingRuntimeBehavior::AutoInstallPrompt),
+            "prompt" => Ok(MissingRuntimeBehavior::Prompt),
+            "warn" => Ok(MissingRuntimeBehavior::Warn),
+            "ignore" => Ok(MissingRuntimeBehavior::Ignore),
+            _ => Err(eyre!(
+                "expected one of: 'autoinstall', 'autoinstall_prompt', 'prompt', 'warn', 'ignore'. Got: {s}"
+            )),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::config::settings::MissingRuntimeBehavior::{AutoInstall, Ignore, Prompt, Warn};
+    use crate::config::settings::MissingRuntimeBehavior::{
+        AutoInstall, AutoInstallPrompt, Ignore, Prompt, Warn,
+    };
 
     #[test]
     fn test_settings_merge() {
@@ -257,11 +521,65 @@ mod tests {
         assert_eq!(s1.missing_runtime_behavior, Some(AutoInstall));
     }
 
+    #[test]
+    fn test_raw_forces_one_job() {
+        let settings = SettingsBuilder {
+            jobs: Some(16),
+            raw: Some(true),
+            ..SettingsBuilder::default()
+        }
+        .build();
+        assert_eq!(settings.jobs, 1);
+    }
+
+    #[test]
+    fn test_proxy_for() {
+        let settings = Settings {
+            proxy: Some("http://proxy.corp:3128".into()),
+            no_proxy: vec!["localhost".into(), ".internal.corp".into()],
+            ..Settings::default()
+        };
+        let proxy = Some("http://proxy.corp:3128");
+        assert_eq!(settings.proxy_for("https://github.com/jdxcode/rtx"), proxy);
+        assert_eq!(settings.proxy_for("git@github.com:jdxcode/rtx.git"), proxy);
+        assert_eq!(settings.proxy_for("http://localhost:8080/x"), None);
+        assert_eq!(settings.proxy_for("https://git.internal.corp/rtx"), None);
+        assert_eq!(settings.proxy_for("ssh://git@internal.corp/rtx"), None);
+        assert_eq!(settings.proxy_for("https://notinternal.corp/rtx"), proxy);
+
+        let settings = Settings {
+            proxy: None,
+            ..settings
+        };
+        assert_eq!(settings.proxy_for("https://github.com/jdxcode/rtx"), None);
+    }
+
     #[test]
     fn test_missing_runtime_behavior_display() {
         assert_eq!(AutoInstall.to_string(), "autoinstall");
+        assert_eq!(AutoInstallPrompt.to_string(), "autoinstall_prompt");
         assert_eq!(Prompt.to_string(), "prompt");
         assert_eq!(Warn.to_string(), "warn");
         assert_eq!(Ignore.to_string(), "ignore");
     }
+
+    #[test]
+    fn test_missing_runtime_behavior_from_str() {
+        assert_eq!(
+            "autoinstall".parse::<MissingRuntimeBehavior>().unwrap(),
+            AutoInstall
+        );
+        assert_eq!("Prompt".parse::<MissingRuntimeBehavior>().unwrap(), Prompt);
+        assert_eq!(
+            "autoinstall_prompt"
+                .parse::<MissingRuntimeBehavior>()
+                .unwrap(),
+            AutoInstallPrompt
+        );
+        let err = "sometimes".parse::<MissingRuntimeBehavior>().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "expected one of: 'autoinstall', 'autoinstall_prompt', 'prompt', 'warn', 'ignore'. Got: sometimes"
+        );
+    }
 }
@@ -1,12 +1,15 @@
 use std::collections::{HashMap, HashSet};
 pub use std::env::*;
+use std::fmt::Display;
 use std::path::PathBuf;
+use std::time::Duration;
 
 use itertools::Itertools;
 use log::LevelFilter;
 use once_cell::sync::Lazy;
 
 use crate::env_diff::{EnvDiff, EnvDiffOperation, EnvDiffPatches};
+use crate::file;
 use crate::file::replace_path;
 
 pub static ARGS: Lazy<Vec<String>> = Lazy::new(|| args().collect());
@@ -22,12 +25,16 @@ pub static XDG_DATA_HOME: Lazy<PathBuf> =
     Lazy::new(|| var_path("XDG_DATA_HOME").unwrap_or_else(|| HOME.join(".local/share")));
 pub static XDG_CONFIG_HOME: Lazy<PathBuf> =
     Lazy::new(|| var_path("XDG_CONFIG_HOME").unwrap_or_else(|| HOME.join(".config")));
+pub static XDG_STATE_HOME: Lazy<PathBuf> =
+    Lazy::new(|| var_path("XDG_STATE_HOME").unwrap_or_else(|| HOME.join(".local/state")));
 pub static RTX_CACHE_DIR: Lazy<PathBuf> =
     Lazy::new(|| var_path("RTX_CACHE_DIR").unwrap_or_else(|| XDG_CACHE_HOME.join("rtx")));
 pub static RTX_CONFIG_DIR: Lazy<PathBuf> =
     Lazy::new(|| var_path("RTX_CONFIG_DIR").unwrap_or_else(|| XDG_CONFIG_HOME.join("rtx")));
 pub static RTX_DATA_DIR: Lazy<PathBuf> =
     Lazy::new(|| var_path("RTX_DATA_DIR").unwrap_or_else(|| XDG_DATA_HOME.join("rtx")));
+pub static RTX_STATE_DIR: Lazy<PathBuf> =
+    Lazy::new(|| var_path("RTX_STATE_DIR").unwrap_or_else(|| XDG_STATE_HOME.join("rtx")));
 pub static RTX_TMP_DIR: Lazy<PathBuf> = Lazy::new(|| temp_dir().join("rtx"));
 
 pub static RTX_DEFAULT_TOOL_VERSIONS_FILENAME: Lazy<String> = Lazy::new(|| {
@@ -38,6 +45,7 @@ pub static RTX_DEFAULT_CONFIG_FILENAME: Lazy<String> =
 pub static RTX_ENV: Lazy<Option<String>> = Lazy::new(|| var("RTX_ENV").ok());
 pub static RTX_CONFIG_FILE: Lazy<Option<PathBuf>> = Lazy::new(|| var_path("RTX_CONFIG_FILE"));
 pub static RTX_USE_TOML: Lazy<bool> = Lazy::new(|| var_is_true("RTX_USE_TOML"));
+pub static RTX_USE_TOOL_VERSIONS: Lazy<bool> = Lazy::new(|| var_is_true("RTX_USE_TOOL_VERSIONS"));
 pub static RTX_EXE: Lazy<PathBuf> = Lazy::new(|| current_exe().unwrap_or_else(|_| "rtx".into()));
 pub static RTX_LOG_LEVEL: Lazy<LevelFilter> = Lazy::new(log_level);
 pub static RTX_LOG_FILE_LEVEL: Lazy<LevelFilter> = Lazy::new(log_file_level);
@@ -52,8 +60,11 @@ pub static RTX_JOBS: Lazy<usize> = Lazy::new(|| {
     var("RTX_JOBS")
         .ok()
         .and_then(|v| v.parse::<usize>().ok())
-        .unwrap_or(4)
+        .unwrap_or_else(|| default_jobs(num_cpus::get()))
 });
+pub static RTX_KEEP_ORDER: Lazy<bool> = Lazy::new(|| var_is_true("RTX_KEEP_ORDER"));
+pub static RTX_SUPERPROJECT_ROOT: Lazy<bool> = Lazy::new(|| var_is_true("RTX_SUPERPROJECT_ROOT"));
+pub static RTX_WARN_SHADOWED_BINS: Lazy<bool> = Lazy::new(|| var_is_true("RTX_WARN_SHADOWED_BINS"));
 
 /// true if inside a script like bin/exec-env or bin/install
 /// used to prevent infinite loops
@@ -61,6 +72,11 @@ pub static __RTX_SCRIPT: Lazy<bool> = Lazy::new(|| var_is_true("__RTX_SCRIPT"));
 pub static __RTX_DIFF: Lazy<EnvDiff> = Lazy::new(get_env_diff);
 pub static CI: Lazy<bool> = Lazy::new(|| var_is_true("CI"));
 pub static PREFER_STALE: Lazy<bool> = Lazy::new(|| prefer_stale(&ARGS));
+/// files passed with `--config`, these replace the config files found in the current directory
+/// and its parents. It needs to be known before the config is loaded so it can't wait for clap.
+pub static CONFIG_FILE_ARGS: Lazy<Vec<PathBuf>> = Lazy::new(|| config_file_args(&ARGS));
+/// true if remote data (e.g.: version lists) must only be read from the cache
+pub static RTX_CACHE_ONLY: Lazy<bool> = Lazy::new(|| var_is_true("RTX_CACHE_ONLY"));
 
 /// essentially, this is whether we show spinners or build output on runtime install
 pub static PRISTINE_ENV: Lazy<HashMap<String, String>> =
@@ -77,6 +93,8 @@ pub static RTX_HIDE_UPDATE_WARNING: Lazy<bool> =
 pub static RTX_ASDF_COMPAT: Lazy<bool> = Lazy::new(|| var_is_true("RTX_ASDF_COMPAT"));
 pub static RTX_SHORTHANDS_FILE: Lazy<Option<PathBuf>> =
     Lazy::new(|| var_path("RTX_SHORTHANDS_FILE"));
+pub static RTX_PROGRESS_LOG_FILE: Lazy<Option<PathBuf>> =
+    Lazy::new(|| var_path("RTX_PROGRESS_LOG_FILE"));
 pub static RTX_DISABLE_DEFAULT_SHORTHANDS: Lazy<bool> =
     Lazy::new(|| var_is_true("RTX_DISABLE_DEFAULT_SHORTHANDS"));
 pub static RTX_RAW: Lazy<bool> = Lazy::new(|| var_is_true("RTX_RAW"));
@@ -89,6 +107,36 @@ pub static RTX_ALWAYS_KEEP_DOWNLOAD: Lazy<bool> =
     Lazy::new(|| var_is_true("RTX_ALWAYS_KEEP_DOWNLOAD"));
 pub static RTX_ALWAYS_KEEP_INSTALL: Lazy<bool> =
     Lazy::new(|| var_is_true("RTX_ALWAYS_KEEP_INSTALL"));
+pub static RTX_BUILD_FROM_SOURCE: Lazy<bool> = Lazy::new(|| var_is_true("RTX_BUILD_FROM_SOURCE"));
+pub static RTX_INSTALL_TIMEOUT: Lazy<Option<Duration>> =
+    Lazy::new(|| var_parse("RTX_INSTALL_TIMEOUT", humantime::parse_duration));
+/// seconds, or a duration like "90s", after which plugin scripts other than install are killed
+pub static RTX_SCRIPT_TIMEOUT: Lazy<Option<Duration>> =
+    Lazy::new(|| var_parse("RTX_SCRIPT_TIMEOUT", parse_seconds));
+/// remember what loose versions in config files like `node 18` resolved to once installed
+pub static RTX_LOCK_RESOLVED_VERSIONS: Lazy<bool> =
+    Lazy::new(|| var_is_true("RTX_LOCK_RESOLVED_VERSIONS"));
+/// how long to wait on a remote host before giving up, e.g.: "30s"
+pub static RTX_HTTP_TIMEOUT: Lazy<Duration> = Lazy::new(|| {
+    var_parse("RTX_HTTP_TIMEOUT", humantime::parse_duration).unwrap_or(Duration::from_secs(30))
+});
+pub static RTX_PROXY: Lazy<Option<String>> = Lazy::new(|| var("RTX_PROXY").ok());
+pub static RTX_PLUGIN_DEFAULT_ORG: Lazy<Option<String>> =
+    Lazy::new(|| var("RTX_PLUGIN_DEFAULT_ORG").ok());
+pub static RTX_PRE_INSTALL_HOOK: Lazy<Option<String>> =
+    Lazy::new(|| var("RTX_PRE_INSTALL_HOOK").ok());
+pub static RTX_POST_INSTALL_HOOK: Lazy<Option<String>> =
+    Lazy::new(|| var("RTX_POST_INSTALL_HOOK").ok());
+pub static RTX_NO_PROXY: Lazy<Vec<String>> = Lazy::new(|| {
+    var("RTX_NO_PROXY")
+        .or_else(|_| var("NO_PROXY"))
+        .or_else(|_| var("no_proxy"))
+        .map(|v| split_no_proxy(&v))
+        .unwrap_or_default()
+});
+/// refuse to install if the installs directory has less free space than this, e.g.: "2G"
+pub static RTX_MIN_FREE_DISK: Lazy<Option<u64>> =
+    Lazy::new(|| var_parse("RTX_MIN_FREE_DISK", file::parse_size));
 
 #[allow(unused)]
 pub static GITHUB_API_TOKEN: Lazy<Option<String>> = Lazy::new(|| var("GITHUB_API_TOKEN").ok());
@@ -162,6 +210,34 @@ fn var_is_false(key: &str) -> bool {
     }
 }
 
+/// "localhost, .corp.example.com" -> ["localhost", ".corp.example.com"]
+pub fn split_no_proxy(v: &str) -> Vec<String> {
+    v.split(',')
+        .map(|h| h.trim().to_string())
+        .filter(|h| !h.is_empty())
+        .collect()
+}
+
+/// a value that can't be parsed is ignored with a warning
+fn var_parse<T, E: Display>(key: &str, parse: impl FnOnce(&str) -> Result<T, E>) -> Option<T> {
+    let v = var(key).ok()?;
+    match parse(&v) {
+        Ok(parsed) => Some(parsed),
+        Err(err) => {
+            warn!("ignoring invalid {}={}: {}", key, v, err);
+            None
+        }
+    }
+}
+
+/// a number of seconds, or a duration like "90s"
+fn parse_seconds(v: &str) -> Result<Duration, humantime::DurationError> {
+    match v.parse() {
+        Ok(secs) => Ok(Duration::from_secs(secs)),
+        Err(_) => humantime::parse_duration(v),
+    }
+}
+
 fn var_path(key: &str) -> Option<PathBuf> {
     var_os(key).map(PathBuf::from).map(replace_path)
 }
@@ -237,6 +313,38 @@ fn prefer_stale(args: &[String]) -> bool {
     false
 }
 
+/// relative paths are from the current directory, the last file given takes precedence so it
+/// comes first like the closest config file does when searching up.
+/// Only rtx's own args before the subcommand are read, a shim like `webpack --config x.js` runs
+/// through here too and its --config is not for rtx.
+fn config_file_args(args: &[String]) -> Vec<PathBuf> {
+    // the global args that take a value as the next arg
+    const WITH_VALUE: [&str; 4] = ["--jobs", "-j", "--log-level", "--missing"];
+    let mut files = vec![];
+    let (_, bin_name) = match args.first() {
+        Some(arg0) => arg0.rsplit_once('/').unwrap_or(("", arg0)),
+        None => return files,
+    };
+    if bin_name != "rtx" {
+        return files;
+    }
+    let mut args = args.iter().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.split_once('=') {
+            Some(("--config", file)) => files.push(PWD.join(file)),
+            _ if arg == "--config" => files.extend(args.next().map(|f| PWD.join(f))),
+            _ if WITH_VALUE.contains(&arg.as_str()) => {
+                args.next();
+            }
+            _ if arg.starts_with('-') => {}
+            // the subcommand
+            _ => break,
+        }
+    }
+    files.reverse();
+    files
+}
+
 fn log_level() -> LevelFilter {
     for (i, arg) in ARGS.iter().enumerate() {
         if arg == "--" {
@@ -282,6 +390,12 @@ fn log_file_level() -> LevelFilter {
     }
 }
 
+/// one job per cpu, but no more than 8 since installs also wait on the network and disk
+/// and some plugins compile with all cores already
+fn default_jobs(cpus: usize) -> usize {
+    cpus.clamp(1, 8)
+}
+
 #[cfg(test)]
 mod tests {
     use std::collections::HashMap;
@@ -307,6 +421,79 @@ mod tests {
         assert_eq!(new_env.get("baz").unwrap(), "qux");
     }
 
+    #[test]
+    fn test_var_parse() {
+        set_var("RTX_TEST_VAR_PARSE", "abc");
+        assert_eq!(
+            var_parse("RTX_TEST_VAR_PARSE", humantime::parse_duration),
+            None
+        );
+        set_var("RTX_TEST_VAR_PARSE", "90s");
+        assert_eq!(
+            var_parse("RTX_TEST_VAR_PARSE", humantime::parse_duration),
+            Some(Duration::from_secs(90))
+        );
+        remove_var("RTX_TEST_VAR_PARSE");
+        assert_eq!(
+            var_parse("RTX_TEST_VAR_PARSE", humantime::parse_duration),
+            None
+        );
+    }
+
+    #[test]
+    fn test_parse_seconds() {
+        assert_eq!(parse_seconds("90").unwrap(), Duration::from_secs(90));
+        assert_eq!(parse_seconds("2m").unwrap(), Duration::from_secs(120));
+        assert!(parse_seconds("abc").is_err());
+    }
+
+    #[test]
+    fn test_default_jobs() {
+        assert_eq!(default_jobs(0), 1);
+        assert_eq!(default_jobs(2), 2);
+        assert_eq!(default_jobs(64), 8);
+        // RTX_JOBS isn't set when testing
+        assert_eq!(*RTX_JOBS, default_jobs(num_cpus::get()));
+    }
+
+    #[test]
+    fn test_config_file_args() {
+        let args =
+            |args: &[&str]| config_file_args(&args.iter().map(|a| a.to_string()).collect_vec());
+        assert_eq!(
+            args(&[
+                "rtx",
+                "--config",
+                "ci.toml",
+                "-j",
+                "4",
+                "--config=/tmp/override.toml",
+                "ls"
+            ]),
+            vec![PathBuf::from("/tmp/override.toml"), PWD.join("ci.toml")]
+        );
+        assert_eq!(
+            args(&[
+                "/usr/bin/rtx",
+                "--log-level",
+                "debug",
+                "--config",
+                "ci.toml",
+                "ls"
+            ]),
+            vec![PWD.join("ci.toml")]
+        );
+        // these are for the subcommand or the tool being run
+        let none: Vec<PathBuf> = vec![];
+        assert_eq!(
+            args(&["rtx", "exec", "--", "webpack", "--config", "x.js"]),
+            none
+        );
+        assert_eq!(args(&["rtx", "x", "node", "--config", "x.js"]), none);
+        assert_eq!(args(&["webpack", "--config", "x.js"]), none);
+        assert_eq!(args(&["/shims/eslint", "--config", ".eslintrc"]), none);
+    }
+
     #[test]
     fn test_var_path() {
         set_var("RTX_TEST_PATH", "/foo/bar");
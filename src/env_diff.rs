@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::ffi::OsString;
 use std::fmt::Debug;
 use std::io::prelude::*;
@@ -83,10 +83,12 @@ impl EnvDiff {
             .collect();
 
         let mut additions = HashMap::new();
+        let mut exported = HashSet::new();
         let mut cur_key = None;
         for line in out.lines() {
             match line.strip_prefix("declare -x ") {
                 Some(line) => {
+                    exported.insert(line.split('=').next().unwrap_or(line).to_string());
                     let (k, v) = line.split_once('=').unwrap_or_default();
                     if valid_key(k) {
                         continue;
@@ -112,7 +114,15 @@ impl EnvDiff {
             }
             additions.insert(k.into(), v);
         }
-        Ok(Self::new(&env, additions))
+        let mut diff = Self::new(&env, additions);
+        // vars the script unset are missing from `export -p`. bash doesn't import vars
+        // that aren't valid identifiers so those can't have been unset by it.
+        for (k, v) in env.iter() {
+            if !exported.contains(k) && is_identifier(k) && !valid_key(k) {
+                diff.old.insert(k.clone(), v.clone());
+            }
+        }
+        Ok(diff)
     }
 
     pub fn deserialize(raw: &str) -> Result<EnvDiff> {
@@ -158,6 +168,11 @@ impl EnvDiff {
     }
 }
 
+fn is_identifier(k: &str) -> bool {
+    k.starts_with(|c: char| c == '_' || c.is_ascii_alphabetic())
+        && k.chars().all(|c| c == '_' || c.is_ascii_alphanumeric())
+}
+
 fn valid_key(k: &str) -> bool {
     k.is_empty()
         || k == "_"
@@ -255,6 +270,8 @@ mod tests {
     use insta::assert_debug_snapshot;
     use pretty_assertions::assert_str_eq;
 
+    use std::fs;
+
     use crate::dirs;
 
     use super::*;
@@ -355,6 +372,23 @@ mod tests {
         assert_debug_snapshot!(ed);
     }
 
+    #[test]
+    fn test_from_bash_script_unset() {
+        let path = dirs::CACHE.join("test-env-diff-unset.sh");
+        fs::write(&path, "unset REMOVED_VAR 'INVALID-NAME'\n").unwrap();
+        let orig = [
+            ("REMOVED_VAR", "1"),
+            ("KEPT_VAR", "2"),
+            ("INVALID-NAME", "3"),
+        ]
+        .map(|(k, v)| (k.to_string(), v.to_string()));
+        let ed = EnvDiff::from_bash_script(&path, orig).unwrap();
+        fs::remove_file(&path).unwrap();
+        // bash never sees INVALID-NAME so it isn't treated as unset
+        assert_eq!(ed.old, HashMap::from([("REMOVED_VAR".into(), "1".into())]));
+        assert!(!ed.new.contains_key("REMOVED_VAR"));
+    }
+
     #[test]
     fn test_invalid_escape_sequence() {
         let input = r#""\g""#;
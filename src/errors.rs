@@ -1,4 +1,5 @@
 use std::process::ExitStatus;
+use std::time::Duration;
 
 use thiserror::Error;
 
@@ -15,8 +16,13 @@ pub enum Error {
     VersionNotFound(PluginName, String),
     #[error("{} exited with non-zero status: {}", .0, render_exit_status(.1))]
     ScriptFailed(String, Option<ExitStatus>),
+    #[error("{} timed out after {}", .0, humantime::format_duration(*.1))]
+    ScriptTimedOut(String, Duration),
     #[error("Config file is not trusted.\nTrust it with `rtx trust`.")]
     UntrustedConfig(),
+    /// exits with the status without printing anything, for commands used in conditionals
+    #[error("exited with status {0}")]
+    Exit(i32),
 }
 
 fn render_exit_status(exit_status: &Option<ExitStatus>) -> String {
@@ -2,7 +2,7 @@ use std::path::{Path, PathBuf};
 use std::time::Duration;
 use std::{fs, io};
 
-use color_eyre::eyre::Result;
+use color_eyre::eyre::{eyre, Result};
 use filetime::{set_file_times, FileTime};
 use std::os::unix::fs::symlink;
 use std::os::unix::prelude::*;
@@ -89,6 +89,23 @@ pub fn find_up(from: &Path, filenames: &[&str]) -> Option<PathBuf> {
     }
 }
 
+/// if `from` is inside a git submodule, the root of the outermost submodule.
+/// A submodule has a `.git` file instead of a directory, the superproject is the
+/// first directory above it with a real `.git` directory.
+pub fn find_submodule_root(from: &Path) -> Option<PathBuf> {
+    let mut submodule_root = None;
+    for dir in from.ancestors() {
+        let git = dir.join(".git");
+        if git.is_dir() {
+            return submodule_root;
+        }
+        if git.is_file() {
+            submodule_root = Some(dir.to_path_buf());
+        }
+    }
+    None
+}
+
 pub fn dir_subdirs(dir: &Path) -> Result<Vec<String>> {
     let mut output = vec![];
 
@@ -195,6 +212,59 @@ pub fn which(name: &str) -> Option<PathBuf> {
     None
 }
 
+/// bytes available to unprivileged users on the filesystem holding `path`
+/// if `path` doesn't exist yet, the closest parent that does is used
+pub fn free_space(path: &Path) -> Result<u64> {
+    let path = path
+        .ancestors()
+        .find(|p| p.exists())
+        .ok_or_else(|| eyre!("{} does not exist", display_path(path)))?;
+    let c_path = std::ffi::CString::new(path.as_os_str().as_bytes())?;
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statvfs(c_path.as_ptr(), &mut stat) } != 0 {
+        return Err(io::Error::last_os_error().into());
+    }
+    #[allow(clippy::unnecessary_cast)]
+    Ok(stat.f_bavail as u64 * stat.f_frsize as u64)
+}
+
+/// total bytes of the files under `path`, 0 if it doesn't exist.
+/// Symlinks are not followed so something linked in is not counted twice.
+pub fn dir_size(path: &Path) -> Result<u64> {
+    let meta = match fs::symlink_metadata(path) {
+        Ok(meta) => meta,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(0),
+        Err(err) => return Err(err.into()),
+    };
+    if meta.is_dir() {
+        let mut size = 0;
+        for entry in fs::read_dir(path)? {
+            size += dir_size(&entry?.path())?;
+        }
+        Ok(size)
+    } else if meta.is_file() {
+        Ok(meta.len())
+    } else {
+        Ok(0)
+    }
+}
+
+/// parses a size like "512M" or "2G" into bytes, a bare number is already in bytes
+pub fn parse_size(s: &str) -> Result<u64> {
+    let s = s.trim();
+    let (num, unit) = s.split_at(s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len()));
+    let num: u64 = num.parse().map_err(|_| eyre!("invalid size: {}", s))?;
+    let multiplier: u64 = match unit.trim().to_uppercase().trim_end_matches('B') {
+        "" => 1,
+        "K" => 1 << 10,
+        "M" => 1 << 20,
+        "G" => 1 << 30,
+        "T" => 1 << 40,
+        _ => return Err(eyre!("invalid size: {}", s)),
+    };
+    Ok(num * multiplier)
+}
+
 #[cfg(test)]
 mod tests {
     use itertools::Itertools;
@@ -251,4 +321,18 @@ mod tests {
         assert_eq!(replace_path(Path::new("~/cwd")), dirs::HOME.join("cwd"));
         assert_eq!(replace_path(Path::new("/cwd")), Path::new("/cwd"));
     }
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("1024").unwrap(), 1024);
+        assert_eq!(parse_size("512M").unwrap(), 512 * 1024 * 1024);
+        assert_eq!(parse_size("2G").unwrap(), 2 * 1024 * 1024 * 1024);
+        assert_eq!(parse_size("2gb").unwrap(), 2 * 1024 * 1024 * 1024);
+        assert!(parse_size("two gigs").is_err());
+    }
+
+    #[test]
+    fn test_free_space() {
+        assert!(free_space(&dirs::HOME.join("does/not/exist")).unwrap() > 0);
+    }
 }
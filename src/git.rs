@@ -1,5 +1,8 @@
+use std::fs;
 use std::fs::create_dir_all;
 use std::path::PathBuf;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 use color_eyre::eyre::{eyre, Result};
 
@@ -8,11 +11,36 @@ use crate::file::touch_dir;
 
 pub struct Git {
     pub dir: PathBuf,
+    proxy: Option<String>,
+}
+
+/// controls how much of a repository `Git::clone_with_options` fetches
+#[derive(Debug, Clone, PartialEq)]
+pub struct CloneOptions {
+    /// number of commits of history to fetch, None fetches everything
+    pub depth: Option<usize>,
+    /// only fetch the branch that is checked out
+    pub single_branch: bool,
+}
+
+impl Default for CloneOptions {
+    fn default() -> Self {
+        Self {
+            depth: Some(1),
+            single_branch: false,
+        }
+    }
 }
 
 impl Git {
     pub fn new(dir: PathBuf) -> Self {
-        Self { dir }
+        Self { dir, proxy: None }
+    }
+
+    /// sets http.proxy for commands that talk to the remote, see `Settings::proxy_for`
+    pub fn with_proxy(mut self, proxy: Option<&str>) -> Self {
+        self.proxy = proxy.map(|p| p.to_string());
+        self
     }
 
     pub fn is_repo(&self) -> bool {
@@ -36,14 +64,13 @@ impl Git {
     pub fn update(&self, gitref: Option<String>) -> Result<(String, String)> {
         let gitref = gitref.map_or_else(|| self.remote_default_branch(), Ok)?;
         debug!("updating {} to {}", self.dir.display(), gitref);
-        self.run_git_command(&[
-            "fetch",
-            "--prune",
-            "--update-head-ok",
-            "origin",
-            format!("{}:{}", gitref, gitref).as_str(),
-        ])?;
+        // fetching with --update-head-ok may move HEAD so this must come first
         let prev_rev = self.current_sha()?;
+        let refspec = format!("{}:{}", gitref, gitref);
+        let proxy_args = self.proxy_args();
+        let mut args = proxy_args.iter().map(|a| a.as_str()).collect::<Vec<_>>();
+        args.extend(["fetch", "--prune", "--update-head-ok", "origin", &refspec]);
+        self.run_git_command(&args)?;
         self.run_git_command(&[
             "-c",
             "advice.detachedHead=false",
@@ -59,8 +86,19 @@ impl Git {
         Ok((prev_rev, post_rev))
     }
 
+    pub fn reset(&self, rev: &str) -> Result<()> {
+        debug!("resetting {} to {}", self.dir.display(), rev);
+        self.run_git_command(&["reset", "--quiet", "--hard", rev])?;
+        touch_dir(&self.dir)?;
+        Ok(())
+    }
+
     pub fn clone(&self, url: &str) -> Result<()> {
-        debug!("cloning {} to {}", url, self.dir.display());
+        self.clone_with_options(url, &CloneOptions::default())
+    }
+
+    pub fn clone_with_options(&self, url: &str, opts: &CloneOptions) -> Result<()> {
+        debug!("cloning {} to {} {:?}", url, self.dir.display(), opts);
         if let Some(parent) = self.dir.parent() {
             create_dir_all(parent)?;
         }
@@ -71,22 +109,168 @@ impl Git {
                 err
             ),
         }
-        cmd!("git", "clone", "-q", "--depth", "1", url, &self.dir).run()?;
+        cmd::cmd("git", self.clone_args(url, opts)).run()?;
         Ok(())
     }
 
+    fn clone_args(&self, url: &str, opts: &CloneOptions) -> Vec<String> {
+        let mut args = self.proxy_args();
+        args.extend(["clone".to_string(), "-q".to_string()]);
+        if let Some(depth) = opts.depth {
+            args.push("--depth".to_string());
+            args.push(depth.to_string());
+        }
+        if opts.single_branch {
+            args.push("--single-branch".to_string());
+        }
+        args.push(url.to_string());
+        args.push(self.dir.to_string_lossy().to_string());
+        args
+    }
+
+    fn proxy_args(&self) -> Vec<String> {
+        match &self.proxy {
+            Some(proxy) => vec!["-c".to_string(), format!("http.proxy={proxy}")],
+            None => vec![],
+        }
+    }
+
     pub fn current_sha(&self) -> Result<String> {
         let sha = cmd!("git", "-C", &self.dir, "rev-parse", "HEAD").read()?;
         debug!("current sha for {}: {}", self.dir.display(), &sha);
         Ok(sha)
     }
 
+    /// the commit HEAD is at, read from .git instead of running git since it's used on every
+    /// prompt for each plugin. None if this isn't a clone or HEAD can't be read.
+    pub fn head_sha(&self) -> Option<String> {
+        let git_dir = self.dir.join(".git");
+        let head = fs::read_to_string(git_dir.join("HEAD")).ok()?;
+        let refname = match head.trim().strip_prefix("ref: ") {
+            Some(refname) => refname.to_string(),
+            None => return Some(head.trim().to_string()),
+        };
+        if let Ok(sha) = fs::read_to_string(git_dir.join(&refname)) {
+            return Some(sha.trim().to_string());
+        }
+        // the ref was packed by git gc
+        let packed = fs::read_to_string(git_dir.join("packed-refs")).ok()?;
+        packed.lines().find_map(|line| match line.split_once(' ') {
+            Some((sha, r)) if r == refname => Some(sha.to_string()),
+            _ => None,
+        })
+    }
+
     pub fn current_sha_short(&self) -> Result<String> {
         let sha = cmd!("git", "-C", &self.dir, "rev-parse", "--short", "HEAD").read()?;
         debug!("current sha for {}: {}", self.dir.display(), &sha);
         Ok(sha)
     }
 
+    /// the commit `gitref` is in this clone, None if it has no branch, tag or commit by that name.
+    /// Annotated tags resolve to the commit they tag, not the tag object, like lightweight ones.
+    pub fn resolve_ref(&self, gitref: &str) -> Result<Option<String>> {
+        let sha = cmd!(
+            "git",
+            "-C",
+            &self.dir,
+            "rev-parse",
+            "--verify",
+            "--quiet",
+            format!("{gitref}^{{commit}}")
+        )
+        .stderr_null()
+        .unchecked()
+        .read()?;
+        Ok(Some(sha).filter(|sha| !sha.is_empty()))
+    }
+
+    /// fetches all of origin's tags, including ones pushed since this was cloned and
+    /// ones that were moved there
+    pub fn fetch_tags(&self) -> Result<()> {
+        debug!("fetching tags for {}", self.dir.display());
+        let proxy_args = self.proxy_args();
+        let mut args = proxy_args.iter().map(|a| a.as_str()).collect::<Vec<_>>();
+        args.extend(["fetch", "--quiet", "--tags", "--force", "origin"]);
+        self.run_git_command(&args)
+    }
+
+    /// true if tracked files have uncommitted changes, untracked files don't count
+    /// since checking out another ref leaves them alone
+    pub fn is_dirty(&self) -> Result<bool> {
+        let status = cmd!(
+            "git",
+            "-C",
+            &self.dir,
+            "status",
+            "--porcelain",
+            "--untracked-files=no"
+        )
+        .read()?;
+        Ok(!status.trim().is_empty())
+    }
+
+    /// (behind, ahead) counts of commits between HEAD and `gitref` on origin, origin's default
+    /// branch if None. It is fetched but nothing is checked out. This is how both plugins and
+    /// tools installed from a ref are told to be outdated.
+    pub fn behind_ahead(&self, gitref: Option<&str>) -> Result<(usize, usize)> {
+        let gitref = gitref.map_or_else(|| self.remote_default_branch(), |r| Ok(r.to_string()))?;
+        let proxy_args = self.proxy_args();
+        let mut args = proxy_args.iter().map(|a| a.as_str()).collect::<Vec<_>>();
+        args.extend(["fetch", "--quiet", "origin", &gitref]);
+        self.run_git_command(&args)?;
+        // an annotated tag is fetched as the tag object, rev-list counts from the commit it tags
+        let counts = cmd!(
+            "git",
+            "-C",
+            &self.dir,
+            "rev-list",
+            "--left-right",
+            "--count",
+            "FETCH_HEAD...HEAD"
+        )
+        .read()?;
+        match counts.split_once('\t') {
+            Some((behind, ahead)) => Ok((behind.trim().parse()?, ahead.trim().parse()?)),
+            None => Err(eyre!("unexpected output from git rev-list: {counts}")),
+        }
+    }
+
+    /// when the commit checked out was made, used as the time the repository was last updated
+    pub fn head_commit_time(&self) -> Result<SystemTime> {
+        let timestamp = cmd!("git", "-C", &self.dir, "log", "-1", "--format=%ct", "HEAD").read()?;
+        let secs = timestamp.trim().parse::<u64>()?;
+        Ok(UNIX_EPOCH + Duration::from_secs(secs))
+    }
+
+    /// the checked out branch, None if HEAD is detached
+    pub fn current_branch(&self) -> Result<Option<String>> {
+        let branch = cmd!("git", "-C", &self.dir, "rev-parse", "--abbrev-ref", "HEAD").read()?;
+        debug!("current branch for {}: {}", self.dir.display(), &branch);
+        Ok(Some(branch).filter(|b| b != "HEAD"))
+    }
+
+    /// commits between two revisions, e.g.: the (pre, post) returned by `update`
+    /// each entry is (short sha, subject)
+    pub fn log_oneline(&self, pre: &str, post: &str) -> Result<Vec<(String, String)>> {
+        let output = cmd!(
+            "git",
+            "-C",
+            &self.dir,
+            "log",
+            "--oneline",
+            "--no-decorate",
+            "--no-color",
+            commit_range(pre, post)
+        )
+        .read()?;
+        Ok(output
+            .lines()
+            .filter_map(|l| l.split_once(' '))
+            .map(|(sha, subject)| (sha.to_string(), subject.to_string()))
+            .collect())
+    }
+
     pub fn get_remote_url(&self) -> Option<String> {
         let res = cmd!(
             "git",
@@ -113,6 +297,37 @@ impl Git {
         }
     }
 
+    /// checks that `url` can be reached by listing its branches, without fetching anything.
+    /// Gives up after `timeout` since an unreachable host can otherwise hang for minutes.
+    pub fn ls_remote(&self, url: &str, timeout: Duration) -> Result<()> {
+        let mut args = self.proxy_args();
+        args.extend(["ls-remote".into(), "--heads".into(), url.to_string()]);
+        let handle = cmd::cmd("git", &args)
+            .env("GIT_TERMINAL_PROMPT", "0")
+            .stdin_null()
+            .stdout_null()
+            .stderr_null()
+            .unchecked()
+            .start()?;
+        let deadline = Instant::now() + timeout;
+        loop {
+            if let Some(output) = handle.try_wait()? {
+                return match output.status.success() {
+                    true => Ok(()),
+                    false => Err(eyre!("git ls-remote failed: {}", output.status)),
+                };
+            }
+            if Instant::now() >= deadline {
+                handle.kill()?;
+                return Err(eyre!(
+                    "git ls-remote timed out after {}",
+                    humantime::format_duration(timeout)
+                ));
+            }
+            thread::sleep(Duration::from_millis(50));
+        }
+    }
+
     pub fn split_url_and_ref(url: &str) -> (String, Option<String>) {
         match url.split_once('#') {
             Some((url, _ref)) => (url.to_string(), Some(_ref.to_string())),
@@ -151,6 +366,178 @@ fn get_git_version() -> Result<String> {
     Ok(version.trim().into())
 }
 
+fn commit_range(pre: &str, post: &str) -> String {
+    format!("{pre}..{post}")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use pretty_assertions::assert_eq;
+
+    use crate::dirs;
+    use crate::file::remove_all;
+    use crate::test::{git_cmd, git_commit};
+
+    use super::*;
+
+    #[test]
+    fn test_clone_args() {
+        let git = Git::new("/tmp/repo".into());
+        let url = "https://github.com/jdxcode/rtx-tiny";
+        let args = |depth, single_branch| {
+            git.clone_args(
+                url,
+                &CloneOptions {
+                    depth,
+                    single_branch,
+                },
+            )
+        };
+        assert_eq!(
+            git.clone_args(url, &CloneOptions::default()),
+            vec!["clone", "-q", "--depth", "1", url, "/tmp/repo"]
+        );
+        assert_eq!(
+            args(Some(10), true),
+            vec![
+                "clone",
+                "-q",
+                "--depth",
+                "10",
+                "--single-branch",
+                url,
+                "/tmp/repo"
+            ]
+        );
+        assert_eq!(
+            args(None, true),
+            vec!["clone", "-q", "--single-branch", url, "/tmp/repo"]
+        );
+        assert_eq!(args(None, false), vec!["clone", "-q", url, "/tmp/repo"]);
+    }
+
+    #[test]
+    fn test_clone_args_proxy() {
+        let url = "https://github.com/jdxcode/rtx-tiny";
+        let git = Git::new("/tmp/repo".into()).with_proxy(Some("http://proxy.corp:3128"));
+        assert_eq!(
+            git.clone_args(url, &CloneOptions::default()),
+            vec![
+                "-c",
+                "http.proxy=http://proxy.corp:3128",
+                "clone",
+                "-q",
+                "--depth",
+                "1",
+                url,
+                "/tmp/repo"
+            ]
+        );
+        let git = Git::new("/tmp/repo".into()).with_proxy(None);
+        assert_eq!(git.proxy_args(), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_log_oneline() {
+        let origin = dirs::CACHE.join("git-log-origin");
+        let dir = dirs::CACHE.join("git-log-clone");
+        let _ = remove_all(&origin);
+        let _ = remove_all(&dir);
+        let commit = |msg: &str| {
+            fs::write(origin.join("README.md"), msg).unwrap();
+            git_commit(&origin, msg);
+        };
+        fs::create_dir_all(&origin).unwrap();
+        commit("first");
+        let git = Git::new(dir.clone());
+        git.clone(origin.to_string_lossy().as_ref()).unwrap();
+        commit("second");
+        commit("third");
+
+        let (pre, post) = git.update(None).unwrap();
+        assert_eq!(commit_range(&pre, &post), format!("{pre}..{post}"));
+        let log = git.log_oneline(&pre, &post).unwrap();
+        let subjects: Vec<_> = log.iter().map(|(_, s)| s.as_str()).collect();
+        assert_eq!(subjects, vec!["third", "second"]);
+        assert!(post.starts_with(&log[0].0));
+        assert!(git.log_oneline(&post, &post).unwrap().is_empty());
+        assert_eq!(git.head_sha(), Some(post.clone()));
+        cmd!("git", "-C", &dir, "pack-refs", "--all").run().unwrap();
+        assert_eq!(git.head_sha(), Some(post.clone()));
+        assert_eq!(Git::new(origin.join("missing")).head_sha(), None);
+
+        remove_all(&origin).unwrap();
+        remove_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_behind_ahead() {
+        let origin = dirs::CACHE.join("git-behind-origin");
+        let dir = dirs::CACHE.join("git-behind-clone");
+        let _ = remove_all(&origin);
+        let _ = remove_all(&dir);
+        let commit = |dir: &PathBuf, msg: &str| {
+            fs::write(dir.join(msg), msg).unwrap();
+            git_commit(dir, msg);
+        };
+        fs::create_dir_all(&origin).unwrap();
+        commit(&origin, "first");
+        let git = Git::new(dir.clone());
+        git.clone(origin.to_string_lossy().as_ref()).unwrap();
+        assert_eq!(git.behind_ahead(None).unwrap(), (0, 0));
+        commit(&origin, "second");
+        commit(&origin, "third");
+        commit(&dir, "local");
+        let local = git.current_sha().unwrap();
+        let behind_ahead = git.behind_ahead(None).unwrap();
+        let sha = git.current_sha().unwrap();
+
+        remove_all(&origin).unwrap();
+        remove_all(&dir).unwrap();
+        assert_eq!(behind_ahead, (2, 1));
+        // only fetched, HEAD is still the local commit
+        assert_eq!(sha, local);
+    }
+
+    #[test]
+    fn test_fetch_tags() {
+        let origin = dirs::CACHE.join("git-tags-origin");
+        let dir = dirs::CACHE.join("git-tags-clone");
+        let _ = remove_all(&origin);
+        let _ = remove_all(&dir);
+        let git_origin = |args: &[&str]| git_cmd(&origin, args).run().unwrap();
+        fs::create_dir_all(&origin).unwrap();
+        fs::write(origin.join("README.md"), "first").unwrap();
+        git_commit(&origin, "first");
+        let git = Git::new(dir.clone());
+        git.clone(origin.to_string_lossy().as_ref()).unwrap();
+        fs::write(origin.join("README.md"), "second").unwrap();
+        git_commit(&origin, "second");
+        git_origin(&["tag", "-a", "v1.2.3", "-m", "annotated"]);
+        git_origin(&["tag", "v1.2.4"]);
+        let tagged = Git::new(origin.clone()).current_sha().unwrap();
+
+        let before = git.resolve_ref("v1.2.3").unwrap();
+        git.fetch_tags().unwrap();
+        let annotated = git.resolve_ref("v1.2.3").unwrap();
+        let lightweight = git.resolve_ref("v1.2.4").unwrap();
+        let behind_tag = git.behind_ahead(Some("v1.2.3")).unwrap();
+        let missing = git.resolve_ref("v9.9.9").unwrap();
+
+        remove_all(&origin).unwrap();
+        remove_all(&dir).unwrap();
+        assert_eq!(before, None);
+        // both kinds of tags resolve to the commit, not the annotated tag's own object
+        assert_eq!(annotated, Some(tagged.clone()));
+        assert_eq!(lightweight, Some(tagged.clone()));
+        // the clone is still on the first commit
+        assert_eq!(behind_tag, (1, 0));
+        assert_eq!(missing, None);
+    }
+}
+
 // #[cfg(test)]
 // mod tests {
 //     use pretty_assertions::assert_str_eq;
@@ -1,4 +1,5 @@
 use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
 use std::io::prelude::*;
 use std::ops::Deref;
 use std::path::PathBuf;
@@ -15,7 +16,7 @@ use serde_derive::{Deserialize, Serialize};
 use crate::env_diff::{EnvDiffOperation, EnvDiffPatches};
 use crate::hash::hash_to_str;
 use crate::shell::Shell;
-use crate::{dirs, env};
+use crate::{dirs, env, file};
 
 /// this function will early-exit the application if hook-env is being
 /// called and it does not need to be
@@ -144,6 +145,35 @@ fn get_rtx_env_vars_hashed() -> String {
     hash_to_str(&env_vars)
 }
 
+/// called when hook-env succeeds so an error in this directory is shown again if it comes back
+pub fn clear_shown_errors() {
+    let path = shown_error_path();
+    if path.exists() {
+        let _ = file::remove_all(path);
+    }
+}
+
+/// records `message` as the last error shown in the current directory, true if it already was
+pub fn already_shown(message: &str) -> bool {
+    let path = shown_error_path();
+    let hash = hash_to_str(&message);
+    if fs::read_to_string(&path).map_or(false, |prev| prev == hash) {
+        trace!("hook-env: suppressing repeated error");
+        return true;
+    }
+    let _ = file::create_dir_all(path.parent().unwrap());
+    if let Err(err) = fs::write(&path, hash) {
+        debug!("failed to record hook-env error: {:#}", err);
+    }
+    false
+}
+
+fn shown_error_path() -> PathBuf {
+    dirs::STATE
+        .join("hook-env-errors")
+        .join(hash_to_str(&*dirs::CURRENT))
+}
+
 pub fn clear_old_env(shell: &dyn Shell) -> String {
     let mut patches = env::__RTX_DIFF.reverse().to_patches();
     if let Some(path) = env::PRISTINE_ENV.deref().get("PATH") {
@@ -234,4 +264,16 @@ mod tests {
             &UNIX_EPOCH
         );
     }
+
+    #[test]
+    fn test_already_shown() {
+        clear_shown_errors();
+        assert!(!already_shown("error reading config: .rtx.toml"));
+        assert!(already_shown("error reading config: .rtx.toml"));
+        assert!(!already_shown("error reading config: .tool-versions"));
+
+        clear_shown_errors();
+        assert!(!already_shown("error reading config: .tool-versions"));
+        clear_shown_errors();
+    }
 }
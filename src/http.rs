@@ -1,17 +1,28 @@
 use color_eyre::eyre::Result;
 use reqwest::blocking::RequestBuilder;
-use reqwest::IntoUrl;
+use reqwest::{IntoUrl, Proxy};
+
+use crate::config::Settings;
+use crate::env::RTX_HTTP_TIMEOUT;
 
 pub struct Client {
     reqwest: reqwest::blocking::Client,
 }
 
 impl Client {
-    pub fn new() -> Result<Self> {
-        let reqwest = reqwest::blocking::ClientBuilder::new()
+    pub fn new(settings: &Settings) -> Result<Self> {
+        let mut builder = reqwest::blocking::ClientBuilder::new()
             .user_agent(format!("rtx/{}", env!("CARGO_PKG_VERSION")))
-            .build()?;
-        Ok(Self { reqwest })
+            .timeout(*RTX_HTTP_TIMEOUT);
+        if settings.proxy.is_some() {
+            let settings = settings.clone();
+            builder = builder.proxy(Proxy::custom(move |url| {
+                settings.proxy_for(url.as_str()).map(|p| p.to_string())
+            }));
+        }
+        Ok(Self {
+            reqwest: builder.build()?,
+        })
     }
 
     pub fn get<U: IntoUrl>(&self, url: U) -> RequestBuilder {
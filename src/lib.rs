@@ -12,6 +12,7 @@ pub mod cli;
 
 mod build_time;
 mod cache;
+pub mod cancel;
 pub mod cmd;
 mod config;
 mod default_shorthands;
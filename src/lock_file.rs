@@ -1,13 +1,18 @@
-use crate::dirs;
 use crate::file::create_dir_all;
 use crate::hash::hash_to_str;
+use crate::{dirs, env};
+use color_eyre::eyre::{eyre, Result};
+use std::io::{Error, ErrorKind};
 use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, Instant};
 
 pub type OnLockedFn = Box<dyn Fn(&Path)>;
 
 pub struct LockFile {
     path: PathBuf,
     on_locked: Option<OnLockedFn>,
+    timeout: Option<Duration>,
 }
 
 impl LockFile {
@@ -16,6 +21,7 @@ impl LockFile {
         Self {
             path,
             on_locked: None,
+            timeout: None,
         }
     }
 
@@ -27,17 +33,94 @@ impl LockFile {
         self
     }
 
+    /// give up with a `TimedOut` error instead of waiting forever for the lock
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
     pub fn lock(self) -> Result<fslock::LockFile, std::io::Error> {
         if let Some(parent) = self.path.parent() {
             create_dir_all(parent)?;
         }
         let mut lock = fslock::LockFile::open(&self.path)?;
         if !lock.try_lock()? {
-            if let Some(f) = self.on_locked {
+            if let Some(f) = &self.on_locked {
                 f(&self.path)
             }
-            lock.lock()?;
+            match self.timeout {
+                Some(timeout) => {
+                    let deadline = Instant::now() + timeout;
+                    while !lock.try_lock()? {
+                        if Instant::now() >= deadline {
+                            return Err(Error::new(
+                                ErrorKind::TimedOut,
+                                format!("timed out waiting for lock on {}", self.path.display()),
+                            ));
+                        }
+                        thread::sleep(Duration::from_millis(100));
+                    }
+                }
+                None => lock.lock()?,
+            }
         }
         Ok(lock)
     }
 }
+
+/// held by commands that change shared state (installs, plugins, shims) for as long as they run
+/// so two of them can't interleave. Plugin scripts run by such a command don't take it again.
+pub fn global_lock(timeout: Duration) -> Result<Option<fslock::LockFile>> {
+    if *env::__RTX_SCRIPT {
+        return Ok(None);
+    }
+    let lock = LockFile::new(&dirs::ROOT)
+        .with_callback(|_| info!("waiting for another rtx process to finish"))
+        .with_timeout(timeout)
+        .lock();
+    match lock {
+        Ok(lock) => Ok(Some(lock)),
+        Err(err) if err.kind() == ErrorKind::TimedOut => Err(eyre!(
+            "another rtx process is running, gave up after waiting {}\nuse --no-lock to skip this check",
+            humantime::format_duration(timeout)
+        )),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc::channel;
+
+    use super::*;
+
+    #[test]
+    fn test_lock_waits_for_release() {
+        let path = dirs::CACHE.join("test-lock-waits");
+        let (tx, rx) = channel();
+        let holder = thread::spawn({
+            let path = path.clone();
+            move || {
+                let lock = LockFile::new(&path).lock().unwrap();
+                tx.send(()).unwrap();
+                thread::sleep(Duration::from_millis(500));
+                drop(lock);
+            }
+        });
+        rx.recv().unwrap();
+
+        let err = LockFile::new(&path)
+            .with_timeout(Duration::from_millis(100))
+            .lock()
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::TimedOut);
+
+        let start = Instant::now();
+        let _lock = LockFile::new(&path)
+            .with_timeout(Duration::from_secs(10))
+            .lock()
+            .unwrap();
+        assert!(start.elapsed() >= Duration::from_millis(200));
+        holder.join().unwrap();
+    }
+}
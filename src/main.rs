@@ -11,6 +11,7 @@ use console::{style, Term};
 use crate::cli::version::VERSION;
 use crate::cli::Cli;
 use crate::config::Config;
+use crate::errors::Error;
 use crate::output::Output;
 
 #[macro_use]
@@ -21,6 +22,7 @@ mod regex;
 
 pub mod build_time;
 mod cache;
+mod cancel;
 mod cli;
 mod cmd;
 mod config;
@@ -58,9 +60,15 @@ fn main() -> Result<()> {
     let log_level = *env::RTX_LOG_LEVEL;
     logger::init(log_level, *env::RTX_LOG_FILE_LEVEL);
     handle_ctrlc();
+    cancel::handle_sigterm();
 
-    match run(&env::ARGS).with_section(|| VERSION.to_string().header("Version:")) {
+    let result = run(&env::ARGS).with_section(|| VERSION.to_string().header("Version:"));
+    if let Some(code) = result.as_ref().err().and_then(exit_status) {
+        exit(code);
+    }
+    match result {
         Ok(()) => Ok(()),
+        Err(err) if cli::hook_env::is_repeated_error(&err) => exit(1),
         Err(err) if log_level < log::LevelFilter::Debug => {
             display_friendly_err(err);
             exit(1);
@@ -88,11 +96,20 @@ fn handle_ctrlc() {
     ctrlc::set_handler(move || {
         let _ = Term::stderr().show_cursor();
         debug!("Ctrl-C pressed, exiting...");
+        cmd::interrupt_running();
         exit(1);
     })
     .expect("Error setting Ctrl-C handler");
 }
 
+/// the status to exit with for `Error::Exit`, which is not displayed
+fn exit_status(err: &Report) -> Option<i32> {
+    match err.downcast_ref() {
+        Some(Error::Exit(code)) => Some(*code),
+        _ => None,
+    }
+}
+
 fn display_friendly_err(err: Report) {
     let dim = |s| style(s).dim().for_stderr();
     let dim_red = |s| style(s).dim().red().for_stderr();
@@ -2,6 +2,32 @@ use std::io;
 use std::io::Write;
 use std::process::ExitCode;
 
+use color_eyre::eyre::Result;
+use indexmap::IndexMap;
+use serde::Serialize;
+use serde_derive::Serialize;
+
+/// Version of the shape of `--json` output, bump it when a field is removed, renamed or changes
+/// type so scripts reading it can tell which rtx they are talking to. Adding fields is fine.
+pub const JSON_SCHEMA_VERSION: u32 = 1;
+
+#[derive(Serialize)]
+struct JsonDocument<'a, T: Serialize> {
+    schema_version: u32,
+    #[serde(flatten)]
+    payload: IndexMap<&'a str, &'a T>,
+}
+
+/// serializes `payload` under `key` in a document that starts with the schema_version,
+/// this is what every `--json` flag prints
+pub fn to_json<T: Serialize>(key: &str, payload: &T) -> Result<String> {
+    let doc = JsonDocument {
+        schema_version: JSON_SCHEMA_VERSION,
+        payload: IndexMap::from([(key, payload)]),
+    };
+    Ok(serde_json::to_string_pretty(&doc)?)
+}
+
 #[derive(Debug)]
 pub enum OutputType {
     Stdout,
@@ -13,6 +39,8 @@ pub struct Output {
     pub stdout: OutputStream,
     pub stderr: OutputStream,
     pub status: ExitCode,
+    /// set by the global --json flag, see `JsonOutput`
+    pub json: bool,
 }
 
 impl Output {
@@ -21,6 +49,7 @@ impl Output {
             stdout: OutputStream::new(OutputType::Stdout),
             stderr: OutputStream::new(OutputType::Stderr),
             status: ExitCode::from(0),
+            json: false,
         }
     }
 
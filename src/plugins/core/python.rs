@@ -10,7 +10,7 @@ use crate::config::{Config, Settings};
 use crate::env::RTX_EXE;
 use crate::file::create_dir_all;
 use crate::git::Git;
-use crate::plugins::{Plugin, PluginName};
+use crate::plugins::{ExecEnvVars, Plugin, PluginName};
 use crate::toolset::{ToolVersion, ToolVersionRequest};
 use crate::ui::progress_report::ProgressReport;
 use crate::{cmd, dirs, env, file, http};
@@ -186,7 +186,7 @@ impl Plugin for PythonPlugin {
         if let Some(patch_url) = &*env::RTX_PYTHON_PATCH_URL {
             pr.set_message(format!("with patch file from: {patch_url}"));
             cmd.arg("--patch");
-            let http = http::Client::new()?;
+            let http = http::Client::new(&config.settings)?;
             let patch = http.get(patch_url).send()?.text()?;
             cmd.stdin_string(patch);
         }
@@ -217,15 +217,18 @@ impl Plugin for PythonPlugin {
         }
     }
 
-    fn exec_env(&self, config: &Config, tv: &ToolVersion) -> Result<HashMap<String, String>> {
+    fn exec_env(&self, config: &Config, tv: &ToolVersion) -> Result<ExecEnvVars> {
         if let Some(virtualenv) = self.get_virtualenv(config, tv, None)? {
-            let hm = HashMap::from([(
+            let set = HashMap::from([(
                 "VIRTUAL_ENV".to_string(),
                 virtualenv.to_string_lossy().to_string(),
             )]);
-            Ok(hm)
+            Ok(ExecEnvVars {
+                set,
+                ..Default::default()
+            })
         } else {
-            Ok(HashMap::new())
+            Ok(ExecEnvVars::default())
         }
     }
 }
@@ -1,4 +1,6 @@
 use std::collections::{BTreeMap, HashMap};
+use std::env::{join_paths, split_paths};
+use std::ffi::{OsStr, OsString};
 use std::fs;
 use std::hash::{Hash, Hasher};
 use std::path::{Path, PathBuf};
@@ -8,7 +10,6 @@ use std::time::Duration;
 use color_eyre::eyre::{eyre, Result, WrapErr};
 use console::style;
 use itertools::Itertools;
-use once_cell::sync::Lazy;
 
 use crate::cache::CacheManager;
 use crate::cmd::cmd;
@@ -16,23 +17,32 @@ use crate::config::{Config, Settings};
 use crate::env::PREFER_STALE;
 use crate::env_diff::{EnvDiff, EnvDiffOperation};
 use crate::errors::Error::PluginNotInstalled;
-use crate::file::remove_all;
-use crate::git::Git;
+use crate::file::{display_path, remove_all};
+use crate::git::{CloneOptions, Git};
 use crate::hash::hash_to_str;
 use crate::plugins::external_plugin_cache::ExternalPluginCache;
 use crate::plugins::rtx_plugin_toml::RtxPluginToml;
-use crate::plugins::Script::{Download, ExecEnv, Install, ParseLegacyFile};
-use crate::plugins::{Plugin, PluginName, PluginType, Script, ScriptManager};
+use crate::plugins::Script::{
+    Download, ExecEnv, Install, ParseLegacyFile, PostInstall, PreInstall,
+};
+use crate::plugins::{
+    count_installed_versions, ExecEnvVars, Plugin, PluginMetadata, PluginName, PluginType, Script,
+    ScriptManager,
+};
 use crate::toolset::{ToolVersion, ToolVersionRequest};
 use crate::ui::progress_report::ProgressReport;
 use crate::{dirs, env, file};
 
+/// how long after it expires the list-all cache is still used while it is fetched again
+const REMOTE_VERSIONS_SWR: Duration = Duration::from_secs(60 * 60 * 24 * 7);
+
 /// This represents a plugin installed to ~/.local/share/rtx/plugins
 #[derive(Debug)]
 pub struct ExternalPlugin {
     pub name: PluginName,
     pub plugin_path: PathBuf,
-    pub repo_url: Option<String>,
+    /// where to clone from instead of the config/shorthands, later ones are fallbacks
+    pub repo_urls: Vec<String>,
     pub toml: RtxPluginToml,
     cache_path: PathBuf,
     downloads_path: PathBuf,
@@ -64,6 +74,7 @@ impl ExternalPlugin {
             cache: ExternalPluginCache::default(),
             remote_version_cache: CacheManager::new(cache_path.join("remote_versions.msgpack.z"))
                 .with_fresh_duration(fresh_duration)
+                .with_stale_while_revalidate(REMOTE_VERSIONS_SWR)
                 .with_fresh_file(plugin_path.clone())
                 .with_fresh_file(plugin_path.join("bin/list-all")),
             latest_stable_cache: CacheManager::new(cache_path.join("latest_stable.msgpack.z"))
@@ -78,21 +89,25 @@ impl ExternalPlugin {
                 .with_fresh_file(plugin_path.join("bin/list-legacy-filenames")),
             plugin_path,
             cache_path,
-            repo_url: None,
+            repo_urls: vec![],
             toml,
         }
     }
 
-    fn fetch_remote_versions(&self, settings: &Settings) -> Result<Vec<String>> {
-        let result = self
-            .script_man
+    /// takes the parts of the plugin it needs so it can run on the thread that refreshes the cache
+    fn fetch_remote_versions(
+        script_man: &ScriptManager,
+        toml: &RtxPluginToml,
+        settings: &Settings,
+    ) -> Result<Vec<String>> {
+        let result = script_man
             .cmd(settings, &Script::ListAll)
             .stdout_capture()
             .stderr_capture()
             .unchecked()
             .run()
             .map_err(|err| {
-                let script = self.script_man.get_script_path(&Script::ListAll);
+                let script = script_man.get_script_path(&Script::ListAll);
                 eyre!("Failed to run {}: {}", script.display(), err)
             })?;
         let stdout = String::from_utf8(result.stdout).unwrap();
@@ -114,7 +129,11 @@ impl ExternalPlugin {
             display_stderr();
         }
 
-        Ok(stdout.split_whitespace().map(|v| v.into()).collect())
+        let versions = stdout.split_whitespace().map(|v| v.into()).collect();
+        match &toml.list_all_transform {
+            Some(transform) => Ok(transform.sort(versions)),
+            None => Ok(versions),
+        }
     }
 
     fn fetch_legacy_filenames(&self, settings: &Settings) -> Result<Vec<String>> {
@@ -214,21 +233,81 @@ impl ExternalPlugin {
             .collect();
         Ok(bin_paths)
     }
-    fn fetch_exec_env(&self, config: &Config, tv: &ToolVersion) -> Result<HashMap<String, String>> {
-        let script = self.script_man_for_tv(config, tv).get_script_path(&ExecEnv);
-        let ed = EnvDiff::from_bash_script(&script, &self.script_man_for_tv(config, tv).env)?;
-        let env = ed
-            .to_patches()
-            .into_iter()
-            .filter_map(|p| match p {
-                EnvDiffOperation::Add(key, value) => Some((key, value)),
-                EnvDiffOperation::Change(key, value) => Some((key, value)),
-                _ => None,
-            })
-            .collect();
+    fn fetch_exec_env(&self, config: &Config, tv: &ToolVersion) -> Result<ExecEnvVars> {
+        let sm = self.script_man_for_tv(config, tv);
+        let script = sm.get_script_path(&ExecEnv);
+        let ed = EnvDiff::from_bash_script(&script, isolate_exec_env(sm.env))?;
+        let mut env = ExecEnvVars::default();
+        for patch in ed.to_patches() {
+            match patch {
+                EnvDiffOperation::Add(key, value) | EnvDiffOperation::Change(key, value) => {
+                    env.set.insert(key, value);
+                }
+                EnvDiffOperation::Remove(key) => env.unset.push(key),
+            }
+        }
+        env.unset.sort();
         Ok(env)
     }
 
+    /// bin/pre-install and bin/post-install, or the same hooks as commands in rtx.plugin.toml
+    fn run_install_hook(
+        &self,
+        config: &Config,
+        sm: &ScriptManager,
+        script: &Script,
+        command: &Option<String>,
+        pr: &ProgressReport,
+    ) -> Result<()> {
+        let result = if sm.script_exists(script) {
+            pr.set_message(script.to_string());
+            sm.run_by_line(&config.settings, script, pr)
+        } else if let Some(command) = command {
+            pr.set_message(script.to_string());
+            sm.run_command_by_line(&config.settings, command, pr)
+        } else {
+            return Ok(());
+        };
+        result.wrap_err_with(|| format!("{script} hook of plugin {} failed", self.name))
+    }
+
+    /// replaces the plugin with a clone of `repository`, returns it and the url without the ref
+    fn clone_repo(
+        &self,
+        config: &Config,
+        pr: &ProgressReport,
+        repository: &str,
+    ) -> Result<(Git, String)> {
+        let (repo_url, repo_ref) = Git::split_url_and_ref(repository);
+        debug!("install {} {:?}", self.name, repository);
+
+        let git = Git::new(self.plugin_path.to_path_buf())
+            .with_proxy(config.settings.proxy_for(&repo_url));
+        if config.settings.plugin_verify_url {
+            pr.set_message(format!("checking {repo_url}"));
+            verify_repo_url(&git, &repo_url)?;
+        }
+
+        if self.is_installed() {
+            self.uninstall(pr)?;
+        }
+
+        pr.set_message(format!("cloning {repo_url}"));
+        let clone_opts = CloneOptions {
+            depth: match config.settings.plugin_clone_depth {
+                0 => None,
+                depth => Some(depth),
+            },
+            single_branch: config.settings.plugin_clone_single_branch,
+        };
+        git.clone_with_options(&repo_url, &clone_opts)?;
+        if let Some(ref_) = &repo_ref {
+            pr.set_message(format!("checking out {ref_}"));
+            git.update(Some(ref_.to_string()))?;
+        }
+        Ok((git, repo_url))
+    }
+
     fn script_man_for_tv(&self, config: &Config, tv: &ToolVersion) -> ScriptManager {
         let mut sm = self.script_man.clone();
         for (key, value) in &tv.opts {
@@ -239,8 +318,13 @@ impl ExternalPlugin {
             let project_root = project_root.to_string_lossy().to_string();
             sm = sm.with_env("RTX_PROJECT_ROOT", project_root);
         }
+        if config.settings.build_from_source {
+            sm = sm.with_env("RTX_BUILD_FROM_SOURCE", "1");
+        }
         let install_type = match &tv.request {
-            ToolVersionRequest::Version(_, _) | ToolVersionRequest::Prefix(_, _) => "version",
+            ToolVersionRequest::Version(_, _)
+            | ToolVersionRequest::Prefix(_, _)
+            | ToolVersionRequest::Range(_, _) => "version",
             ToolVersionRequest::Ref(_, _) => "ref",
             ToolVersionRequest::Path(_, _) => "path",
             ToolVersionRequest::System(_) => {
@@ -276,6 +360,19 @@ impl ExternalPlugin {
     }
 }
 
+/// bin/exec-env scripts often build on $PATH. If it still has another tool's bin dirs (from
+/// `rtx activate`, `rtx exec` or a shim) the result depends on which tools were set up before
+/// this one, so those dirs and the shims are dropped to give every tool the same starting point.
+fn isolate_exec_env(mut env: HashMap<OsString, OsString>) -> HashMap<OsString, OsString> {
+    if let Some(path) = env.get(OsStr::new("PATH")) {
+        let path = split_paths(path)
+            .filter(|p| !p.starts_with(&*dirs::INSTALLS) && p != &*dirs::SHIMS)
+            .collect_vec();
+        env.insert("PATH".into(), join_paths(path).unwrap());
+    }
+    env
+}
+
 fn build_script_man(name: &str, plugin_path: &Path) -> ScriptManager {
     ScriptManager::new(plugin_path.to_path_buf())
         .with_env("RTX_PLUGIN_NAME", name.to_string())
@@ -306,8 +403,18 @@ impl Plugin for ExternalPlugin {
         PluginType::External
     }
     fn list_remote_versions(&self, settings: &Settings) -> Result<Vec<String>> {
-        self.remote_version_cache
-            .get_or_try_init(|| self.fetch_remote_versions(settings))
+        let cache = &self.remote_version_cache;
+        let fetch = {
+            let (script_man, toml) = (self.script_man.clone(), self.toml.clone());
+            let settings = settings.clone();
+            move || Self::fetch_remote_versions(&script_man, &toml, &settings)
+        };
+        let versions = match settings.cache_only {
+            true => cache.get_or_try_init_cache_only(true, fetch),
+            // a day old list is still good enough to use while list-all runs again
+            false => cache.get_or_try_init_swr(fetch),
+        };
+        versions
             .map_err(|err| {
                 eyre!(
                     "Failed listing remote versions for plugin {}: {}",
@@ -323,7 +430,7 @@ impl Plugin for ExternalPlugin {
             return Ok(None);
         }
         self.latest_stable_cache
-            .get_or_try_init(|| self.fetch_latest_stable(settings))
+            .get_or_try_init_cache_only(settings.cache_only, || self.fetch_latest_stable(settings))
             .map_err(|err| {
                 eyre!(
                     "Failed fetching latest stable version for plugin {}: {}",
@@ -344,25 +451,47 @@ impl Plugin for ExternalPlugin {
     }
 
     fn install(&self, config: &Config, pr: &mut ProgressReport) -> Result<()> {
-        let repository = self
-            .repo_url
-            .clone()
-            .or_else(|| config.get_repo_url(&self.name))
-            .ok_or_else(|| eyre!("No repository found for plugin {}", self.name))?;
-        let (repo_url, repo_ref) = Git::split_url_and_ref(&repository);
-        debug!("install {} {:?}", self.name, repository);
-
-        if self.is_installed() {
-            self.uninstall(pr)?;
+        let repositories = match self.repo_urls.is_empty() {
+            true => config.get_repo_urls(&self.name),
+            false => self.repo_urls.clone(),
+        };
+        if repositories.is_empty() {
+            return Err(eyre!("No repository found for plugin {}", self.name));
         }
-
-        let git = Git::new(self.plugin_path.to_path_buf());
-        pr.set_message(format!("cloning {repo_url}"));
-        git.clone(&repo_url)?;
-        if let Some(ref_) = &repo_ref {
-            pr.set_message(format!("checking out {ref_}"));
-            git.update(Some(ref_.to_string()))?;
+        let mut cloned = None;
+        for (i, repository) in repositories.iter().enumerate() {
+            if let Some(next) = repositories.get(i + 1) {
+                // e.g.: plugin_default_org's fork, which the org may not have
+                let git = Git::new(self.plugin_path.clone())
+                    .with_proxy(config.settings.proxy_for(repository));
+                if let Err(err) = git.ls_remote(repository, Duration::from_secs(10)) {
+                    debug!("{repository} is not available, trying {next}: {err:#}");
+                    continue;
+                }
+            }
+            match self.clone_repo(config, pr, repository) {
+                Ok(result) => {
+                    cloned = Some(result);
+                    break;
+                }
+                Err(err) if i + 1 < repositories.len() => {
+                    warn!(
+                        "failed to install plugin {} from {repository}, trying {}: {err:#}",
+                        self.name,
+                        repositories[i + 1]
+                    );
+                }
+                Err(err) if repositories.len() > 1 => {
+                    return Err(err.wrap_err(format!(
+                        "failed to install plugin {} from any of its {} repositories",
+                        self.name,
+                        repositories.len()
+                    )));
+                }
+                Err(err) => return Err(err),
+            }
         }
+        let (git, repo_url) = cloned.unwrap();
 
         pr.set_message("loading plugin remote versions");
         if self.has_list_all_script() {
@@ -385,14 +514,19 @@ impl Plugin for ExternalPlugin {
         Ok(())
     }
 
-    fn update(&self, gitref: Option<String>) -> Result<()> {
+    fn update(
+        &self,
+        config: &Config,
+        gitref: Option<String>,
+        force: bool,
+    ) -> Result<Option<(String, String)>> {
         let plugin_path = self.plugin_path.to_path_buf();
         if plugin_path.is_symlink() {
             warn!(
                 "Plugin: {} is a symlink, not updating",
                 style(&self.name).cyan().for_stderr()
             );
-            return Ok(());
+            return Ok(None);
         }
         let git = Git::new(plugin_path);
         if !git.is_repo() {
@@ -400,12 +534,49 @@ impl Plugin for ExternalPlugin {
                 "Plugin {} is not a git repository, not updating",
                 style(&self.name).cyan().for_stderr()
             );
-            return Ok(());
+            return Ok(None);
         }
-        // TODO: asdf_run_hook "pre_plugin_update"
-        let (_pre, _post) = git.update(gitref)?;
-        // TODO: asdf_run_hook "post_plugin_update"
-        Ok(())
+        if !force && git.is_dirty()? {
+            warn!(
+                "Plugin {} has local changes, not updating. Use --force to discard them",
+                style(&self.name).cyan().for_stderr()
+            );
+            return Ok(None);
+        }
+        let remote_url = git.get_remote_url().unwrap_or_default();
+        let git = git.with_proxy(config.settings.proxy_for(&remote_url));
+        if self.script_man.script_exists(&Script::PrePluginUpdate) {
+            let sm = self
+                .script_man
+                .clone()
+                .with_env("ASDF_PLUGIN_PATH", &self.plugin_path)
+                .with_env("ASDF_PLUGIN_PREV_REF", git.current_sha()?);
+            sm.run(&config.settings, &Script::PrePluginUpdate)
+                .wrap_err_with(|| {
+                    format!("pre-plugin-update failed, not updating {}", self.name)
+                })?;
+        }
+        let (pre, post) = git.update(gitref)?;
+        let sm = self
+            .script_man
+            .clone()
+            .with_env("ASDF_PLUGIN_PATH", &self.plugin_path)
+            .with_env("ASDF_PLUGIN_PREV_REF", &pre)
+            .with_env("ASDF_PLUGIN_POST_REF", &post);
+        if !sm.script_exists(&Script::PostPluginUpdate) {
+            return Ok(Some((pre, post)));
+        }
+        if let Err(err) = sm.run(&config.settings, &Script::PostPluginUpdate) {
+            if !config.settings.plugin_update_rollback {
+                return Err(err);
+            }
+            git.reset(&pre)?;
+            return Err(err.wrap_err(format!(
+                "post-plugin-update failed, rolled back {} to {}",
+                self.name, pre
+            )));
+        }
+        Ok(Some((pre, post)))
     }
 
     fn uninstall(&self, pr: &ProgressReport) -> Result<()> {
@@ -443,7 +614,7 @@ impl Plugin for ExternalPlugin {
         }
         let aliases = self
             .alias_cache
-            .get_or_try_init(|| self.fetch_aliases(settings))
+            .get_or_try_init_cache_only(settings.cache_only, || self.fetch_aliases(settings))
             .map_err(|err| {
                 eyre!(
                     "Failed fetching aliases for plugin {}: {}",
@@ -465,7 +636,9 @@ impl Plugin for ExternalPlugin {
             return Ok(vec![]);
         }
         self.legacy_filename_cache
-            .get_or_try_init(|| self.fetch_legacy_filenames(settings))
+            .get_or_try_init_cache_only(settings.cache_only, || {
+                self.fetch_legacy_filenames(settings)
+            })
             .map_err(|err| {
                 eyre!(
                     "Failed fetching legacy filenames for plugin {}: {}",
@@ -538,26 +711,64 @@ impl Plugin for ExternalPlugin {
         tv: &ToolVersion,
         pr: &ProgressReport,
     ) -> Result<()> {
-        let run_script = |script| {
-            self.script_man_for_tv(config, tv)
-                .run_by_line(&config.settings, script, pr)
-        };
+        let sm = self.script_man_for_tv(config, tv);
+        let run_script = |script| sm.run_by_line(&config.settings, script, pr);
 
-        if self.script_man_for_tv(config, tv).script_exists(&Download) {
+        self.run_install_hook(config, &sm, &PreInstall, &self.toml.pre_install, pr)?;
+        if tv.download_complete_path().exists() {
+            debug!("using {}", display_path(&tv.download_path()));
+        } else if sm.script_exists(&Download) {
             pr.set_message("downloading");
             run_script(&Download)?;
         }
+        if let ToolVersionRequest::Ref(_, gitref) = &tv.request {
+            fetch_missing_tags(config, tv, gitref, pr)?;
+        }
         pr.set_message("installing");
         run_script(&Install)?;
+        self.run_install_hook(config, &sm, &PostInstall, &self.toml.post_install, pr)?;
 
         Ok(())
     }
 
+    fn download_version(
+        &self,
+        config: &Config,
+        tv: &ToolVersion,
+        pr: &ProgressReport,
+    ) -> Result<()> {
+        let sm = self.script_man_for_tv(config, tv);
+        if !sm.script_exists(&Download) {
+            return Err(eyre!(
+                "plugin {} has no bin/download script, it downloads in bin/install",
+                self.name
+            ));
+        }
+        pr.set_message("downloading");
+        sm.run_by_line(&config.settings, &Download, pr)
+    }
+
     fn uninstall_version(&self, config: &Config, tv: &ToolVersion) -> Result<()> {
         if self.plugin_path.join("bin/uninstall").exists() {
             self.script_man_for_tv(config, tv)
                 .run(&config.settings, &Script::Uninstall)?;
         }
+        self.cache.invalidate(tv)
+    }
+
+    fn pre_uninstall(&self, config: &Config, tv: &ToolVersion) -> Result<()> {
+        if self.script_man.script_exists(&Script::PreUninstall) {
+            self.script_man_for_tv(config, tv)
+                .run(&config.settings, &Script::PreUninstall)?;
+        }
+        Ok(())
+    }
+
+    fn post_uninstall(&self, config: &Config, tv: &ToolVersion) -> Result<()> {
+        if self.script_man.script_exists(&Script::PostUninstall) {
+            self.script_man_for_tv(config, tv)
+                .run(&config.settings, &Script::PostUninstall)?;
+        }
         Ok(())
     }
 
@@ -566,18 +777,472 @@ impl Plugin for ExternalPlugin {
             .list_bin_paths(config, self, tv, || self.fetch_bin_paths(config, tv))
     }
 
-    fn exec_env(&self, config: &Config, tv: &ToolVersion) -> Result<HashMap<String, String>> {
+    fn exec_env(&self, config: &Config, tv: &ToolVersion) -> Result<ExecEnvVars> {
         if matches!(tv.request, ToolVersionRequest::System(_)) {
-            return Ok(EMPTY_HASH_MAP.clone());
+            return Ok(ExecEnvVars::default());
         }
         if !self.script_man.script_exists(&ExecEnv) || *env::__RTX_SCRIPT {
             // if the script does not exist, or we're already running from within a script,
             // the second is to prevent infinite loops
-            return Ok(EMPTY_HASH_MAP.clone());
+            return Ok(ExecEnvVars::default());
         }
         self.cache
             .exec_env(config, self, tv, || self.fetch_exec_env(config, tv))
     }
+
+    fn display_name(&self) -> &str {
+        self.toml.display_name.as_deref().unwrap_or(&self.name)
+    }
+
+    fn install_path_template(&self) -> Option<String> {
+        self.toml.install_path.clone()
+    }
+
+    fn min_free_disk(&self) -> Option<u64> {
+        self.toml.min_free_disk
+    }
+
+    fn post_install_test(&self) -> Option<String> {
+        self.toml.post_install_test.clone()
+    }
+
+    fn metadata(&self, settings: &Settings) -> Result<PluginMetadata> {
+        let git = Git::new(self.plugin_path.clone());
+        let is_repo = git.is_repo();
+        Ok(PluginMetadata {
+            name: self.name.clone(),
+            plugin_type: self.get_type(),
+            remote_url: is_repo.then(|| git.get_remote_url()).flatten(),
+            sha: is_repo.then(|| git.current_sha_short()).transpose()?,
+            branch: match is_repo {
+                true => git.current_branch()?,
+                false => None,
+            },
+            installed_versions: count_installed_versions(&self.installs_path),
+            aliases: self.get_aliases(settings)?,
+            legacy_filenames: self.legacy_filenames(settings)?,
+            min_rtx_version: self.toml.min_rtx_version.clone(),
+        })
+    }
+
+    fn missing_scripts(&self) -> Vec<String> {
+        let mut expected = vec![Script::ListAll, Install];
+        // bin/download is optional unless bin/install expects something to have been downloaded
+        let install = self.script_man.get_script_path(&Install);
+        if fs::read_to_string(install).map_or(false, |s| s.contains("ASDF_DOWNLOAD_PATH")) {
+            expected.push(Download);
+        }
+        expected
+            .into_iter()
+            .filter(|script| !self.script_man.script_exists(script))
+            .map(|script| format!("bin/{script}"))
+            .collect()
+    }
+}
+
+/// a mistyped shorthand or a deleted repository otherwise only shows up as a git error partway
+/// through the clone, possibly after the existing plugin was already removed
+fn verify_repo_url(git: &Git, url: &str) -> Result<()> {
+    git.ls_remote(url, *env::RTX_HTTP_TIMEOUT)
+        .wrap_err_with(|| format!("repository {url} does not exist or is not reachable"))
+}
+
+/// plugins that build refs from source tend to put a clone of the tool in the download dir,
+/// often copied from one they keep around or left there by `--download-only`. A tag pushed after
+/// that was cloned isn't in it, so tags are fetched before bin/install checks the ref out.
+fn fetch_missing_tags(
+    config: &Config,
+    tv: &ToolVersion,
+    gitref: &str,
+    pr: &ProgressReport,
+) -> Result<()> {
+    let git = Git::new(tv.download_path());
+    if !git.is_repo() || git.resolve_ref(gitref)?.is_some() {
+        return Ok(());
+    }
+    let proxy = git
+        .get_remote_url()
+        .and_then(|url| config.settings.proxy_for(&url));
+    pr.set_message(format!("fetching tags for {gitref}"));
+    if let Err(err) = git.with_proxy(proxy).fetch_tags() {
+        pr.warn(format!("failed to fetch tags: {err:#}"));
+    }
+    Ok(())
 }
 
-static EMPTY_HASH_MAP: Lazy<HashMap<String, String>> = Lazy::new(HashMap::new);
+#[cfg(test)]
+mod tests {
+    use crate::cmd;
+    use crate::test::{git_cmd, git_commit};
+    use crate::tool::Tool;
+    use crate::toolset::ToolVersionOptions;
+
+    use super::*;
+
+    #[test]
+    fn test_missing_scripts() {
+        let mut plugin = ExternalPlugin::new(&"missing-install".to_string());
+        plugin.plugin_path = dirs::HOME.join("fixtures/plugins/missing-install");
+        plugin.script_man = ScriptManager::new(plugin.plugin_path.clone());
+        assert_eq!(plugin.missing_scripts(), vec!["bin/install"]);
+
+        assert!(ExternalPlugin::new(&"dummy".to_string())
+            .missing_scripts()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_remote_versions_stale_while_revalidate() {
+        let name = "swr-versions".to_string();
+        let plugin_path = dirs::PLUGINS.join(&name);
+        let _ = remove_all(&plugin_path);
+        let _ = remove_all(dirs::CACHE.join(&name));
+        fs::create_dir_all(plugin_path.join("bin")).unwrap();
+        let list_all = plugin_path.join("bin/list-all");
+        fs::write(&list_all, "#!/usr/bin/env bash\necho 2.0.0\n").unwrap();
+        file::make_executable(&list_all).unwrap();
+        let plugin = ExternalPlugin::new(&name);
+        plugin
+            .remote_version_cache
+            .write(vec!["1.0.0".into()])
+            .unwrap();
+        let expired = filetime::FileTime::from_unix_time(
+            filetime::FileTime::now().unix_seconds() - 60 * 60 * 48,
+            0,
+        );
+        let cache_file = dirs::CACHE.join(&name).join("remote_versions.msgpack.z");
+        filetime::set_file_mtime(cache_file, expired).unwrap();
+
+        let settings = Settings::default();
+        let stale = plugin.list_remote_versions(&settings).unwrap();
+        crate::cache::finish_refreshes();
+        let refreshed = ExternalPlugin::new(&name).list_remote_versions(&settings);
+        remove_all(&plugin_path).unwrap();
+        remove_all(dirs::CACHE.join(&name)).unwrap();
+        assert_eq!(stale, vec!["1.0.0"]);
+        assert_eq!(refreshed.unwrap(), vec!["2.0.0"]);
+    }
+
+    #[test]
+    fn test_script_man_build_from_source() {
+        let name = "tiny".to_string();
+        let plugin = ExternalPlugin::new(&name);
+        let tool = Tool::new(name.clone(), Box::new(ExternalPlugin::new(&name)));
+        let tvr = ToolVersionRequest::Version(name, "3.1.0".into());
+        let tv = ToolVersion::new(&tool, tvr, ToolVersionOptions::default(), "3.1.0".into());
+        let key = OsString::from("RTX_BUILD_FROM_SOURCE");
+
+        let mut config = Config::load().unwrap();
+        let sm = plugin.script_man_for_tv(&config, &tv);
+        assert!(!sm.env.contains_key(&key));
+
+        config.settings.build_from_source = true;
+        let sm = plugin.script_man_for_tv(&config, &tv);
+        assert_eq!(sm.env.get(&key), Some(&OsString::from("1")));
+    }
+
+    #[test]
+    fn test_exec_env_isolation() {
+        let config = Config::load().unwrap();
+        let fixture = |name: &str| {
+            let mut plugin = ExternalPlugin::new(&name.to_string());
+            plugin.plugin_path = dirs::HOME.join("fixtures/plugins").join(name);
+            plugin.script_man = build_script_man(name, &plugin.plugin_path);
+            let tool = Tool::new(
+                name.to_string(),
+                Box::new(ExternalPlugin::new(&name.into())),
+            );
+            let tvr = ToolVersionRequest::Version(name.to_string(), "1.0.0".into());
+            let tv = ToolVersion::new(&tool, tvr, ToolVersionOptions::default(), "1.0.0".into());
+            (plugin, tv)
+        };
+        let (mut a, a_tv) = fixture("exec-env-a");
+        let (mut b, b_tv) = fixture("exec-env-b");
+        let a_env = a.fetch_exec_env(&config, &a_tv).unwrap();
+        let b_env = b.fetch_exec_env(&config, &b_tv).unwrap();
+        assert!(!a_env.set["EXEC_ENV_A_PATH"].is_empty());
+
+        // evaluate each again as if the other tool had already put its bin dir on PATH
+        let leak = |plugin: &mut ExternalPlugin, other: &ToolVersion| {
+            let path = plugin.script_man.env[OsStr::new("PATH")].clone();
+            let mut paths = vec![other.install_path().join("bin"), dirs::SHIMS.clone()];
+            paths.extend(split_paths(&path));
+            plugin.script_man = plugin
+                .script_man
+                .clone()
+                .with_env("PATH", join_paths(paths).unwrap());
+        };
+        leak(&mut a, &b_tv);
+        leak(&mut b, &a_tv);
+        assert_eq!(a.fetch_exec_env(&config, &a_tv).unwrap(), a_env);
+        assert_eq!(b.fetch_exec_env(&config, &b_tv).unwrap(), b_env);
+    }
+
+    #[test]
+    fn test_uninstall_invalidates_cache() {
+        let name = "tiny".to_string();
+        let plugin = ExternalPlugin::new(&name);
+        let tool = Tool::new(name.clone(), Box::new(ExternalPlugin::new(&name)));
+        let tvr = ToolVersionRequest::Version(name, "0.9.9".into());
+        let tv = ToolVersion::new(&tool, tvr, ToolVersionOptions::default(), "0.9.9".into());
+        let config = Config::load().unwrap();
+        let pr = ProgressReport::new(false);
+        let exec_env = |plugin: &ExternalPlugin| plugin.exec_env(&config, &tv).unwrap().set;
+
+        file::create_dir_all(tv.install_path()).unwrap();
+        plugin.install_version(&config, &tv, &pr).unwrap();
+        assert!(tool
+            .list_installed_versions()
+            .unwrap()
+            .contains(&tv.version));
+        assert_eq!(exec_env(&plugin)["JDXCODE_TINY"], "0.9.9");
+
+        plugin.uninstall_version(&config, &tv).unwrap();
+        remove_all(tv.install_path()).unwrap();
+        assert!(!tool
+            .list_installed_versions()
+            .unwrap()
+            .contains(&tv.version));
+        assert!(!tv.cache_path().exists());
+
+        // reinstalled as something else, the env comes from the new install
+        file::create_dir_all(tv.install_path()).unwrap();
+        fs::write(tv.install_path().join("VERSION"), "reinstalled").unwrap();
+        let reinstalled = exec_env(&plugin);
+        remove_all(tv.install_path()).unwrap();
+        remove_all(tv.cache_path()).unwrap();
+        assert_eq!(reinstalled["JDXCODE_TINY"], "reinstalled");
+    }
+
+    #[test]
+    fn test_exec_env_cache_opts() {
+        let name = "exec-env-opts".to_string();
+        let dir = dirs::CACHE.join("exec-env-opts-plugin");
+        let _ = remove_all(&dir);
+        let _ = remove_all(dirs::CACHE.join(&name));
+        file::create_dir_all(dir.join("bin")).unwrap();
+        let script = dir.join("bin/exec-env");
+        fs::write(
+            &script,
+            "#!/usr/bin/env bash\nexport EXEC_ENV_FLAVOR=\"$RTX_TOOL_OPTS__FLAVOR\"\n",
+        )
+        .unwrap();
+        file::make_executable(&script).unwrap();
+        let mut plugin = ExternalPlugin::new(&name);
+        plugin.plugin_path = dir.clone();
+        plugin.script_man = build_script_man(&name, &plugin.plugin_path);
+        let tool = Tool::new(name.clone(), Box::new(ExternalPlugin::new(&name)));
+        let tv = |flavor: &str| {
+            let tvr = ToolVersionRequest::Version(name.clone(), "1.0.0".into());
+            let opts = ToolVersionOptions::from([("flavor".to_string(), flavor.to_string())]);
+            ToolVersion::new(&tool, tvr, opts, "1.0.0".into())
+        };
+        let config = Config::load().unwrap();
+
+        let (a, b) = (tv("a"), tv("b"));
+        let env_a = plugin.exec_env(&config, &a).unwrap();
+        let env_b = plugin.exec_env(&config, &b).unwrap();
+        let cache_files = fs::read_dir(a.cache_path()).unwrap().count();
+        remove_all(&dir).unwrap();
+        remove_all(dirs::CACHE.join(&name)).unwrap();
+        assert_eq!(env_a.set["EXEC_ENV_FLAVOR"], "a");
+        assert_eq!(env_b.set["EXEC_ENV_FLAVOR"], "b");
+        assert_eq!(cache_files, 2);
+    }
+
+    #[test]
+    fn test_install_ref_fetches_tags() {
+        let name = "fetch-tags".to_string();
+        let dir = dirs::CACHE.join("fetch-tags-plugin");
+        let origin = dirs::CACHE.join("fetch-tags-origin");
+        let stale = dirs::CACHE.join("fetch-tags-stale");
+        for d in [&dir, &origin, &stale] {
+            let _ = remove_all(d);
+        }
+        file::create_dir_all(&origin).unwrap();
+        fs::write(origin.join("README.md"), "first").unwrap();
+        git_commit(&origin, "first");
+        Git::new(stale.clone())
+            .clone(origin.to_string_lossy().as_ref())
+            .unwrap();
+        // tagged after the clone bin/download copies was made
+        fs::write(origin.join("README.md"), "tagged").unwrap();
+        git_commit(&origin, "tagged");
+        git_cmd(&origin, &["tag", "-a", "v1.0.0", "-m", "v1.0.0"])
+            .run()
+            .unwrap();
+
+        file::create_dir_all(dir.join("bin")).unwrap();
+        for (script, body) in [
+            ("download", format!("cp -a {}/. \"$RTX_DOWNLOAD_PATH\"", stale.display())),
+            (
+                "install",
+                "git -C \"$RTX_DOWNLOAD_PATH\" -c advice.detachedHead=false checkout -q \"$ASDF_INSTALL_VERSION\"\n\
+                 cp \"$RTX_DOWNLOAD_PATH/README.md\" \"$RTX_INSTALL_PATH\""
+                    .to_string(),
+            ),
+        ] {
+            let path = dir.join("bin").join(script);
+            fs::write(&path, format!("#!/usr/bin/env bash\nset -e\n{body}\n")).unwrap();
+            file::make_executable(&path).unwrap();
+        }
+        let mut plugin = ExternalPlugin::new(&name);
+        plugin.plugin_path = dir.clone();
+        plugin.script_man = build_script_man(&name, &plugin.plugin_path);
+        let tool = Tool::new(name.clone(), Box::new(plugin));
+        let tvr = ToolVersionRequest::Ref(name.clone(), "v1.0.0".into());
+        let tv = ToolVersion::new(
+            &tool,
+            tvr,
+            ToolVersionOptions::default(),
+            "ref-v1.0.0".into(),
+        );
+
+        let config = Config::load().unwrap();
+        let mut pr = ProgressReport::new(false);
+        let result = tool.install_version(&config, &tv, &mut pr, false);
+        let installed = fs::read_to_string(tv.install_path().join("README.md"));
+        for d in [&dir, &origin, &stale] {
+            remove_all(d).unwrap();
+        }
+        for d in [&*dirs::INSTALLS, &*dirs::DOWNLOADS, &*dirs::CACHE] {
+            let _ = remove_all(d.join(&name));
+        }
+        result.unwrap();
+        assert_eq!(installed.unwrap(), "tagged");
+    }
+
+    #[test]
+    fn test_metadata() {
+        let name = "metadata".to_string();
+        let mut plugin = ExternalPlugin::new(&name);
+        plugin.plugin_path = dirs::HOME.join("fixtures/plugins/metadata");
+        plugin.script_man = build_script_man(&name, &plugin.plugin_path);
+        plugin.toml =
+            RtxPluginToml::from_file(&plugin.plugin_path.join("rtx.plugin.toml")).unwrap();
+        file::create_dir_all(plugin.installs_path.join("2.0.0")).unwrap();
+        file::make_symlink(
+            &plugin.installs_path.join("2.0.0"),
+            &plugin.installs_path.join("latest"),
+        )
+        .unwrap();
+
+        let metadata = plugin.metadata(&Settings::default());
+        remove_all(&plugin.installs_path).unwrap();
+        let metadata = metadata.unwrap();
+        assert_eq!(metadata.name, "metadata");
+        assert!(matches!(metadata.plugin_type, PluginType::External));
+        assert_eq!(metadata.remote_url, None);
+        assert_eq!(metadata.sha, None);
+        assert_eq!(metadata.installed_versions, 1);
+        assert_eq!(
+            metadata.aliases,
+            BTreeMap::from([
+                ("lts".to_string(), "2.0.0".to_string()),
+                ("stable".to_string(), "2.1.0".to_string()),
+            ])
+        );
+        assert_eq!(metadata.legacy_filenames, vec![".metadata-version"]);
+        assert_eq!(metadata.min_rtx_version, Some("1.30.0".into()));
+    }
+
+    #[test]
+    fn test_verify_repo_url() {
+        let repo = dirs::CACHE.join("verify-repo-url");
+        let _ = remove_all(&repo);
+        file::create_dir_all(&repo).unwrap();
+        cmd!("git", "init", "-q", &repo).run().unwrap();
+        let git = Git::new(dirs::PLUGINS.join("verify-repo-url"));
+
+        let reachable = verify_repo_url(&git, repo.to_str().unwrap());
+        let unreachable = verify_repo_url(&git, repo.join("missing").to_str().unwrap());
+        remove_all(&repo).unwrap();
+        assert!(reachable.is_ok());
+        assert!(format!("{:#}", unreachable.unwrap_err()).contains("is not reachable"));
+    }
+
+    #[test]
+    fn test_install_hooks() {
+        let name = "install-hooks".to_string();
+        let dir = dirs::CACHE.join("install-hooks");
+        let log = dir.join("hooks.log");
+        let _ = remove_all(&dir);
+        file::create_dir_all(dir.join("bin")).unwrap();
+        let script = |name: &str, body: &str| {
+            let path = dir.join("bin").join(name);
+            fs::write(&path, format!("#!/usr/bin/env bash\n{body}\n")).unwrap();
+            file::make_executable(&path).unwrap();
+        };
+        script(
+            "pre-install",
+            &format!("echo \"pre $RTX_INSTALL_VERSION\" >> {}", log.display()),
+        );
+        script(
+            "install",
+            &format!(
+                "mkdir -p $RTX_INSTALL_PATH && echo install >> {}",
+                log.display()
+            ),
+        );
+        let mut plugin = ExternalPlugin::new(&name);
+        plugin.plugin_path = dir.clone();
+        plugin.script_man = build_script_man(&name, &plugin.plugin_path);
+        plugin.toml.post_install = Some(format!(
+            "echo \"post $RTX_INSTALL_PATH\" >> {}",
+            log.display()
+        ));
+        let tool = Tool::new(name.clone(), Box::new(ExternalPlugin::new(&name)));
+        let tvr = ToolVersionRequest::Version(name.clone(), "1.0.0".into());
+        let tv = ToolVersion::new(&tool, tvr, ToolVersionOptions::default(), "1.0.0".into());
+        let config = Config::load().unwrap();
+        let pr = ProgressReport::new(false);
+
+        plugin.install_version(&config, &tv, &pr).unwrap();
+        let _ = remove_all(dirs::INSTALLS.join(&name));
+        assert_eq!(
+            fs::read_to_string(&log).unwrap(),
+            format!("pre 1.0.0\ninstall\npost {}\n", tv.install_path().display())
+        );
+
+        // a failing pre-install hook stops the install before it starts
+        fs::remove_file(&log).unwrap();
+        script("pre-install", "exit 1");
+        let err = plugin.install_version(&config, &tv, &pr).unwrap_err();
+        remove_all(&dir).unwrap();
+        assert_eq!(
+            err.to_string(),
+            "pre-install hook of plugin install-hooks failed"
+        );
+        assert!(!tv.install_path().exists());
+    }
+
+    #[test]
+    fn test_install_from_mirror() {
+        let name = "install-from-mirror".to_string();
+        let repo = dirs::CACHE.join("install-from-mirror");
+        let _ = remove_all(&repo);
+        file::create_dir_all(&repo).unwrap();
+        git_cmd(&repo, &["init", "-q"]).run().unwrap();
+        git_cmd(&repo, &["commit", "-q", "--allow-empty", "-m", "init"])
+            .run()
+            .unwrap();
+        let mirror = format!("file://{}", repo.display());
+        let mut plugin = ExternalPlugin::new(&name);
+        plugin.repo_urls = vec![
+            format!(
+                "file://{}",
+                dirs::CACHE.join("missing-mirror.git").display()
+            ),
+            mirror.clone(),
+        ];
+        let config = Config::load().unwrap();
+        let mut pr = ProgressReport::new(false);
+
+        let result = plugin.install(&config, &mut pr);
+        let remote_url = Git::new(plugin.plugin_path.clone()).get_remote_url();
+        remove_all(&plugin.plugin_path).unwrap();
+        remove_all(&repo).unwrap();
+        result.unwrap();
+        assert_eq!(remote_url, Some(mirror));
+    }
+}
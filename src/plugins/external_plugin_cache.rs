@@ -1,10 +1,10 @@
 use crate::cache::CacheManager;
 use crate::config::Config;
 use crate::hash::hash_to_str;
-use crate::plugins::ExternalPlugin;
+use crate::plugins::{ExecEnvVars, ExternalPlugin};
 use crate::tera::{get_tera, BASE_CONTEXT};
-use crate::toolset::{ToolVersion, ToolVersionRequest};
-use crate::{dirs, env};
+use crate::toolset::{ToolVersion, ToolVersionOptions, ToolVersionRequest};
+use crate::{dirs, env, file};
 use color_eyre::eyre::{eyre, Result};
 use std::collections::HashMap;
 use std::path::PathBuf;
@@ -13,7 +13,7 @@ use std::sync::RwLock;
 #[derive(Debug, Default)]
 pub struct ExternalPluginCache {
     list_bin_paths: RwLock<HashMap<ToolVersionRequest, CacheManager<Vec<PathBuf>>>>,
-    exec_env: RwLock<HashMap<ToolVersionRequest, CacheManager<HashMap<String, String>>>>,
+    exec_env: RwLock<HashMap<(ToolVersionRequest, ToolVersionOptions), CacheManager<ExecEnvVars>>>,
 }
 
 impl ExternalPluginCache {
@@ -51,18 +51,25 @@ impl ExternalPluginCache {
         plugin: &ExternalPlugin,
         tv: &ToolVersion,
         fetch: F,
-    ) -> Result<HashMap<String, String>>
+    ) -> Result<ExecEnvVars>
     where
-        F: FnOnce() -> Result<HashMap<String, String>>,
+        F: FnOnce() -> Result<ExecEnvVars>,
     {
         let mut w = self.exec_env.write().unwrap();
-        let cm = w.entry(tv.request.clone()).or_insert_with(|| {
+        let key = (tv.request.clone(), tv.opts.clone());
+        let cm = w.entry(key).or_insert_with(|| {
             let exec_env_filename = match &plugin.toml.exec_env.cache_key {
                 Some(key) => {
                     let key = render_cache_key(config, tv, key);
                     let filename = format!("{}.msgpack.z", key);
                     tv.cache_path().join("exec_env").join(filename)
                 }
+                // the opts are passed to bin/exec-env so each set of them gets its own cache
+                None if !tv.opts.is_empty() => {
+                    let mut opts = hash_to_str(&tv.opts);
+                    opts.truncate(10);
+                    tv.cache_path().join(format!("exec_env-{opts}.msgpack.z"))
+                }
                 None => tv.cache_path().join("exec_env.msgpack.z"),
             };
             CacheManager::new(exec_env_filename)
@@ -72,6 +79,18 @@ impl ExternalPluginCache {
         });
         cm.get_or_try_init(fetch).cloned()
     }
+
+    /// forgets the bin paths and env of an uninstalled version, in memory and on disk, so
+    /// reinstalling it in the same process doesn't reuse what the old install returned
+    pub fn invalidate(&self, tv: &ToolVersion) -> Result<()> {
+        self.list_bin_paths.write().unwrap().remove(&tv.request);
+        self.exec_env
+            .write()
+            .unwrap()
+            .retain(|(request, _), _| request != &tv.request);
+        file::remove_all(tv.cache_path())?;
+        Ok(())
+    }
 }
 
 fn render_cache_key(config: &Config, tv: &ToolVersion, cache_key: &[String]) -> String {
@@ -2,13 +2,15 @@ use std::collections::{BTreeMap, HashMap};
 use std::fmt::Debug;
 use std::path::{Path, PathBuf};
 
-use color_eyre::eyre::Result;
+use color_eyre::eyre::{eyre, Result};
+use serde_derive::{Deserialize, Serialize};
 
 pub use external_plugin::ExternalPlugin;
 pub use rtx_plugin_toml::RtxPluginToml;
 pub use script_manager::{Script, ScriptManager};
 
 use crate::config::{Config, Settings};
+use crate::dirs;
 use crate::toolset::ToolVersion;
 use crate::ui::progress_report::ProgressReport;
 
@@ -22,10 +24,19 @@ pub type PluginName = String;
 
 pub trait Plugin: Debug + Send + Sync {
     fn name(&self) -> &PluginName;
+    /// human-friendly name for output, the name is still used to refer to the plugin
+    fn display_name(&self) -> &str {
+        self.name()
+    }
     fn get_type(&self) -> PluginType {
         PluginType::Core
     }
     fn list_remote_versions(&self, settings: &Settings) -> Result<Vec<String>>;
+    /// whether an exact version is available to install, without installing it
+    fn version_exists(&self, settings: &Settings, version: &str) -> Result<bool> {
+        let versions = self.list_remote_versions(settings)?;
+        Ok(versions.iter().any(|v| v == version))
+    }
     fn latest_stable_version(&self, _settings: &Settings) -> Result<Option<String>> {
         Ok(None)
     }
@@ -38,8 +49,15 @@ pub trait Plugin: Debug + Send + Sync {
     fn install(&self, _config: &Config, _pr: &mut ProgressReport) -> Result<()> {
         Ok(())
     }
-    fn update(&self, _git_ref: Option<String>) -> Result<()> {
-        Ok(())
+    /// returns the (pre, post) revisions if the plugin was updated
+    /// `force` discards local changes to the plugin instead of skipping it
+    fn update(
+        &self,
+        _config: &Config,
+        _git_ref: Option<String>,
+        _force: bool,
+    ) -> Result<Option<(String, String)>> {
+        Ok(None)
     }
     fn uninstall(&self, _pr: &ProgressReport) -> Result<()> {
         Ok(())
@@ -62,17 +80,102 @@ pub trait Plugin: Debug + Send + Sync {
     }
     fn install_version(&self, config: &Config, tv: &ToolVersion, pr: &ProgressReport)
         -> Result<()>;
+    /// only fetches the version into its download path, for `rtx install --download-only`
+    fn download_version(
+        &self,
+        _config: &Config,
+        _tv: &ToolVersion,
+        _pr: &ProgressReport,
+    ) -> Result<()> {
+        Err(eyre!("{} does not support --download-only", self.name()))
+    }
     fn uninstall_version(&self, _config: &Config, _tv: &ToolVersion) -> Result<()> {
         Ok(())
     }
+    /// runs before anything is removed, an error here aborts the uninstall
+    fn pre_uninstall(&self, _config: &Config, _tv: &ToolVersion) -> Result<()> {
+        Ok(())
+    }
+    /// runs once the version has been removed
+    fn post_uninstall(&self, _config: &Config, _tv: &ToolVersion) -> Result<()> {
+        Ok(())
+    }
     fn list_bin_paths(&self, _config: &Config, tv: &ToolVersion) -> Result<Vec<PathBuf>> {
         Ok(vec![tv.install_path().join("bin")])
     }
-    fn exec_env(&self, _config: &Config, _tv: &ToolVersion) -> Result<HashMap<String, String>> {
-        Ok(HashMap::new())
+    fn exec_env(&self, _config: &Config, _tv: &ToolVersion) -> Result<ExecEnvVars> {
+        Ok(ExecEnvVars::default())
+    }
+    /// template for where versions are installed relative to ~/.local/share/rtx/installs
+    /// e.g.: "{{plugin}}/{{version}}", the path has to stay inside of it
+    fn install_path_template(&self) -> Option<String> {
+        None
+    }
+    /// scripts like "bin/install" the plugin needs but doesn't have
+    fn missing_scripts(&self) -> Vec<String> {
+        vec![]
+    }
+    /// free disk space in bytes the plugin needs to install a version, checked before installing
+    fn min_free_disk(&self) -> Option<u64> {
+        None
+    }
+    /// shell command run with the new version's bin paths and exec-env after installing it,
+    /// the install fails if it does. e.g.: "node --version"
+    fn post_install_test(&self) -> Option<String> {
+        None
+    }
+    /// everything `rtx plugins info` shows about the plugin
+    fn metadata(&self, settings: &Settings) -> Result<PluginMetadata> {
+        Ok(PluginMetadata {
+            name: self.name().clone(),
+            plugin_type: self.get_type(),
+            remote_url: self.get_remote_url(),
+            sha: None,
+            branch: None,
+            installed_versions: count_installed_versions(&dirs::INSTALLS.join(self.name())),
+            aliases: self.get_aliases(settings)?,
+            legacy_filenames: self.legacy_filenames(settings)?,
+            min_rtx_version: None,
+        })
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct PluginMetadata {
+    pub name: PluginName,
+    #[serde(rename = "type")]
+    pub plugin_type: PluginType,
+    pub remote_url: Option<String>,
+    pub sha: Option<String>,
+    pub branch: Option<String>,
+    pub installed_versions: usize,
+    pub aliases: BTreeMap<String, String>,
+    pub legacy_filenames: Vec<String>,
+    pub min_rtx_version: Option<String>,
+}
+
+/// how a tool changes the environment of the commands run with it
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExecEnvVars {
+    /// vars that are added or get a new value
+    pub set: HashMap<String, String>,
+    /// vars that are removed from the parent environment
+    pub unset: Vec<String>,
+}
+
+/// versions are directories, symlinks like "latest" point to them and are not counted
+pub fn count_installed_versions(installs_path: &Path) -> usize {
+    match std::fs::read_dir(installs_path) {
+        Ok(entries) => entries
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().map_or(false, |t| t.is_dir()))
+            .count(),
+        Err(_) => 0,
     }
 }
 
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "lowercase")]
 pub enum PluginType {
     #[allow(dead_code)]
     Core,
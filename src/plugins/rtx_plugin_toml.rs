@@ -3,8 +3,11 @@ use std::path::Path;
 
 use color_eyre::eyre::eyre;
 use color_eyre::{Result, Section};
+use regex::Regex;
 use toml_edit::{Document, Item, Value};
+use versions::Versioning;
 
+use crate::file::parse_size;
 use crate::parse_error;
 
 #[derive(Debug, Default, Clone)]
@@ -13,8 +16,48 @@ pub struct RtxPluginTomlScriptConfig {
     pub data: Option<String>,
 }
 
+/// rewrites versions from bin/list-all into keys that sort correctly,
+/// e.g.: "12-25-2023" with regex `^(\d+)-(\d+)-(\d+)$` and sort-key "$3.$1.$2" sorts as "2023.12.25".
+/// The original strings are what get displayed and installed.
+#[derive(Debug, Clone)]
+pub struct RtxPluginTomlListAllTransform {
+    pub regex: Regex,
+    pub sort_key: String,
+}
+
+impl RtxPluginTomlListAllTransform {
+    /// versions the regex does not match are sorted by their original string
+    pub fn sort_key(&self, version: &str) -> String {
+        match self.regex.captures(version) {
+            Some(caps) => {
+                let mut key = String::new();
+                caps.expand(&self.sort_key, &mut key);
+                key
+            }
+            None => version.to_string(),
+        }
+    }
+
+    pub fn sort(&self, versions: Vec<String>) -> Vec<String> {
+        let mut keyed = versions
+            .into_iter()
+            .map(|v| (Versioning::new(&self.sort_key(&v)).unwrap_or_default(), v))
+            .collect::<Vec<_>>();
+        keyed.sort_by(|(a, _), (b, _)| a.cmp(b));
+        keyed.into_iter().map(|(_, v)| v).collect()
+    }
+}
+
 #[derive(Debug, Default, Clone)]
 pub struct RtxPluginToml {
+    pub display_name: Option<String>,
+    pub install_path: Option<String>,
+    pub min_free_disk: Option<u64>,
+    pub min_rtx_version: Option<String>,
+    pub post_install_test: Option<String>,
+    pub pre_install: Option<String>,
+    pub post_install: Option<String>,
+    pub list_all_transform: Option<RtxPluginTomlListAllTransform>,
     pub exec_env: RtxPluginTomlScriptConfig,
     pub list_aliases: RtxPluginTomlScriptConfig,
     pub list_bin_paths: RtxPluginTomlScriptConfig,
@@ -43,6 +86,41 @@ impl RtxPluginToml {
         let doc: Document = s.parse().suggestion("ensure file is valid TOML")?;
         for (k, v) in doc.iter() {
             match k {
+                "display-name" => match v.as_value() {
+                    Some(v) => self.display_name = Some(self.parse_string(k, v)?),
+                    _ => parse_error!(k, v, "string")?,
+                },
+                "install-path" => match v.as_value() {
+                    Some(v) => self.install_path = Some(self.parse_string(k, v)?),
+                    _ => parse_error!(k, v, "string")?,
+                },
+                "min-free-disk" => match v.as_value() {
+                    Some(Value::Integer(i)) => match u64::try_from(*i.value()) {
+                        Ok(size) => self.min_free_disk = Some(size),
+                        Err(_) => parse_error!(k, v, "non-negative integer")?,
+                    },
+                    Some(Value::String(s)) => self.min_free_disk = Some(parse_size(s.value())?),
+                    _ => parse_error!(k, v, "string or integer")?,
+                },
+                "min-rtx-version" => match v.as_value() {
+                    Some(v) => self.min_rtx_version = Some(self.parse_string(k, v)?),
+                    _ => parse_error!(k, v, "string")?,
+                },
+                "post-install-test" => match v.as_value() {
+                    Some(v) => self.post_install_test = Some(self.parse_string(k, v)?),
+                    _ => parse_error!(k, v, "string")?,
+                },
+                "pre-install" => match v.as_value() {
+                    Some(v) => self.pre_install = Some(self.parse_string(k, v)?),
+                    _ => parse_error!(k, v, "string")?,
+                },
+                "post-install" => match v.as_value() {
+                    Some(v) => self.post_install = Some(self.parse_string(k, v)?),
+                    _ => parse_error!(k, v, "string")?,
+                },
+                "list-all-transform" => {
+                    self.list_all_transform = Some(self.parse_list_all_transform(k, v)?)
+                }
                 "exec-env" => self.exec_env = self.parse_script_config(k, v)?,
                 "list-aliases" => self.list_aliases = self.parse_script_config(k, v)?,
                 "list-bin-paths" => self.list_bin_paths = self.parse_script_config(k, v)?,
@@ -79,6 +157,37 @@ impl RtxPluginToml {
         }
     }
 
+    fn parse_list_all_transform(
+        &mut self,
+        key: &str,
+        v: &Item,
+    ) -> Result<RtxPluginTomlListAllTransform> {
+        let table = match v.as_table_like() {
+            Some(table) => table,
+            _ => parse_error!(key, v, "table")?,
+        };
+        let mut regex = None;
+        let mut sort_key = None;
+        for (k, v) in table.iter() {
+            let key = format!("{}.{}", key, k);
+            let v = match v.as_value() {
+                Some(v) => self.parse_string(&key, v)?,
+                _ => parse_error!(key, v, "string")?,
+            };
+            match k {
+                "regex" => {
+                    regex = Some(Regex::new(&v).map_err(|err| eyre!("invalid {key}: {err}"))?)
+                }
+                "sort-key" => sort_key = Some(v),
+                _ => Err(eyre!("unknown key: {}", key))?,
+            }
+        }
+        match (regex, sort_key) {
+            (Some(regex), Some(sort_key)) => Ok(RtxPluginTomlListAllTransform { regex, sort_key }),
+            _ => Err(eyre!("{key} requires both regex and sort-key")),
+        }
+    }
+
     fn parse_string_array(&mut self, k: &str, v: &Item) -> Result<Vec<String>> {
         match v.as_array() {
             Some(arr) => {
@@ -142,6 +251,91 @@ mod tests {
         "###);
     }
 
+    #[test]
+    fn test_install_path() {
+        let cf = parse(r#"install-path = "{{version}}/{{plugin}}""#);
+
+        assert_eq!(cf.install_path, Some("{{version}}/{{plugin}}".into()));
+    }
+
+    #[test]
+    fn test_min_free_disk() {
+        let cf = parse(r#"min-free-disk = "2G""#);
+        assert_eq!(cf.min_free_disk, Some(2 * 1024 * 1024 * 1024));
+        let cf = parse("min-free-disk = 1024");
+        assert_eq!(cf.min_free_disk, Some(1024));
+        let err = RtxPluginToml::init()
+            .parse("min-free-disk = -1")
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            r#"expected value of "min-free-disk" to be a non-negative integer, got:  -1"#
+        );
+    }
+
+    #[test]
+    fn test_display_name() {
+        let cf = parse(r#"display-name = "Node.js""#);
+        assert_eq!(cf.display_name, Some("Node.js".into()));
+        assert_eq!(parse("").display_name, None);
+    }
+
+    #[test]
+    fn test_min_rtx_version() {
+        let cf = parse(r#"min-rtx-version = "1.30.0""#);
+        assert_eq!(cf.min_rtx_version, Some("1.30.0".into()));
+    }
+
+    #[test]
+    fn test_post_install_test() {
+        let cf = parse(r#"post-install-test = "node --version""#);
+        assert_eq!(cf.post_install_test, Some("node --version".into()));
+    }
+
+    #[test]
+    fn test_install_hooks() {
+        let cf = parse(&formatdoc! {r#"
+        pre-install = "./scripts/check-deps.sh"
+        post-install = "rm -rf $RTX_DOWNLOAD_PATH/tmp"
+        "#});
+        assert_eq!(cf.pre_install, Some("./scripts/check-deps.sh".into()));
+        assert_eq!(
+            cf.post_install,
+            Some("rm -rf $RTX_DOWNLOAD_PATH/tmp".into())
+        );
+    }
+
+    #[test]
+    fn test_list_all_transform() {
+        let cf = parse(&formatdoc! {r#"
+        [list-all-transform]
+        regex = '^(\d+)-(\d+)-(\d+)$'
+        sort-key = "$3.$1.$2"
+        "#});
+        let transform = cf.list_all_transform.unwrap();
+        assert_eq!(transform.sort_key("12-25-2022"), "2022.12.25");
+        let versions = ["01-15-2023", "12-25-2022", "06-01-2023", "02-03-2021"]
+            .iter()
+            .map(|v| v.to_string())
+            .collect();
+        assert_eq!(
+            transform.sort(versions),
+            vec!["02-03-2021", "12-25-2022", "01-15-2023", "06-01-2023"]
+        );
+    }
+
+    #[test]
+    fn test_list_all_transform_invalid() {
+        let mut cf = RtxPluginToml::init();
+        let err = cf
+            .parse("[list-all-transform]\nregex = '^(\\d+)$'")
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "list-all-transform requires both regex and sort-key"
+        );
+    }
+
     fn parse(s: &str) -> RtxPluginToml {
         let mut cf = RtxPluginToml::init();
         cf.parse(s).unwrap();
@@ -3,18 +3,21 @@ use std::collections::HashMap;
 use std::ffi::OsString;
 use std::fmt;
 use std::fmt::{Display, Formatter};
+use std::os::unix::process::CommandExt;
 use std::path::PathBuf;
 use std::process::Output;
+use std::thread;
+use std::time::{Duration, Instant};
 
 use color_eyre::eyre::{Context, Result};
 use duct::Expression;
 use indexmap::indexmap;
 use once_cell::sync::Lazy;
 
-use crate::cmd::{cmd, CmdLineRunner};
+use crate::cmd::{cmd, kill_process_group, CmdLineRunner};
 use crate::config::Settings;
 use crate::errors::Error;
-use crate::errors::Error::ScriptFailed;
+use crate::errors::Error::{ScriptFailed, ScriptTimedOut};
 use crate::file::{basename, display_path};
 use crate::ui::progress_report::ProgressReport;
 use crate::{dirs, env};
@@ -28,24 +31,25 @@ pub struct ScriptManager {
 
 #[derive(Debug, Clone)]
 pub enum Script {
-    // PreInstall,
-    // PostInstall,
-    // PreUninstall,
-    // PostUninstall,
-
     // Plugin
     LatestStable,
+    PrePluginUpdate,
+    PostPluginUpdate,
     ListAliases,
     ListAll,
     ListLegacyFilenames,
     ParseLegacyFile(String),
 
     // RuntimeVersion
+    PreInstall,
     Download,
     ExecEnv,
     Install,
+    PostInstall,
     ListBinPaths,
+    PreUninstall,
     Uninstall,
+    PostUninstall,
 }
 
 impl Display for Script {
@@ -53,14 +57,20 @@ impl Display for Script {
         match self {
             // Plugin
             Script::LatestStable => write!(f, "latest-stable"),
+            Script::PrePluginUpdate => write!(f, "pre-plugin-update"),
+            Script::PostPluginUpdate => write!(f, "post-plugin-update"),
             Script::ListAll => write!(f, "list-all"),
             Script::ListLegacyFilenames => write!(f, "list-legacy-filenames"),
             Script::ListAliases => write!(f, "list-aliases"),
             Script::ParseLegacyFile(_) => write!(f, "parse-legacy-file"),
 
             // RuntimeVersion
+            Script::PreInstall => write!(f, "pre-install"),
             Script::Install => write!(f, "install"),
+            Script::PostInstall => write!(f, "post-install"),
+            Script::PreUninstall => write!(f, "pre-uninstall"),
             Script::Uninstall => write!(f, "uninstall"),
+            Script::PostUninstall => write!(f, "post-uninstall"),
             Script::ListBinPaths => write!(f, "list-bin-paths"),
             Script::ExecEnv => write!(f, "exec-env"),
             Script::Download => write!(f, "download"),
@@ -134,13 +144,15 @@ impl ScriptManager {
 
     pub fn run(&self, settings: &Settings, script: &Script) -> Result<()> {
         let cmd = self.cmd(settings, script);
-        let Output { status, .. } = cmd.unchecked().run()?;
+        let path = display_path(&self.get_script_path(script));
+        let Output { status, .. } = match script_timeout(settings) {
+            Some(timeout) => run_with_timeout(cmd, &path, timeout)?,
+            None => cmd.unchecked().run()?,
+        };
 
         match status.success() {
             true => Ok(()),
-            false => {
-                Err(ScriptFailed(display_path(&self.get_script_path(script)), Some(status)).into())
-            }
+            false => Err(ScriptFailed(path, Some(status)).into()),
         }
     }
 
@@ -149,8 +161,19 @@ impl ScriptManager {
         if !verbose && !settings.raw {
             cmd = cmd.stderr_null();
         }
-        cmd.read()
-            .with_context(|| ScriptFailed(display_path(&self.get_script_path(script)), None))
+        let path = display_path(&self.get_script_path(script));
+        let timeout = match script_timeout(settings) {
+            Some(timeout) => timeout,
+            None => return cmd.read().with_context(|| ScriptFailed(path, None)),
+        };
+        let Output { status, stdout, .. } = run_with_timeout(cmd.stdout_capture(), &path, timeout)?;
+        match status.success() {
+            // trimmed like duct's read()
+            true => Ok(String::from_utf8(stdout)?
+                .trim_end_matches('\n')
+                .to_string()),
+            false => Err(ScriptFailed(path, Some(status)).into()),
+        }
     }
 
     pub fn run_by_line(
@@ -159,16 +182,121 @@ impl ScriptManager {
         script: &Script,
         pr: &ProgressReport,
     ) -> Result<()> {
-        let mut cmd = CmdLineRunner::new(settings, self.get_script_path(script));
+        let cmd = CmdLineRunner::new(settings, self.get_script_path(script));
+        let path = display_path(&self.get_script_path(script));
+        self.execute_by_line(cmd, path, pr)
+    }
+
+    /// runs a shell command (e.g.: a hook from rtx.plugin.toml) with the same env as the scripts
+    pub fn run_command_by_line(
+        &self,
+        settings: &Settings,
+        command: &str,
+        pr: &ProgressReport,
+    ) -> Result<()> {
+        let mut cmd = CmdLineRunner::new(settings, "sh");
+        cmd.arg("-c").arg(command);
+        self.execute_by_line(cmd, command.to_string(), pr)
+    }
+
+    // install scripts run by line are not affected by script_timeout, they can prompt or take a
+    // long time to compile. RTX_INSTALL_TIMEOUT is the opt-in for those.
+    fn execute_by_line<'a>(
+        &self,
+        mut cmd: CmdLineRunner<'a>,
+        path: String,
+        pr: &'a ProgressReport,
+    ) -> Result<()> {
         cmd.with_pr(pr).env_clear().envs(&self.env);
+        if let Some(timeout) = *env::RTX_INSTALL_TIMEOUT {
+            cmd.with_timeout(timeout);
+        }
         if let Err(e) = cmd.execute() {
             let status = match e.downcast_ref::<Error>() {
                 Some(ScriptFailed(_, status)) => *status,
+                Some(ScriptTimedOut(_, timeout)) => {
+                    return Err(ScriptTimedOut(path, *timeout).into());
+                }
                 _ => None,
             };
-            let path = display_path(&self.get_script_path(script));
             return Err(ScriptFailed(path, status).into());
         }
         Ok(())
     }
 }
+
+/// the script_timeout setting, which is not enforced in raw mode since the script is attached to
+/// the terminal and moving it to its own process group would stop it from reading from it
+fn script_timeout(settings: &Settings) -> Option<Duration> {
+    match settings.raw {
+        true => None,
+        false => settings.script_timeout,
+    }
+}
+
+/// kills the script and anything it started if it is still running after `timeout`
+fn run_with_timeout(cmd: Expression, path: &str, timeout: Duration) -> Result<Output> {
+    let handle = cmd
+        .before_spawn(|cmd| {
+            cmd.process_group(0);
+            Ok(())
+        })
+        .unchecked()
+        .start()?;
+    let deadline = Instant::now() + timeout;
+    loop {
+        if let Some(output) = handle.try_wait()? {
+            return Ok(output.clone());
+        }
+        if Instant::now() >= deadline {
+            for pid in handle.pids() {
+                kill_process_group(pid);
+            }
+            let _ = handle.wait();
+            return Err(ScriptTimedOut(path.to_string(), timeout).into());
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use pretty_assertions::assert_eq;
+
+    use crate::file;
+    use crate::file::remove_all;
+
+    use super::*;
+
+    #[test]
+    fn test_script_timeout() {
+        let dir = dirs::CACHE.join("script-timeout");
+        let _ = remove_all(&dir);
+        file::create_dir_all(dir.join("bin")).unwrap();
+        let script = |name: &str, body: &str| {
+            let path = dir.join("bin").join(name);
+            fs::write(&path, format!("#!/usr/bin/env bash\n{body}\n")).unwrap();
+            file::make_executable(&path).unwrap();
+        };
+        // sleep is a child of the script, it has to be killed too for the read to return
+        script("list-all", "echo 1.0.0\nsleep 10\necho 2.0.0");
+        script("list-aliases", "echo lts 1.0.0");
+        let sm = ScriptManager::new(dir.clone());
+        let mut settings = Settings::default();
+        settings.script_timeout = Some(Duration::from_millis(200));
+
+        let start = Instant::now();
+        let err = sm.read(&settings, &Script::ListAll, false).unwrap_err();
+        let elapsed = start.elapsed();
+        let aliases = sm.read(&settings, &Script::ListAliases, false);
+        remove_all(&dir).unwrap();
+        assert!(elapsed < Duration::from_secs(5));
+        assert!(matches!(err.downcast_ref(), Some(ScriptTimedOut(..))));
+        assert!(err
+            .to_string()
+            .ends_with("script-timeout/bin/list-all timed out after 200ms"));
+        assert_eq!(aliases.unwrap(), "lts 1.0.0");
+    }
+}
@@ -52,6 +52,10 @@ impl Shell for Bash {
         out
     }
 
+    fn activate_shims(&self, shims: &Path) -> String {
+        format!("export PATH=\"{}:$PATH\"\n", shims.display())
+    }
+
     fn deactivate(&self) -> String {
         formatdoc! {r#"
             PROMPT_COMMAND="${{PROMPT_COMMAND//_rtx_hook/}}"
@@ -86,6 +90,12 @@ mod tests {
         assert_snapshot!(bash.activate(exe, true));
     }
 
+    #[test]
+    fn test_activate_shims() {
+        let shims = Path::new("/some/dir/shims");
+        assert_snapshot!(Bash::default().activate_shims(shims));
+    }
+
     #[test]
     fn test_set_env() {
         assert_snapshot!(Bash::default().set_env("FOO", "1"));
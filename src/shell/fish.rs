@@ -68,6 +68,11 @@ impl Shell for Fish {
         out
     }
 
+    fn activate_shims(&self, shims: &Path) -> String {
+        // fish_add_path leaves PATH alone if shims is already in it, this always moves it first
+        format!("set -gx PATH {} $PATH\n", shims.display())
+    }
+
     fn deactivate(&self) -> String {
         formatdoc! {r#"
           functions --erase __rtx_env_eval
@@ -103,6 +108,12 @@ mod tests {
         assert_snapshot!(fish.activate(exe, true));
     }
 
+    #[test]
+    fn test_activate_shims() {
+        let shims = Path::new("/some/dir/shims");
+        assert_snapshot!(Fish::default().activate_shims(shims));
+    }
+
     #[test]
     fn test_set_env() {
         assert_snapshot!(Fish::default().set_env("FOO", "1"));
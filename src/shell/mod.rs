@@ -51,6 +51,8 @@ impl Display for ShellType {
 
 pub trait Shell {
     fn activate(&self, exe: &Path, status: bool) -> String;
+    /// for `rtx activate --shims`, puts the shims dir at the front of PATH and nothing else
+    fn activate_shims(&self, shims: &Path) -> String;
     fn deactivate(&self) -> String;
     fn set_env(&self, k: &str, v: &str) -> String;
     fn unset_env(&self, k: &str) -> String;
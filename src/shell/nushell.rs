@@ -95,6 +95,13 @@ impl Shell for Nushell {
         out
     }
 
+    fn activate_shims(&self, shims: &Path) -> String {
+        format!(
+            "let-env PATH = ($env.PATH | prepend '{}')\n",
+            shims.display()
+        )
+    }
+
     fn deactivate(&self) -> String {
         self.unset_env("RTX_SHELL")
     }
@@ -127,6 +134,12 @@ mod tests {
         assert_snapshot!(nushell.activate(exe, true));
     }
 
+    #[test]
+    fn test_activate_shims() {
+        let shims = Path::new("/some/dir/shims");
+        assert_snapshot!(Nushell::default().activate_shims(shims));
+    }
+
     #[test]
     fn test_set_env() {
         assert_snapshot!(Nushell::default().set_env("FOO", "1"));
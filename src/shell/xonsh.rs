@@ -74,6 +74,20 @@ impl Shell for Xonsh {
         out
     }
 
+    fn activate_shims(&self, shims: &Path) -> String {
+        let shims = shims.to_string_lossy();
+        formatdoc! {r#"
+            from os               import environ
+            from xonsh.built_ins  import XSH
+
+            envx = XSH.env
+            envx['PATH'].add('{shims}', front=True, replace=True)
+            environ['PATH'] = envx.get_detyped('PATH')
+            "#,
+            shims = xonsh_escape_sq(&shims)
+        }
+    }
+
     fn deactivate(&self) -> String {
         formatdoc! {r#"
             from xonsh.built_ins  import XSH
@@ -136,6 +150,12 @@ mod tests {
         insta::assert_snapshot!(xonsh.activate(exe, true));
     }
 
+    #[test]
+    fn test_activate_shims() {
+        let shims = Path::new("/some/dir/shims");
+        assert_snapshot!(Xonsh::default().activate_shims(shims));
+    }
+
     #[test]
     fn test_set_env() {
         insta::assert_snapshot!(Xonsh::default().set_env("FOO", "1"));
@@ -59,6 +59,10 @@ impl Shell for Zsh {
         out
     }
 
+    fn activate_shims(&self, shims: &Path) -> String {
+        Bash::default().activate_shims(shims)
+    }
+
     fn deactivate(&self) -> String {
         formatdoc! {r#"
         precmd_functions=( ${{precmd_functions:#_rtx_hook}} )
@@ -91,6 +95,12 @@ mod tests {
         assert_snapshot!(zsh.activate(exe, true));
     }
 
+    #[test]
+    fn test_activate_shims() {
+        let shims = Path::new("/some/dir/shims");
+        assert_snapshot!(Zsh::default().activate_shims(shims));
+    }
+
     #[test]
     fn test_set_env() {
         assert_snapshot!(Zsh::default().set_env("FOO", "1"));
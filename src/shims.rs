@@ -1,10 +1,12 @@
+use std::collections::BTreeSet;
 use std::ffi::OsString;
 use std::fs;
-use std::path::{Path, PathBuf};
+use std::path::{Component, Path, PathBuf};
 use std::process::exit;
 
 use color_eyre::eyre::{eyre, Result};
 use indoc::formatdoc;
+use once_cell::sync::Lazy;
 use rayon::prelude::*;
 
 use crate::cli::command::Command;
@@ -15,9 +17,15 @@ use crate::fake_asdf;
 use crate::file::{create_dir_all, remove_all};
 use crate::lock_file::LockFile;
 use crate::output::Output;
+use crate::plugins::PluginName;
 use crate::toolset::{ToolVersion, Toolset, ToolsetBuilder};
 use crate::{dirs, file};
 
+/// shims added with `rtx shim add` for bins `list_bin_paths` doesn't find.
+/// Each file is named after the shim and has the tool on the first line and the bin's path
+/// inside the tool's install dir on the second, they survive `rtx reshim` clearing the shims.
+static MANUAL_SHIMS: Lazy<PathBuf> = Lazy::new(|| dirs::ROOT.join("manual-shims"));
+
 // executes as if it was a shim if the command is not "rtx", e.g.: "node"
 #[allow(dead_code)]
 pub fn handle_shim(mut config: Config, args: &[String], out: &mut Output) -> Result<Config> {
@@ -32,6 +40,7 @@ pub fn handle_shim(mut config: Config, args: &[String], out: &mut Output) -> Res
         c: None,
         command: Some(args),
         cd: None,
+        no_install: false,
     };
     exec.run(config, out)?;
     exit(0);
@@ -41,22 +50,17 @@ fn which_shim(config: &mut Config, bin_name: &str) -> Result<PathBuf> {
     let shim = dirs::SHIMS.join(bin_name);
     if shim.exists() {
         let ts = ToolsetBuilder::new().build(config)?;
+        if let Some(bin) = which_manual_shim(config, &ts, bin_name) {
+            return Ok(bin);
+        }
         if let Some((p, tv)) = ts.which(config, bin_name) {
             if let Some(bin) = p.which(config, &tv, bin_name)? {
                 return Ok(bin);
             }
         }
         // fallback for "system"
-        for path in &*env::PATH {
-            if fs::canonicalize(path).unwrap_or_default()
-                == fs::canonicalize(&*dirs::SHIMS).unwrap_or_default()
-            {
-                continue;
-            }
-            let bin = path.join(bin_name);
-            if bin.exists() {
-                return Ok(bin);
-            }
+        if let Some(bin) = which_system(bin_name) {
+            return Ok(bin);
         }
         let tvs = ts.list_rtvs_with_bin(config, bin_name)?;
         err_no_version_set(bin_name, tvs)?;
@@ -64,6 +68,109 @@ fn which_shim(config: &mut Config, bin_name: &str) -> Result<PathBuf> {
     Err(eyre!("{} is not a valid shim", bin_name))
 }
 
+/// finds a bin on PATH, skipping rtx's shims
+pub fn which_system(bin_name: &str) -> Option<PathBuf> {
+    let shims = fs::canonicalize(&*dirs::SHIMS).unwrap_or_default();
+    env::PATH
+        .iter()
+        .filter(|path| fs::canonicalize(path).unwrap_or_default() != shims)
+        .map(|path| path.join(bin_name))
+        .find(|bin| bin.exists())
+}
+
+/// the bin a manual shim points to in the current version of its tool
+fn which_manual_shim(config: &Config, ts: &Toolset, bin_name: &str) -> Option<PathBuf> {
+    let (tool, bin) = read_manual_shim(bin_name)?;
+    ts.list_current_installed_versions(config)
+        .into_iter()
+        .find(|(p, _)| p.name == tool)
+        .map(|(_, tv)| tv.install_path().join(bin))
+        .filter(|bin| bin.exists())
+}
+
+fn read_manual_shim(name: &str) -> Option<(PluginName, PathBuf)> {
+    let contents = fs::read_to_string(MANUAL_SHIMS.join(name)).ok()?;
+    let mut lines = contents.lines();
+    match (lines.next(), lines.next()) {
+        (Some(tool), Some(bin)) => Some((tool.to_string(), PathBuf::from(bin))),
+        _ => None,
+    }
+}
+
+fn list_manual_shims() -> Result<Vec<String>> {
+    if !MANUAL_SHIMS.exists() {
+        return Ok(vec![]);
+    }
+    let mut names = vec![];
+    for entry in MANUAL_SHIMS.read_dir()? {
+        names.push(entry?.file_name().to_string_lossy().to_string());
+    }
+    Ok(names)
+}
+
+/// creates a shim for `bin`, a path inside the install dir of `tool`'s versions, e.g.: "libexec/foo"
+/// the shim is named after the file and dispatches through rtx like the others
+pub fn add_manual_shim(tool: &PluginName, bin: &Path) -> Result<PathBuf> {
+    let is_inside = bin.components().all(|c| matches!(c, Component::Normal(_)));
+    let name = match bin.file_name() {
+        Some(name) if is_inside => name.to_string_lossy().to_string(),
+        _ => {
+            return Err(eyre!(
+                "{} must be a path inside the tool's install directory, e.g.: bin/foo",
+                bin.display()
+            ))
+        }
+    };
+    let shim = dirs::SHIMS.join(&name);
+    match read_manual_shim(&name) {
+        Some((t, b)) if &t == tool && b == bin => {}
+        Some((t, b)) => {
+            return Err(eyre!(
+                "{} is already a shim for {} in {}, remove it first with `rtx shim rm {}`",
+                name,
+                t,
+                b.display(),
+                name
+            ))
+        }
+        None if shim.is_symlink() || shim.exists() => {
+            warn!(
+                "{name} already has a shim, it now runs {tool}'s {}",
+                bin.display()
+            );
+        }
+        None => {}
+    }
+    create_dir_all(&*MANUAL_SHIMS)?;
+    fs::write(
+        MANUAL_SHIMS.join(&name),
+        format!("{}\n{}\n", tool, bin.display()),
+    )?;
+    create_dir_all(&*dirs::SHIMS)?;
+    let rtx_bin = file::which("rtx").unwrap_or(env::RTX_EXE.clone());
+    file::make_symlink(&rtx_bin, &shim)?;
+    Ok(shim)
+}
+
+pub fn remove_manual_shim(name: &str) -> Result<()> {
+    // a single file name so nothing outside the shim dirs can be removed
+    let mut components = Path::new(name).components();
+    let is_file_name = matches!(
+        (components.next(), components.next()),
+        (Some(Component::Normal(_)), None)
+    );
+    let entry = MANUAL_SHIMS.join(name);
+    if !is_file_name || !entry.exists() {
+        return Err(eyre!("{} is not a shim added with `rtx shim add`", name));
+    }
+    fs::remove_file(entry)?;
+    let shim = dirs::SHIMS.join(name);
+    if shim.is_symlink() || shim.exists() {
+        fs::remove_file(shim)?;
+    }
+    Ok(())
+}
+
 pub fn reshim(config: &mut Config, ts: &Toolset) -> Result<()> {
     let _lock = LockFile::new(&dirs::SHIMS)
         .with_callback(|l| {
@@ -76,6 +183,53 @@ pub fn reshim(config: &mut Config, ts: &Toolset) -> Result<()> {
     create_dir_all(&*dirs::SHIMS)?;
     let rtx_bin = file::which("rtx").unwrap_or(env::RTX_EXE.clone());
 
+    for bin_name in list_installed_bins(config, ts)? {
+        let symlink_path = dirs::SHIMS.join(bin_name);
+        file::make_symlink(&rtx_bin, &symlink_path).map_err(|err| {
+            eyre!(
+                "Failed to create symlink from {} to {}: {}",
+                rtx_bin.display(),
+                symlink_path.display(),
+                err
+            )
+        })?;
+    }
+    for (bin_name, target) in list_plugin_shims(config)? {
+        make_shim(&target, &dirs::SHIMS.join(bin_name))?;
+    }
+    for name in list_manual_shims()? {
+        file::make_symlink(&rtx_bin, &dirs::SHIMS.join(name))?;
+    }
+
+    Ok(())
+}
+
+/// removes the shims that no installed tool has a bin for anymore, e.g.: after an uninstall.
+/// Unlike `reshim` nothing else is touched. Returns the names of the removed shims.
+pub fn prune_shims(config: &Config, ts: &Toolset) -> Result<Vec<String>> {
+    let _lock = LockFile::new(&dirs::SHIMS).lock();
+    if !dirs::SHIMS.exists() {
+        return Ok(vec![]);
+    }
+    let mut valid = list_installed_bins(config, ts)?;
+    valid.extend(list_plugin_shims(config)?.into_iter().map(|(name, _)| name));
+    valid.extend(list_manual_shims()?);
+
+    let mut removed = vec![];
+    for shim in dirs::SHIMS.read_dir()? {
+        let shim = shim?;
+        let name = shim.file_name().to_string_lossy().to_string();
+        if !valid.contains(&name) {
+            fs::remove_file(shim.path())?;
+            removed.push(name);
+        }
+    }
+    removed.sort();
+    Ok(removed)
+}
+
+/// names of the files in the bin paths of every installed version
+fn list_installed_bins(config: &Config, ts: &Toolset) -> Result<BTreeSet<String>> {
     let paths: Vec<PathBuf> = ts
         .list_installed_versions(config)?
         .into_par_iter()
@@ -88,6 +242,7 @@ pub fn reshim(config: &mut Config, ts: &Toolset) -> Result<()> {
         })
         .collect();
 
+    let mut bins = BTreeSet::new();
     for path in paths {
         if !path.exists() {
             continue;
@@ -97,35 +252,26 @@ pub fn reshim(config: &mut Config, ts: &Toolset) -> Result<()> {
             if !bin.file_type()?.is_file() && !bin.file_type()?.is_symlink() {
                 continue;
             }
-            let bin_name = bin.file_name().into_string().unwrap();
-            let symlink_path = dirs::SHIMS.join(bin_name);
-            file::make_symlink(&rtx_bin, &symlink_path).map_err(|err| {
-                eyre!(
-                    "Failed to create symlink from {} to {}: {}",
-                    rtx_bin.display(),
-                    symlink_path.display(),
-                    err
-                )
-            })?;
+            bins.insert(bin.file_name().into_string().unwrap());
         }
     }
+    Ok(bins)
+}
+
+/// shims plugins ship in their own shims/ dir, as (name, path to the plugin's shim)
+fn list_plugin_shims(config: &Config) -> Result<Vec<(String, PathBuf)>> {
+    let mut shims = vec![];
     for plugin in config.tools.values() {
-        match plugin.plugin_path.join("shims").read_dir() {
-            Ok(files) => {
-                for bin in files {
-                    let bin = bin?;
-                    let bin_name = bin.file_name().into_string().unwrap();
-                    let symlink_path = dirs::SHIMS.join(bin_name);
-                    make_shim(&bin.path(), &symlink_path)?;
-                }
-            }
-            Err(_) => {
-                continue;
-            }
+        let files = match plugin.plugin_path.join("shims").read_dir() {
+            Ok(files) => files,
+            Err(_) => continue,
+        };
+        for bin in files {
+            let bin = bin?;
+            shims.push((bin.file_name().into_string().unwrap(), bin.path()));
         }
     }
-
-    Ok(())
+    Ok(shims)
 }
 
 fn make_shim(target: &Path, shim: &Path) -> Result<()> {
@@ -164,3 +310,21 @@ fn err_no_version_set(bin_name: &str, tvs: Vec<ToolVersion>) -> Result<()> {
     }
     Err(eyre!(msg.trim().to_string()))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_which_manual_shim() {
+        let mut config = Config::load().unwrap();
+        let ts = ToolsetBuilder::new().build(&mut config).unwrap();
+        add_manual_shim(&"tiny".into(), Path::new("VERSION")).unwrap();
+        let bin = which_manual_shim(&config, &ts, "VERSION");
+        remove_manual_shim("VERSION").unwrap();
+
+        // tiny 3 is set in test/cwd/.test-tool-versions
+        assert_eq!(bin, Some(dirs::INSTALLS.join("tiny/3.1.0/VERSION")));
+        assert_eq!(which_manual_shim(&config, &ts, "VERSION"), None);
+    }
+}
@@ -20,17 +20,32 @@ pub fn get_shorthands(settings: &Settings) -> Shorthands {
                 .map(|(k, v)| (k.to_string(), v.to_string())),
         );
     };
-    if let Some(f) = &settings.shorthands_file {
-        match parse_shorthands_file(f.clone()) {
-            Ok(custom) => {
-                shorthands.extend(custom);
-            }
+    shorthands.extend(custom_shorthands(settings));
+    shorthands
+}
+
+fn custom_shorthands(settings: &Settings) -> Shorthands {
+    match &settings.shorthands_file {
+        Some(f) => match parse_shorthands_file(f.clone()) {
+            Ok(custom) => custom,
             Err(err) => {
                 warn!("Failed to read shorthands file: {} {:#}", &f.display(), err);
+                Shorthands::new()
             }
-        }
+        },
+        None => Shorthands::new(),
     }
-    shorthands
+}
+
+/// https://github.com/<org>/asdf-<name>.git when `plugin_default_org` is set, unless the
+/// shorthands file has the plugin. The org may not have forked it, so this is only a guess that
+/// installing the plugin checks before falling back to the default shorthand.
+pub fn get_org_repo_url(settings: &Settings, name: &str) -> Option<String> {
+    let org = settings.plugin_default_org.as_ref()?;
+    if custom_shorthands(settings).contains_key(name) {
+        return None;
+    }
+    Some(format!("https://github.com/{org}/asdf-{name}.git"))
 }
 
 fn parse_shorthands_file(mut f: PathBuf) -> Result<Shorthands> {
@@ -70,6 +85,27 @@ mod tests {
         assert_str_eq!(shorthands["xxxxxx"], "https://xxxxxx");
     }
 
+    #[test]
+    fn test_get_org_repo_url() {
+        let settings = Settings {
+            plugin_default_org: Some("my-org".into()),
+            shorthands_file: Some("../fixtures/shorthands.toml".into()),
+            ..Settings::default()
+        };
+        assert_str_eq!(
+            get_org_repo_url(&settings, "not-a-shorthand").unwrap(),
+            "https://github.com/my-org/asdf-not-a-shorthand.git"
+        );
+        // the shorthands file takes precedence
+        assert_eq!(get_org_repo_url(&settings, "xxxxxx"), None);
+
+        let settings = Settings {
+            plugin_default_org: None,
+            ..settings
+        };
+        assert_eq!(get_org_repo_url(&settings, "not-a-shorthand"), None);
+    }
+
     #[test]
     fn test_get_shorthands_missing_file() {
         let settings = Settings {
@@ -1,10 +1,19 @@
+use std::collections::BTreeMap;
 use std::env::{join_paths, set_current_dir};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
 
+use color_eyre::eyre::{eyre, Result};
+use duct::Expression;
 use indoc::indoc;
 
-use crate::{assert_cli, env};
+use crate::config::{Config, Settings};
+use crate::plugins::{Plugin, PluginName, PluginType};
+use crate::tool::Tool;
+use crate::toolset::ToolVersion;
+use crate::ui::progress_report::ProgressReport;
+use crate::{assert_cli, dirs, env, file};
 
 #[ctor::ctor]
 fn init() {
@@ -22,10 +31,15 @@ fn init() {
     env::set_var("RTX_DATA_DIR", env::HOME.join("data"));
     env::set_var("RTX_CONFIG_DIR", env::HOME.join("config"));
     env::set_var("RTX_CACHE_DIR", env::HOME.join("data/cache"));
+    env::set_var("RTX_STATE_DIR", env::HOME.join("data/state"));
     env::set_var("RTX_DEFAULT_TOOL_VERSIONS_FILENAME", ".test-tool-versions");
     env::set_var("RTX_DEFAULT_CONFIG_FILENAME", ".test.rtx.toml");
     env::set_var("RTX_MISSING_RUNTIME_BEHAVIOR", "autoinstall");
     //env::set_var("TERM", "dumb");
+    if env::var("__RTX_TEST_ISOLATED").is_ok() {
+        // started by `run_isolated`, the test process that started it already set everything up
+        return;
+    }
     reset_config();
     assert_cli!("trust");
     assert_cli!("plugins", "uninstall", "tiny-link");
@@ -79,3 +93,137 @@ pub fn replace_path(input: &str) -> String {
         .replace(&home, "~")
         .replace(&*env::RTX_EXE.to_string_lossy(), "rtx")
 }
+
+/// git in `dir` with an author set, so commits work without a git config
+pub fn git_cmd(dir: &Path, args: &[&str]) -> Expression {
+    let mut git_args = vec!["-C", dir.to_str().unwrap()];
+    git_args.extend(["-c", "user.name=rtx", "-c", "user.email=rtx@example.com"]);
+    git_args.extend(args);
+    crate::cmd::cmd("git", git_args)
+}
+
+/// commits everything in `dir`, which is made a repo first if it isn't one
+pub fn git_commit(dir: &Path, msg: &str) {
+    if !dir.join(".git").exists() {
+        git_cmd(dir, &["init", "-q"]).run().unwrap();
+    }
+    git_cmd(dir, &["add", "-A"]).run().unwrap();
+    git_cmd(dir, &["commit", "-qm", msg]).run().unwrap();
+}
+
+/// runs the test `name` (e.g.: "tool::tests::test_install_canceled") again on its own in a child
+/// process, for tests that change state of the whole process like the cancel flag or that check
+/// what is written to stderr. Returns the child's stderr once the test passed there, in the child
+/// it returns None so the test carries on.
+pub fn run_isolated(name: &str) -> Option<String> {
+    if env::var("__RTX_TEST_ISOLATED").is_ok() {
+        return None;
+    }
+    let exe = std::env::current_exe().unwrap();
+    let output = crate::cmd!(exe, "--exact", name, "--test-threads=1", "--nocapture")
+        .env("__RTX_TEST_ISOLATED", "1")
+        .stdout_capture()
+        .stderr_capture()
+        .unchecked()
+        .run()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+    assert!(
+        stdout.contains("test result: ok. 1 passed"),
+        "{stdout}\n{stderr}"
+    );
+    Some(stderr)
+}
+
+/// a plugin that doesn't run any scripts, to test how plugins are used. The steps named in `fails`
+/// (e.g.: "install_version") return an error, the ones that succeed are added to `calls` as
+/// "<step> <name>".
+#[derive(Debug, Default)]
+pub struct StubPlugin {
+    pub name: PluginName,
+    pub versions: Vec<String>,
+    pub aliases: BTreeMap<String, String>,
+    /// an external plugin is cloned to `dirs::PLUGINS` when installed, a core one is not
+    pub external: bool,
+    pub install_path_template: Option<String>,
+    pub min_free_disk: Option<u64>,
+    pub post_install_test: Option<String>,
+    /// called to install a version, e.g.: to write files to its install path
+    pub on_install: Option<fn(&ToolVersion) -> Result<()>>,
+    pub calls: Arc<Mutex<Vec<String>>>,
+    pub fails: Vec<&'static str>,
+}
+
+impl StubPlugin {
+    /// with only version 1.0.0
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.into(),
+            versions: vec!["1.0.0".into()],
+            ..Default::default()
+        }
+    }
+
+    pub fn tool(self) -> Tool {
+        Tool::new(self.name.clone(), Box::new(self))
+    }
+
+    fn step(&self, step: &str) -> Result<()> {
+        if self.fails.contains(&step) {
+            return Err(eyre!("{} failed for {}", step, self.name));
+        }
+        self.calls
+            .lock()
+            .unwrap()
+            .push(format!("{} {}", step, self.name));
+        Ok(())
+    }
+}
+
+impl Plugin for StubPlugin {
+    fn name(&self) -> &PluginName {
+        &self.name
+    }
+    fn get_type(&self) -> PluginType {
+        match self.external {
+            true => PluginType::External,
+            false => PluginType::Core,
+        }
+    }
+    fn list_remote_versions(&self, _settings: &Settings) -> Result<Vec<String>> {
+        Ok(self.versions.clone())
+    }
+    fn install(&self, _config: &Config, _pr: &mut ProgressReport) -> Result<()> {
+        self.step("install")?;
+        Ok(file::create_dir_all(dirs::PLUGINS.join(&self.name))?)
+    }
+    fn get_aliases(&self, _settings: &Settings) -> Result<BTreeMap<String, String>> {
+        Ok(self.aliases.clone())
+    }
+    fn install_version(&self, _: &Config, tv: &ToolVersion, _: &ProgressReport) -> Result<()> {
+        self.step("install_version")?;
+        match self.on_install {
+            Some(on_install) => on_install(tv),
+            None => Ok(()),
+        }
+    }
+    fn uninstall_version(&self, _config: &Config, _tv: &ToolVersion) -> Result<()> {
+        self.step("uninstall_version")
+    }
+    fn pre_uninstall(&self, _config: &Config, _tv: &ToolVersion) -> Result<()> {
+        self.step("pre_uninstall")
+    }
+    fn post_uninstall(&self, _config: &Config, _tv: &ToolVersion) -> Result<()> {
+        self.step("post_uninstall")
+    }
+    fn install_path_template(&self) -> Option<String> {
+        self.install_path_template.clone()
+    }
+    fn min_free_disk(&self) -> Option<u64> {
+        self.min_free_disk
+    }
+    fn post_install_test(&self) -> Option<String> {
+        self.post_install_test.clone()
+    }
+}
@@ -1,21 +1,26 @@
-use std::collections::{BTreeMap, HashMap};
+use std::collections::BTreeMap;
+use std::env::join_paths;
 use std::fs::{remove_file, File};
-use std::path::{Path, PathBuf};
+use std::path::{Component, Path, PathBuf};
 
-use color_eyre::eyre::{eyre, Result};
+use color_eyre::eyre::{eyre, Result, WrapErr};
 use console::style;
+use indicatif::HumanBytes;
 use itertools::Itertools;
+use once_cell::sync::OnceCell;
 use regex::Regex;
 use versions::Versioning;
 
+use crate::cmd::CmdLineRunner;
 use crate::config::{Config, Settings};
 use crate::file::{create_dir_all, display_path, remove_all_with_warning};
 use crate::lock_file::LockFile;
-use crate::plugins::{ExternalPlugin, Plugin, PluginType};
+use crate::plugins::{ExecEnvVars, ExternalPlugin, Plugin, PluginType};
 use crate::runtime_symlinks::is_runtime_symlink;
-use crate::toolset::{ToolVersion, ToolVersionRequest};
+use crate::tera::{get_tera, BASE_CONTEXT};
+use crate::toolset::{InstallReceipt, ToolVersion, ToolVersionRequest};
 use crate::ui::progress_report::{ProgressReport, PROG_TEMPLATE};
-use crate::{dirs, file};
+use crate::{cancel, dirs, env, file, shims};
 
 #[derive(Debug)]
 pub struct Tool {
@@ -23,13 +28,19 @@ pub struct Tool {
     pub plugin: Box<dyn Plugin>,
     pub installs_path: PathBuf,
     pub plugin_path: PathBuf,
+    /// the plugin's install path template rendered with VERSION_PLACEHOLDER as the version
+    install_layout: OnceCell<Option<String>>,
 }
 
+/// stands in for the version in a rendered install path template
+const VERSION_PLACEHOLDER: &str = "__RTX_VERSION__";
+
 impl Tool {
     pub fn new(name: String, plugin: Box<dyn Plugin>) -> Self {
         Self {
             installs_path: dirs::INSTALLS.join(&name),
             plugin_path: dirs::PLUGINS.join(&name),
+            install_layout: OnceCell::new(),
             name,
             plugin,
         }
@@ -49,23 +60,94 @@ impl Tool {
         self.plugin.is_installed()
     }
 
+    pub fn display_name(&self) -> &str {
+        self.plugin.display_name()
+    }
+
     pub fn get_remote_url(&self) -> Option<String> {
         self.plugin.get_remote_url()
     }
 
     pub fn list_installed_versions(&self) -> Result<Vec<String>> {
-        Ok(match self.installs_path.exists() {
-            true => file::dir_subdirs(&self.installs_path)?
+        let (installs_path, prefix, suffix) = self.versions_dir();
+        Ok(match installs_path.exists() {
+            true => file::dir_subdirs(&installs_path)?
                 .iter()
-                .filter(|v| !is_runtime_symlink(&self.installs_path.join(v)))
-                .map(|v| Versioning::new(v).unwrap_or_default())
-                .sorted()
+                .filter(|v| !is_runtime_symlink(&installs_path.join(v)))
+                .filter_map(|v| v.strip_prefix(&prefix)?.strip_suffix(&suffix))
+                .filter(|v| !v.is_empty() && self.install_path_for(v).exists())
+                // sorted as versions but kept as the dir names, not everything parses as one
+                .sorted_by_cached_key(|v| Versioning::new(v).unwrap_or_default())
                 .map(|v| v.to_string())
                 .collect(),
             false => vec![],
         })
     }
 
+    /// where a version with the given pathname is installed
+    /// uses the plugin's install path template if it has one, otherwise ~/.local/share/rtx/installs/<plugin>/<pathname>
+    pub fn install_path_for(&self, pathname: &str) -> PathBuf {
+        match self.install_layout() {
+            Some(layout) => dirs::INSTALLS.join(layout.replace(VERSION_PLACEHOLDER, pathname)),
+            None => self.installs_path.join(pathname),
+        }
+    }
+
+    /// the install path template is rendered once, the version is filled in for each install path
+    fn install_layout(&self) -> Option<&str> {
+        self.install_layout
+            .get_or_init(|| {
+                let tmpl = self.plugin.install_path_template()?;
+                match self.render_install_layout(&tmpl) {
+                    Ok(layout) => Some(layout),
+                    Err(err) => {
+                        warn!("{}: invalid install path template: {:#}", self.name, err);
+                        None
+                    }
+                }
+            })
+            .as_deref()
+    }
+
+    fn render_install_layout(&self, tmpl: &str) -> Result<String> {
+        let mut ctx = BASE_CONTEXT.clone();
+        ctx.insert("plugin", &self.name);
+        ctx.insert("version", VERSION_PLACEHOLDER);
+        let layout = get_tera(&self.plugin_path).render_str(tmpl, &ctx)?;
+        let layout = layout.trim();
+        if !layout.contains(VERSION_PLACEHOLDER) {
+            return Err(eyre!("{} does not contain {{{{version}}}}", layout));
+        }
+        // e.g.: "../bin/{{version}}" or "/opt/{{version}}"
+        if !Path::new(layout)
+            .components()
+            .all(|c| matches!(c, Component::Normal(_) | Component::CurDir))
+        {
+            return Err(eyre!(
+                "{} is not inside {}",
+                layout,
+                display_path(&dirs::INSTALLS)
+            ));
+        }
+        Ok(layout.to_string())
+    }
+
+    /// the directory containing one entry per installed version and the text around the version
+    /// in those entries' names, e.g.: `<installs>/node`, "v" and "" for "{{plugin}}/v{{version}}/root"
+    fn versions_dir(&self) -> (PathBuf, String, String) {
+        if let Some(layout) = self.install_layout() {
+            let mut dir = dirs::INSTALLS.to_path_buf();
+            for component in Path::new(layout).components() {
+                let component = component.as_os_str().to_string_lossy();
+                match component.split_once(VERSION_PLACEHOLDER) {
+                    Some((prefix, suffix)) => return (dir, prefix.into(), suffix.into()),
+                    None => dir.push(&*component),
+                }
+            }
+        }
+        (self.installs_path.clone(), String::new(), String::new())
+    }
+
     pub fn list_installed_versions_matching(&self, query: &str) -> Result<Vec<String>> {
         let mut query = query;
         if query == "latest" {
@@ -82,25 +164,49 @@ impl Tool {
         Ok(versions)
     }
 
+    /// installed versions that are `prefix` or start with it followed by a new segment,
+    /// so "1" matches "1.2.3" but not "10.0.0"
+    pub fn list_installed_versions_with_prefix(&self, prefix: &str) -> Result<Vec<String>> {
+        let versions = self
+            .list_installed_versions()?
+            .into_iter()
+            .filter(|v| version_has_prefix(v, prefix))
+            .collect();
+        Ok(versions)
+    }
+
     pub fn list_remote_versions(&self, settings: &Settings) -> Result<Vec<String>> {
         self.plugin.list_remote_versions(settings)
     }
 
+    pub fn version_exists(&self, settings: &Settings, version: &str) -> Result<bool> {
+        self.plugin.version_exists(settings, version)
+    }
+
     pub fn list_versions_matching(&self, settings: &Settings, query: &str) -> Result<Vec<String>> {
         let mut query = query;
         if query == "latest" {
             query = "[0-9]";
         }
+        let query_regex =
+            Regex::new((String::from(r"^\s*") + query).as_str()).expect("error parsing regex");
+        let versions = self
+            .list_stable_remote_versions(settings)?
+            .into_iter()
+            .filter(|v| query_regex.is_match(v))
+            .collect();
+        Ok(versions)
+    }
+
+    /// remote versions without prereleases like "-rc1" or "-beta"
+    pub fn list_stable_remote_versions(&self, settings: &Settings) -> Result<Vec<String>> {
         let version_regex = regex!(
             r"(^Available versions:|-src|-dev|-latest|-stm|[-\\.]rc|-milestone|-alpha|-beta|[-\\.]pre|-next|(a|b|c)[0-9]+|snapshot|master)"
         );
-        let query_regex =
-            Regex::new((String::from(r"^\s*") + query).as_str()).expect("error parsing regex");
         let versions = self
             .list_remote_versions(settings)?
             .into_iter()
             .filter(|v| !version_regex.is_match(v))
-            .filter(|v| query_regex.is_match(v))
             .collect();
         Ok(versions)
     }
@@ -160,8 +266,8 @@ impl Tool {
             "{} {} ",
             style("rtx").dim().for_stderr(),
             match tv {
-                Some(tv) => tv.to_string(),
-                None => self.name.to_string(),
+                Some(tv) => format!("{}@{}", self.display_name(), tv.version),
+                None => self.display_name().to_string(),
             }
         ));
         pr.enable_steady_tick();
@@ -186,14 +292,42 @@ impl Tool {
         force: bool,
     ) -> Result<()> {
         self.decorate_progress_bar(pr, Some(tv));
+        if cancel::is_cancelled() {
+            return Err(eyre!("not installing {tv}, rtx is exiting"));
+        }
+        self.check_free_disk(tv)?;
         let _lock = self.get_lock(&tv.install_path(), force)?;
+        let _installing = cancel::Installing::start();
+        if let Some(hook) = &config.settings.pre_install_hook {
+            pr.set_message("pre-install hook");
+            self.run_install_hook(config, tv, pr, hook)
+                .wrap_err_with(|| format!("pre-install hook for {tv} failed: {hook}"))?;
+        }
+        if force {
+            let _ = remove_file(tv.download_complete_path());
+        }
         self.create_install_dirs(tv)?;
 
-        if let Err(e) = self.plugin.install_version(config, tv, pr) {
+        let result = self
+            .plugin
+            .install_version(config, tv, pr)
+            .and_then(|_| self.run_post_install_test(config, tv, pr));
+        if cancel::is_cancelled() {
+            // removed even with always_keep_install, whatever is there is incomplete
+            pr.error();
+            let _ = remove_all_with_warning(tv.install_path());
+            return Err(eyre!("installing {tv} was canceled"));
+        }
+        if let Err(e) = result {
             self.cleanup_install_dirs_on_error(&config.settings, tv);
             return Err(e);
         }
         self.cleanup_install_dirs(&config.settings, tv);
+        // a kept download is used again by the next install only if it was made for that
+        let _ = remove_file(tv.download_complete_path());
+        if let Err(err) = InstallReceipt::new(self, tv).write(tv) {
+            debug!("error writing install receipt for {}: {:#}", tv, err);
+        }
         // attempt to touch all the .tool-version files to trigger updates in hook-env
         let mut touch_dirs = vec![dirs::ROOT.to_path_buf()];
         touch_dirs.extend(config.config_files.keys().cloned());
@@ -206,6 +340,12 @@ impl Tool {
         if let Err(err) = remove_file(self.incomplete_file_path(tv)) {
             debug!("error removing incomplete file: {:?}", err);
         }
+        if let Some(hook) = &config.settings.post_install_hook {
+            pr.set_message("post-install hook");
+            if let Err(err) = self.run_install_hook(config, tv, pr, hook) {
+                pr.warn(format!("post-install hook for {tv} failed: {err:#}"));
+            }
+        }
         pr.finish();
 
         Ok(())
@@ -221,6 +361,7 @@ impl Tool {
         pr.set_message(format!("uninstall {tv}"));
 
         if !dryrun {
+            self.plugin.pre_uninstall(config, tv)?;
             self.plugin.uninstall_version(config, tv)?;
         }
         let rmdir = |dir: &Path| {
@@ -235,6 +376,11 @@ impl Tool {
         };
         rmdir(&tv.install_path())?;
         rmdir(&tv.download_path())?;
+        if !dryrun {
+            if let Err(err) = self.plugin.post_uninstall(config, tv) {
+                pr.warn(format!("post-uninstall failed for {tv}: {err:#}"));
+            }
+        }
         Ok(())
     }
 
@@ -246,8 +392,13 @@ impl Tool {
         let _lock = self.get_lock(&self.plugin_path, force)?;
         self.plugin.install(config, pr)
     }
-    pub fn update(&self, git_ref: Option<String>) -> Result<()> {
-        self.plugin.update(git_ref)
+    pub fn update(
+        &self,
+        config: &Config,
+        git_ref: Option<String>,
+        force: bool,
+    ) -> Result<Option<(String, String)>> {
+        self.plugin.update(config, git_ref, force)
     }
     pub fn uninstall(&self, pr: &ProgressReport) -> Result<()> {
         self.plugin.uninstall(pr)
@@ -268,9 +419,9 @@ impl Tool {
             _ => self.plugin.list_bin_paths(config, tv),
         }
     }
-    pub fn exec_env(&self, config: &Config, tv: &ToolVersion) -> Result<HashMap<String, String>> {
+    pub fn exec_env(&self, config: &Config, tv: &ToolVersion) -> Result<ExecEnvVars> {
         match tv.request {
-            ToolVersionRequest::System(_) => Ok(HashMap::new()),
+            ToolVersionRequest::System(_) => Ok(ExecEnvVars::default()),
             _ => self.plugin.exec_env(config, tv),
         }
     }
@@ -281,6 +432,9 @@ impl Tool {
         tv: &ToolVersion,
         bin_name: &str,
     ) -> Result<Option<PathBuf>> {
+        if let ToolVersionRequest::System(_) = tv.request {
+            return Ok(shims::which_system(bin_name));
+        }
         let bin_paths = self.plugin.list_bin_paths(config, tv)?;
         for bin_path in bin_paths {
             let bin_path = bin_path.join(bin_name);
@@ -295,9 +449,60 @@ impl Tool {
         tv.cache_path().join("incomplete")
     }
 
+    /// fails if there is less free space than RTX_MIN_FREE_DISK or what the plugin asks for
+    fn check_free_disk(&self, tv: &ToolVersion) -> Result<()> {
+        let min = match (*env::RTX_MIN_FREE_DISK, self.plugin.min_free_disk()) {
+            (Some(a), Some(b)) => a.max(b),
+            (Some(min), None) | (None, Some(min)) => min,
+            (None, None) => return Ok(()),
+        };
+        let install_path = tv.install_path();
+        let free = file::free_space(&install_path)?;
+        if free < min {
+            return Err(eyre!(
+                "not enough free disk space to install {}: {} needs at least {} but only {} is available",
+                tv,
+                display_path(&install_path),
+                HumanBytes(min),
+                HumanBytes(free)
+            ));
+        }
+        Ok(())
+    }
+
+    /// runs only the plugin's download step, what it fetched is left in the download path
+    pub fn download_version(
+        &self,
+        config: &Config,
+        tv: &ToolVersion,
+        pr: &mut ProgressReport,
+    ) -> Result<()> {
+        self.decorate_progress_bar(pr, Some(tv));
+        let _lock = self.get_lock(&tv.install_path(), false)?;
+        let _ = remove_all_with_warning(tv.download_path());
+        create_dir_all(tv.download_path())?;
+        match self.plugin.download_version(config, tv, pr) {
+            Ok(()) => {
+                File::create(tv.download_complete_path())?;
+                pr.finish_with_message(format!(
+                    "downloaded to {}",
+                    display_path(&tv.download_path())
+                ));
+                Ok(())
+            }
+            Err(err) => {
+                pr.error();
+                let _ = remove_all_with_warning(tv.download_path());
+                Err(err)
+            }
+        }
+    }
+
     fn create_install_dirs(&self, tv: &ToolVersion) -> Result<()> {
         let _ = remove_all_with_warning(tv.install_path());
-        let _ = remove_all_with_warning(tv.download_path());
+        if !tv.download_complete_path().exists() {
+            let _ = remove_all_with_warning(tv.download_path());
+        }
         let _ = remove_all_with_warning(tv.cache_path());
         let _ = remove_file(tv.install_path()); // removes if it is a symlink
         create_dir_all(tv.install_path())?;
@@ -306,10 +511,55 @@ impl Tool {
         File::create(self.incomplete_file_path(tv))?;
         Ok(())
     }
+    fn run_post_install_test(
+        &self,
+        config: &Config,
+        tv: &ToolVersion,
+        pr: &ProgressReport,
+    ) -> Result<()> {
+        let test = match self.plugin.post_install_test() {
+            Some(test) => test,
+            None => return Ok(()),
+        };
+        pr.set_message(format!("verifying: {test}"));
+        let mut path = self.list_bin_paths(config, tv)?;
+        path.extend(env::PATH.clone());
+        let mut cmd = CmdLineRunner::new(&config.settings, "sh");
+        cmd.arg("-c")
+            .arg(&test)
+            .with_pr(pr)
+            .envs(self.exec_env(config, tv)?.set)
+            .env("PATH", join_paths(path)?);
+        cmd.execute()
+            .wrap_err_with(|| format!("post-install test for {tv} failed: {test}"))
+    }
+
+    /// runs the user's pre_install_hook/post_install_hook, not to be confused with a plugin's hooks
+    fn run_install_hook(
+        &self,
+        config: &Config,
+        tv: &ToolVersion,
+        pr: &ProgressReport,
+        hook: &str,
+    ) -> Result<()> {
+        let mut cmd = CmdLineRunner::new(&config.settings, "sh");
+        cmd.arg("-c")
+            .arg(hook)
+            .with_pr(pr)
+            .env("RTX_PLUGIN_NAME", &self.name)
+            .env("RTX_INSTALL_VERSION", &tv.version)
+            .env("RTX_INSTALL_PATH", tv.install_path())
+            .env("RTX_DOWNLOAD_PATH", tv.download_path());
+        cmd.execute()
+    }
+
     fn cleanup_install_dirs_on_error(&self, settings: &Settings, tv: &ToolVersion) {
         if !settings.always_keep_install {
             let _ = remove_all_with_warning(tv.install_path());
-            self.cleanup_install_dirs(settings, tv);
+            // one from --download-only is kept to retry with, it may not be possible to get again
+            if !tv.download_complete_path().exists() {
+                self.cleanup_install_dirs(settings, tv);
+            }
         }
     }
     fn cleanup_install_dirs(&self, settings: &Settings, tv: &ToolVersion) {
@@ -338,3 +588,375 @@ impl PartialEq for Tool {
         self.plugin_path == other.plugin_path
     }
 }
+
+/// prefix matching that respects version segments so "1" matches "1.2.3" but not "10.0.0"
+pub fn version_has_prefix(version: &str, prefix: &str) -> bool {
+    match version.strip_prefix(prefix) {
+        Some(rest) => {
+            rest.is_empty()
+                || prefix.ends_with(['.', '-'])
+                || rest.starts_with(|c: char| !c.is_ascii_alphanumeric())
+        }
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use pretty_assertions::assert_eq;
+
+    use crate::config::Config;
+    use crate::file::remove_all;
+    use crate::test::{run_isolated, StubPlugin};
+    use crate::toolset::ToolVersionOptions;
+
+    use super::*;
+
+    #[test]
+    fn test_list_stable_remote_versions() {
+        let tool = StubPlugin {
+            versions: ["1.0.0", "2.0.0-rc1", "2.0.0"].map(String::from).to_vec(),
+            ..StubPlugin::new("prerelease")
+        }
+        .tool();
+        let settings = Settings::default();
+        assert_eq!(
+            tool.list_stable_remote_versions(&settings).unwrap(),
+            vec!["1.0.0", "2.0.0"]
+        );
+        assert_eq!(
+            tool.list_remote_versions(&settings).unwrap(),
+            vec!["1.0.0", "2.0.0-rc1", "2.0.0"]
+        );
+        assert_eq!(
+            tool.latest_version(&settings, Some("2".into())).unwrap(),
+            Some("2.0.0".into())
+        );
+    }
+
+    #[test]
+    fn test_version_has_prefix() {
+        assert!(version_has_prefix("18.1.0", "18"));
+        assert!(version_has_prefix("18", "18"));
+        assert!(version_has_prefix("18.1.0", "18."));
+        assert!(version_has_prefix("18.1.0-rc1", "18.1.0"));
+        assert!(!version_has_prefix("180.0.0", "18"));
+        assert!(!version_has_prefix("1.0.10", "1.0.1"));
+        assert!(!version_has_prefix("17.0.0", "18"));
+    }
+
+    #[test]
+    fn test_list_installed_versions_with_prefix() {
+        let name = "installed-prefix".to_string();
+        let tool = Tool::new(name.clone(), Box::new(ExternalPlugin::new(&name)));
+        let _ = remove_all(&tool.installs_path);
+        for v in [
+            "1.0.0",
+            "1.1.0",
+            "10.0.0",
+            "101.0",
+            "2.0.0+build.1",
+            "3.0(beta)",
+        ] {
+            create_dir_all(tool.installs_path.join(v)).unwrap();
+        }
+        let with_prefix = |prefix| tool.list_installed_versions_with_prefix(prefix).unwrap();
+        assert_eq!(with_prefix("1"), vec!["1.0.0", "1.1.0"]);
+        assert_eq!(with_prefix("1.1"), vec!["1.1.0"]);
+        assert_eq!(with_prefix("10"), vec!["10.0.0"]);
+        assert_eq!(with_prefix("2.0.0+build.1"), vec!["2.0.0+build.1"]);
+        assert_eq!(with_prefix("2.0.0+"), Vec::<String>::new());
+        assert_eq!(with_prefix("3.0(beta)"), vec!["3.0(beta)"]);
+        remove_all(&tool.installs_path).unwrap();
+    }
+
+    #[test]
+    fn test_install_path_template() {
+        let name = "custom-layout".to_string();
+        let tool = StubPlugin {
+            install_path_template: Some("{{plugin}}-layout/{{version}}/root".into()),
+            ..StubPlugin::new(&name)
+        }
+        .tool();
+        let _ = remove_all(dirs::INSTALLS.join("custom-layout-layout"));
+        let tvr = ToolVersionRequest::Version(name.clone(), "1.0.0".into());
+        let tv = ToolVersion::new(&tool, tvr, ToolVersionOptions::default(), "1.0.0".into());
+        assert_eq!(
+            tv.install_path(),
+            dirs::INSTALLS.join("custom-layout-layout/1.0.0/root")
+        );
+        assert!(!tool.is_version_installed(&tv));
+
+        let config = Config::load().unwrap();
+        let mut pr = ProgressReport::new(false);
+        tool.install_version(&config, &tv, &mut pr, false).unwrap();
+        assert!(tool.is_version_installed(&tv));
+        assert_eq!(tool.list_installed_versions().unwrap(), vec!["1.0.0"]);
+        assert_eq!(
+            tool.list_installed_versions_matching("1").unwrap(),
+            vec!["1.0.0"]
+        );
+
+        remove_all(dirs::INSTALLS.join("custom-layout-layout")).unwrap();
+        remove_all(dirs::DOWNLOADS.join("custom-layout")).unwrap();
+        remove_all(dirs::CACHE.join("custom-layout")).unwrap();
+    }
+
+    #[test]
+    fn test_install_path_template_version_prefix() {
+        let name = "custom-layout-v".to_string();
+        let tool = StubPlugin {
+            install_path_template: Some("{{plugin}}/v{{version}}/root".into()),
+            ..StubPlugin::new(&name)
+        }
+        .tool();
+        let _ = remove_all(&tool.installs_path);
+        let tvr = ToolVersionRequest::Version(name.clone(), "1.0.0".into());
+        let tv = ToolVersion::new(&tool, tvr, ToolVersionOptions::default(), "1.0.0".into());
+        assert_eq!(tv.install_path(), tool.installs_path.join("v1.0.0/root"));
+
+        create_dir_all(tv.install_path()).unwrap();
+        // not made by the template, e.g.: left over from before it was added
+        create_dir_all(tool.installs_path.join("2.0.0/root")).unwrap();
+        assert_eq!(tool.list_installed_versions().unwrap(), vec!["1.0.0"]);
+        remove_all(&tool.installs_path).unwrap();
+    }
+
+    #[test]
+    fn test_install_path_template_outside_installs() {
+        for template in ["../{{plugin}}/{{version}}", "/tmp/{{plugin}}/{{version}}"] {
+            let tool = StubPlugin {
+                install_path_template: Some(template.into()),
+                ..StubPlugin::new("custom-layout-escape")
+            }
+            .tool();
+            // the template is ignored
+            assert_eq!(
+                tool.install_path_for("1.0.0"),
+                dirs::INSTALLS.join("custom-layout-escape/1.0.0")
+            );
+        }
+    }
+
+    #[test]
+    fn test_install_min_free_disk() {
+        let name = "huge".to_string();
+        let calls = Arc::new(Mutex::new(vec![]));
+        let tool = StubPlugin {
+            min_free_disk: Some(u64::MAX),
+            calls: calls.clone(),
+            ..StubPlugin::new(&name)
+        }
+        .tool();
+        let tvr = ToolVersionRequest::Version(name, "1.0.0".into());
+        let tv = ToolVersion::new(&tool, tvr, ToolVersionOptions::default(), "1.0.0".into());
+
+        let config = Config::load().unwrap();
+        let mut pr = ProgressReport::new(false);
+        let err = tool
+            .install_version(&config, &tv, &mut pr, false)
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("not enough free disk space to install huge@1.0.0"));
+        assert!(!tv.install_path().exists());
+        assert!(calls.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_uninstall_hooks() {
+        let config = Config::load().unwrap();
+        let pr = ProgressReport::new(true);
+        let uninstall = |plugin: StubPlugin| {
+            let tool = plugin.tool();
+            let tvr = ToolVersionRequest::Version(tool.name.clone(), "1.0.0".into());
+            let tv = ToolVersion::new(&tool, tvr, ToolVersionOptions::default(), "1.0.0".into());
+            create_dir_all(tv.install_path()).unwrap();
+            let result = tool.uninstall_version(&config, &tv, &pr, false);
+            let installed = tv.install_path().exists();
+            remove_all(dirs::INSTALLS.join(&tool.name)).unwrap();
+            (result, installed)
+        };
+
+        let calls = Arc::new(Mutex::new(vec![]));
+        let plugin = StubPlugin {
+            calls: calls.clone(),
+            ..StubPlugin::new("hooks")
+        };
+        let (result, installed) = uninstall(plugin);
+        assert!(result.is_ok());
+        assert!(!installed);
+        assert_eq!(
+            *calls.lock().unwrap(),
+            vec![
+                "pre_uninstall hooks",
+                "uninstall_version hooks",
+                "post_uninstall hooks"
+            ]
+        );
+
+        let calls = Arc::new(Mutex::new(vec![]));
+        let plugin = StubPlugin {
+            calls: calls.clone(),
+            fails: vec!["pre_uninstall"],
+            ..StubPlugin::new("hooks-fail-pre")
+        };
+        let (result, installed) = uninstall(plugin);
+        assert!(result.is_err());
+        assert!(installed);
+        assert!(calls.lock().unwrap().is_empty());
+
+        let calls = Arc::new(Mutex::new(vec![]));
+        let plugin = StubPlugin {
+            calls: calls.clone(),
+            fails: vec!["post_uninstall"],
+            ..StubPlugin::new("hooks-fail-post")
+        };
+        let (result, installed) = uninstall(plugin);
+        assert!(result.is_ok());
+        assert!(!installed);
+        assert_eq!(
+            *calls.lock().unwrap(),
+            vec![
+                "pre_uninstall hooks-fail-post",
+                "uninstall_version hooks-fail-post"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_install_post_install_test() {
+        let config = Config::load().unwrap();
+        let install = |name: &str, test: &str| {
+            let tool = StubPlugin {
+                post_install_test: Some(test.into()),
+                on_install: Some(|tv| {
+                    let bin = tv.install_path().join("bin");
+                    create_dir_all(&bin)?;
+                    std::fs::write(bin.join("verified"), "#!/bin/sh\necho ok\n")?;
+                    file::make_executable(&bin.join("verified"))
+                }),
+                ..StubPlugin::new(name)
+            }
+            .tool();
+            let tvr = ToolVersionRequest::Version(name.into(), "1.0.0".into());
+            let tv = ToolVersion::new(&tool, tvr, ToolVersionOptions::default(), "1.0.0".into());
+            let mut pr = ProgressReport::new(true);
+            let result = tool.install_version(&config, &tv, &mut pr, false);
+            let installed = tool.is_version_installed(&tv);
+            let _ = remove_all(dirs::INSTALLS.join(name));
+            (result, installed)
+        };
+
+        // the new version's bin dir is on PATH
+        let (result, installed) = install("verified-ok", "verified");
+        result.unwrap();
+        assert!(installed);
+
+        let (result, installed) = install("verified-fail", "verified && exit 1");
+        let err = result.unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "post-install test for verified-fail@1.0.0 failed: verified && exit 1"
+        );
+        assert!(!installed);
+    }
+
+    #[test]
+    fn test_install_canceled() {
+        // once canceled every install in the process is, the other tests' too
+        if run_isolated("tool::tests::test_install_canceled").is_some() {
+            return;
+        }
+        cancel::handle_sigterm();
+        let config = Config::load().unwrap();
+        let name = "canceled".to_string();
+        // writes part of the install then rtx receives SIGTERM
+        let tool = StubPlugin {
+            versions: vec!["1.0.0".into(), "2.0.0".into()],
+            on_install: Some(|tv| {
+                std::fs::write(tv.install_path().join("partial"), "")?;
+                unsafe { libc::raise(libc::SIGTERM) };
+                Ok(())
+            }),
+            ..StubPlugin::new(&name)
+        }
+        .tool();
+        let tv = |v: &str| {
+            let tvr = ToolVersionRequest::Version(name.clone(), v.into());
+            ToolVersion::new(&tool, tvr, ToolVersionOptions::default(), v.into())
+        };
+        let (first, second) = (tv("1.0.0"), tv("2.0.0"));
+        let mut pr = ProgressReport::new(true);
+        let result = tool.install_version(&config, &first, &mut pr, false);
+        let exists = first.install_path().exists();
+        // nothing else is started once canceled
+        let next = tool.install_version(&config, &second, &mut pr, false);
+        let _ = remove_all(dirs::INSTALLS.join(&name));
+
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "installing canceled@1.0.0 was canceled"
+        );
+        // the test config has always_keep_install but a canceled install is removed anyway
+        assert!(config.settings.always_keep_install);
+        assert!(!exists);
+        assert!(next.is_err());
+        assert!(!second.install_path().exists());
+    }
+
+    #[test]
+    fn test_install_hooks() {
+        let mut config = Config::load().unwrap();
+        let name = "install-hooks".to_string();
+        let tool = StubPlugin::new(&name).tool();
+        let tvr = ToolVersionRequest::Version(name.clone(), "1.0.0".into());
+        let tv = ToolVersion::new(&tool, tvr, ToolVersionOptions::default(), "1.0.0".into());
+        let log = dirs::CACHE.join("install-hooks.log");
+        let _ = std::fs::remove_file(&log);
+        let hook = |when: &str| {
+            format!(
+                "echo {when} $RTX_PLUGIN_NAME@$RTX_INSTALL_VERSION $RTX_INSTALL_PATH >> {}",
+                log.display()
+            )
+        };
+        let mut install = |pre: Option<String>, post: Option<String>| {
+            config.settings.pre_install_hook = pre;
+            config.settings.post_install_hook = post;
+            let mut pr = ProgressReport::new(true);
+            let result = tool.install_version(&config, &tv, &mut pr, false);
+            let installed = tool.is_version_installed(&tv);
+            let _ = remove_all(dirs::INSTALLS.join(&name));
+            (result, installed)
+        };
+
+        let (result, installed) = install(Some(hook("pre")), Some(hook("post")));
+        result.unwrap();
+        assert!(installed);
+        let path = tv.install_path();
+        assert_eq!(
+            std::fs::read_to_string(&log).unwrap(),
+            format!(
+                "pre install-hooks@1.0.0 {0}\npost install-hooks@1.0.0 {0}\n",
+                path.display()
+            )
+        );
+        std::fs::remove_file(&log).unwrap();
+
+        // a failing pre-install hook stops the install
+        let (result, installed) = install(Some("exit 1".into()), Some(hook("post")));
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "pre-install hook for install-hooks@1.0.0 failed: exit 1"
+        );
+        assert!(!installed);
+        assert!(!log.exists());
+
+        // a failing post-install hook does not
+        let (result, installed) = install(None, Some("exit 1".into()));
+        result.unwrap();
+        assert!(installed);
+    }
+}
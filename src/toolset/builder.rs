@@ -1,18 +1,28 @@
-use color_eyre::eyre::Result;
+use color_eyre::eyre::{eyre, Result};
 use itertools::Itertools;
 use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
+use crate::cache;
+use crate::cache::CacheManager;
 use crate::cli::args::tool::ToolArg;
+use crate::config::config_file::ConfigFileType;
 use crate::config::Config;
-use crate::env;
-use crate::toolset::{ToolSource, ToolVersionRequest, Toolset};
+use crate::git::Git;
+use crate::hash::hash_to_str;
+use crate::plugins::PluginName;
+use crate::toolset::version_lock::VersionLock;
+use crate::toolset::{ToolSource, ToolVersion, ToolVersionOptions, ToolVersionRequest, Toolset};
 use crate::ui::multi_progress_report::MultiProgressReport;
+use crate::{dirs, env};
 
 #[derive(Debug, Default)]
 pub struct ToolsetBuilder {
     args: Vec<ToolArg>,
     install_missing: bool,
     latest_versions: bool,
+    resolution_cache: bool,
 }
 
 impl ToolsetBuilder {
@@ -35,18 +45,31 @@ impl ToolsetBuilder {
         self
     }
 
+    /// reuse the versions resolved the last time the same config was loaded
+    pub fn with_resolution_cache(mut self) -> Self {
+        self.resolution_cache = true;
+        self
+    }
+
     pub fn build(self, config: &mut Config) -> Result<Toolset> {
         let mut toolset = Toolset {
             latest_versions: self.latest_versions,
             ..Default::default()
         };
         load_config_files(config, &mut toolset);
-        load_runtime_env(&mut toolset, env::vars().collect());
+        load_runtime_env(
+            &mut toolset,
+            env::vars().collect(),
+            config.settings.asdf_compat,
+        );
         load_runtime_args(&mut toolset, &self.args);
-        toolset.resolve(config);
+        match self.resolution_cache {
+            true => resolve_cached(config, &mut toolset),
+            false => toolset.resolve(config),
+        }
 
         if self.install_missing {
-            let mpr = MultiProgressReport::new(config.settings.verbose);
+            let mpr = MultiProgressReport::new(&config.settings);
             toolset.install_missing(config, mpr)?;
         }
 
@@ -57,19 +80,62 @@ impl ToolsetBuilder {
 
 fn load_config_files(config: &Config, ts: &mut Toolset) {
     for cf in config.config_files.values().rev() {
-        ts.merge(cf.to_toolset());
+        match cf.get_type() {
+            ConfigFileType::ToolVersions if config.settings.asdf_compat => {
+                ts.merge(&rename_asdf_plugins(cf.to_toolset()))
+            }
+            _ => ts.merge(cf.to_toolset()),
+        }
+    }
+}
+
+/// plugins that asdf and rtx know by different names
+const ASDF_PLUGIN_NAMES: &[(&str, &str)] = &[("golang", "go"), ("nodejs", "node")];
+
+/// makes .tool-versions files written for asdf use rtx's plugin names
+fn rename_asdf_plugins(ts: &Toolset) -> Toolset {
+    let mut renamed = Toolset {
+        source: ts.source.clone(),
+        ..Default::default()
+    };
+    for (plugin_name, tvl) in &ts.versions {
+        let plugin_name = ASDF_PLUGIN_NAMES
+            .iter()
+            .find(|(asdf, _)| asdf == plugin_name)
+            .map_or(plugin_name.as_str(), |(_, rtx)| rtx);
+        for (tvr, opts) in &tvl.requests {
+            let tvr = ToolVersionRequest::new(plugin_name.to_string(), &tvr.version());
+            renamed.add_version(tvr, opts.clone());
+        }
     }
+    renamed
 }
 
-fn load_runtime_env(ts: &mut Toolset, env: BTreeMap<String, String>) {
-    for (k, v) in env {
-        if k.starts_with("RTX_") && k.ends_with("_VERSION") {
-            let plugin_name = k[4..k.len() - 8].to_lowercase();
+/// RTX_<PLUGIN>_VERSION, and asdf's ASDF_<PLUGIN>_VERSION with asdf_compat
+fn load_runtime_env(ts: &mut Toolset, env: BTreeMap<String, String>, asdf_compat: bool) {
+    // asdf's are merged first so RTX_<PLUGIN>_VERSION wins if both are set
+    let prefixes = match asdf_compat {
+        true => vec!["ASDF_", "RTX_"],
+        false => vec!["RTX_"],
+    };
+    for prefix in prefixes {
+        for (k, v) in &env {
+            if !k.starts_with(prefix) || !k.ends_with("_VERSION") || k.len() <= prefix.len() + 8 {
+                continue;
+            }
+            let plugin_name = k[prefix.len()..k.len() - 8].to_lowercase();
             if plugin_name == "install" {
                 // ignore RTX_INSTALL_VERSION
                 continue;
             }
-            let source = ToolSource::Environment(k, v.clone());
+            let plugin_name = match prefix {
+                "ASDF_" => ASDF_PLUGIN_NAMES
+                    .iter()
+                    .find(|(asdf, _)| asdf == &plugin_name)
+                    .map_or(plugin_name.clone(), |(_, rtx)| rtx.to_string()),
+                _ => plugin_name,
+            };
+            let source = ToolSource::Environment(k.clone(), v.clone());
             let mut env_ts = Toolset::new(source);
             for v in v.split_whitespace() {
                 let tvr = ToolVersionRequest::new(plugin_name.clone(), v);
@@ -91,3 +157,253 @@ fn load_runtime_args(ts: &mut Toolset, args: &[ToolArg]) {
         ts.merge(&arg_ts);
     }
 }
+
+/// (request, version, options) of every resolved version, by plugin
+type ResolvedVersions = BTreeMap<PluginName, Vec<(String, String, ToolVersionOptions)>>;
+
+/// how long a resolution is used for, the same as the plugins' list-all cache so new versions
+/// are picked up by `latest` and prefixes at about the same time
+const RESOLUTION_TTL: Duration = Duration::from_secs(60 * 60 * 24);
+
+/// resolving can mean running list-all for every plugin, which is too slow to do on
+/// every prompt. The result is cached under a key made from everything that can change it:
+/// the requests themselves, the config files they (and aliases) came from along with
+/// their version locks, and the plugins' commits and installed versions.
+fn resolve_cached(config: &mut Config, ts: &mut Toolset) {
+    let path = resolution_cache_path(config, ts);
+    let cm = CacheManager::new(path.clone()).with_fresh_duration(Some(RESOLUTION_TTL));
+    let mut resolved = false;
+    let cached = cm.get_or_try_init(|| {
+        ts.resolve(config);
+        resolved = true;
+        // every directory and config change adds a file, remove the ones nothing reads anymore
+        cache::prune(path.parent().unwrap(), RESOLUTION_TTL);
+        let versions = resolved_versions(ts);
+        let requested = ts.versions.values().map(|tvl| tvl.requests.len());
+        match versions.values().map(|v| v.len()).sum::<usize>() == requested.sum::<usize>() {
+            true => Ok(versions),
+            false => Err(eyre!("some versions could not be resolved")),
+        }
+    });
+    match cached {
+        Ok(_) if resolved => {}
+        Ok(cached) => load_resolved_versions(config, ts, cached),
+        Err(err) => {
+            debug!("not caching resolved versions: {:#}", err);
+            if !resolved {
+                ts.resolve(config);
+            }
+        }
+    }
+}
+
+fn resolution_cache_path(config: &Config, ts: &Toolset) -> PathBuf {
+    let modified = |p: &Path| p.metadata().and_then(|m| m.modified()).ok();
+    let config_files = config
+        .config_files
+        .keys()
+        .map(|p| (p.clone(), modified(p), modified(&VersionLock::path(p))))
+        .collect_vec();
+    let requests = ts
+        .versions
+        .iter()
+        .map(|(plugin, tvl)| {
+            // a plugin update changes what its versions resolve to
+            let plugin_sha = Git::new(dirs::PLUGINS.join(plugin)).head_sha();
+            let installs_dir = modified(&dirs::INSTALLS.join(plugin));
+            (plugin, &tvl.requests, plugin_sha, installs_dir)
+        })
+        .collect_vec();
+    let key = hash_to_str(&(config_files, requests, ts.latest_versions));
+    dirs::STATE
+        .join("resolved")
+        .join(format!("{key}.msgpack.z"))
+}
+
+fn resolved_versions(ts: &Toolset) -> ResolvedVersions {
+    ts.versions
+        .iter()
+        .map(|(plugin, tvl)| {
+            let versions = tvl
+                .versions
+                .iter()
+                .map(|tv| (tv.request.version(), tv.version.clone(), tv.opts.clone()))
+                .collect();
+            (plugin.clone(), versions)
+        })
+        .collect()
+}
+
+fn load_resolved_versions(config: &mut Config, ts: &mut Toolset, cached: &ResolvedVersions) {
+    ts.list_missing_plugins(config);
+    for (plugin, tvl) in ts.versions.iter_mut() {
+        tvl.versions.clear();
+        let tool = match config.tools.get(plugin) {
+            Some(tool) => tool,
+            None => continue,
+        };
+        for (request, version, opts) in cached.get(plugin).into_iter().flatten() {
+            let tvr = ToolVersionRequest::new(plugin.clone(), request);
+            let tv = ToolVersion::new(tool, tvr, opts.clone(), version.clone());
+            tvl.versions.push(tv);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use filetime::FileTime;
+    use pretty_assertions::assert_eq;
+
+    use crate::config::config_file::tool_versions::ToolVersions;
+    use crate::config::config_file::ConfigFile;
+
+    use super::*;
+
+    #[test]
+    fn test_rename_asdf_plugins() {
+        let tv = ToolVersions::parse_str(
+            "nodejs 18.2.0\ntiny 3\n",
+            PathBuf::from("/tmp/.tool-versions"),
+            false,
+        )
+        .unwrap();
+        let ts = rename_asdf_plugins(tv.to_toolset());
+        assert_eq!(ts.versions.keys().collect_vec(), vec!["node", "tiny"]);
+        assert_eq!(
+            ts.versions["node"].requests[0].0,
+            ToolVersionRequest::new("node".into(), "18.2.0")
+        );
+    }
+
+    #[test]
+    fn test_load_config_files_asdf_compat() {
+        let mut config = Config::load().unwrap();
+        let path = PathBuf::from("/tmp/.tool-versions");
+        let tv = ToolVersions::parse_str("nodejs 18.2.0\n", path.clone(), false).unwrap();
+        config.config_files.clear();
+        config.config_files.insert(path, Box::new(tv));
+        let plugins = |config: &Config| {
+            let mut ts = Toolset::default();
+            load_config_files(config, &mut ts);
+            ts.versions.keys().cloned().collect_vec()
+        };
+
+        config.settings.asdf_compat = false;
+        assert_eq!(plugins(&config), vec!["nodejs"]);
+        config.settings.asdf_compat = true;
+        assert_eq!(plugins(&config), vec!["node"]);
+    }
+
+    #[test]
+    fn test_load_runtime_env() {
+        let env = BTreeMap::from(
+            [
+                ("RTX_TINY_VERSION", "2"),
+                ("ASDF_TINY_VERSION", "1"),
+                ("ASDF_NODEJS_VERSION", "20 18"),
+                ("RTX_INSTALL_VERSION", "1.0.0"),
+                ("RTX_VERSION", "1.0.0"),
+            ]
+            .map(|(k, v)| (k.to_string(), v.to_string())),
+        );
+        let requests = |asdf_compat| {
+            let mut ts = Toolset::default();
+            load_runtime_env(&mut ts, env.clone(), asdf_compat);
+            ts.versions
+                .iter()
+                .map(|(p, tvl)| {
+                    (
+                        p.clone(),
+                        tvl.requests.iter().map(|(r, _)| r.version()).join(" "),
+                    )
+                })
+                .collect::<BTreeMap<_, _>>()
+        };
+        assert_eq!(
+            requests(false),
+            BTreeMap::from([("tiny".to_string(), "2".to_string())])
+        );
+        assert_eq!(
+            requests(true),
+            BTreeMap::from([
+                ("node".to_string(), "20 18".to_string()),
+                ("tiny".to_string(), "2".to_string()),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_install_from_env() {
+        let mut config = Config::load().unwrap();
+        let mut ts = Toolset::default();
+        load_config_files(&config, &mut ts);
+        let env = BTreeMap::from([("RTX_TINY_VERSION".to_string(), "1.1.0".to_string())]);
+        load_runtime_env(&mut ts, env, false);
+        ts.resolve(&mut config);
+        let tv = ts.versions["tiny"].versions[0].clone();
+        assert_eq!(tv.version, "1.1.0");
+        let _ = fs::remove_dir_all(tv.install_path());
+
+        let mpr = MultiProgressReport::new(&config.settings);
+        ts.install_missing(&mut config, mpr).unwrap();
+        let installed = config
+            .get_or_create_tool(&"tiny".into())
+            .is_version_installed(&tv);
+        fs::remove_dir_all(tv.install_path()).unwrap();
+        assert!(installed);
+    }
+
+    #[test]
+    fn test_resolution_cache() {
+        let mut config = Config::load().unwrap();
+        let mut ts = Toolset::default();
+        load_config_files(&config, &mut ts);
+        let path = resolution_cache_path(&config, &ts);
+        let _ = fs::remove_file(&path);
+
+        let ts = ToolsetBuilder::new()
+            .with_resolution_cache()
+            .build(&mut config)
+            .unwrap();
+        assert!(path.exists());
+        let mut cached = resolved_versions(&ts);
+
+        // an unchanged directory reads the versions back instead of resolving again
+        cached.get_mut("tiny").unwrap()[0].1 = "9.9.9".into();
+        fs::remove_file(&path).unwrap();
+        CacheManager::new(path.clone()).write(cached).unwrap();
+        let ts = ToolsetBuilder::new()
+            .with_resolution_cache()
+            .build(&mut config)
+            .unwrap();
+        assert_eq!(ts.versions["tiny"].versions[0].version, "9.9.9");
+
+        // an expired resolution is resolved again
+        let expired = FileTime::from_unix_time(FileTime::now().unix_seconds() - 60 * 60 * 25, 0);
+        filetime::set_file_mtime(&path, expired).unwrap();
+        let ts = ToolsetBuilder::new()
+            .with_resolution_cache()
+            .build(&mut config)
+            .unwrap();
+        assert_ne!(ts.versions["tiny"].versions[0].version, "9.9.9");
+
+        // different requests use a different cache file, writing it removes expired ones
+        let old = path.with_file_name("old.msgpack.z");
+        fs::write(&old, "").unwrap();
+        filetime::set_file_mtime(&old, expired).unwrap();
+        let args = [ToolArg::parse("tiny@2")];
+        let ts = ToolsetBuilder::new()
+            .with_args(&args)
+            .with_resolution_cache()
+            .build(&mut config)
+            .unwrap();
+        assert_eq!(ts.versions["tiny"].versions[0].version, "2.1.0");
+        assert!(!old.exists());
+        assert!(path.starts_with(&*dirs::STATE));
+        fs::remove_file(resolution_cache_path(&config, &ts)).unwrap();
+        fs::remove_file(&path).unwrap();
+    }
+}
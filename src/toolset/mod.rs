@@ -1,37 +1,45 @@
 use std::collections::{BTreeMap, HashMap, HashSet};
 use std::env::join_paths;
 use std::fmt::{Display, Formatter};
-use std::path::PathBuf;
-use std::sync::Arc;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
 
-use color_eyre::eyre::Result;
+use color_eyre::eyre::{eyre, Report, Result};
 use console::style;
 use dialoguer::theme::ColorfulTheme;
 use dialoguer::MultiSelect;
 use indexmap::IndexMap;
 use itertools::Itertools;
+use once_cell::sync::Lazy;
 use rayon::prelude::*;
 use rayon::ThreadPoolBuilder;
 
 pub use builder::ToolsetBuilder;
+pub use install_receipt::InstallReceipt;
 pub use tool_source::ToolSource;
 pub use tool_version::ToolVersion;
 pub use tool_version_list::ToolVersionList;
 pub use tool_version_request::ToolVersionRequest;
+pub use version_lock::write_version_locks;
 
 use crate::config::{Config, MissingRuntimeBehavior};
-use crate::env;
 use crate::plugins::PluginName;
 use crate::runtime_symlinks::rebuild_symlinks;
 use crate::shims::reshim;
 use crate::tool::Tool;
 use crate::ui::multi_progress_report::MultiProgressReport;
+use crate::ui::prompt;
+use crate::{dirs, env, file};
 
 mod builder;
+mod install_receipt;
 mod tool_source;
 mod tool_version;
 mod tool_version_list;
 mod tool_version_request;
+mod version_lock;
 
 pub type ToolVersionOptions = BTreeMap<String, String>;
 
@@ -90,25 +98,37 @@ impl Toolset {
         if versions.is_empty() {
             return Ok(());
         }
-        let display_versions = display_versions(&versions);
-        let plural_versions = if versions.len() == 1 { "" } else { "s" };
-        let warn = || {
+        let warn = |versions: &[ToolVersion]| {
+            let plural_versions = if versions.len() == 1 { "" } else { "s" };
             warn!(
                 "Tool{} not installed: {}",
-                plural_versions, display_versions
+                plural_versions,
+                display_versions(versions)
             );
         };
         match config.settings.missing_runtime_behavior {
             MissingRuntimeBehavior::Ignore => {}
             MissingRuntimeBehavior::Warn => {
-                warn();
+                warn(&versions);
             }
             MissingRuntimeBehavior::Prompt => {
-                let versions = prompt_for_versions(&versions)?;
-                if versions.is_empty() {
-                    warn();
+                let selected = prompt_for_versions(&versions)?;
+                if selected.is_empty() {
+                    warn(&versions);
                 } else {
-                    self.install_missing_versions(config, versions, mpr)?;
+                    self.install_missing_versions(config, selected, mpr)?;
+                }
+            }
+            MissingRuntimeBehavior::AutoInstallPrompt => {
+                let mut answers = INSTALL_ANSWERS.lock().unwrap();
+                let (accepted, declined) =
+                    prompt_per_tool(versions, &mut answers, prompt::confirm)?;
+                drop(answers);
+                if !declined.is_empty() {
+                    warn(&declined);
+                }
+                if !accepted.is_empty() {
+                    self.install_missing_versions(config, accepted, mpr)?;
                 }
             }
             MissingRuntimeBehavior::AutoInstall => {
@@ -145,46 +165,74 @@ impl Toolset {
                     .map(|v| v.plugin_name.clone())
                     .unique()
                     .collect_vec();
-                let selected_versions = selected_versions
-                    .into_iter()
-                    .map(|v| (v.plugin_name.clone(), v.request))
-                    .collect::<HashSet<_>>();
                 self.install_missing_plugins(config, plugins, &mpr)?;
-                self.versions
-                    .iter_mut()
-                    .par_bridge()
-                    .filter_map(|(p, v)| match config.tools.get(p) {
-                        Some(plugin) if plugin.is_installed() => Some((plugin, v)),
-                        _ => None,
-                    })
-                    .map(|(plugin, v)| {
-                        let versions = v
-                            .versions
-                            .iter_mut()
-                            .filter(|tv| {
-                                !plugin.is_version_installed(tv)
-                                    && selected_versions
-                                        .contains(&(tv.plugin_name.clone(), tv.request.clone()))
-                            })
-                            .collect_vec();
-                        (plugin, versions)
-                    })
-                    .filter(|(_, versions)| !versions.is_empty())
-                    .map(|(plugin, versions)| {
-                        for tv in versions {
-                            let mut pr = mpr.add();
-                            // TODO: is this necessary?
-                            // version.resolve(config, plugin.clone(), self.latest_versions)?;
-                            plugin.install_version(config, tv, &mut pr, false)?;
-                        }
-                        Ok(())
-                    })
-                    .collect::<Result<Vec<()>>>()?;
+                self.install_selected_versions(config, selected_versions, &mpr)?;
                 reshim(config, self)?;
                 rebuild_symlinks(config)?;
                 Ok(())
             })
     }
+    /// installs each plugin's versions in parallel, or one at a time in config order with
+    /// `keep_order` so the output is the same on every run
+    fn install_selected_versions(
+        &mut self,
+        config: &Config,
+        selected_versions: Vec<ToolVersion>,
+        mpr: &MultiProgressReport,
+    ) -> Result<()> {
+        let selected_versions = selected_versions
+            .into_iter()
+            .map(|v| (v.plugin_name.clone(), v.request))
+            .collect::<HashSet<_>>();
+        let to_install = self
+            .versions
+            .iter_mut()
+            .filter_map(|(p, v)| match config.tools.get(p) {
+                Some(plugin) if plugin.is_installed() => Some((plugin, v)),
+                _ => None,
+            })
+            .map(|(plugin, v)| {
+                let versions = v
+                    .versions
+                    .iter_mut()
+                    .filter(|tv| {
+                        !plugin.is_version_installed(tv)
+                            && selected_versions
+                                .contains(&(tv.plugin_name.clone(), tv.request.clone()))
+                    })
+                    .collect_vec();
+                (plugin, versions)
+            })
+            .filter(|(_, versions)| !versions.is_empty())
+            .collect_vec();
+        let install = |(plugin, versions): (&Arc<Tool>, Vec<&mut ToolVersion>)| -> Result<()> {
+            for tv in versions {
+                let mut pr = mpr.add();
+                // TODO: is this necessary?
+                // version.resolve(config, plugin.clone(), self.latest_versions)?;
+                let start = Instant::now();
+                let result = plugin.install_version(config, tv, &mut pr, false);
+                if let Some(summary) = mpr.summary() {
+                    summary.add_result(tv, &result, start.elapsed());
+                }
+                result?;
+            }
+            Ok(())
+        };
+        if !mpr.keep_going() {
+            return match config.settings.keep_order {
+                true => to_install.into_iter().try_for_each(install),
+                false => to_install.into_par_iter().try_for_each(install),
+            };
+        }
+        // with --summary a failure doesn't stop the other tools so they all end up in it
+        let results = match config.settings.keep_order {
+            true => to_install.into_iter().map(install).collect_vec(),
+            false => to_install.into_par_iter().map(install).collect(),
+        };
+        results.into_iter().collect()
+    }
+
     fn install_missing_plugins(
         &mut self,
         config: &mut Config,
@@ -196,17 +244,14 @@ impl Toolset {
         }
         let missing_plugins = missing_plugins
             .into_par_iter()
-            .map(|p| config.tools.get(&p).unwrap())
+            .map(|p| config.tools.get(&p).unwrap().clone())
             .filter(|p| !p.is_installed())
-            .map(|p| {
-                let mut pr = mpr.add();
-                p.install(config, &mut pr, false)
-            })
-            .collect::<Result<Vec<_>>>()?;
+            .collect::<Vec<_>>();
+        let result = install_plugins(config, &missing_plugins, mpr);
         if !missing_plugins.is_empty() {
             self.resolve(config);
         }
-        Ok(())
+        result
     }
 
     pub fn list_missing_versions(&self, config: &Config) -> Vec<&ToolVersion> {
@@ -289,12 +334,18 @@ impl Toolset {
         env.insert("PATH".to_string(), path_env);
         env
     }
+    /// env vars from the plugins' exec-env scripts, overridden by [env] from config files
     pub fn env(&self, config: &Config) -> BTreeMap<String, String> {
-        let mut entries: BTreeMap<String, String> = self
-            .list_current_installed_versions(config)
+        let mut entries = self.exec_env(config);
+        entries.extend(config.env.clone());
+        entries
+    }
+    /// env vars from the plugins' exec-env scripts, without [env] from config files
+    pub fn exec_env(&self, config: &Config) -> BTreeMap<String, String> {
+        self.list_current_installed_versions(config)
             .into_par_iter()
             .flat_map(|(p, tv)| match p.exec_env(config, &tv) {
-                Ok(env) => env.into_iter().collect(),
+                Ok(env) => without_soft_env(env.set, &env::PRISTINE_ENV),
                 Err(e) => {
                     warn!("Error running exec-env: {:#}", e);
                     Vec::new()
@@ -305,9 +356,19 @@ impl Toolset {
             .filter(|(k, _)| k != "RTX_ADD_PATH")
             .filter(|(k, _)| !k.starts_with("RTX_TOOL_OPTS__"))
             .rev()
-            .collect();
-        entries.extend(config.env.clone());
-        entries
+            .collect()
+    }
+    /// env vars the plugins' exec-env scripts remove from the parent environment
+    pub fn exec_env_unset(&self, config: &Config) -> Vec<String> {
+        self.list_current_installed_versions(config)
+            .into_par_iter()
+            // errors were already shown by exec_env()
+            .flat_map(|(p, tv)| p.exec_env(config, &tv).map_or(vec![], |env| env.unset))
+            .collect::<Vec<_>>()
+            .into_iter()
+            .sorted()
+            .dedup()
+            .collect()
     }
     pub fn path_env(&self, config: &Config) -> String {
         let installs = self.list_paths(config);
@@ -328,9 +389,19 @@ impl Toolset {
             })
             .collect()
     }
+    /// bins of the current versions that come before one of the same name in `path`
+    pub fn list_shadowed_bins(&self, config: &Config, path: &[PathBuf]) -> Vec<(String, PathBuf)> {
+        shadowed_bins(&self.list_paths(config), path)
+    }
     pub fn which(&self, config: &Config, bin_name: &str) -> Option<(Arc<Tool>, ToolVersion)> {
-        self.list_current_installed_versions(config)
+        // "system" versions match anything on PATH so they are checked last
+        let (system, installed): (Vec<_>, Vec<_>) = self
+            .list_current_installed_versions(config)
+            .into_iter()
+            .partition(|(_, tv)| matches!(tv.request, ToolVersionRequest::System(_)));
+        installed
             .into_par_iter()
+            .chain(system)
             .find_first(|(p, tv)| {
                 if let Ok(x) = p.which(config, tv, bin_name) {
                     x.is_some()
@@ -340,6 +411,19 @@ impl Toolset {
             })
     }
 
+    /// every tool that provides `bin_name`, in the order `which` checks them
+    pub fn which_all(&self, config: &Config, bin_name: &str) -> Vec<(Arc<Tool>, ToolVersion)> {
+        let (system, installed): (Vec<_>, Vec<_>) = self
+            .list_current_installed_versions(config)
+            .into_iter()
+            .partition(|(_, tv)| matches!(tv.request, ToolVersionRequest::System(_)));
+        installed
+            .into_par_iter()
+            .chain(system)
+            .filter(|(p, tv)| matches!(p.which(config, tv, bin_name), Ok(Some(_))))
+            .collect()
+    }
+
     pub fn list_rtvs_with_bin(&self, config: &Config, bin_name: &str) -> Result<Vec<ToolVersion>> {
         Ok(self
             .list_installed_versions(config)?
@@ -388,3 +472,377 @@ fn prompt_for_versions(versions: &[ToolVersion]) -> Result<Vec<ToolVersion>> {
         .map(|i| versions[i].clone())
         .collect())
 }
+
+/// the answers to the autoinstall_prompt questions, each tool is only asked about once per run
+static INSTALL_ANSWERS: Lazy<Mutex<HashMap<PluginName, bool>>> = Lazy::new(Default::default);
+
+/// asks whether to install the missing versions of each tool that hasn't been answered for yet,
+/// returns the versions to install and the ones that were declined
+fn prompt_per_tool(
+    versions: Vec<ToolVersion>,
+    answers: &mut HashMap<PluginName, bool>,
+    mut confirm: impl FnMut(&str) -> io::Result<bool>,
+) -> Result<(Vec<ToolVersion>, Vec<ToolVersion>)> {
+    for plugin in versions.iter().map(|tv| &tv.plugin_name).unique() {
+        if answers.contains_key(plugin) {
+            continue;
+        }
+        let tool_versions = versions
+            .iter()
+            .filter(|tv| &tv.plugin_name == plugin)
+            .cloned()
+            .collect_vec();
+        let answer = confirm(&format!("Install {}?", display_versions(&tool_versions)))?;
+        answers.insert(plugin.clone(), answer);
+    }
+    Ok(versions
+        .into_iter()
+        .partition(|tv| answers[&tv.plugin_name]))
+}
+
+/// (bin name, path of the bin it hides) for the executables in `bin_paths` that are also in `path`,
+/// rtx's own dirs in `path` like the shims are skipped
+fn shadowed_bins(bin_paths: &[PathBuf], path: &[PathBuf]) -> Vec<(String, PathBuf)> {
+    let is_bin = |p: &Path| p.is_file() && file::is_executable(p);
+    let path = path
+        .iter()
+        .filter(|p| !bin_paths.contains(p) && **p != *dirs::SHIMS)
+        .collect_vec();
+    bin_paths
+        .iter()
+        .filter_map(|dir| dir.read_dir().ok())
+        .flatten()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| is_bin(&entry.path()))
+        .map(|entry| entry.file_name().to_string_lossy().to_string())
+        .unique()
+        .sorted()
+        .filter_map(|name| {
+            let hidden = path.iter().map(|dir| dir.join(&name)).find(|p| is_bin(p))?;
+            Some((name, hidden))
+        })
+        .collect()
+}
+
+/// exec-env scripts can export RTX_SOFT_ENV with a space-separated list of vars
+/// that should only be set if the user has not already set them
+fn without_soft_env(
+    mut env: HashMap<String, String>,
+    parent_env: &HashMap<String, String>,
+) -> Vec<(String, String)> {
+    if let Some(soft) = env.remove("RTX_SOFT_ENV") {
+        for key in soft.split_whitespace() {
+            if parent_env.contains_key(key) {
+                env.remove(key);
+            }
+        }
+    }
+    env.into_iter().collect()
+}
+
+/// Installs the plugins at the same time, as many as the thread pool it is called from allows
+/// (`jobs`, which raw mode sets to 1). A plugin failing doesn't stop the others from being
+/// installed, all of the failures are reported together once they are done.
+pub fn install_plugins(
+    config: &Config,
+    tools: &[Arc<Tool>],
+    mpr: &MultiProgressReport,
+) -> Result<()> {
+    let failures = tools
+        .par_iter()
+        .filter_map(|tool| {
+            let mut pr = mpr.add();
+            match tool.install(config, &mut pr, false) {
+                Ok(()) => None,
+                Err(err) => {
+                    pr.error();
+                    Some((tool.name.clone(), err))
+                }
+            }
+        })
+        .collect();
+    plugin_install_result(failures)
+}
+
+/// a single failure is returned as is, several are combined into one error naming each plugin
+pub fn plugin_install_result(mut failures: Vec<(PluginName, Report)>) -> Result<()> {
+    match failures.len() {
+        0 => Ok(()),
+        1 => Err(failures.pop().unwrap().1),
+        n => Err(eyre!(
+            "failed to install {n} plugins:\n{}",
+            failures
+                .iter()
+                .map(|(name, err)| format!("  {name}: {err:#}"))
+                .join("\n")
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use crate::dirs;
+    use crate::file::remove_all;
+    use crate::test::StubPlugin;
+    use crate::ui::install_summary::{InstallStatus, InstallSummary};
+
+    use super::*;
+
+    fn order_toolset(
+        config: &mut Config,
+        installed: &Arc<Mutex<Vec<String>>>,
+        names: &[&str],
+        failing: &[&str],
+    ) -> (Toolset, Vec<ToolVersion>) {
+        let mut ts = Toolset::new(ToolSource::Argument);
+        for name in names {
+            let tool = StubPlugin {
+                calls: installed.clone(),
+                fails: match failing.contains(name) {
+                    true => vec!["install_version"],
+                    false => vec![],
+                },
+                ..StubPlugin::new(name)
+            }
+            .tool();
+            config.tools.insert(name.to_string(), Arc::new(tool));
+            let tvr = ToolVersionRequest::Version(name.to_string(), "1.0.0".into());
+            ts.add_version(tvr, ToolVersionOptions::new());
+        }
+        ts.resolve(config);
+        let missing = ts
+            .list_missing_versions(config)
+            .into_iter()
+            .cloned()
+            .collect_vec();
+        (ts, missing)
+    }
+
+    #[test]
+    fn test_install_keep_order() {
+        let mut config = Config::load().unwrap();
+        config.settings.keep_order = true;
+        let installed = Arc::new(Mutex::new(vec![]));
+        let names = ["order-c", "order-a", "order-d", "order-b"];
+        let (mut ts, missing) = order_toolset(&mut config, &installed, &names, &[]);
+        let mpr = MultiProgressReport::new(&config.settings);
+        let result = ts.install_selected_versions(&config, missing, &mpr);
+        for name in names {
+            remove_all(dirs::INSTALLS.join(name)).unwrap();
+        }
+        result.unwrap();
+        assert_eq!(
+            *installed.lock().unwrap(),
+            names.map(|name| format!("install_version {name}"))
+        );
+    }
+
+    #[test]
+    fn test_install_summary() {
+        let mut config = Config::load().unwrap();
+        config.settings.keep_order = true;
+        let installed = Arc::new(Mutex::new(vec![]));
+        let names = ["summary-a", "summary-fail", "summary-b"];
+        let (mut ts, missing) = order_toolset(&mut config, &installed, &names, &["summary-fail"]);
+        let summary = Arc::new(InstallSummary::default());
+        let mpr = MultiProgressReport::new(&config.settings)
+            .with_summary(summary.clone())
+            .with_keep_going(true);
+        let result = ts.install_selected_versions(&config, missing, &mpr);
+        for name in names {
+            remove_all(dirs::INSTALLS.join(name)).unwrap();
+        }
+        assert!(result.is_err());
+        // the failure doesn't stop the tools after it from being installed
+        assert_eq!(
+            *installed.lock().unwrap(),
+            ["install_version summary-a", "install_version summary-b"]
+        );
+        let entries = summary
+            .entries()
+            .into_iter()
+            .map(|e| (e.plugin, e.requested, e.version, e.status))
+            .collect_vec();
+        assert_eq!(
+            entries,
+            [
+                ("summary-a", "1.0.0", "1.0.0", InstallStatus::Installed),
+                ("summary-fail", "1.0.0", "1.0.0", InstallStatus::Failed),
+                ("summary-b", "1.0.0", "1.0.0", InstallStatus::Installed),
+            ]
+            .map(|(p, r, v, s)| (p.to_string(), r.to_string(), v.to_string(), s))
+        );
+    }
+
+    #[test]
+    fn test_install_fail_fast() {
+        let mut config = Config::load().unwrap();
+        config.settings.keep_order = true;
+        let installed = Arc::new(Mutex::new(vec![]));
+        let names = ["fast-a", "fast-fail", "fast-b"];
+        let (mut ts, missing) = order_toolset(&mut config, &installed, &names, &["fast-fail"]);
+        let mpr = MultiProgressReport::new(&config.settings);
+        let result = ts.install_selected_versions(&config, missing, &mpr);
+        for name in names {
+            remove_all(dirs::INSTALLS.join(name)).unwrap();
+        }
+        assert!(result.is_err());
+        assert_eq!(*installed.lock().unwrap(), ["install_version fast-a"]);
+    }
+
+    #[test]
+    fn test_prompt_per_tool() {
+        let mut config = Config::load().unwrap();
+        let mut tv = |plugin: &str, v: &str| {
+            let tool = config.get_or_create_tool(&plugin.to_string());
+            let request = ToolVersionRequest::new(plugin.to_string(), v);
+            ToolVersion::new(&tool, request, Default::default(), v.to_string())
+        };
+        let versions = vec![
+            tv("tiny", "1.0.0"),
+            tv("dummy", "1.0.0"),
+            tv("tiny", "2.0.0"),
+        ];
+        let mut asked = vec![];
+        let mut answers = HashMap::new();
+        let (accepted, declined) = prompt_per_tool(versions, &mut answers, |msg| {
+            asked.push(console::strip_ansi_codes(msg).to_string());
+            Ok(msg.contains("tiny"))
+        })
+        .unwrap();
+        assert_eq!(
+            asked,
+            ["Install tiny@1.0.0, tiny@2.0.0?", "Install dummy@1.0.0?"]
+        );
+        assert_eq!(
+            accepted.iter().map(|tv| tv.to_string()).collect_vec(),
+            ["tiny@1.0.0", "tiny@2.0.0"]
+        );
+        assert_eq!(
+            declined.iter().map(|tv| tv.to_string()).collect_vec(),
+            ["dummy@1.0.0"]
+        );
+
+        // the answers are remembered for the other versions of those tools
+        let versions = vec![tv("tiny", "3.0.0"), tv("dummy", "2.0.0")];
+        let (accepted, declined) =
+            prompt_per_tool(versions, &mut answers, |_| panic!("asked again")).unwrap();
+        assert_eq!(
+            accepted.iter().map(|tv| tv.to_string()).collect_vec(),
+            ["tiny@3.0.0"]
+        );
+        assert_eq!(
+            declined.iter().map(|tv| tv.to_string()).collect_vec(),
+            ["dummy@2.0.0"]
+        );
+    }
+
+    #[test]
+    fn test_install_plugins_reports_all_failures() {
+        let config = Config::load().unwrap();
+        let names = ["add-a", "add-fail-b", "add-c", "add-fail-d"];
+        let tools = names
+            .iter()
+            .map(|name| {
+                let plugin = StubPlugin {
+                    external: true,
+                    fails: match name.contains("fail") {
+                        true => vec!["install"],
+                        false => vec![],
+                    },
+                    ..StubPlugin::new(name)
+                };
+                Arc::new(plugin.tool())
+            })
+            .collect_vec();
+        let mpr = MultiProgressReport::new(&config.settings);
+        let result = install_plugins(&config, &tools, &mpr);
+        let installed = names
+            .iter()
+            .filter(|name| dirs::PLUGINS.join(name).exists())
+            .collect_vec();
+        for name in names {
+            let _ = remove_all(dirs::PLUGINS.join(name));
+        }
+        assert_eq!(installed, [&"add-a", &"add-c"]);
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "failed to install 2 plugins:\n  \
+             add-fail-b: install failed for add-fail-b\n  \
+             add-fail-d: install failed for add-fail-d"
+        );
+    }
+
+    #[test]
+    fn test_which_all() {
+        let mut config = Config::load().unwrap();
+        let mut ts = Toolset::new(ToolSource::Argument);
+        for v in ["3.1.0", "2.1.0"] {
+            let tvr = ToolVersionRequest::Version("tiny".into(), v.into());
+            ts.add_version(tvr, ToolVersionOptions::new());
+        }
+        ts.resolve(&mut config);
+        let versions = ts
+            .which_all(&config, "rtx-tiny")
+            .into_iter()
+            .map(|(_, tv)| tv.version)
+            .collect_vec();
+        assert_eq!(versions, ["3.1.0", "2.1.0"]);
+        assert!(ts.which_all(&config, "not-a-bin").is_empty());
+    }
+
+    #[test]
+    fn test_shadowed_bins() {
+        let root = dirs::CACHE.join("test-shadowed-bins");
+        let _ = remove_all(&root);
+        let (tool, system, other) = (root.join("tool"), root.join("system"), root.join("other"));
+        let bins = [
+            (&tool, "python", true),
+            (&tool, "node", true),
+            (&tool, "pip", true),
+            (&system, "python", true),
+            (&system, "ruby", true),
+            // not executable so nothing is hidden
+            (&other, "node", false),
+        ];
+        for (dir, name, executable) in bins {
+            std::fs::create_dir_all(dir).unwrap();
+            std::fs::write(dir.join(name), "#!/bin/sh\n").unwrap();
+            if executable {
+                file::make_executable(&dir.join(name)).unwrap();
+            }
+        }
+        let path = [other.clone(), tool.clone(), system.clone()];
+        let shadowed = shadowed_bins(&[tool], &path);
+        remove_all(&root).unwrap();
+
+        assert_eq!(
+            shadowed,
+            vec![("python".to_string(), system.join("python"))]
+        );
+    }
+
+    #[test]
+    fn test_without_soft_env() {
+        let env = HashMap::from([
+            ("JAVA_HOME".to_string(), "/rtx/java".to_string()),
+            ("GOROOT".to_string(), "/rtx/go".to_string()),
+            ("FOO".to_string(), "/rtx/foo".to_string()),
+            ("RTX_SOFT_ENV".to_string(), "JAVA_HOME GOROOT".to_string()),
+        ]);
+        let parent_env = HashMap::from([
+            ("JAVA_HOME".to_string(), "/usr/lib/jvm".to_string()),
+            ("FOO".to_string(), "/usr/foo".to_string()),
+        ]);
+        let env: BTreeMap<_, _> = without_soft_env(env, &parent_env).into_iter().collect();
+        assert_eq!(
+            env,
+            BTreeMap::from([
+                ("FOO".to_string(), "/rtx/foo".to_string()),
+                ("GOROOT".to_string(), "/rtx/go".to_string()),
+            ])
+        );
+    }
+}
@@ -7,7 +7,7 @@ use serde_derive::Serialize;
 use crate::file::display_path;
 
 /// where a tool version came from (e.g.: .tool-versions)
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub enum ToolSource {
     ToolVersions(PathBuf),
     RtxToml(PathBuf),
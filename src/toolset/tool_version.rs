@@ -2,11 +2,13 @@ use std::fmt::{Display, Formatter};
 use std::fs;
 use std::path::PathBuf;
 
-use color_eyre::eyre::Result;
-use versions::{Chunk, Version};
+use color_eyre::eyre::{eyre, Result, WrapErr};
+use semver::VersionReq;
+use versions::{Chunk, Version, Versioning};
 
-use crate::config::Config;
+use crate::config::{Config, Settings};
 use crate::dirs;
+use crate::git::Git;
 use crate::hash::hash_to_str;
 use crate::plugins::PluginName;
 use crate::tool::Tool;
@@ -19,6 +21,7 @@ pub struct ToolVersion {
     pub plugin_name: PluginName,
     pub version: String,
     pub opts: ToolVersionOptions,
+    install_path: PathBuf,
 }
 
 impl ToolVersion {
@@ -28,12 +31,18 @@ impl ToolVersion {
         opts: ToolVersionOptions,
         version: String,
     ) -> Self {
-        ToolVersion {
+        let mut tv = ToolVersion {
             plugin_name: tool.name.to_string(),
             version,
             request,
             opts,
-        }
+            install_path: PathBuf::new(),
+        };
+        tv.install_path = match &tv.request {
+            ToolVersionRequest::Path(_, p) => p.clone(),
+            _ => tool.install_path_for(&tv.tv_pathname()),
+        };
+        tv
     }
 
     pub fn resolve(
@@ -50,6 +59,9 @@ impl ToolVersion {
             ToolVersionRequest::Prefix(_, prefix) => {
                 Self::resolve_prefix(config, tool, request, &prefix, opts)?
             }
+            ToolVersionRequest::Range(_, req) => {
+                Self::resolve_range(config, tool, request, &req, opts)?
+            }
             _ => {
                 let version = request.version();
                 Self::new(tool, request, opts, version)
@@ -59,11 +71,7 @@ impl ToolVersion {
     }
 
     pub fn install_path(&self) -> PathBuf {
-        let pathname = match &self.request {
-            ToolVersionRequest::Path(_, p) => p.to_string_lossy().to_string(),
-            _ => self.tv_pathname(),
-        };
-        dirs::INSTALLS.join(&self.plugin_name).join(pathname)
+        self.install_path.clone()
     }
     pub fn cache_path(&self) -> PathBuf {
         dirs::CACHE.join(&self.plugin_name).join(self.tv_pathname())
@@ -73,10 +81,89 @@ impl ToolVersion {
             .join(&self.plugin_name)
             .join(self.tv_pathname())
     }
+    /// written by `rtx install --download-only` once bin/download finished so the install that
+    /// follows uses what was downloaded instead of downloading it again
+    pub fn download_complete_path(&self) -> PathBuf {
+        self.download_path().join(".rtx-download-complete")
+    }
+    /// the newest installed version that satisfies the request. That is older than this one
+    /// when a prefix, range or `latest` resolved to a remote version that isn't installed yet.
+    pub fn installed_match(&self, tool: &Tool) -> Result<Option<Self>> {
+        if tool.is_version_installed(self) {
+            return Ok(Some(self.clone()));
+        }
+        let v = match &self.request {
+            ToolVersionRequest::Range(_, req) => {
+                latest_in_range(tool.list_installed_versions()?, req)
+            }
+            ToolVersionRequest::Version(_, v) if v == "latest" => {
+                tool.latest_installed_version()?
+            }
+            ToolVersionRequest::Version(_, v) | ToolVersionRequest::Prefix(_, v) => {
+                tool.list_installed_versions_with_prefix(v)?.pop()
+            }
+            _ => None,
+        };
+        Ok(v.map(|v| Self::new(tool, self.request.clone(), self.opts.clone(), v)))
+    }
+
+    /// what installing this request again would get, if that is newer than this version.
+    /// A request for an exact version is never outdated, `latest`, prefixes and
+    /// aliases are checked against the newest remote version they match.
+    pub fn newer_version(&self, tool: &Tool, settings: &Settings) -> Result<Option<String>> {
+        let latest = match &self.request {
+            ToolVersionRequest::Version(_, v) if v == &self.version => return Ok(None),
+            ToolVersionRequest::Version(_, v) if v == "latest" => {
+                tool.latest_version(settings, None)?
+            }
+            ToolVersionRequest::Version(_, v) if v.starts_with("latest:") => {
+                let q = v.trim_start_matches("latest:");
+                let q = tool
+                    .get_aliases(settings)?
+                    .get(q)
+                    .cloned()
+                    .unwrap_or(q.into());
+                tool.list_versions_matching(settings, &latest_query(&q))?
+                    .pop()
+            }
+            ToolVersionRequest::Version(_, v) => {
+                let v = match tool.get_aliases(settings)?.get(v) {
+                    Some(alias) => alias.clone(),
+                    None => v.clone(),
+                };
+                tool.latest_version(settings, Some(v))?
+            }
+            ToolVersionRequest::Prefix(_, p) => tool.latest_version(settings, Some(p.clone()))?,
+            ToolVersionRequest::Range(_, req) => {
+                latest_in_range(tool.list_stable_remote_versions(settings)?, req)
+            }
+            ToolVersionRequest::Ref(_, r) => return self.newer_ref(r),
+            ToolVersionRequest::Path(_, _) | ToolVersionRequest::System(_) => return Ok(None),
+        };
+        let is_newer =
+            |latest: &String| match (Versioning::new(latest), Versioning::new(&self.version)) {
+                (Some(latest), Some(current)) => latest > current,
+                _ => latest != &self.version,
+            };
+        Ok(latest.filter(is_newer))
+    }
+
+    /// a ref is outdated when it has commits on origin that weren't there when it was checked
+    /// out. This can only be told for plugins that leave a git checkout in the install dir.
+    fn newer_ref(&self, r: &str) -> Result<Option<String>> {
+        let git = Git::new(self.install_path());
+        if !git.is_repo() {
+            return Ok(None);
+        }
+        let (behind, _) = git.behind_ahead(Some(r))?;
+        Ok((behind > 0).then(|| format!("ref:{r} (+{behind} commits)")))
+    }
+
     fn tv_pathname(&self) -> String {
         match &self.request {
             ToolVersionRequest::Version(_, _) => self.version.to_string(),
             ToolVersionRequest::Prefix(_, _) => self.version.to_string(),
+            ToolVersionRequest::Range(_, _) => self.version.to_string(),
             ToolVersionRequest::Ref(_, r) => format!("ref-{}", r),
             ToolVersionRequest::Path(_, p) => format!("path-{}", hash_to_str(p)),
             ToolVersionRequest::System(_) => "system".to_string(),
@@ -102,6 +189,16 @@ impl ToolVersion {
             Some(("prefix", p)) => {
                 return Self::resolve_prefix(config, tool, request, p, opts);
             }
+            Some(("latest", q)) => {
+                return Self::resolve_latest_matching(
+                    config,
+                    tool,
+                    request,
+                    latest_versions,
+                    q,
+                    opts,
+                );
+            }
             _ => (),
         }
 
@@ -144,6 +241,29 @@ impl ToolVersion {
         Self::resolve_prefix(config, tool, request, &v, opts)
     }
 
+    /// `latest:18` is the newest 18.x.x, `latest:lts` the newest version of the plugin's lts alias
+    fn resolve_latest_matching(
+        config: &Config,
+        tool: &Tool,
+        request: ToolVersionRequest,
+        latest_versions: bool,
+        q: &str,
+        opts: ToolVersionOptions,
+    ) -> Result<Self> {
+        let q = config.resolve_alias(&tool.name, q)?;
+        let query = latest_query(&q);
+        let build = |v| Ok(Self::new(tool, request.clone(), opts.clone(), v));
+        if !latest_versions {
+            if let Some(v) = tool.list_installed_versions_matching(&query)?.pop() {
+                return build(v);
+            }
+        }
+        match tool.list_versions_matching(&config.settings, &query)?.pop() {
+            Some(v) => build(v),
+            None => Err(eyre!("no version of {} matches latest:{}", tool.name, q)),
+        }
+    }
+
     /// resolve a version like `12.0.0!-1` which becomes `11.0.0`, `12.1.0!-0.1` becomes `12.0.0`
     fn resolve_bang(
         config: &Config,
@@ -154,10 +274,14 @@ impl ToolVersion {
     ) -> Result<Option<Self>> {
         let (wanted, minus) = v.split_once("!-").unwrap();
         let wanted = match wanted {
-            "latest" => tool.latest_version(&config.settings, None)?.unwrap(),
+            "latest" => match tool.latest_version(&config.settings, None)? {
+                Some(latest) => latest,
+                None => return Ok(None),
+            },
             _ => config.resolve_alias(&tool.name, wanted)?,
         };
-        let wanted = version_sub(&wanted, minus);
+        let wanted = version_sub(&wanted, minus)
+            .wrap_err_with(|| format!("failed to resolve {}@{}", tool.name, v))?;
         let tv = tool
             .latest_version(&config.settings, Some(wanted))?
             .map(|v| Self::new(tool, request, opts.clone(), v));
@@ -171,6 +295,9 @@ impl ToolVersion {
         prefix: &str,
         opts: ToolVersionOptions,
     ) -> Result<Self> {
+        if config.settings.cache_only {
+            return Self::resolve_installed_prefix(tool, request, prefix, opts);
+        }
         let matches = tool.list_versions_matching(&config.settings, prefix)?;
         let v = match matches.last() {
             Some(v) => v,
@@ -180,6 +307,41 @@ impl ToolVersion {
         Ok(Self::new(tool, request, opts, v.to_string()))
     }
 
+    fn resolve_range(
+        config: &Config,
+        tool: &Tool,
+        request: ToolVersionRequest,
+        req: &VersionReq,
+        opts: ToolVersionOptions,
+    ) -> Result<Self> {
+        let versions = match config.settings.cache_only {
+            true => tool.list_installed_versions()?,
+            false => tool.list_stable_remote_versions(&config.settings)?,
+        };
+        match latest_in_range(versions, req) {
+            Some(v) => Ok(Self::new(tool, request, opts, v)),
+            None => Err(eyre!("no version of {} satisfies {}", tool.name, req)),
+        }
+    }
+
+    /// only looks at installed versions so list-all is never run
+    fn resolve_installed_prefix(
+        tool: &Tool,
+        request: ToolVersionRequest,
+        prefix: &str,
+        opts: ToolVersionOptions,
+    ) -> Result<Self> {
+        let matches = tool.list_installed_versions_matching(prefix)?;
+        match matches.last() {
+            Some(v) => Ok(Self::new(tool, request, opts, v.to_string())),
+            None => Err(eyre!(
+                "no installed version of {} matching prefix {}",
+                tool.name,
+                prefix
+            )),
+        }
+    }
+
     fn resolve_ref(tool: &Tool, r: String, opts: ToolVersionOptions) -> Self {
         let request = ToolVersionRequest::Ref(tool.name.clone(), r);
         let version = request.version();
@@ -200,31 +362,258 @@ impl Display for ToolVersion {
     }
 }
 
+/// matches "18" and "18.2.0" but not "180.0.0" for "18"
+fn latest_query(q: &str) -> String {
+    format!(r"{}([.+-]|$)", regex::escape(q))
+}
+
+/// the newest of `versions` (sorted oldest first) the range allows
+fn latest_in_range(versions: Vec<String>, req: &VersionReq) -> Option<String> {
+    versions
+        .into_iter()
+        .rev()
+        .find(|v| to_semver(v).map_or(false, |v| req.matches(&v)))
+}
+
+/// versions like "20" or "3.11" are read as "20.0.0" and "3.11.0", None if it isn't semver at all
+fn to_semver(v: &str) -> Option<semver::Version> {
+    let v = v.strip_prefix('v').unwrap_or(v);
+    let (core, rest) = v.split_at(v.find(['-', '+']).unwrap_or(v.len()));
+    let padding = match core.matches('.').count() {
+        0 => ".0.0",
+        1 => ".0",
+        _ => "",
+    };
+    semver::Version::parse(&format!("{core}{padding}{rest}")).ok()
+}
+
 /// subtracts sub from orig and removes suffix
 /// e.g. version_sub("18.2.3", "2") -> "16"
 /// e.g. version_sub("18.2.3", "0.1") -> "18.1"
-fn version_sub(orig: &str, sub: &str) -> String {
-    let mut orig = Version::new(orig).unwrap();
-    let sub = Version::new(sub).unwrap();
-    while orig.chunks.0.len() > sub.chunks.0.len() {
-        orig.chunks.0.pop();
+/// e.g. version_sub("1.2.0-rc1", "0.1") -> "1.1"
+/// fails if a part of orig that is subtracted from isn't a number, e.g.: "1.x" and "0.1"
+fn version_sub(orig: &str, sub: &str) -> Result<String> {
+    let err = |reason: &str| eyre!("cannot subtract {sub} from {orig}: {reason}");
+    let mut version = Version::new(orig).ok_or_else(|| err("not a valid version"))?;
+    let sub_version = Version::new(sub).ok_or_else(|| err("not a valid version"))?;
+    if sub_version.chunks.0.len() > version.chunks.0.len() {
+        return Err(err(&format!("{sub} has more parts than {orig}")));
     }
-    for (i, orig_chunk) in orig.clone().chunks.0.iter().enumerate() {
-        let m = sub.nth(i).unwrap();
-        orig.chunks.0[i] = Chunk::Numeric(orig_chunk.single_digit().unwrap() - m);
+    version.chunks.0.truncate(sub_version.chunks.0.len());
+    version.release = None;
+    version.meta = None;
+    for (i, chunk) in version.chunks.0.iter_mut().enumerate() {
+        let n = chunk
+            .single_digit()
+            .ok_or_else(|| err(&format!("{chunk} is not a number")))?;
+        let m = sub_version
+            .nth(i)
+            .ok_or_else(|| err(&format!("{sub} is not a number")))?;
+        let diff = n
+            .checked_sub(m)
+            .ok_or_else(|| err("the result is negative"))?;
+        *chunk = Chunk::Numeric(diff);
     }
-    orig.to_string()
+    Ok(version.to_string())
 }
 
 #[cfg(test)]
 mod tests {
+    use std::collections::BTreeMap;
+    use std::sync::Arc;
+
     use pretty_assertions::assert_str_eq;
 
+    use crate::file::remove_all;
+    use crate::plugins::ExternalPlugin;
+    use crate::test::{git_cmd, git_commit, StubPlugin};
+
     use super::*;
 
+    fn outdated_tool(name: &str) -> Tool {
+        StubPlugin {
+            versions: ["1.0.0", "1.1.0", "1.2.0", "2.0.0", "2.1.0"]
+                .map(String::from)
+                .to_vec(),
+            aliases: BTreeMap::from([("lts".to_string(), "1".to_string())]),
+            ..StubPlugin::new(name)
+        }
+        .tool()
+    }
+
+    fn is_outdated(request: &str, version: &str) -> bool {
+        let name = "outdated".to_string();
+        let tool = outdated_tool(&name);
+        let request = ToolVersionRequest::new(name, request);
+        let tv = ToolVersion::new(&tool, request, Default::default(), version.into());
+        tv.newer_version(&tool, &Settings::default())
+            .unwrap()
+            .is_some()
+    }
+
+    #[test]
+    fn test_is_outdated_exact() {
+        assert!(!is_outdated("1.0.0", "1.0.0"));
+        assert!(!is_outdated("2.1.0", "2.1.0"));
+    }
+
+    #[test]
+    fn test_is_outdated_range() {
+        let tool = outdated_tool("outdated");
+        let tvr = ToolVersionRequest::new("outdated".into(), "1");
+        let tv = ToolVersion::new(&tool, tvr, Default::default(), "1.1.0".into());
+        let newer = tv.newer_version(&tool, &Settings::default()).unwrap();
+        assert_eq!(newer, Some("1.2.0".to_string()));
+        assert!(is_outdated("1", "1.1.0"));
+        assert!(!is_outdated("1", "1.2.0"));
+        assert!(is_outdated("prefix:2", "2.0.0"));
+        assert!(!is_outdated("prefix:2", "2.1.0"));
+    }
+
+    #[test]
+    fn test_is_outdated_semver_range() {
+        assert!(is_outdated("^1.1", "1.1.0"));
+        assert!(!is_outdated("^1.1", "1.2.0"));
+        assert!(!is_outdated("~2.0", "2.0.0"));
+    }
+
+    #[test]
+    fn test_resolve_range() {
+        let config = Config::load().unwrap();
+        let name = "outdated".to_string();
+        let tool = outdated_tool(&name);
+        let resolve = |request: &str| {
+            let request = ToolVersionRequest::new(name.clone(), request);
+            ToolVersion::resolve(&config, &tool, request, Default::default(), false)
+        };
+        assert_str_eq!(resolve("^1.1").unwrap().version, "1.2.0");
+        assert_str_eq!(resolve("~2.0").unwrap().version, "2.0.0");
+        assert_str_eq!(resolve(">=1.1,<2").unwrap().version, "1.2.0");
+        assert_str_eq!(
+            resolve("^3").unwrap_err().to_string(),
+            "no version of outdated satisfies ^3"
+        );
+    }
+
+    #[test]
+    fn test_to_semver() {
+        let v = |s: &str| to_semver(s).map(|v| v.to_string());
+        assert_eq!(v("20"), Some("20.0.0".into()));
+        assert_eq!(v("3.11"), Some("3.11.0".into()));
+        assert_eq!(v("v1.2.3-rc1"), Some("1.2.3-rc1".into()));
+        assert_eq!(v("ref-master"), None);
+    }
+
+    #[test]
+    fn test_resolve_latest_matching() {
+        let mut config = Config::load().unwrap();
+        let name = "latest-matching".to_string();
+        let tool = Arc::new(outdated_tool(&name));
+        config.tools.insert(name.clone(), tool.clone());
+        let resolve = |request: &str, latest_versions| {
+            let request = ToolVersionRequest::new(name.clone(), request);
+            ToolVersion::resolve(&config, &tool, request, Default::default(), latest_versions)
+                .map(|tv| tv.version)
+        };
+        assert_str_eq!(resolve("latest:1", true).unwrap(), "1.2.0");
+        assert_str_eq!(resolve("latest:2.0", true).unwrap(), "2.0.0");
+        assert_str_eq!(resolve("latest:lts", true).unwrap(), "1.2.0");
+        assert_str_eq!(
+            resolve("latest:3", true).unwrap_err().to_string(),
+            "no version of latest-matching matches latest:3"
+        );
+
+        // an installed version is preferred unless the latest versions are asked for
+        let installed = dirs::INSTALLS.join(&name).join("1.1.0");
+        fs::create_dir_all(&installed).unwrap();
+        let preferred = resolve("latest:1", false);
+        let latest = resolve("latest:1", true);
+        remove_all(dirs::INSTALLS.join(&name)).unwrap();
+        assert_str_eq!(preferred.unwrap(), "1.1.0");
+        assert_str_eq!(latest.unwrap(), "1.2.0");
+    }
+
+    #[test]
+    fn test_is_outdated_latest_matching() {
+        assert!(is_outdated("latest:1", "1.1.0"));
+        assert!(!is_outdated("latest:1", "1.2.0"));
+        assert!(!is_outdated("latest:lts", "1.2.0"));
+    }
+
+    #[test]
+    fn test_is_outdated_ref() {
+        let name = "outdated-ref".to_string();
+        let tool = outdated_tool(&name);
+        let tvr = ToolVersionRequest::Ref(name.clone(), "v1".into());
+        let tv = ToolVersion::new(&tool, tvr, Default::default(), "ref-v1".into());
+        let origin = dirs::CACHE.join("outdated-ref-origin");
+        let _ = remove_all(&origin);
+        let _ = remove_all(dirs::INSTALLS.join(&name));
+        fs::create_dir_all(&origin).unwrap();
+        fs::write(origin.join("README.md"), "first").unwrap();
+        git_commit(&origin, "first");
+        git_cmd(&origin, &["tag", "v1"]).run().unwrap();
+        Git::new(tv.install_path())
+            .clone(origin.to_string_lossy().as_ref())
+            .unwrap();
+        let newer = || tv.newer_version(&tool, &Settings::default()).unwrap();
+        let up_to_date = newer();
+
+        // the tag is moved to a newer commit
+        fs::write(origin.join("README.md"), "second").unwrap();
+        git_commit(&origin, "second");
+        git_cmd(&origin, &["tag", "-f", "v1"]).run().unwrap();
+        let outdated = newer();
+        remove_all(&origin).unwrap();
+        remove_all(dirs::INSTALLS.join(&name)).unwrap();
+        assert_eq!(up_to_date, None);
+        assert_eq!(outdated, Some("ref:v1 (+1 commits)".to_string()));
+    }
+
+    #[test]
+    fn test_is_outdated_latest() {
+        assert!(is_outdated("latest", "1.2.0"));
+        assert!(!is_outdated("latest", "2.1.0"));
+    }
+
     #[test]
     fn test_version_sub() {
-        assert_str_eq!(version_sub("18.2.3", "2"), "16");
-        assert_str_eq!(version_sub("18.2.3", "0.1"), "18.1");
+        assert_str_eq!(version_sub("18.2.3", "2").unwrap(), "16");
+        assert_str_eq!(version_sub("18.2.3", "0.1").unwrap(), "18.1");
+        assert_str_eq!(version_sub("1.2.0-rc1", "1").unwrap(), "0");
+        assert_str_eq!(version_sub("1.2.0-rc1", "0.1").unwrap(), "1.1");
+        assert_str_eq!(version_sub("1.2.x", "0.1").unwrap(), "1.1");
+        let err = |orig, sub| version_sub(orig, sub).unwrap_err().to_string();
+        assert_str_eq!(
+            err("18.2", "0.0.1"),
+            "cannot subtract 0.0.1 from 18.2: 0.0.1 has more parts than 18.2"
+        );
+        assert_str_eq!(
+            err("1.2.x", "0.0.1"),
+            "cannot subtract 0.0.1 from 1.2.x: x is not a number"
+        );
+        assert_str_eq!(
+            err("1.2.0", "2"),
+            "cannot subtract 2 from 1.2.0: the result is negative"
+        );
+    }
+
+    #[test]
+    fn test_resolve_installed_prefix() {
+        let plugin_name = "tiny".to_string();
+        let plugin = ExternalPlugin::new(&plugin_name);
+        let tool = Tool::new(plugin_name.clone(), Box::new(plugin));
+        let request = ToolVersionRequest::Prefix(plugin_name.clone(), "3".into());
+        let tv =
+            ToolVersion::resolve_installed_prefix(&tool, request, "3", Default::default()).unwrap();
+        assert_str_eq!(tv.version, "3.1.0");
+
+        let request = ToolVersionRequest::Prefix(plugin_name, "9".into());
+        let err = ToolVersion::resolve_installed_prefix(&tool, request, "9", Default::default())
+            .unwrap_err();
+        assert_str_eq!(
+            err.to_string(),
+            "no installed version of tiny matching prefix 9"
+        );
     }
 }
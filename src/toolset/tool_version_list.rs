@@ -1,5 +1,6 @@
 use crate::config::Config;
 use crate::toolset::tool_version_request::ToolVersionRequest;
+use crate::toolset::version_lock::VersionLock;
 use crate::toolset::{ToolSource, ToolVersion, ToolVersionOptions};
 
 /// represents several versions of a tool for a particular plugin
@@ -29,7 +30,20 @@ impl ToolVersionList {
                 return;
             }
         };
+        let lock = match &self.source {
+            ToolSource::ToolVersions(path) | ToolSource::RtxToml(path)
+                if config.settings.lock_resolved_versions =>
+            {
+                VersionLock::read(path)
+            }
+            _ => VersionLock::default(),
+        };
         for (tvr, opts) in &mut self.requests {
+            if let Some(version) = lock.get(tvr) {
+                let tv = ToolVersion::new(plugin, tvr.clone(), opts.clone(), version.clone());
+                self.versions.push(tv);
+                continue;
+            }
             match tvr.resolve(config, plugin, opts.clone(), latest_versions) {
                 Ok(v) => self.versions.push(v),
                 Err(err) => warn!("failed to resolve tool version: {:#}", err),
@@ -2,6 +2,7 @@ use std::fmt::{Display, Formatter};
 use std::path::PathBuf;
 
 use color_eyre::eyre::Result;
+use semver::VersionReq;
 
 use crate::config::Config;
 use crate::plugins::PluginName;
@@ -12,6 +13,8 @@ use crate::toolset::{ToolVersion, ToolVersionOptions};
 pub enum ToolVersionRequest {
     Version(PluginName, String),
     Prefix(PluginName, String),
+    /// npm/cargo-style range like `^1.2` or `>=1.4,<2`, resolves to the newest version it allows
+    Range(PluginName, VersionReq),
     Ref(PluginName, String),
     Path(PluginName, PathBuf),
     System(PluginName),
@@ -27,9 +30,13 @@ impl ToolVersionRequest {
             Some(("ref", r)) => Self::Ref(plugin_name, r.to_string()),
             Some(("prefix", p)) => Self::Prefix(plugin_name, p.to_string()),
             Some(("path", p)) => Self::Path(plugin_name, PathBuf::from(p)),
+            // "latest:18", resolved with the other plain versions
+            Some(("latest", _)) => Self::Version(plugin_name, s.to_string()),
             None => {
                 if s == "system" {
                     Self::System(plugin_name)
+                } else if let Some(req) = parse_range(&s) {
+                    Self::Range(plugin_name, req)
                 } else {
                     Self::Version(plugin_name, s.to_string())
                 }
@@ -42,6 +49,7 @@ impl ToolVersionRequest {
         match self {
             Self::Version(p, _) => p,
             Self::Prefix(p, _) => p,
+            Self::Range(p, _) => p,
             Self::Ref(p, _) => p,
             Self::Path(p, _) => p,
             Self::System(p) => p,
@@ -52,6 +60,8 @@ impl ToolVersionRequest {
         match self {
             Self::Version(_, v) => v.clone(),
             Self::Prefix(_, p) => format!("prefix:{p}"),
+            // without the spaces semver puts after commas so it stays one word in .tool-versions
+            Self::Range(_, r) => r.to_string().replace(", ", ","),
             Self::Ref(_, r) => format!("ref:{r}"),
             Self::Path(_, p) => format!("path:{}", p.display()),
             Self::System(_) => "system".to_string(),
@@ -69,8 +79,39 @@ impl ToolVersionRequest {
     }
 }
 
+/// only strings starting with an operator are ranges, "1.2" is still a plain version (or prefix)
+fn parse_range(s: &str) -> Option<VersionReq> {
+    if !s.starts_with(['^', '~', '>', '<', '=']) {
+        return None;
+    }
+    VersionReq::parse(s).ok()
+}
+
 impl Display for ToolVersionRequest {
     fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         write!(f, "{}@{}", self.plugin_name(), self.version())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_range() {
+        let new = |s: &str| ToolVersionRequest::new("node".into(), s);
+        assert!(matches!(new("^1.2"), ToolVersionRequest::Range(_, _)));
+        assert!(matches!(new("~1.4"), ToolVersionRequest::Range(_, _)));
+        assert_eq!(new(">=1.4, <2").version(), ">=1.4,<2");
+        assert_eq!(new("^1.2").to_string(), "node@^1.2");
+        assert!(matches!(new("1.2"), ToolVersionRequest::Version(_, _)));
+        assert!(matches!(new("prefix:^1"), ToolVersionRequest::Prefix(_, _)));
+        // not a valid range, left as a version so the error names it
+        assert!(matches!(
+            new("^not-a-range"),
+            ToolVersionRequest::Version(_, _)
+        ));
+    }
+}
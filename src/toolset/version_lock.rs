@@ -0,0 +1,153 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use color_eyre::eyre::Result;
+use itertools::Itertools;
+use serde_derive::{Deserialize, Serialize};
+
+use crate::hash::hash_to_str;
+use crate::plugins::PluginName;
+use crate::toolset::{ToolSource, ToolVersionRequest, Toolset};
+
+/// what the loose requests in a config file like `node 18` resolved to when they were installed,
+/// written next to the config as `<config>.lock` with `lock_resolved_versions`.
+/// This is only used while the config file is the same as when the lock was written.
+#[derive(Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VersionLock {
+    config_hash: String,
+    tools: BTreeMap<PluginName, BTreeMap<String, String>>,
+}
+
+impl VersionLock {
+    pub fn path(config_path: &Path) -> PathBuf {
+        let mut filename = config_path.file_name().unwrap_or_default().to_os_string();
+        filename.push(".lock");
+        config_path.with_file_name(filename)
+    }
+
+    /// empty if there is no lock or the config file changed since it was written
+    pub fn read(config_path: &Path) -> Self {
+        let lock = fs::read_to_string(Self::path(config_path))
+            .ok()
+            .and_then(|s| serde_json::from_str::<Self>(&s).ok());
+        match lock {
+            Some(lock) if Some(lock.config_hash.clone()) == config_hash(config_path) => lock,
+            _ => Self::default(),
+        }
+    }
+
+    pub fn get(&self, tvr: &ToolVersionRequest) -> Option<&String> {
+        self.tools.get(tvr.plugin_name())?.get(&tvr.version())
+    }
+
+    fn write(&self, config_path: &Path) -> Result<()> {
+        let path = Self::path(config_path);
+        fs::write(path, serde_json::to_string_pretty(self)? + "\n")?;
+        Ok(())
+    }
+}
+
+/// adds the versions the loose requests in `ts` resolved to to the lock of the config file each
+/// came from, requests for exact versions, refs and paths are left out
+pub fn write_version_locks(ts: &Toolset) -> Result<()> {
+    let by_config = ts
+        .versions
+        .values()
+        .filter_map(|tvl| match &tvl.source {
+            ToolSource::ToolVersions(path) | ToolSource::RtxToml(path) => Some((path, tvl)),
+            _ => None,
+        })
+        .into_group_map();
+    for (config_path, tvls) in by_config {
+        let config_hash = match config_hash(config_path) {
+            Some(hash) => hash,
+            None => continue,
+        };
+        let mut lock = VersionLock::read(config_path);
+        lock.config_hash = config_hash;
+        for tv in tvls.iter().flat_map(|tvl| &tvl.versions) {
+            let request = tv.request.version();
+            let is_loose = matches!(
+                tv.request,
+                ToolVersionRequest::Version(..)
+                    | ToolVersionRequest::Prefix(..)
+                    | ToolVersionRequest::Range(..)
+            );
+            if is_loose && request != tv.version {
+                lock.tools
+                    .entry(tv.plugin_name.clone())
+                    .or_default()
+                    .insert(request, tv.version.clone());
+            }
+        }
+        if !lock.tools.is_empty() {
+            lock.write(config_path)?;
+        }
+    }
+    Ok(())
+}
+
+fn config_hash(config_path: &Path) -> Option<String> {
+    fs::read_to_string(config_path)
+        .ok()
+        .map(|s| hash_to_str(&s))
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use crate::config::{config_file, Config};
+    use crate::file::remove_all;
+    use crate::{dirs, env};
+
+    use super::*;
+
+    #[test]
+    fn test_version_lock() {
+        let dir = dirs::CACHE.join("test-version-lock");
+        let _ = remove_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let config_path = dir.join(&*env::RTX_DEFAULT_TOOL_VERSIONS_FILENAME);
+        fs::write(&config_path, "tiny 2 3.1.0\n").unwrap();
+        assert_eq!(
+            VersionLock::path(&dir.join(".rtx.toml")),
+            dir.join(".rtx.toml.lock")
+        );
+
+        let mut config = Config::load().unwrap();
+        let resolve = |config: &mut Config| {
+            let cf = config_file::parse(&config_path, true).unwrap();
+            let mut ts = cf.to_toolset().clone();
+            ts.resolve(config);
+            ts
+        };
+        write_version_locks(&resolve(&mut config)).unwrap();
+        let lock = VersionLock::read(&config_path);
+        let tvr = |v: &str| ToolVersionRequest::new("tiny".into(), v);
+        // 3.1.0 is exact so it isn't locked
+        assert_eq!(lock.tools["tiny"].len(), 1);
+        assert_eq!(lock.get(&tvr("2")), Some(&"2.1.0".to_string()));
+
+        // a lock written when 2.0.0 was the newest 2.x is still used
+        let lock = VersionLock {
+            tools: BTreeMap::from([(
+                "tiny".into(),
+                BTreeMap::from([("2".into(), "2.0.0".into())]),
+            )]),
+            ..lock
+        };
+        lock.write(&config_path).unwrap();
+        config.settings.lock_resolved_versions = true;
+        let ts = resolve(&mut config);
+        let versions = ts.versions["tiny"].versions.iter().map(|tv| &tv.version);
+        assert_eq!(versions.collect_vec(), ["2.0.0", "3.1.0"]);
+
+        // but not once the config changes
+        fs::write(&config_path, "tiny 2\n").unwrap();
+        let lock_after_change = VersionLock::read(&config_path);
+        remove_all(&dir).unwrap();
+        assert_eq!(lock_after_change, VersionLock::default());
+    }
+}
@@ -0,0 +1,146 @@
+use std::fmt::{Display, Formatter};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use color_eyre::eyre::Result;
+use serde_derive::Serialize;
+
+use crate::plugins::PluginName;
+use crate::toolset::ToolVersion;
+
+/// what happened to each tool version during `rtx install --summary`, filled in by the
+/// install loops as they go so it can be shown once everything is done
+#[derive(Debug, Default)]
+pub struct InstallSummary {
+    entries: Mutex<Vec<InstallSummaryEntry>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct InstallSummaryEntry {
+    pub plugin: PluginName,
+    pub requested: String,
+    pub version: String,
+    pub status: InstallStatus,
+    pub duration_ms: u128,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum InstallStatus {
+    Installed,
+    Skipped,
+    Failed,
+}
+
+impl Display for InstallStatus {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InstallStatus::Installed => write!(f, "installed"),
+            InstallStatus::Skipped => write!(f, "skipped"),
+            InstallStatus::Failed => write!(f, "failed"),
+        }
+    }
+}
+
+impl InstallSummary {
+    pub fn add(&self, tv: &ToolVersion, status: InstallStatus, duration: Duration) {
+        self.entries.lock().unwrap().push(InstallSummaryEntry {
+            plugin: tv.plugin_name.clone(),
+            requested: tv.request.version(),
+            version: tv.version.clone(),
+            status,
+            duration_ms: duration.as_millis(),
+        });
+    }
+
+    pub fn add_result<T>(&self, tv: &ToolVersion, result: &Result<T>, duration: Duration) {
+        let status = match result {
+            Ok(_) => InstallStatus::Installed,
+            Err(_) => InstallStatus::Failed,
+        };
+        self.add(tv, status, duration);
+    }
+
+    /// in the order the installs finished
+    pub fn entries(&self) -> Vec<InstallSummaryEntry> {
+        self.entries.lock().unwrap().clone()
+    }
+
+    pub fn render(&self) -> String {
+        let header = ["plugin", "requested", "version", "status", "duration"].map(String::from);
+        let rows = self
+            .entries()
+            .into_iter()
+            .map(|e| {
+                let duration = match e.status {
+                    InstallStatus::Skipped => String::new(),
+                    _ => format!("{:.1}s", e.duration_ms as f64 / 1000.0),
+                };
+                [
+                    e.plugin,
+                    e.requested,
+                    e.version,
+                    e.status.to_string(),
+                    duration,
+                ]
+            })
+            .collect::<Vec<_>>();
+        let widths = (0..header.len())
+            .map(|i| {
+                rows.iter()
+                    .map(|r| r[i].len())
+                    .chain([header[i].len()])
+                    .max()
+                    .unwrap()
+            })
+            .collect::<Vec<_>>();
+        [header]
+            .into_iter()
+            .chain(rows)
+            .map(|row| {
+                row.iter()
+                    .zip(&widths)
+                    .map(|(cell, width)| format!("{cell:width$}"))
+                    .collect::<Vec<_>>()
+                    .join("  ")
+                    .trim_end()
+                    .to_string()
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_str_eq;
+
+    use super::*;
+
+    fn entry(plugin: &str, version: &str, status: InstallStatus, ms: u128) -> InstallSummaryEntry {
+        InstallSummaryEntry {
+            plugin: plugin.into(),
+            requested: version.split('.').next().unwrap().into(),
+            version: version.into(),
+            status,
+            duration_ms: ms,
+        }
+    }
+
+    #[test]
+    fn test_render() {
+        let summary = InstallSummary::default();
+        summary.entries.lock().unwrap().extend([
+            entry("node", "20.1.0", InstallStatus::Installed, 12345),
+            entry("python", "3.11.4", InstallStatus::Failed, 800),
+            entry("tiny", "3.1.0", InstallStatus::Skipped, 0),
+        ]);
+        assert_str_eq!(
+            summary.render(),
+            "plugin  requested  version  status     duration\n\
+             node    20         20.1.0   installed  12.3s\n\
+             python  3          3.11.4   failed     0.8s\n\
+             tiny    3          3.1.0    skipped"
+        );
+    }
+}
@@ -1,3 +1,4 @@
+pub mod install_summary;
 pub mod multi_progress_report;
 pub mod progress_report;
 pub mod prompt;
@@ -1,30 +1,79 @@
+use std::fs::{File, OpenOptions};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use color_eyre::eyre::Result;
 use console::style;
 use indicatif::MultiProgress;
 
+use crate::config::Settings;
+use crate::file::create_dir_all;
+use crate::ui::install_summary::InstallSummary;
 use crate::ui::progress_report::ProgressReport;
 
+/// groups the reports of tasks running at the same time, e.g.: parallel installs.
+/// On a tty each report gets its own line in one multi-bar block, otherwise every
+/// line is printed with the report's prefix.
 #[derive(Debug)]
 pub struct MultiProgressReport {
     mp: Option<MultiProgress>,
+    plain: bool,
+    log_file: Option<Arc<Mutex<File>>>,
+    summary: Option<Arc<InstallSummary>>,
+    keep_going: bool,
 }
 
 impl MultiProgressReport {
-    pub fn new(verbose: bool) -> Self {
-        match verbose {
-            true => Self { mp: None },
-            false => Self {
-                mp: Some(MultiProgress::new()),
-            },
+    pub fn new(settings: &Settings) -> Self {
+        let log_file =
+            settings
+                .progress_log_file
+                .as_ref()
+                .and_then(|path| match open_log_file(path) {
+                    Ok(f) => Some(Arc::new(Mutex::new(f))),
+                    Err(err) => {
+                        warn!("failed to open {}: {:#}", path.display(), err);
+                        None
+                    }
+                });
+        let plain = settings.plain_progress;
+        let mp = match settings.verbose || plain {
+            true => None,
+            false => Some(MultiProgress::new()),
+        };
+        Self {
+            mp,
+            plain,
+            log_file,
+            summary: None,
+            keep_going: false,
         }
     }
+    /// installs done with these reports record what happened to `summary`
+    pub fn with_summary(mut self, summary: Arc<InstallSummary>) -> Self {
+        self.summary = Some(summary);
+        self
+    }
+    pub fn summary(&self) -> Option<&InstallSummary> {
+        self.summary.as_deref()
+    }
+    /// installs after a failed one still run instead of stopping at the first error
+    pub fn with_keep_going(mut self, keep_going: bool) -> Self {
+        self.keep_going = keep_going;
+        self
+    }
+    pub fn keep_going(&self) -> bool {
+        self.keep_going
+    }
     pub fn add(&self) -> ProgressReport {
         match &self.mp {
             Some(mp) => {
-                let mut pr = ProgressReport::new(false);
+                let mut pr = ProgressReport::new(false).with_log_file(self.log_file.clone());
                 pr.pb = Some(mp.add(pr.pb.unwrap()));
                 pr
             }
-            None => ProgressReport::new(true),
+            None if self.plain => ProgressReport::new_plain(self.log_file.clone()),
+            None => ProgressReport::new(true).with_log_file(self.log_file.clone()),
         }
     }
     pub fn suspend<F: FnOnce() -> R, R>(&self, f: F) -> R {
@@ -55,13 +104,20 @@ impl MultiProgressReport {
     // }
 }
 
+fn open_log_file(path: &Path) -> Result<File> {
+    if let Some(parent) = path.parent() {
+        create_dir_all(parent)?;
+    }
+    Ok(OpenOptions::new().create(true).append(true).open(path)?)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
     fn test_multi_progress_report() {
-        let mpr = MultiProgressReport::new(false);
+        let mpr = MultiProgressReport::new(&Settings::default());
         let pr = mpr.add();
         pr.set_style(indicatif::ProgressStyle::with_template("").unwrap());
         pr.enable_steady_tick();
@@ -1,7 +1,10 @@
 use std::borrow::Cow;
+use std::fs::File;
+use std::io::Write;
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
-use console::style;
+use console::{strip_ansi_codes, style};
 use indicatif::{ProgressBar, ProgressStyle};
 use once_cell::sync::Lazy;
 
@@ -9,6 +12,8 @@ use once_cell::sync::Lazy;
 pub struct ProgressReport {
     pub pb: Option<ProgressBar>,
     prefix: String,
+    plain: bool,
+    log_file: Option<Arc<Mutex<File>>>,
 }
 
 pub static PROG_TEMPLATE: Lazy<ProgressStyle> = Lazy::new(|| {
@@ -41,9 +46,63 @@ impl ProgressReport {
         ProgressReport {
             pb,
             prefix: String::new(),
+            plain: false,
+            log_file: None,
         }
     }
 
+    /// for when stderr is not a tty (e.g.: CI)
+    /// there is no spinner, just one line when each phase starts and finishes.
+    /// Output from plugin scripts only goes to the log file, if there is one.
+    pub fn new_plain(log_file: Option<Arc<Mutex<File>>>) -> ProgressReport {
+        ProgressReport {
+            pb: None,
+            prefix: String::new(),
+            plain: true,
+            log_file,
+        }
+    }
+
+    pub fn with_log_file(mut self, log_file: Option<Arc<Mutex<File>>>) -> Self {
+        self.log_file = log_file;
+        self
+    }
+
+    pub fn is_plain(&self) -> bool {
+        self.plain
+    }
+
+    /// writes a line to the log file without displaying it
+    pub fn log<S: AsRef<str>>(&self, message: S) {
+        if let Some(log_file) = &self.log_file {
+            let line = self.plain_line(message.as_ref());
+            if let Err(err) = writeln!(log_file.lock().unwrap(), "{}", line) {
+                debug!("failed to write to progress log file: {:#}", err);
+            }
+        }
+    }
+
+    /// puts the prefix in front of every line so output from reports running
+    /// at the same time can be told apart
+    fn prefix_lines(&self, message: &str) -> String {
+        let prefix = self.prefix();
+        message
+            .replace('\r', "")
+            .lines()
+            .map(|line| format!("{prefix}{line}"))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn plain_line(&self, message: &str) -> String {
+        strip_ansi_codes(&self.prefix_lines(message)).to_string()
+    }
+
+    fn eprintln_plain(&self, message: &str) {
+        eprintln!("{}", self.plain_line(message));
+        self.log(message);
+    }
+
     pub fn enable_steady_tick(&self) {
         match &self.pb {
             Some(pb) => pb.enable_steady_tick(Duration::from_millis(250)),
@@ -78,47 +137,78 @@ impl ProgressReport {
     }
     pub fn set_message<S: AsRef<str>>(&self, message: S) {
         match &self.pb {
-            Some(pb) => pb.set_message(message.as_ref().replace('\r', "")),
-            None => eprintln!("{}{}", self.prefix, message.as_ref()),
+            Some(pb) => {
+                pb.set_message(message.as_ref().replace('\r', ""));
+                self.log(message);
+            }
+            None if self.plain => self.eprintln_plain(message.as_ref()),
+            None => {
+                eprintln!("{}{}", self.prefix, message.as_ref());
+                self.log(message);
+            }
         }
     }
     pub fn println<S: AsRef<str>>(&self, message: S) {
         match &self.pb {
-            Some(pb) => pb.println(message),
-            None => eprintln!("{}", message.as_ref()),
+            Some(pb) => {
+                self.log(&message);
+                pb.println(self.prefix_lines(message.as_ref()))
+            }
+            None if self.plain => self.eprintln_plain(message.as_ref()),
+            None => {
+                eprintln!("{}", self.prefix_lines(message.as_ref()));
+                self.log(message);
+            }
         }
     }
     pub fn warn<S: AsRef<str>>(&self, message: S) {
         match &self.pb {
-            Some(pb) => pb.println(format!("{} {}", style("[WARN]").yellow(), message.as_ref())),
-            None => eprintln!("{}{}", self.prefix, message.as_ref()),
+            Some(pb) => {
+                self.log(&message);
+                pb.println(format!("{} {}", style("[WARN]").yellow(), message.as_ref()))
+            }
+            None if self.plain => self.eprintln_plain(message.as_ref()),
+            None => {
+                eprintln!("{}{}", self.prefix, message.as_ref());
+                self.log(message);
+            }
         }
     }
     pub fn error(&self) {
         match &self.pb {
             Some(pb) => {
+                self.log("failed");
                 pb.set_style(ERROR_TEMPLATE.clone());
                 pb.finish()
             }
-            None => (),
+            None if self.plain => self.eprintln_plain("failed"),
+            None => self.log("failed"),
         }
     }
     pub fn finish(&self) {
         match &self.pb {
             Some(pb) => {
+                self.log("done");
                 pb.set_style(SUCCESS_TEMPLATE.clone());
                 pb.finish()
             }
-            None => (),
+            None if self.plain => self.eprintln_plain("done"),
+            None => self.log("done"),
         }
     }
     pub fn finish_with_message(&self, message: impl Into<Cow<'static, str>>) {
+        let message = message.into();
         match &self.pb {
             Some(pb) => {
+                self.log(&message);
                 pb.set_style(SUCCESS_TEMPLATE.clone());
                 pb.finish_with_message(message);
             }
-            None => eprintln!("{}{}", self.prefix, message.into()),
+            None if self.plain => self.eprintln_plain(&message),
+            None => {
+                eprintln!("{}{}", self.prefix, message);
+                self.log(message);
+            }
         }
     }
     // pub fn clear(&self) {
@@ -131,6 +221,12 @@ impl ProgressReport {
 
 #[cfg(test)]
 mod tests {
+    use std::fs;
+    use std::thread;
+
+    use crate::dirs;
+    use crate::test::run_isolated;
+
     use super::*;
 
     #[test]
@@ -142,6 +238,84 @@ mod tests {
         pr.finish_with_message("message");
     }
 
+    #[test]
+    fn test_progress_report_plain() {
+        let path = dirs::CACHE.join("test-progress-report.log");
+        let _ = fs::remove_file(&path);
+        let log_file = File::create(&path).unwrap();
+        let mut pr = ProgressReport::new_plain(Some(Arc::new(Mutex::new(log_file))));
+        pr.set_prefix(format!(
+            "{} tiny@1.0.0 ",
+            style("rtx").dim().force_styling(true)
+        ));
+        pr.set_message("installing");
+        pr.log(
+            style("output from bin/install")
+                .red()
+                .force_styling(true)
+                .to_string(),
+        );
+        pr.finish();
+
+        let log = fs::read_to_string(&path).unwrap();
+        assert!(!log.contains('\x1b'));
+        assert_eq!(
+            log,
+            "rtx tiny@1.0.0 installing\nrtx tiny@1.0.0 output from bin/install\nrtx tiny@1.0.0 done\n"
+        );
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_progress_report_concurrent() {
+        // every line is whole and has the prefix of the report that wrote it
+        let assert_lines = |output: &str| {
+            assert_eq!(output.lines().count(), 8 * 41, "{output}");
+            for i in 0..8 {
+                let prefix = format!("tool-{i} ");
+                let lines = output
+                    .lines()
+                    .filter(|l| l.starts_with(&prefix))
+                    .collect::<Vec<_>>();
+                let expected = (0..20)
+                    .flat_map(|j| [format!("{prefix}line {j}a"), format!("{prefix}line {j}b")])
+                    .chain([format!("{prefix}done")])
+                    .collect::<Vec<_>>();
+                assert_eq!(lines, expected);
+            }
+        };
+        // the lines written to stderr are only seen from outside of the test process
+        if let Some(stderr) =
+            run_isolated("ui::progress_report::tests::test_progress_report_concurrent")
+        {
+            assert_lines(&stderr);
+            return;
+        }
+
+        let path = dirs::CACHE.join("test-progress-report-concurrent.log");
+        let _ = fs::remove_file(&path);
+        let log_file = Arc::new(Mutex::new(File::create(&path).unwrap()));
+        let handles = (0..8)
+            .map(|i| {
+                let log_file = log_file.clone();
+                thread::spawn(move || {
+                    let mut pr = ProgressReport::new_plain(Some(log_file));
+                    pr.set_prefix(format!("tool-{i} "));
+                    for j in 0..20 {
+                        pr.println(format!("line {j}a\nline {j}b"));
+                    }
+                    pr.finish();
+                })
+            })
+            .collect::<Vec<_>>();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        assert_lines(&fs::read_to_string(&path).unwrap());
+        fs::remove_file(&path).unwrap();
+    }
+
     #[test]
     fn test_progress_report_verbose() {
         let mut pr = ProgressReport::new(true);